@@ -1,7 +1,7 @@
 use rusty_machine::linalg::{Matrix, BaseMatrix};
 use rusty_machine::learning::{LearningResult, SupModel};
 use rusty_machine::analysis::score::row_accuracy;
-use rusty_machine::analysis::cross_validation::k_fold_validate;
+use rusty_machine::analysis::cross_validation::{k_fold_validate, KFold};
 use rand::{thread_rng, Rng};
 use test::{Bencher, black_box};
 
@@ -52,10 +52,12 @@ macro_rules! bench {
             let inputs = generate_data(rows, cols);
             let targets = generate_data(rows, 1);
 
+            let kfold = KFold::new(k).unwrap();
+
             b.iter(|| {
                 let mut model = DummyModel { sum: 0f64 };
                 let _ = black_box(
-                    k_fold_validate(&mut model, &inputs, &targets, k, row_accuracy)
+                    k_fold_validate(&mut model, &inputs, &targets, &kfold, row_accuracy)
                 );
             });
         }
@@ -3,7 +3,8 @@ use test::{Bencher, black_box};
 use rand::{random, Closed01};
 use std::vec::Vec;
 
-use rusty_machine::learning::nnet::{NeuralNet, BCECriterion};
+use rusty_machine::learning::nnet::{NeuralNet, BCECriterion, MSECriterion};
+use rusty_machine::learning::nnet::net_layer::Linear;
 use rusty_machine::learning::toolkit::regularization::Regularization;
 use rusty_machine::learning::toolkit::activ_fn::Sigmoid;
 use rusty_machine::learning::optim::grad_desc::StochasticGD;
@@ -11,6 +12,8 @@ use rusty_machine::learning::optim::grad_desc::StochasticGD;
 use rusty_machine::linalg::Matrix;
 use rusty_machine::learning::SupModel;
 
+use examples::util::generate_regression_data;
+
 fn generate_data() -> (Matrix<f64>, Matrix<f64>, Matrix<f64>) {
     const THRESHOLD: f64 = 0.7;
     const SAMPLES: usize = 1000;
@@ -52,7 +55,8 @@ fn nnet_and_gate_train(b: &mut Bencher) {
     let criterion = BCECriterion::new(Regularization::L2(0.));
 
     b.iter(|| {
-        let mut model = black_box(NeuralNet::mlp(layers, criterion, StochasticGD::default(), Sigmoid));
+        let sgd = StochasticGD::new(0.1, 0.1, 20);
+        let mut model = black_box(NeuralNet::mlp(layers, criterion, sgd, Sigmoid));
         let _ = black_box(model.train(&inputs, &targets).unwrap());
     })
 }
@@ -63,10 +67,44 @@ fn nnet_and_gate_predict(b: &mut Bencher) {
     let layers = &[2, 1];
     let criterion = BCECriterion::new(Regularization::L2(0.));
 
-    let mut model = NeuralNet::mlp(layers, criterion, StochasticGD::default(), Sigmoid);
+    let mut model = NeuralNet::mlp(layers, criterion, StochasticGD::new(0.1, 0.1, 20), Sigmoid);
     let _ = model.train(&inputs, &targets);
 
     b.iter(|| {
         let _ = black_box(model.predict(&test_inputs));
     })
 }
+
+#[bench]
+fn nnet_hidden_layers_train(b: &mut Bencher) {
+    let (inputs, targets, _) = generate_data();
+    let criterion = BCECriterion::new(Regularization::L2(0.));
+
+    b.iter(|| {
+        let mut model = NeuralNet::new(criterion, StochasticGD::new(0.1, 0.1, 5));
+        model.add(Box::new(Linear::new(2, 20)))
+             .add(Box::new(Sigmoid))
+             .add(Box::new(Linear::new(20, 20)))
+             .add(Box::new(Sigmoid))
+             .add(Box::new(Linear::new(20, 1)))
+             .add(Box::new(Sigmoid));
+        let mut model = black_box(model);
+        let _ = black_box(model.train(&inputs, &targets).unwrap());
+    })
+}
+
+#[bench]
+fn nnet_sine_regression_train(b: &mut Bencher) {
+    let (inputs, targets) = generate_regression_data();
+    let targets = Matrix::new(targets.size(), 1, targets.into_vec());
+    let criterion = MSECriterion::new(Regularization::L2(0.));
+
+    b.iter(|| {
+        let mut model = NeuralNet::new(criterion, StochasticGD::new(0.1, 0.1, 50));
+        model.add(Box::new(Linear::new(1, 10)))
+             .add(Box::new(Sigmoid))
+             .add(Box::new(Linear::new(10, 1)));
+        let mut model = black_box(model);
+        let _ = black_box(model.train(&inputs, &targets).unwrap());
+    })
+}
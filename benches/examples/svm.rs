@@ -1,13 +1,16 @@
 use rusty_machine::learning::svm::SVM;
 // Necessary for the training trait.
 use rusty_machine::learning::SupModel;
-use rusty_machine::learning::toolkit::kernel::HyperTan;
+use rusty_machine::learning::svm::svr::SVR;
+use rusty_machine::learning::toolkit::kernel::{HyperTan, SquaredExp};
 
 use rusty_machine::linalg::Matrix;
 use rusty_machine::linalg::Vector;
 
 use test::{Bencher, black_box};
 
+use examples::util::generate_regression_data;
+
 fn generate_data() -> (Matrix<f64>, Vector<f64>) {
     // Training data
     let inputs = Matrix::new(11, 1, vec![
@@ -51,3 +54,13 @@ fn svm_sign_learner_predict(b: &mut Bencher) {
         let _ = black_box(svm_mod.predict(&test_inputs).unwrap());
     });
 }
+
+#[bench]
+fn svr_sine_regression_train(b: &mut Bencher) {
+    let (inputs, targets) = generate_regression_data();
+
+    b.iter(|| {
+        let mut svr = black_box(SVR::new(SquaredExp::new(1., 1.), 10., 0.05));
+        let _ = black_box(svr.train(&inputs, &targets).unwrap());
+    });
+}
@@ -0,0 +1,12 @@
+use rusty_machine::linalg::Matrix;
+use rusty_machine::linalg::Vector;
+
+/// The sine regression data shared by the neural network and support vector regression benches.
+pub fn generate_regression_data() -> (Matrix<f64>, Vector<f64>) {
+    const SAMPLES: usize = 200;
+
+    let xs = (0..SAMPLES).map(|i| i as f64 * 6.28 / SAMPLES as f64).collect::<Vec<_>>();
+    let ys = xs.iter().map(|x| x.sin()).collect::<Vec<_>>();
+
+    (Matrix::new(SAMPLES, 1, xs), Vector::new(ys))
+}
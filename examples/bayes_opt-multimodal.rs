@@ -0,0 +1,38 @@
+extern crate rusty_machine;
+extern crate rand;
+
+use rusty_machine::learning::bayes_opt::{BayesianOptimizer, Acquisition};
+use rusty_machine::learning::toolkit::kernel::SquaredExp;
+use rusty_machine::benchmark_functions::analytic::Multimodal1D;
+
+use rand::{StdRng, SeedableRng};
+
+fn main() {
+    println!("Bayesian optimization of -x sin(10x) over [0, 1.2]:");
+
+    let mut optimizer = BayesianOptimizer::new(vec![(0., 1.2)],
+                                               SquaredExp::new(0.1, 1.),
+                                               Acquisition::ExpectedImprovement { xi: 0.01 });
+
+    let seed: &[_] = &[1, 2, 3];
+    let mut rng = StdRng::from_seed(seed);
+
+    // Start from a few random evaluations.
+    optimizer.maximize(|x| x[0].multimodal1_d(), 3, 0, &mut rng).unwrap();
+
+    for i in 0..15 {
+        let x = optimizer.suggest(&mut rng).unwrap();
+        let y = x[0].multimodal1_d();
+        optimizer.observe(&x, y);
+
+        if let Some(err) = optimizer.hyperopt_error() {
+            println!("Kept the previous kernel, the hyperparameter fit failed: {}", err);
+        }
+
+        let (_, best) = optimizer.best().unwrap();
+        println!("Iteration {:>2}: evaluated f({:.4}) = {:.4}, best so far {:.4}", i + 1, x[0], y, best);
+    }
+
+    let (x, y) = optimizer.best().unwrap();
+    println!("Best point found: f({:.4}) = {:.4}", x[0], y);
+}
@@ -44,7 +44,7 @@ fn main() {
     let criterion = BCECriterion::new(Regularization::L2(0.));
     // Create a multilayer perceptron with an input layer of size 2 and output layer of size 1
     // Uses a Sigmoid activation function and uses Stochastic gradient descent for training
-    let mut model = NeuralNet::mlp(layers, criterion, StochasticGD::default(), Sigmoid);
+    let mut model = NeuralNet::mlp(layers, criterion, StochasticGD::new(0.1, 0.1, 20), Sigmoid);
 
     println!("Training...");
     // Our train function returns a Result<(), E>
@@ -251,7 +251,7 @@ fn main() {
     
     // Configure the neural network
     let criterion = BCECriterion::new(Regularization::L2(0.));
-    let mut model = NeuralNet::mlp(&layers, criterion, StochasticGD::default(), Sigmoid);
+    let mut model = NeuralNet::mlp(&layers, criterion, StochasticGD::new(0.1, 0.1, 20), Sigmoid);
 
     println!("\nTraining the neural network...");
     model.train(&inputs, &targets).unwrap();
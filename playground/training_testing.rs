@@ -2,11 +2,12 @@ extern crate rusty_machine;
 
 use rusty_machine::benchmark_functions::analytic::Multimodal1D;
 use rusty_machine::learning::nnet::{MSECriterion, BCECriterion, NeuralNet};
+use rusty_machine::learning::nnet::net_layer::Linear;
 use rusty_machine::learning::optim::grad_desc::StochasticGD;
 use rusty_machine::learning::toolkit::activ_fn::Sigmoid;
-use rusty_machine::learning::toolkit::cost_fn::{CostFunc, MeanSqError};
 use rusty_machine::learning::toolkit::regularization::Regularization;
 
+use rusty_machine::analysis::score::{r2_score, rmse_per_column, neg_max_error};
 use rusty_machine::learning::SupModel;
 use rusty_machine::linalg::Matrix;
 
@@ -24,22 +25,28 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let inputs = Matrix::new(num_samples, input_dim, inputs_vec.clone());
     let targets = Matrix::new(num_samples, output_dim, targets_vec.clone());
 
-    let layers = &[1, 30,30, 10];
     let criterion = MSECriterion::new(Regularization::L2(0.00001));
     //let criterion = BCECriterion::new(Regularization::L2(0.));
-    // Create a multilayer perceptron with an input layer of size 1 and output layer of size 1
-    // Uses a Sigmoid activation function and uses Stochastic gradient descent for training
-    let mut model = NeuralNet::mlp(layers, criterion, StochasticGD::default(), Sigmoid);
+    // Create a network with an input layer of size 1, two hidden Sigmoid layers of size 30
+    // and a linear output layer of size 1, so the output can fit negative targets.
+    // Uses Stochastic gradient descent for training
+    let mut model = NeuralNet::new(criterion, StochasticGD::new(0.1, 0.1, 20));
+    model.add(Box::new(Linear::new(input_dim, 30)))
+        .add(Box::new(Sigmoid))
+        .add(Box::new(Linear::new(30, 30)))
+        .add(Box::new(Sigmoid))
+        .add(Box::new(Linear::new(30, output_dim)));
 
     println!("Training...");
     // Our train function returns a Result<(), E>
     model.train(&inputs, &targets).unwrap();
 
     let prediction = model.predict(&inputs).unwrap();
-    let mse = MeanSqError::cost(&prediction, &targets);
 
     println!("Evaluation...");
-    println!("MSE: {}", mse);
+    println!("RMSE: {}", rmse_per_column(&prediction, &targets)[0]);
+    println!("R2: {}", r2_score(&prediction, &targets));
+    println!("Max error: {}", -neg_max_error(&prediction, &targets));
 
     // Call the plotting function
     plot::plot_comparison::plot_comparison(
@@ -1,26 +1,56 @@
 //! Module for performing cross-validation of models.
+//!
+//! The samples are divided into training and test sets by a `Splitter`.
+//! The available splitters are:
+//!
+//! - `KFold` : k folds of (optionally shuffled) samples.
+//! - `StratifiedKFold` : k folds preserving the class proportions.
+//! - `GroupKFold` : k folds which never split a group of samples.
+//! - `LeaveOneOut` : each sample is tested on its own.
+//! - `ShuffleSplit` : independent random test sets of a fixed fraction.
+//! - `TimeSeriesSplit` : test sets following their training sets in time.
+//!
+//! Random splitters use `thread_rng`, unless the generator is given to
+//! `k_fold_validate_with_rng` or `cross_validate_with_rng`.
+//!
+//! Any inputs and targets implementing `RowSelect`, such as `Matrix<f64>`,
+//! `Vector<f64>` and `Vector<usize>`, can be cross validated.
 
 use std::cmp;
+use std::collections::HashMap;
+use std::hash::Hash;
 use std::iter::Chain;
 use std::slice::Iter;
-use linalg::{BaseMatrix, Matrix};
+use linalg::{BaseMatrix, Matrix, Vector};
 use learning::{LearningResult, SupModel};
-use learning::toolkit::rand_utils::in_place_fisher_yates;
+use learning::error::{Error, ErrorKind};
+use learning::toolkit::parallel::MaybeSync;
+use learning::toolkit::rand_utils::in_place_fisher_yates_with_rng;
 
-/// Randomly splits the inputs into k 'folds'. For each fold a model
-/// is trained using all inputs except for that fold, and tested on the
-/// data in the fold. Returns the scores for each fold.
+use rand::{Rng, thread_rng};
+
+#[cfg(feature = "parallel")]
+use learning::toolkit::parallel;
+
+/// Splits the inputs into training and test sets using `splitter`. For
+/// each split a model is trained using the training set, and tested on
+/// the test set. Returns the scores for each split.
+///
+/// The inputs and targets may be any types whose rows can be selected,
+/// such as `Matrix<f64>`, `Vector<f64>` or `Vector<usize>`.
 ///
 /// # Arguments
-/// * `model` - Used to train and predict for each fold.
+/// * `model` - Used to train and predict for each split. Passing `&model` for a
+/// `Clone` model trains a clone on each split instead, concurrently with the
+/// `parallel` feature. See `SplitModels`.
 /// * `inputs` - All input samples.
 /// * `targets` - All targets.
-/// * `k` - Number of folds to use.
-/// * `score` - Used to compare the outputs for each fold to the targets. Higher scores are better. See the `analysis::score` module for examples.
+/// * `splitter` - Divides the samples into training and test sets, e.g. `KFold`.
+/// * `score` - Used to compare the outputs for each split to the targets. Higher scores are better. See the `analysis::score` module for examples.
 ///
 /// # Examples
 /// ```
-/// use rusty_machine::analysis::cross_validation::k_fold_validate;
+/// use rusty_machine::analysis::cross_validation::{k_fold_validate, KFold};
 /// use rusty_machine::analysis::score::row_accuracy;
 /// use rusty_machine::learning::naive_bayes::{NaiveBayes, Bernoulli};
 /// use rusty_machine::linalg::{BaseMatrix, Matrix};
@@ -39,52 +69,850 @@ use learning::toolkit::rand_utils::in_place_fisher_yates;
 ///     &mut model,
 ///     &inputs,
 ///     &targets,
-///     3,
+///     &KFold::new(3).unwrap(),
 ///     // Score each fold by the fraction of test samples where
 ///     // the model's prediction equals the target.
 ///     row_accuracy
 /// ).unwrap();
 /// ```
-pub fn k_fold_validate<M, S>(model: &mut M,
-                             inputs: &Matrix<f64>,
-                             targets: &Matrix<f64>,
-                             k: usize,
-                             score: S) -> LearningResult<Vec<f64>>
-    where S: Fn(&Matrix<f64>, &Matrix<f64>) -> f64,
-          M: SupModel<Matrix<f64>, Matrix<f64>>,
+pub fn k_fold_validate<M, I, T, S, Sp>(model: M,
+                                       inputs: &I,
+                                       targets: &T,
+                                       splitter: &Sp,
+                                       score: S) -> LearningResult<Vec<f64>>
+    where S: Fn(&T, &T) -> f64,
+          M: SplitModels<I, T>,
+          I: RowSelect,
+          T: RowSelect,
+          Sp: Splitter,
+{
+    k_fold_validate_with_rng(model, inputs, targets, splitter, score, &mut thread_rng())
+}
+
+/// Cross validates a model as `k_fold_validate` does, with random
+/// splitters drawing from `rng`.
+///
+/// This allows the splits to be reproduced with a seeded generator.
+///
+/// # Examples
+/// ```
+/// extern crate rand;
+/// # extern crate rusty_machine;
+/// use rand::{SeedableRng, StdRng};
+/// use rusty_machine::analysis::cross_validation::{k_fold_validate_with_rng, ShuffleSplit};
+/// use rusty_machine::learning::lin_reg::LinRegressor;
+/// use rusty_machine::linalg::{Matrix, Vector};
+///
+/// # fn main() {
+/// let inputs = Matrix::new(6, 1, vec![0., 1., 2., 3., 4., 5.]);
+/// let targets = Vector::new(vec![1., 2., 5., 6., 9., 11.]);
+/// let seed: &[_] = &[1, 2, 3];
+///
+/// // The negated sum of absolute errors.
+/// let neg_abs_error = |o: &Vector<f64>, t: &Vector<f64>| {
+///     -o.data().iter().zip(t.data()).map(|(a, b)| (a - b).abs()).sum::<f64>()
+/// };
+///
+/// let mut model = LinRegressor::default();
+/// let splitter = ShuffleSplit::new(3, 0.5).unwrap();
+/// let mut validate = || {
+///     k_fold_validate_with_rng(&mut model, &inputs, &targets, &splitter,
+///                              &neg_abs_error, &mut StdRng::from_seed(seed)).unwrap()
+/// };
+///
+/// // The same seed gives the same random test sets.
+/// assert_eq!(validate(), validate());
+/// # }
+/// ```
+pub fn k_fold_validate_with_rng<M, I, T, S, Sp, R>(model: M,
+                                                   inputs: &I,
+                                                   targets: &T,
+                                                   splitter: &Sp,
+                                                   score: S,
+                                                   rng: &mut R) -> LearningResult<Vec<f64>>
+    where S: Fn(&T, &T) -> f64,
+          M: SplitModels<I, T>,
+          I: RowSelect,
+          T: RowSelect,
+          Sp: Splitter,
+          R: Rng,
+{
+    cross_validate_with_rng(model, inputs, targets, splitter, score, rng).map(|cv| cv.scores)
+}
+
+/// Cross validates a model as `k_fold_validate` does, also keeping
+/// the predictions made for each test set.
+///
+/// # Examples
+/// ```
+/// use rusty_machine::analysis::cross_validation::{cross_validate, KFold};
+/// use rusty_machine::learning::lin_reg::LinRegressor;
+/// use rusty_machine::linalg::{Matrix, Vector};
+///
+/// let inputs = Matrix::new(6, 1, vec![0., 1., 2., 3., 4., 5.]);
+/// let targets = Vector::new(vec![1., 3., 5., 7., 9., 11.]);
+///
+/// // The negated sum of absolute errors.
+/// let neg_abs_error = |o: &Vector<f64>, t: &Vector<f64>| {
+///     -o.data().iter().zip(t.data()).map(|(a, b)| (a - b).abs()).sum::<f64>()
+/// };
+///
+/// let mut model = LinRegressor::default();
+/// let cv = cross_validate(&mut model, &inputs, &targets, &KFold::new(3).unwrap(),
+///                         neg_abs_error).unwrap();
+///
+/// assert_eq!(cv.scores().len(), 3);
+///
+/// // Each sample is predicted by the model which did not train on it.
+/// let predictions = cv.out_of_fold_predictions().unwrap();
+/// assert!(predictions.data().iter().zip(targets.data()).all(|(p, t)| (p - t).abs() < 1e-6));
+/// ```
+pub fn cross_validate<M, I, T, S, Sp>(model: M,
+                                      inputs: &I,
+                                      targets: &T,
+                                      splitter: &Sp,
+                                      score: S) -> LearningResult<CrossValidation<T>>
+    where S: Fn(&T, &T) -> f64,
+          M: SplitModels<I, T>,
+          I: RowSelect,
+          T: RowSelect,
+          Sp: Splitter,
+{
+    cross_validate_with_rng(model, inputs, targets, splitter, score, &mut thread_rng())
+}
+
+/// Cross validates a model as `cross_validate` does, with random
+/// splitters drawing from `rng`.
+///
+/// This allows the splits to be reproduced with a seeded generator.
+///
+/// # Examples
+/// ```
+/// extern crate rand;
+/// # extern crate rusty_machine;
+/// use rand::{SeedableRng, StdRng};
+/// use rusty_machine::analysis::cross_validation::{cross_validate_with_rng, KFold};
+/// use rusty_machine::learning::lin_reg::LinRegressor;
+/// use rusty_machine::linalg::{Matrix, Vector};
+///
+/// # fn main() {
+/// let inputs = Matrix::new(6, 1, vec![0., 1., 2., 3., 4., 5.]);
+/// let targets = Vector::new(vec![1., 2., 5., 6., 9., 11.]);
+/// let seed: &[_] = &[1, 2, 3];
+///
+/// // The negated sum of absolute errors.
+/// let neg_abs_error = |o: &Vector<f64>, t: &Vector<f64>| {
+///     -o.data().iter().zip(t.data()).map(|(a, b)| (a - b).abs()).sum::<f64>()
+/// };
+///
+/// let mut model = LinRegressor::default();
+/// let kfold = KFold::new(3).unwrap();
+/// let first = cross_validate_with_rng(&mut model, &inputs, &targets, &kfold,
+///                                     &neg_abs_error, &mut StdRng::from_seed(seed)).unwrap();
+/// let second = cross_validate_with_rng(&mut model, &inputs, &targets, &kfold,
+///                                      &neg_abs_error, &mut StdRng::from_seed(seed)).unwrap();
+///
+/// // The same seed gives the same shuffled folds.
+/// assert_eq!(first.scores(), second.scores());
+/// # }
+/// ```
+pub fn cross_validate_with_rng<M, I, T, S, Sp, R>(model: M,
+                                                  inputs: &I,
+                                                  targets: &T,
+                                                  splitter: &Sp,
+                                                  score: S,
+                                                  rng: &mut R) -> LearningResult<CrossValidation<T>>
+    where S: Fn(&T, &T) -> f64,
+          M: SplitModels<I, T>,
+          I: RowSelect,
+          T: RowSelect,
+          Sp: Splitter,
+          R: Rng,
 {
-    assert_eq!(inputs.rows(), targets.rows());
-    let num_samples = inputs.rows();
-    let shuffled_indices = create_shuffled_indices(num_samples);
-    let folds = Folds::new(&shuffled_indices, k);
+    if inputs.num_rows() != targets.num_rows() {
+        return Err(Error::new(ErrorKind::InvalidData,
+                              "The number of inputs and targets do not match."));
+    }
+    let num_samples = inputs.num_rows();
+    let splits = splitter.split(num_samples, rng)?;
+    let outputs = model.train_predict(inputs, targets, &splits)?;
 
-    let mut costs: Vec<f64> = Vec::new();
+    let mut scores = Vec::with_capacity(splits.len());
+    let mut predictions = Vec::with_capacity(splits.len());
 
-    for p in folds {
-        // TODO: don't allocate fresh buffers for every fold
-        let train_inputs = inputs.select_rows(p.train_indices_iter.clone());
-        let train_targets = targets.select_rows(p.train_indices_iter.clone());
-        let test_inputs = inputs.select_rows(p.test_indices_iter.clone());
-        let test_targets = targets.select_rows(p.test_indices_iter.clone());
+    for ((_, test_idx), outputs) in splits.into_iter().zip(outputs) {
+        scores.push(score(&outputs, &targets.take_rows(&test_idx)));
+        predictions.push((test_idx, outputs));
+    }
+
+    Ok(CrossValidation {
+        num_samples: num_samples,
+        scores: scores,
+        predictions: predictions,
+    })
+}
+
+/// The models trained on each split during cross validation.
+///
+/// This is implemented for `&mut M`, which trains the model itself on
+/// each split in turn and leaves it trained on the last split.
+///
+/// It is also implemented for `&M` when `M` is `Clone`, which trains
+/// a clone of the model on each split and leaves the model untouched.
+/// With the `parallel` feature the clones are trained concurrently. The
+/// results are the same for any number of threads when the model
+/// training is deterministic.
+pub trait SplitModels<I, T> {
+    /// Trains a model on the training rows of each split and returns
+    /// its predictions for the test rows, in split order.
+    fn train_predict(self,
+                     inputs: &I,
+                     targets: &T,
+                     splits: &[(Vec<usize>, Vec<usize>)])
+                     -> LearningResult<Vec<T>>;
+}
 
-        model.train(&train_inputs, &train_targets)?;
-        let outputs = model.predict(&test_inputs)?;
-        costs.push(score(&outputs, &test_targets));
+impl<'a, M, I, T> SplitModels<I, T> for &'a mut M
+    where M: SupModel<I, T>,
+          I: RowSelect,
+          T: RowSelect,
+{
+    fn train_predict(self,
+                     inputs: &I,
+                     targets: &T,
+                     splits: &[(Vec<usize>, Vec<usize>)])
+                     -> LearningResult<Vec<T>> {
+        splits.iter()
+            .map(|&(ref train_idx, ref test_idx)| {
+                // TODO: don't allocate fresh buffers for every fold
+                self.train(&inputs.take_rows(train_idx), &targets.take_rows(train_idx))?;
+                self.predict(&inputs.take_rows(test_idx))
+            })
+            .collect()
     }
+}
 
-    Ok(costs)
+impl<'a, M, I, T> SplitModels<I, T> for &'a M
+    where M: SupModel<I, T> + Clone + MaybeSync,
+          I: RowSelect + MaybeSync,
+          T: RowSelect + MaybeSync,
+{
+    fn train_predict(self,
+                     inputs: &I,
+                     targets: &T,
+                     splits: &[(Vec<usize>, Vec<usize>)])
+                     -> LearningResult<Vec<T>> {
+        let train_split = |&(ref train_idx, ref test_idx): &(Vec<usize>, Vec<usize>)|
+                           -> LearningResult<T> {
+            let mut model = self.clone();
+            model.train(&inputs.take_rows(train_idx), &targets.take_rows(train_idx))?;
+            model.predict(&inputs.take_rows(test_idx))
+        };
+
+        #[cfg(not(feature = "parallel"))]
+        let outputs = splits.iter().map(train_split).collect::<Vec<_>>();
+        #[cfg(feature = "parallel")]
+        let outputs = parallel::par_map(splits, train_split);
+
+        outputs.into_iter().collect()
+    }
 }
 
-/// A permutation of 0..n.
-struct ShuffledIndices(Vec<usize>);
+/// The results of cross validating a model.
+#[derive(Clone, Debug)]
+pub struct CrossValidation<T> {
+    num_samples: usize,
+    scores: Vec<f64>,
+    predictions: Vec<(Vec<usize>, T)>,
+}
+
+impl<T> CrossValidation<T> {
+    /// The score of each split.
+    pub fn scores(&self) -> &[f64] {
+        &self.scores
+    }
+
+    /// The test indices of each split with the predictions made for them.
+    pub fn split_predictions(&self) -> &[(Vec<usize>, T)] {
+        &self.predictions
+    }
+}
+
+impl<T: RowSelect> CrossValidation<T> {
+    /// The prediction for every sample, made by the model which
+    /// was not trained on it, in the order of the samples.
+    ///
+    /// # Failures
+    ///
+    /// - The splits did not test every sample exactly once, as with
+    /// `ShuffleSplit` or `TimeSeriesSplit`.
+    pub fn out_of_fold_predictions(&self) -> LearningResult<T> {
+        let mut positions = vec![None; self.num_samples];
+        let mut offset = 0;
+        for &(ref test_idx, _) in &self.predictions {
+            for (j, &i) in test_idx.iter().enumerate() {
+                if positions[i].is_some() {
+                    return Err(Error::new(ErrorKind::InvalidState,
+                                          "A sample was tested in more than one split."));
+                }
+                positions[i] = Some(offset + j);
+            }
+            offset += test_idx.len();
+        }
+
+        let rows = positions.into_iter()
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidState, "Not every sample was tested."))?;
+        let parts = self.predictions.iter().map(|&(_, ref p)| p).collect::<Vec<_>>();
+        Ok(T::stack(&parts)?.take_rows(&rows))
+    }
+}
+
+/// Trait for data sets whose rows are samples which can be selected.
+pub trait RowSelect: Sized {
+    /// The number of rows.
+    fn num_rows(&self) -> usize;
+
+    /// Returns the rows at the given indices, in order.
+    fn take_rows(&self, indices: &[usize]) -> Self;
+
+    /// Returns the rows of all the parts, in order.
+    ///
+    /// Returns an error if the parts do not have matching columns.
+    fn stack(parts: &[&Self]) -> LearningResult<Self>;
+}
+
+impl<T: Copy> RowSelect for Matrix<T> {
+    fn num_rows(&self) -> usize {
+        self.rows()
+    }
+
+    fn take_rows(&self, indices: &[usize]) -> Self {
+        self.select_rows(indices)
+    }
+
+    fn stack(parts: &[&Self]) -> LearningResult<Self> {
+        let cols = parts.first().map_or(0, |p| p.cols());
+        if parts.iter().any(|p| p.cols() != cols) {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The parts must have the same number of columns."));
+        }
+
+        let rows = parts.iter().map(|p| p.rows()).sum();
+        let data = parts.iter().flat_map(|p| p.data().iter().cloned()).collect::<Vec<_>>();
+        Ok(Matrix::new(rows, cols, data))
+    }
+}
+
+impl<T: Copy> RowSelect for Vector<T> {
+    fn num_rows(&self) -> usize {
+        self.size()
+    }
+
+    fn take_rows(&self, indices: &[usize]) -> Self {
+        Vector::new(indices.iter().map(|&i| self[i]).collect::<Vec<_>>())
+    }
+
+    fn stack(parts: &[&Self]) -> LearningResult<Self> {
+        Ok(Vector::new(parts.iter().flat_map(|p| p.data().iter().cloned()).collect::<Vec<_>>()))
+    }
+}
+
+/// Trait for dividing samples into training and test sets.
+pub trait Splitter {
+    /// Returns the training and test indices of each split of
+    /// `num_samples` samples.
+    ///
+    /// Random splitters draw from `rng`, others ignore it. Returns an
+    /// error if the samples cannot be split.
+    fn split<R: Rng>(&self,
+                     num_samples: usize,
+                     rng: &mut R)
+                     -> LearningResult<Vec<(Vec<usize>, Vec<usize>)>>;
+}
+
+/// K-fold splitter.
+///
+/// Divides the samples into k folds of sizes differing by at most one,
+/// and tests on each fold in turn. The samples are shuffled first by default.
+#[derive(Clone, Copy, Debug)]
+pub struct KFold {
+    num_folds: usize,
+    shuffle: bool,
+}
+
+impl KFold {
+    /// Constructs a shuffled k-fold splitter with `num_folds` folds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rand;
+    /// # extern crate rusty_machine;
+    /// use rand::thread_rng;
+    /// use rusty_machine::analysis::cross_validation::{KFold, Splitter};
+    ///
+    /// # fn main() {
+    /// let mut kfold = KFold::new(2).unwrap();
+    /// kfold.set_shuffle(false);
+    ///
+    /// let splits = kfold.split(4, &mut thread_rng()).unwrap();
+    /// assert_eq!(splits[0], (vec![2, 3], vec![0, 1]));
+    /// # }
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - `num_folds` is less than 2.
+    pub fn new(num_folds: usize) -> LearningResult<KFold> {
+        check_num_folds(num_folds)?;
+        Ok(KFold {
+            num_folds: num_folds,
+            shuffle: true,
+        })
+    }
+
+    /// Sets whether the samples are shuffled before dividing them into folds.
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        self.shuffle = shuffle;
+    }
+}
+
+impl Splitter for KFold {
+    /// # Failures
+    ///
+    /// - There are fewer samples than folds.
+    fn split<R: Rng>(&self,
+                     num_samples: usize,
+                     rng: &mut R)
+                     -> LearningResult<Vec<(Vec<usize>, Vec<usize>)>> {
+        if num_samples < self.num_folds {
+            return Err(Error::new(ErrorKind::InvalidData, "Require num_samples >= num_folds"));
+        }
+
+        let mut indices: Vec<usize> = (0..num_samples).collect();
+        if self.shuffle {
+            in_place_fisher_yates_with_rng(&mut indices, rng);
+        }
+        let shuffled_indices = ShuffledIndices(indices);
+
+        Ok(Folds::new(&shuffled_indices, self.num_folds)
+            .map(|p| (p.train_indices_iter.cloned().collect(),
+                      p.test_indices_iter.cloned().collect()))
+            .collect())
+    }
+}
+
+/// Stratified k-fold splitter.
+///
+/// Divides the samples into k folds which each contain close to the
+/// same proportion of every class, which keeps scores on imbalanced
+/// data meaningful. The samples of each class are shuffled first by default.
+#[derive(Clone, Debug)]
+pub struct StratifiedKFold {
+    num_folds: usize,
+    classes: Vec<usize>,
+    shuffle: bool,
+}
+
+impl StratifiedKFold {
+    /// Constructs a shuffled stratified k-fold splitter with `num_folds`
+    /// folds for samples with the given class labels.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rand;
+    /// # extern crate rusty_machine;
+    /// use rand::thread_rng;
+    /// use rusty_machine::analysis::cross_validation::{StratifiedKFold, Splitter};
+    ///
+    /// # fn main() {
+    /// let labels = [0, 0, 0, 0, 1, 1];
+    /// let mut skf = StratifiedKFold::new(2, &labels).unwrap();
+    /// skf.set_shuffle(false);
+    ///
+    /// let splits = skf.split(6, &mut thread_rng()).unwrap();
+    /// assert_eq!(splits[0].1, vec![0, 2, 4]);
+    /// assert_eq!(splits[1].1, vec![1, 3, 5]);
+    /// # }
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - `num_folds` is less than 2.
+    pub fn new<T: Hash + Eq>(num_folds: usize, labels: &[T]) -> LearningResult<StratifiedKFold> {
+        check_num_folds(num_folds)?;
+        Ok(StratifiedKFold {
+            num_folds: num_folds,
+            classes: dense_ids(labels),
+            shuffle: true,
+        })
+    }
+
+    /// Sets whether the samples of each class are shuffled before
+    /// dividing them into folds.
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        self.shuffle = shuffle;
+    }
+}
+
+impl Splitter for StratifiedKFold {
+    /// # Failures
+    ///
+    /// - `num_samples` differs from the number of labels.
+    /// - There are fewer samples than folds.
+    fn split<R: Rng>(&self,
+                     num_samples: usize,
+                     rng: &mut R)
+                     -> LearningResult<Vec<(Vec<usize>, Vec<usize>)>> {
+        if num_samples != self.classes.len() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of samples must match the number of labels."));
+        }
+        if num_samples < self.num_folds {
+            return Err(Error::new(ErrorKind::InvalidData, "Require num_samples >= num_folds"));
+        }
+
+        let num_classes = self.classes.iter().max().map_or(0, |c| c + 1);
+        let mut members = vec![Vec::new(); num_classes];
+        for (i, &c) in self.classes.iter().enumerate() {
+            members[c].push(i);
+        }
+
+        // Deal the samples of each class to the folds in turn, continuing
+        // from the last fold dealt to so that the fold sizes stay balanced.
+        let mut fold_of = vec![0; num_samples];
+        let mut next = 0;
+        for class in &mut members {
+            if self.shuffle {
+                in_place_fisher_yates_with_rng(class, rng);
+            }
+            for &i in class.iter() {
+                fold_of[i] = next % self.num_folds;
+                next += 1;
+            }
+        }
+
+        Ok(fold_partitions(&fold_of, self.num_folds))
+    }
+}
+
+/// Group k-fold splitter.
+///
+/// Divides the samples into k folds such that all samples of a group are
+/// in the same fold, so a model is never tested on a group it was trained
+/// on. Groups are assigned largest first to the fold with fewest samples.
+#[derive(Clone, Debug)]
+pub struct GroupKFold {
+    num_folds: usize,
+    groups: Vec<usize>,
+}
+
+impl GroupKFold {
+    /// Constructs a group k-fold splitter with `num_folds` folds for
+    /// samples in the given groups.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rand;
+    /// # extern crate rusty_machine;
+    /// use rand::thread_rng;
+    /// use rusty_machine::analysis::cross_validation::{GroupKFold, Splitter};
+    ///
+    /// # fn main() {
+    /// let groups = ["a", "a", "a", "b", "b", "c"];
+    /// let gkf = GroupKFold::new(2, &groups).unwrap();
+    ///
+    /// let splits = gkf.split(6, &mut thread_rng()).unwrap();
+    /// assert_eq!(splits[0].1, vec![0, 1, 2]);
+    /// assert_eq!(splits[1].1, vec![3, 4, 5]);
+    /// # }
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - `num_folds` is less than 2.
+    pub fn new<T: Hash + Eq>(num_folds: usize, groups: &[T]) -> LearningResult<GroupKFold> {
+        check_num_folds(num_folds)?;
+        Ok(GroupKFold {
+            num_folds: num_folds,
+            groups: dense_ids(groups),
+        })
+    }
+}
+
+impl Splitter for GroupKFold {
+    /// # Failures
+    ///
+    /// - `num_samples` differs from the number of groups labels.
+    /// - There are fewer distinct groups than folds.
+    fn split<R: Rng>(&self,
+                     num_samples: usize,
+                     _: &mut R)
+                     -> LearningResult<Vec<(Vec<usize>, Vec<usize>)>> {
+        if num_samples != self.groups.len() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of samples must match the number of group labels."));
+        }
+
+        let num_groups = self.groups.iter().max().map_or(0, |g| g + 1);
+        if num_groups < self.num_folds {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "There must be at least as many groups as folds."));
+        }
+
+        let mut group_sizes = vec![0; num_groups];
+        for &g in &self.groups {
+            group_sizes[g] += 1;
+        }
+
+        let mut order = (0..num_groups).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| group_sizes[b].cmp(&group_sizes[a]));
+
+        let mut fold_sizes = vec![0; self.num_folds];
+        let mut group_fold = vec![0; num_groups];
+        for g in order {
+            let fold = (0..self.num_folds)
+                .min_by_key(|&f| fold_sizes[f])
+                .expect("There are at least two folds.");
+            group_fold[g] = fold;
+            fold_sizes[fold] += group_sizes[g];
+        }
+
+        let fold_of = self.groups.iter().map(|&g| group_fold[g]).collect::<Vec<_>>();
+        Ok(fold_partitions(&fold_of, self.num_folds))
+    }
+}
+
+/// Leave-one-out splitter.
+///
+/// Tests on each sample in turn, training on all of the others.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LeaveOneOut;
+
+impl Splitter for LeaveOneOut {
+    /// # Failures
+    ///
+    /// - There are fewer than two samples.
+    fn split<R: Rng>(&self,
+                     num_samples: usize,
+                     _: &mut R)
+                     -> LearningResult<Vec<(Vec<usize>, Vec<usize>)>> {
+        if num_samples < 2 {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Leave one out requires at least two samples."));
+        }
+        Ok((0..num_samples)
+            .map(|i| ((0..num_samples).filter(|&j| j != i).collect(), vec![i]))
+            .collect())
+    }
+}
+
+/// Shuffle splitter.
+///
+/// Draws independent random test sets containing a fixed fraction of
+/// the samples. Unlike k-fold, test sets of different splits may overlap.
+#[derive(Clone, Copy, Debug)]
+pub struct ShuffleSplit {
+    num_splits: usize,
+    test_fraction: f64,
+}
+
+impl ShuffleSplit {
+    /// Constructs a shuffle splitter with `num_splits` splits each testing
+    /// on `test_fraction` of the samples, rounded up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rand;
+    /// # extern crate rusty_machine;
+    /// use rand::thread_rng;
+    /// use rusty_machine::analysis::cross_validation::{ShuffleSplit, Splitter};
+    ///
+    /// # fn main() {
+    /// let splits = ShuffleSplit::new(5, 0.25).unwrap().split(10, &mut thread_rng()).unwrap();
+    /// assert_eq!(splits.len(), 5);
+    /// assert!(splits.iter().all(|s| s.0.len() == 7 && s.1.len() == 3));
+    /// # }
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - `num_splits` is zero.
+    /// - `test_fraction` is not strictly between 0 and 1.
+    pub fn new(num_splits: usize, test_fraction: f64) -> LearningResult<ShuffleSplit> {
+        check_num_splits(num_splits)?;
+        if !(test_fraction > 0f64 && test_fraction < 1f64) {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "The test fraction must be strictly between 0 and 1."));
+        }
+        Ok(ShuffleSplit {
+            num_splits: num_splits,
+            test_fraction: test_fraction,
+        })
+    }
+}
+
+impl Splitter for ShuffleSplit {
+    /// # Failures
+    ///
+    /// - There are fewer than two samples.
+    fn split<R: Rng>(&self,
+                     num_samples: usize,
+                     rng: &mut R)
+                     -> LearningResult<Vec<(Vec<usize>, Vec<usize>)>> {
+        if num_samples < 2 {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Shuffle split requires at least two samples."));
+        }
+        let num_test = ((self.test_fraction * num_samples as f64).ceil() as usize)
+            .max(1)
+            .min(num_samples - 1);
+
+        Ok((0..self.num_splits)
+            .map(|_| {
+                let mut indices = (0..num_samples).collect::<Vec<_>>();
+                in_place_fisher_yates_with_rng(&mut indices, rng);
+                let mut train = indices.split_off(num_test);
+                train.sort_unstable();
+                indices.sort_unstable();
+                (train, indices)
+            })
+            .collect())
+    }
+}
+
+/// Time series splitter.
+///
+/// The samples are assumed to be in temporal order. Divides them into
+/// `num_splits + 1` consecutive blocks and tests on each block after the
+/// first, training only on the samples preceding it. This avoids
+/// training on the future of the test set.
+#[derive(Clone, Copy, Debug)]
+pub struct TimeSeriesSplit {
+    num_splits: usize,
+    max_train_size: Option<usize>,
+}
+
+impl TimeSeriesSplit {
+    /// Constructs a time series splitter with `num_splits` splits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rand;
+    /// # extern crate rusty_machine;
+    /// use rand::thread_rng;
+    /// use rusty_machine::analysis::cross_validation::{TimeSeriesSplit, Splitter};
+    ///
+    /// # fn main() {
+    /// let splits = TimeSeriesSplit::new(3).unwrap().split(8, &mut thread_rng()).unwrap();
+    /// assert_eq!(splits, vec![(vec![0, 1], vec![2, 3]),
+    ///                         (vec![0, 1, 2, 3], vec![4, 5]),
+    ///                         (vec![0, 1, 2, 3, 4, 5], vec![6, 7])]);
+    /// # }
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - `num_splits` is zero.
+    pub fn new(num_splits: usize) -> LearningResult<TimeSeriesSplit> {
+        check_num_splits(num_splits)?;
+        Ok(TimeSeriesSplit {
+            num_splits: num_splits,
+            max_train_size: None,
+        })
+    }
+
+    /// Limits training to the most recent `max_train_size` samples
+    /// before each test set.
+    ///
+    /// # Failures
+    ///
+    /// - `max_train_size` is zero.
+    pub fn set_max_train_size(&mut self, max_train_size: usize) -> LearningResult<()> {
+        if max_train_size == 0 {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "The maximum training size must be positive."));
+        }
+        self.max_train_size = Some(max_train_size);
+        Ok(())
+    }
+}
+
+impl Splitter for TimeSeriesSplit {
+    /// # Failures
+    ///
+    /// - There are not more samples than splits.
+    fn split<R: Rng>(&self,
+                     num_samples: usize,
+                     _: &mut R)
+                     -> LearningResult<Vec<(Vec<usize>, Vec<usize>)>> {
+        if num_samples <= self.num_splits {
+            return Err(Error::new(ErrorKind::InvalidData, "Require num_samples > num_splits"));
+        }
+
+        // Any remainder is added to the first training set.
+        let test_size = num_samples / (self.num_splits + 1);
+        let first_test = num_samples - self.num_splits * test_size;
 
-/// Permute the indices of the inputs samples.
-fn create_shuffled_indices(num_samples: usize) -> ShuffledIndices {
-    let mut indices: Vec<usize> = (0..num_samples).collect();
-    in_place_fisher_yates(&mut indices);
-    ShuffledIndices(indices)
+        Ok((0..self.num_splits)
+            .map(|i| {
+                let test_start = first_test + i * test_size;
+                let train_start = self.max_train_size
+                    .map_or(0, |m| test_start.saturating_sub(m));
+                ((train_start..test_start).collect(),
+                 (test_start..test_start + test_size).collect())
+            })
+            .collect())
+    }
+}
+
+/// Checks that a k-fold splitter has at least two folds.
+fn check_num_folds(num_folds: usize) -> LearningResult<()> {
+    if num_folds < 2 {
+        return Err(Error::new(ErrorKind::InvalidParameters, "There must be at least two folds."));
+    }
+    Ok(())
+}
+
+/// Checks that a splitter makes at least one split.
+fn check_num_splits(num_splits: usize) -> LearningResult<()> {
+    if num_splits == 0 {
+        return Err(Error::new(ErrorKind::InvalidParameters, "There must be at least one split."));
+    }
+    Ok(())
+}
+
+/// Maps labels to ids 0..n in order of first appearance.
+fn dense_ids<T: Hash + Eq>(labels: &[T]) -> Vec<usize> {
+    let mut ids = HashMap::new();
+    labels.iter()
+        .map(|l| {
+            let next = ids.len();
+            *ids.entry(l).or_insert(next)
+        })
+        .collect()
 }
 
+/// Returns the training and test indices of each fold, given
+/// the fold of each sample.
+fn fold_partitions(fold_of: &[usize], num_folds: usize) -> Vec<(Vec<usize>, Vec<usize>)> {
+    (0..num_folds)
+        .map(|f| {
+            let (test, train): (Vec<usize>, Vec<usize>) =
+                (0..fold_of.len()).partition(|&i| fold_of[i] == f);
+            (train, test)
+        })
+        .collect()
+}
+
+/// A permutation of 0..n.
+struct ShuffledIndices(Vec<usize>);
+
 /// A partition of indices of all available samples into
 /// a training set and a test set.
 struct Partition<'a> {
@@ -199,7 +1027,151 @@ impl<'a> Iterator for Folds<'a> {
 
 #[cfg(test)]
 mod tests {
-    use super::{ShuffledIndices, Folds};
+    use super::{ShuffledIndices, Folds, Splitter, KFold, StratifiedKFold, GroupKFold,
+                LeaveOneOut, ShuffleSplit, TimeSeriesSplit, RowSelect, CrossValidation};
+    use linalg::{Matrix, Vector};
+    use rand::{SeedableRng, StdRng};
+
+    fn rng() -> StdRng {
+        let seed: &[_] = &[1, 2, 3];
+        StdRng::from_seed(seed)
+    }
+
+    // Every sample is tested exactly once, and never trained on in the same split.
+    fn assert_partitions(splits: &[(Vec<usize>, Vec<usize>)], num_samples: usize) {
+        let mut tested = vec![0; num_samples];
+        for &(ref train, ref test) in splits {
+            assert_eq!(train.len() + test.len(), num_samples);
+            for &i in test {
+                assert!(!train.contains(&i));
+                tested[i] += 1;
+            }
+        }
+        assert!(tested.iter().all(|&t| t == 1));
+    }
+
+    #[test]
+    fn test_kfold() {
+        let mut kfold = KFold::new(3).unwrap();
+        kfold.set_shuffle(false);
+        assert_eq!(kfold.split(6, &mut rng()).unwrap(), vec![
+            (vec![2, 3, 4, 5], vec![0, 1]),
+            (vec![0, 1, 4, 5], vec![2, 3]),
+            (vec![0, 1, 2, 3], vec![4, 5])
+            ]);
+
+        let splits = KFold::new(3).unwrap().split(10, &mut rng()).unwrap();
+        assert_partitions(&splits, 10);
+        assert_eq!(splits.iter().map(|s| s.1.len()).collect::<Vec<_>>(), vec![4, 3, 3]);
+    }
+
+    #[test]
+    fn test_kfold_invalid() {
+        assert!(KFold::new(1).is_err());
+        assert!(KFold::new(3).unwrap().split(2, &mut rng()).is_err());
+    }
+
+    #[test]
+    fn test_stratified_kfold() {
+        // An imbalanced set with 8 samples of class "a" and 4 of class "b".
+        let labels = ["a", "b", "a", "a", "b", "a", "a", "a", "b", "a", "b", "a"];
+        let splits = StratifiedKFold::new(4, &labels).unwrap().split(12, &mut rng()).unwrap();
+
+        assert_partitions(&splits, 12);
+        for &(_, ref test) in &splits {
+            assert_eq!(test.iter().filter(|&&i| labels[i] == "a").count(), 2);
+            assert_eq!(test.iter().filter(|&&i| labels[i] == "b").count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_stratified_kfold_uneven() {
+        let labels = [0, 0, 0, 1, 1];
+        let mut skf = StratifiedKFold::new(2, &labels).unwrap();
+        skf.set_shuffle(false);
+
+        assert_eq!(skf.split(5, &mut rng()).unwrap(), vec![
+            (vec![1, 3], vec![0, 2, 4]),
+            (vec![0, 2, 4], vec![1, 3])
+            ]);
+    }
+
+    #[test]
+    fn test_stratified_kfold_wrong_length() {
+        let labels = [0, 1, 0];
+        assert!(StratifiedKFold::new(2, &labels).unwrap().split(4, &mut rng()).is_err());
+    }
+
+    #[test]
+    fn test_group_kfold() {
+        let groups = [3, 1, 1, 2, 3, 3, 2, 1, 1, 4];
+        let splits = GroupKFold::new(3, &groups).unwrap().split(10, &mut rng()).unwrap();
+
+        assert_partitions(&splits, 10);
+        for &(ref train, ref test) in &splits {
+            for &i in test {
+                assert!(train.iter().all(|&j| groups[j] != groups[i]));
+            }
+        }
+        // Group 1 has 4 samples, group 3 has 3, and groups 2 and 4 share a fold.
+        assert_eq!(splits[0].1, vec![1, 2, 7, 8]);
+        assert_eq!(splits[1].1, vec![0, 4, 5]);
+        assert_eq!(splits[2].1, vec![3, 6, 9]);
+    }
+
+    #[test]
+    fn test_group_kfold_too_few_groups() {
+        let groups = [0, 0, 1, 1];
+        assert!(GroupKFold::new(3, &groups).unwrap().split(4, &mut rng()).is_err());
+    }
+
+    #[test]
+    fn test_leave_one_out() {
+        let splits = LeaveOneOut.split(3, &mut rng()).unwrap();
+        assert_eq!(splits, vec![
+            (vec![1, 2], vec![0]),
+            (vec![0, 2], vec![1]),
+            (vec![0, 1], vec![2])
+            ]);
+    }
+
+    #[test]
+    fn test_shuffle_split() {
+        let splits = ShuffleSplit::new(4, 0.3).unwrap().split(10, &mut rng()).unwrap();
+
+        assert_eq!(splits.len(), 4);
+        for &(ref train, ref test) in &splits {
+            assert_eq!(test.len(), 3);
+            assert_eq!(train.len(), 7);
+            assert!(test.iter().all(|i| !train.contains(i)));
+        }
+    }
+
+    #[test]
+    fn test_shuffle_split_invalid_fraction() {
+        assert!(ShuffleSplit::new(4, 1.).is_err());
+    }
+
+    #[test]
+    fn test_time_series_split() {
+        let splits = TimeSeriesSplit::new(3).unwrap().split(10, &mut rng()).unwrap();
+        assert_eq!(splits, vec![
+            (vec![0, 1, 2, 3], vec![4, 5]),
+            (vec![0, 1, 2, 3, 4, 5], vec![6, 7]),
+            (vec![0, 1, 2, 3, 4, 5, 6, 7], vec![8, 9])
+            ]);
+
+        let mut tss = TimeSeriesSplit::new(3).unwrap();
+        tss.set_max_train_size(3).unwrap();
+        let splits = tss.split(10, &mut rng()).unwrap();
+        assert_eq!(splits[0], (vec![1, 2, 3], vec![4, 5]));
+        assert_eq!(splits[2], (vec![5, 6, 7], vec![8, 9]));
+    }
+
+    #[test]
+    fn test_time_series_split_too_few_samples() {
+        assert!(TimeSeriesSplit::new(3).unwrap().split(3, &mut rng()).is_err());
+    }
 
     // k % n == 0
     #[test]
@@ -275,6 +1247,68 @@ mod tests {
             ]);
     }
 
+    #[test]
+    fn test_matrix_row_select() {
+        let m = Matrix::new(3, 2, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(m.num_rows(), 3);
+
+        let selected = m.take_rows(&[2, 0]);
+        assert_eq!(selected, Matrix::new(2, 2, vec![4, 5, 0, 1]));
+
+        let stacked = Matrix::stack(&[&selected, &m.take_rows(&[1])]).unwrap();
+        assert_eq!(stacked, Matrix::new(3, 2, vec![4, 5, 0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn test_matrix_stack_mismatched_cols() {
+        let a = Matrix::new(1, 2, vec![0., 1.]);
+        let b = Matrix::new(1, 3, vec![0., 1., 2.]);
+        assert!(Matrix::stack(&[&a, &b]).is_err());
+    }
+
+    #[test]
+    fn test_vector_row_select() {
+        let v = Vector::new(vec![3usize, 1, 4, 1, 5]);
+        assert_eq!(v.num_rows(), 5);
+
+        let selected = v.take_rows(&[4, 0, 2]);
+        assert_eq!(selected, Vector::new(vec![5, 3, 4]));
+
+        let stacked = Vector::stack(&[&selected, &v.take_rows(&[1])]).unwrap();
+        assert_eq!(stacked, Vector::new(vec![5, 3, 4, 1]));
+    }
+
+    #[test]
+    fn test_out_of_fold_predictions() {
+        let cv = CrossValidation {
+            num_samples: 5,
+            scores: vec![0., 0.],
+            predictions: vec![(vec![3, 0], Vector::new(vec![30., 0.])),
+                              (vec![4, 1, 2], Vector::new(vec![40., 10., 20.]))],
+        };
+
+        assert_eq!(cv.out_of_fold_predictions().unwrap(),
+                   Vector::new(vec![0., 10., 20., 30., 40.]));
+    }
+
+    #[test]
+    fn test_out_of_fold_predictions_incomplete() {
+        let missing = CrossValidation {
+            num_samples: 3,
+            scores: vec![0.],
+            predictions: vec![(vec![0, 2], Vector::new(vec![0., 2.]))],
+        };
+        assert!(missing.out_of_fold_predictions().is_err());
+
+        let repeated = CrossValidation {
+            num_samples: 2,
+            scores: vec![0., 0.],
+            predictions: vec![(vec![0, 1], Vector::new(vec![0., 1.])),
+                              (vec![1], Vector::new(vec![1.]))],
+        };
+        assert!(repeated.out_of_fold_predictions().is_err());
+    }
+
     fn collect_folds<'a>(folds: Folds<'a>) -> Vec<(Vec<usize>, Vec<usize>)> {
         folds
             .map(|p|
@@ -283,3 +1317,50 @@ mod tests {
             .collect::<Vec<(Vec<usize>, Vec<usize>)>>()
     }
 }
+
+#[cfg(all(test, feature = "parallel"))]
+mod par_tests {
+    use super::{k_fold_validate_with_rng, KFold};
+    use analysis::score::neg_mean_squared_error;
+    use learning::{LearningResult, SupModel};
+    use learning::toolkit::parallel;
+    use linalg::{Axes, BaseMatrix, Matrix};
+    use rand::{SeedableRng, StdRng};
+
+    /// Predicts the column means of the training targets.
+    #[derive(Clone)]
+    struct MeanModel {
+        means: Vec<f64>,
+    }
+
+    impl SupModel<Matrix<f64>, Matrix<f64>> for MeanModel {
+        fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+            let data = (0..inputs.rows()).flat_map(|_| self.means.clone()).collect::<Vec<_>>();
+            Ok(Matrix::new(inputs.rows(), self.means.len(), data))
+        }
+
+        fn train(&mut self, _: &Matrix<f64>, targets: &Matrix<f64>) -> LearningResult<()> {
+            self.means = targets.mean(Axes::Row).into_vec();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_cloned_k_fold_independent_of_threads() {
+        let inputs = Matrix::new(9, 1, (0..9).map(|x| x as f64).collect::<Vec<_>>());
+        let targets = Matrix::new(9, 1, vec![1.0, 4.0, 2.0, 8.0, 5.0, 7.0, 3.0, 6.0, 0.0]);
+        let seed: &[_] = &[4, 2];
+        let model = MeanModel { means: Vec::new() };
+
+        let validate = || {
+            k_fold_validate_with_rng(&model, &inputs, &targets, &KFold::new(3).unwrap(),
+                                     neg_mean_squared_error,
+                                     &mut StdRng::from_seed(seed)).unwrap()
+        };
+        let single = parallel::with_num_threads(1, &validate);
+        let multi = parallel::with_num_threads(4, &validate);
+
+        assert_eq!(single.len(), 3);
+        assert_eq!(single, multi);
+    }
+}
@@ -1,11 +1,18 @@
 //! Functions for scoring a set of predictions, i.e. evaluating
-//! how close predictions and truth are. All functions in this
+//! how close predictions and truth are. All scores in this
 //! module obey the convention that higher is better.
 
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
 use libnum::{Zero, One};
+use rulinalg::utils;
 
-use linalg::{BaseMatrix, Matrix};
+use linalg::{BaseMatrix, Matrix, Vector};
+use analysis::confusion_matrix::confusion_matrix;
 use learning::toolkit::cost_fn::{CostFunc, MeanSqError};
+use learning::toolkit::distance::{Metric, Euclidean};
 
 // ************************************
 // Classification Scores
@@ -197,161 +204,2221 @@ pub fn f1<'a, I, T>(outputs: I, targets: I) -> f64
     2.0f64 * tpos / (2.0f64 * tpos + fneg + fpos)
 }
 
-// ************************************
-// Regression Scores
-// ************************************
+/// The averaging of a per-class score over the classes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Average {
+    /// The unweighted mean of the per-class scores.
+    Macro,
+    /// The score computed from the counts pooled over all classes.
+    Micro,
+    /// The mean of the per-class scores weighted by their support.
+    Weighted,
+}
 
-// TODO: generalise to accept arbitrary iterators of diff-able things
-/// Returns the additive inverse of the mean-squared-error of the
-/// outputs. So higher is better, and the returned value is always
-/// negative.
-pub fn neg_mean_squared_error(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64
+/// Returns the precision score for multi class classification.
+///
+/// # Arguments
+///
+/// * `outputs` - Iterator of output (predicted) labels.
+/// * `targets` - Iterator of expected (actual) labels.
+/// * `average` - How the per-class precisions are averaged.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::{multiclass_precision, Average};
+/// let outputs = [0, 0, 2, 2, 0, 2];
+/// let targets = [2, 0, 2, 2, 0, 1];
+///
+/// let score = multiclass_precision(outputs.iter(), targets.iter(), Average::Macro);
+/// assert!((score - 4. / 9.).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different length
+/// - outputs and targets are empty
+pub fn multiclass_precision<'a, I, T>(outputs: I, targets: I, average: Average) -> f64
+    where I: IntoIterator<Item=&'a T>,
+          T: 'a + Ord + Eq + Hash + Copy
 {
-    // MeanSqError divides the actual mean squared error by two.
-    -2f64 * MeanSqError::cost(outputs, targets)
+    report_from_iters(outputs, targets).precision(average)
 }
 
-#[cfg(test)]
-mod tests {
-    use linalg::Matrix;
-    use super::{accuracy, precision, recall, f1, neg_mean_squared_error};
+/// Returns the recall score for multi class classification.
+///
+/// # Arguments
+///
+/// * `outputs` - Iterator of output (predicted) labels.
+/// * `targets` - Iterator of expected (actual) labels.
+/// * `average` - How the per-class recalls are averaged.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::{multiclass_recall, Average};
+/// let outputs = [0, 0, 2, 2, 0, 2];
+/// let targets = [2, 0, 2, 2, 0, 1];
+///
+/// let score = multiclass_recall(outputs.iter(), targets.iter(), Average::Macro);
+/// assert!((score - 5. / 9.).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different length
+/// - outputs and targets are empty
+pub fn multiclass_recall<'a, I, T>(outputs: I, targets: I, average: Average) -> f64
+    where I: IntoIterator<Item=&'a T>,
+          T: 'a + Ord + Eq + Hash + Copy
+{
+    report_from_iters(outputs, targets).recall(average)
+}
 
-    #[test]
-    fn test_accuracy() {
-        let outputs = [1, 2, 3, 4, 5, 6];
-        let targets = [1, 2, 3, 3, 5, 1];
-        assert_eq!(accuracy(outputs.iter(), targets.iter()), 2f64/3f64);
+/// Returns the f1 score for multi class classification.
+///
+/// # Arguments
+///
+/// * `outputs` - Iterator of output (predicted) labels.
+/// * `targets` - Iterator of expected (actual) labels.
+/// * `average` - How the per-class f1 scores are averaged.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::{multiclass_f1, Average};
+/// let outputs = [0, 0, 2, 2, 0, 2];
+/// let targets = [2, 0, 2, 2, 0, 1];
+///
+/// let score = multiclass_f1(outputs.iter(), targets.iter(), Average::Weighted);
+/// assert!((score - 0.6).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different length
+/// - outputs and targets are empty
+pub fn multiclass_f1<'a, I, T>(outputs: I, targets: I, average: Average) -> f64
+    where I: IntoIterator<Item=&'a T>,
+          T: 'a + Ord + Eq + Hash + Copy
+{
+    report_from_iters(outputs, targets).f1(average)
+}
 
-        let outputs = [1, 1, 1, 0, 0, 0];
-        let targets = [1, 1, 1, 0, 0, 1];
-        assert_eq!(accuracy(outputs.iter(), targets.iter()), 5.0f64 / 6.0f64);
+/// Builds the classification report of labels given by iterators.
+fn report_from_iters<'a, I, T>(outputs: I, targets: I) -> ClassificationReport<T>
+    where I: IntoIterator<Item=&'a T>,
+          T: 'a + Ord + Eq + Hash + Copy
+{
+    let outputs = outputs.into_iter().cloned().collect::<Vec<_>>();
+    let targets = targets.into_iter().cloned().collect::<Vec<_>>();
+    classification_report(&outputs, &targets)
+}
+
+/// Per-class precision, recall and f1 scores of a classification.
+///
+/// A class which is never predicted has a precision of 0, and a
+/// class which never occurs has a recall of 0.
+///
+/// The report is displayed as a table of the per-class and averaged scores.
+#[derive(Clone, Debug)]
+pub struct ClassificationReport<T> {
+    labels: Vec<T>,
+    confusion: Matrix<usize>,
+}
+
+/// Returns the classification report of a set of predictions.
+///
+/// The classes are the distinct labels appearing in either
+/// outputs or targets, in increasing order.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::{classification_report, Average};
+/// let outputs = [0, 0, 2, 2, 0, 2];
+/// let targets = [2, 0, 2, 2, 0, 1];
+///
+/// let report = classification_report(&outputs, &targets);
+/// assert_eq!(report.labels(), &[0, 1, 2]);
+/// assert_eq!(report.support(), vec![2, 1, 3]);
+/// assert_eq!(report.recall_per_class()[0], 1.);
+/// assert!((report.f1(Average::Micro) - 2. / 3.).abs() < 1e-12);
+///
+/// println!("{}", report);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different length
+/// - outputs and targets are empty
+pub fn classification_report<T>(outputs: &[T], targets: &[T]) -> ClassificationReport<T>
+    where T: Ord + Eq + Hash + Copy
+{
+    assert!(!outputs.is_empty(), "outputs and targets must not be empty");
+    let confusion = confusion_matrix(outputs, targets, None);
+
+    let mut labels = outputs.iter().chain(targets).cloned().collect::<Vec<_>>();
+    labels.sort();
+    labels.dedup();
+
+    ClassificationReport {
+        labels: labels,
+        confusion: confusion,
     }
+}
 
-    #[test]
-    fn test_precision() {
-        let outputs = [1, 1, 1, 0, 0, 0];
-        let targets = [1, 1, 0, 0, 1, 1];
-        assert_eq!(precision(outputs.iter(), targets.iter()), 2.0f64 / 3.0f64);
+impl<T> ClassificationReport<T> {
+    /// The class labels, in the order of the per-class scores.
+    pub fn labels(&self) -> &[T] {
+        &self.labels
+    }
 
-        let outputs = [1, 1, 1, 0, 1, 1];
-        let targets = [1, 1, 0, 0, 1, 1];
-        assert_eq!(precision(outputs.iter(), targets.iter()), 0.8);
+    /// The confusion matrix the report is built from.
+    ///
+    /// Rows are actual classes and columns are predicted classes.
+    pub fn confusion_matrix(&self) -> &Matrix<usize> {
+        &self.confusion
+    }
 
-        let outputs = [0, 0, 0, 1, 1, 1];
-        let targets = [1, 1, 1, 1, 1, 0];
-        assert_eq!(precision(outputs.iter(), targets.iter()), 2.0f64 / 3.0f64);
+    /// The number of samples actually in each class.
+    pub fn support(&self) -> Vec<usize> {
+        (0..self.labels.len())
+            .map(|i| (0..self.labels.len()).map(|j| self.confusion[[i, j]]).sum())
+            .collect()
+    }
 
-        let outputs = [1, 1, 1, 1, 1, 0];
-        let targets = [0, 0, 0, 1, 1, 1];
-        assert_eq!(precision(outputs.iter(), targets.iter()), 0.4);
+    /// The fraction of samples which were correctly classified.
+    pub fn accuracy(&self) -> f64 {
+        let correct = (0..self.labels.len()).map(|i| self.confusion[[i, i]]).sum::<usize>();
+        correct as f64 / self.confusion.data().iter().sum::<usize>() as f64
     }
 
-    #[test]
-    #[should_panic]
-    fn test_precision_outputs_not_2class() {
-        let outputs = [1, 2, 1, 0, 0, 0];
-        let targets = [1, 1, 0, 0, 1, 1];
-        precision(outputs.iter(), targets.iter());
+    /// The precision of each class.
+    pub fn precision_per_class(&self) -> Vec<f64> {
+        let predicted = self.predicted();
+        (0..self.labels.len())
+            .map(|i| ratio(self.confusion[[i, i]], predicted[i]))
+            .collect()
     }
 
-    #[test]
-    #[should_panic]
-    fn test_precision_targets_not_2class() {
-        let outputs = [1, 0, 1, 0, 0, 0];
-        let targets = [1, 2, 0, 0, 1, 1];
-        precision(outputs.iter(), targets.iter());
+    /// The recall of each class.
+    pub fn recall_per_class(&self) -> Vec<f64> {
+        let support = self.support();
+        (0..self.labels.len())
+            .map(|i| ratio(self.confusion[[i, i]], support[i]))
+            .collect()
     }
 
-    #[test]
-    fn test_recall() {
-        let outputs = [1, 1, 1, 0, 0, 0];
-        let targets = [1, 1, 0, 0, 1, 1];
-        assert_eq!(recall(outputs.iter(), targets.iter()), 0.5);
+    /// The f1 score of each class.
+    pub fn f1_per_class(&self) -> Vec<f64> {
+        let predicted = self.predicted();
+        let support = self.support();
+        (0..self.labels.len())
+            .map(|i| ratio(2 * self.confusion[[i, i]], predicted[i] + support[i]))
+            .collect()
+    }
 
-        let outputs = [1, 1, 1, 0, 1, 1];
-        let targets = [1, 1, 0, 0, 1, 1];
-        assert_eq!(recall(outputs.iter(), targets.iter()), 1.0);
+    /// The averaged precision.
+    pub fn precision(&self, average: Average) -> f64 {
+        self.average(self.precision_per_class(), average)
+    }
 
-        let outputs = [0, 0, 0, 1, 1, 1];
-        let targets = [1, 1, 1, 1, 1, 0];
-        assert_eq!(recall(outputs.iter(), targets.iter()), 0.4);
+    /// The averaged recall.
+    pub fn recall(&self, average: Average) -> f64 {
+        self.average(self.recall_per_class(), average)
+    }
 
-        let outputs = [1, 1, 1, 1, 1, 0];
-        let targets = [0, 0, 0, 1, 1, 1];
-        assert_eq!(recall(outputs.iter(), targets.iter()), 2.0f64 / 3.0f64);
+    /// The averaged f1 score.
+    pub fn f1(&self, average: Average) -> f64 {
+        self.average(self.f1_per_class(), average)
     }
 
-    #[test]
-    #[should_panic]
-    fn test_recall_outputs_not_2class() {
-        let outputs = [1, 2, 1, 0, 0, 0];
-        let targets = [1, 1, 0, 0, 1, 1];
-        recall(outputs.iter(), targets.iter());
+    /// The number of samples predicted in each class.
+    fn predicted(&self) -> Vec<usize> {
+        (0..self.labels.len())
+            .map(|j| (0..self.labels.len()).map(|i| self.confusion[[i, j]]).sum())
+            .collect()
     }
 
-    #[test]
-    #[should_panic]
-    fn test_recall_targets_not_2class() {
-        let outputs = [1, 0, 1, 0, 0, 0];
-        let targets = [1, 2, 0, 0, 1, 1];
-        recall(outputs.iter(), targets.iter());
+    fn average(&self, scores: Vec<f64>, average: Average) -> f64 {
+        match average {
+            Average::Macro => scores.iter().sum::<f64>() / scores.len() as f64,
+            // Each sample is counted once as a true or false positive and once
+            // as a true positive or false negative, so all micro averages agree.
+            Average::Micro => self.accuracy(),
+            Average::Weighted => {
+                let support = self.support();
+                let total = support.iter().sum::<usize>() as f64;
+                scores.iter().zip(support).map(|(s, n)| s * n as f64).sum::<f64>() / total
+            }
+        }
     }
+}
 
-    #[test]
-    fn test_f1() {
-        let outputs = [1, 1, 1, 0, 0, 0];
-        let targets = [1, 1, 0, 0, 1, 1];
-        assert_eq!(f1(outputs.iter(), targets.iter()), 0.5714285714285714);
+impl<T: fmt::Display> fmt::Display for ClassificationReport<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = self.labels.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+        let width = names.iter().map(|n| n.len()).max().unwrap_or(0).max("weighted avg".len());
+        let support = self.support();
+        let total = support.iter().sum::<usize>();
 
-        let outputs = [1, 1, 1, 0, 1, 1];
-        let targets = [1, 1, 0, 0, 1, 1];
-        assert_eq!(f1(outputs.iter(), targets.iter()), 0.8888888888888888);
+        writeln!(f, "{:>w$} {:>9} {:>9} {:>9} {:>9}",
+                 "", "precision", "recall", "f1-score", "support", w = width)?;
+        writeln!(f)?;
 
-        let outputs = [0, 0, 0, 1, 1, 1];
-        let targets = [1, 1, 1, 1, 1, 0];
-        assert_eq!(f1(outputs.iter(), targets.iter()), 0.5);
+        let rows = names.iter()
+            .zip(self.precision_per_class())
+            .zip(self.recall_per_class())
+            .zip(self.f1_per_class())
+            .zip(support);
+        for ((((name, p), r), f1), n) in rows {
+            writeln!(f, "{:>w$} {:>9.2} {:>9.2} {:>9.2} {:>9}", name, p, r, f1, n, w = width)?;
+        }
+        writeln!(f)?;
 
-        let outputs = [1, 1, 1, 1, 1, 0];
-        let targets = [0, 0, 0, 1, 1, 1];
-        assert_eq!(f1(outputs.iter(), targets.iter()), 0.5);
+        writeln!(f, "{:>w$} {:>9} {:>9} {:>9.2} {:>9}",
+                 "accuracy", "", "", self.accuracy(), total, w = width)?;
+        let averages = [("macro avg", Average::Macro), ("weighted avg", Average::Weighted)];
+        for &(name, average) in &averages {
+            writeln!(f, "{:>w$} {:>9.2} {:>9.2} {:>9.2} {:>9}",
+                     name,
+                     self.precision(average),
+                     self.recall(average),
+                     self.f1(average),
+                     total,
+                     w = width)?;
+        }
+        Ok(())
     }
+}
 
+/// Returns the ratio of two counts, or 0 if the denominator is 0.
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0f64
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
 
-    #[test]
-    #[should_panic]
-    fn test_f1_outputs_not_2class() {
-        let outputs = [1, 2, 1, 0, 0, 0];
-        let targets = [1, 1, 0, 0, 1, 1];
-        f1(outputs.iter(), targets.iter());
+// ************************************
+// Probability Scores
+// ************************************
+
+/// Returns the additive inverse of the Brier score of predicted
+/// probabilities for 2 class classification.
+///
+/// The Brier score is the mean squared difference between the predicted
+/// probability of the positive class and the target, see
+/// [Brier score](https://en.wikipedia.org/wiki/Brier_score) for details.
+/// So higher is better, and the returned value is never positive.
+///
+/// # Arguments
+///
+/// * `probs` - Iterator of predicted probabilities of the positive class.
+/// * `targets` - Iterator of expected (actual) labels which only contains 0 or 1.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::neg_brier_score;
+/// let probs = [0.9, 0.2, 0.6, 0.5];
+/// let targets = [1., 0., 0., 1.];
+///
+/// let score = neg_brier_score(probs.iter(), targets.iter());
+/// assert!((score + 0.165).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - probs and targets have different length
+/// - probs or targets are empty
+/// - targets contains a value which is not 0 or 1
+pub fn neg_brier_score<'a, I>(probs: I, targets: I) -> f64
+    where I: ExactSizeIterator<Item=&'a f64>
+{
+    assert!(probs.len() == targets.len(), "probs and targets must have the same length");
+    assert!(probs.len() > 0, "probs and targets must not be empty");
+
+    let len = probs.len() as f64;
+    let mut sum = 0f64;
+    for (p, t) in probs.zip(targets) {
+        if (*t != 0f64) & (*t != 1f64) {
+            panic!("brier score must be used for 2 class classification")
+        }
+        sum += (p - t) * (p - t);
     }
+    -sum / len
+}
 
-    #[test]
-    #[should_panic]
-    fn test_f1_targets_not_2class() {
-        let outputs = [1, 0, 1, 0, 0, 0];
-        let targets = [1, 2, 0, 0, 1, 1];
-        f1(outputs.iter(), targets.iter());
+/// Returns the reliability curve of predicted probabilities for 2 class
+/// classification.
+///
+/// The probabilities are grouped into `bins` equal width bins over [0, 1].
+/// For each non-empty bin the mean predicted probability and the fraction
+/// of positive targets are returned, in increasing order of probability.
+/// A well calibrated model has the two close to equal.
+///
+/// # Arguments
+///
+/// * `probs` - Iterator of predicted probabilities of the positive class.
+/// * `targets` - Iterator of expected (actual) labels which only contains 0 or 1.
+/// * `bins` - The number of bins.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::reliability_curve;
+/// let probs = [0.1, 0.2, 0.3, 0.8, 0.9];
+/// let targets = [0., 0., 1., 1., 1.];
+///
+/// let (mean_probs, frac_pos) = reliability_curve(probs.iter(), targets.iter(), 2);
+/// assert!((mean_probs[0] - 0.2).abs() < 1e-12);
+/// assert!((mean_probs[1] - 0.85).abs() < 1e-12);
+/// assert_eq!(frac_pos, vec![1. / 3., 1.]);
+/// ```
+///
+/// # Panics
+///
+/// - probs and targets have different length
+/// - bins is zero
+/// - probs contains a value outside of [0, 1]
+/// - targets contains a value which is not 0 or 1
+pub fn reliability_curve<'a, I>(probs: I, targets: I, bins: usize) -> (Vec<f64>, Vec<f64>)
+    where I: ExactSizeIterator<Item=&'a f64>
+{
+    assert!(probs.len() == targets.len(), "probs and targets must have the same length");
+    assert!(bins > 0, "there must be at least one bin");
+
+    let mut prob_sums = vec![0f64; bins];
+    let mut pos_counts = vec![0f64; bins];
+    let mut counts = vec![0f64; bins];
+
+    for (p, t) in probs.zip(targets) {
+        if (*p < 0f64) | (*p > 1f64) {
+            panic!("probabilities must lie in [0, 1]")
+        }
+        if (*t != 0f64) & (*t != 1f64) {
+            panic!("reliability curve must be used for 2 class classification")
+        }
+
+        // A probability of exactly 1 belongs to the last bin.
+        let bin = ((p * bins as f64) as usize).min(bins - 1);
+        prob_sums[bin] += *p;
+        pos_counts[bin] += *t;
+        counts[bin] += 1f64;
     }
 
-    #[test]
-    fn test_neg_mean_squared_error_1d() {
-        let outputs = Matrix::new(3, 1, vec![1f64, 2f64, 3f64]);
-        let targets = Matrix::new(3, 1, vec![2f64, 4f64, 3f64]);
-        assert_eq!(neg_mean_squared_error(&outputs, &targets), -5f64/3f64);
+    let mut mean_probs = Vec::with_capacity(bins);
+    let mut frac_pos = Vec::with_capacity(bins);
+    for ((p, t), c) in prob_sums.into_iter().zip(pos_counts).zip(counts) {
+        if c > 0f64 {
+            mean_probs.push(p / c);
+            frac_pos.push(t / c);
+        }
     }
+    (mean_probs, frac_pos)
+}
 
-    #[test]
-    fn test_neg_mean_squared_error_2d() {
-        let outputs = Matrix::new(3, 2, vec![
-            1f64, 2f64,
-            3f64, 4f64,
-            5f64, 6f64
-            ]);
-        let targets = Matrix::new(3, 2, vec![
-            1.5f64, 2.5f64,
-            5f64,   6f64,
-            5.5f64, 6.5f64
-            ]);
-        assert_eq!(neg_mean_squared_error(&outputs, &targets), -3f64);
+/// Returns the receiver operating characteristic curve of scores for
+/// 2 class classification.
+///
+/// For each distinct score, in decreasing order, the false positive rate
+/// and true positive rate of classifying the samples with at least that
+/// score as positive are returned with the score as threshold. The curve
+/// starts at (0, 0) with an infinite threshold. See
+/// [ROC curve](https://en.wikipedia.org/wiki/Receiver_operating_characteristic)
+/// for details.
+///
+/// # Arguments
+///
+/// * `scores` - Iterator of scores, higher meaning more likely positive.
+/// * `targets` - Iterator of expected (actual) labels which only contains 0 or 1.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::roc_curve;
+/// let scores = [0.1, 0.4, 0.35, 0.8];
+/// let targets = [0., 0., 1., 1.];
+///
+/// let (fpr, tpr, thresholds) = roc_curve(scores.iter(), targets.iter());
+/// assert_eq!(fpr, vec![0., 0., 0.5, 0.5, 1.]);
+/// assert_eq!(tpr, vec![0., 0.5, 0.5, 1., 1.]);
+/// assert_eq!(&thresholds[1..], &[0.8, 0.4, 0.35, 0.1]);
+/// ```
+///
+/// # Panics
+///
+/// - scores and targets have different length
+/// - scores and targets are empty
+/// - targets contains a value which is not 0 or 1
+/// - targets does not contain both classes
+pub fn roc_curve<'a, I>(scores: I, targets: I) -> (Vec<f64>, Vec<f64>, Vec<f64>)
+    where I: ExactSizeIterator<Item=&'a f64>
+{
+    let (tps, fps, mut thresholds) = threshold_counts(scores, targets);
+    let pos = tps[tps.len() - 1];
+    let neg = fps[fps.len() - 1];
+    assert!(pos > 0f64 && neg > 0f64, "roc curve requires both classes in targets");
+
+    let mut fpr = vec![0f64];
+    let mut tpr = vec![0f64];
+    fpr.extend(fps.iter().map(|fp| fp / neg));
+    tpr.extend(tps.iter().map(|tp| tp / pos));
+    thresholds.insert(0, f64::INFINITY);
+    (fpr, tpr, thresholds)
+}
+
+/// Returns the area under the receiver operating characteristic curve of
+/// scores for 2 class classification.
+///
+/// This is the probability that a random positive sample scores higher
+/// than a random negative one, counting ties as half.
+///
+/// # Arguments
+///
+/// * `scores` - Iterator of scores, higher meaning more likely positive.
+/// * `targets` - Iterator of expected (actual) labels which only contains 0 or 1.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::roc_auc_score;
+/// let scores = [0.1, 0.4, 0.35, 0.8];
+/// let targets = [0., 0., 1., 1.];
+///
+/// assert_eq!(roc_auc_score(scores.iter(), targets.iter()), 0.75);
+/// ```
+///
+/// # Panics
+///
+/// - scores and targets have different length
+/// - scores and targets are empty
+/// - targets contains a value which is not 0 or 1
+/// - targets does not contain both classes
+pub fn roc_auc_score<'a, I>(scores: I, targets: I) -> f64
+    where I: ExactSizeIterator<Item=&'a f64>
+{
+    let (fpr, tpr, _) = roc_curve(scores, targets);
+    fpr.windows(2)
+        .zip(tpr.windows(2))
+        .fold(0f64, |s, (x, y)| s + (x[1] - x[0]) * (y[0] + y[1]) / 2f64)
+}
+
+/// Returns the precision-recall curve of scores for 2 class classification.
+///
+/// For each distinct score, in decreasing order, the precision and recall
+/// of classifying the samples with at least that score as positive are
+/// returned with the score as threshold. See
+/// [Precision and Recall](https://en.wikipedia.org/wiki/Precision_and_recall) for details.
+///
+/// # Arguments
+///
+/// * `scores` - Iterator of scores, higher meaning more likely positive.
+/// * `targets` - Iterator of expected (actual) labels which only contains 0 or 1.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::precision_recall_curve;
+/// let scores = [0.1, 0.4, 0.35, 0.8];
+/// let targets = [0., 0., 1., 1.];
+///
+/// let (precision, recall, thresholds) = precision_recall_curve(scores.iter(), targets.iter());
+/// assert_eq!(precision, vec![1., 0.5, 2. / 3., 0.5]);
+/// assert_eq!(recall, vec![0.5, 0.5, 1., 1.]);
+/// assert_eq!(thresholds, vec![0.8, 0.4, 0.35, 0.1]);
+/// ```
+///
+/// # Panics
+///
+/// - scores and targets have different length
+/// - scores and targets are empty
+/// - targets contains a value which is not 0 or 1
+/// - targets does not contain a positive sample
+pub fn precision_recall_curve<'a, I>(scores: I, targets: I) -> (Vec<f64>, Vec<f64>, Vec<f64>)
+    where I: ExactSizeIterator<Item=&'a f64>
+{
+    let (tps, fps, thresholds) = threshold_counts(scores, targets);
+    let pos = tps[tps.len() - 1];
+    assert!(pos > 0f64, "precision recall curve requires a positive target");
+
+    let precision = tps.iter().zip(&fps).map(|(tp, fp)| tp / (tp + fp)).collect();
+    let recall = tps.iter().map(|tp| tp / pos).collect();
+    (precision, recall, thresholds)
+}
+
+/// Returns the average precision of scores for 2 class classification.
+///
+/// This summarises the precision-recall curve as the sum of the precision
+/// at each threshold weighted by the increase in recall from the previous
+/// threshold.
+///
+/// # Arguments
+///
+/// * `scores` - Iterator of scores, higher meaning more likely positive.
+/// * `targets` - Iterator of expected (actual) labels which only contains 0 or 1.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::average_precision_score;
+/// let scores = [0.1, 0.4, 0.35, 0.8];
+/// let targets = [0., 0., 1., 1.];
+///
+/// let score = average_precision_score(scores.iter(), targets.iter());
+/// assert!((score - 5. / 6.).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - scores and targets have different length
+/// - scores and targets are empty
+/// - targets contains a value which is not 0 or 1
+/// - targets does not contain a positive sample
+pub fn average_precision_score<'a, I>(scores: I, targets: I) -> f64
+    where I: ExactSizeIterator<Item=&'a f64>
+{
+    let (precision, recall, _) = precision_recall_curve(scores, targets);
+    let mut prev_recall = 0f64;
+    let mut sum = 0f64;
+    for (p, r) in precision.into_iter().zip(recall) {
+        sum += (r - prev_recall) * p;
+        prev_recall = r;
+    }
+    sum
+}
+
+/// Returns the additive inverse of the log loss of predicted
+/// probabilities for 2 class classification.
+///
+/// The log loss is the mean negative log-likelihood of the targets, see
+/// [Cross entropy](https://en.wikipedia.org/wiki/Cross_entropy) for details.
+/// Probabilities are clipped to [1e-15, 1 - 1e-15] to keep the loss finite.
+/// So higher is better, and the returned value is never positive.
+///
+/// # Arguments
+///
+/// * `probs` - Iterator of predicted probabilities of the positive class.
+/// * `targets` - Iterator of expected (actual) labels which only contains 0 or 1.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::neg_log_loss;
+/// let probs = [0.9, 0.2];
+/// let targets = [1., 0.];
+///
+/// let score = neg_log_loss(probs.iter(), targets.iter());
+/// assert!((score - (0.9f64.ln() + 0.8f64.ln()) / 2.).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - probs and targets have different length
+/// - probs or targets are empty
+/// - probs contains a value outside of [0, 1]
+/// - targets contains a value which is not 0 or 1
+pub fn neg_log_loss<'a, I>(probs: I, targets: I) -> f64
+    where I: ExactSizeIterator<Item=&'a f64>
+{
+    assert!(probs.len() == targets.len(), "probs and targets must have the same length");
+    assert!(probs.len() > 0, "probs and targets must not be empty");
+
+    let len = probs.len() as f64;
+    let mut sum = 0f64;
+    for (p, t) in probs.zip(targets) {
+        if (*p < 0f64) | (*p > 1f64) {
+            panic!("probabilities must lie in [0, 1]")
+        }
+        if (*t != 0f64) & (*t != 1f64) {
+            panic!("log loss must be used for 2 class classification")
+        }
+        let p = p.max(LOG_LOSS_EPS).min(1f64 - LOG_LOSS_EPS);
+        sum += t * p.ln() + (1f64 - t) * (1f64 - p).ln();
+    }
+    sum / len
+}
+
+/// Returns the additive inverse of the log loss of rows of predicted
+/// class probabilities against one-hot targets.
+///
+/// Probabilities are clipped to [1e-15, 1 - 1e-15] to keep the loss finite.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::neg_row_log_loss;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let probs = Matrix::new(2, 3, vec![0.7, 0.2, 0.1,
+///                                    0.1, 0.1, 0.8]);
+/// let targets = Matrix::new(2, 3, vec![1., 0., 0.,
+///                                      0., 0., 1.]);
+///
+/// let score = neg_row_log_loss(&probs, &targets);
+/// assert!((score - (0.7f64.ln() + 0.8f64.ln()) / 2.).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - probs and targets have different shapes
+/// - probs and targets are empty
+pub fn neg_row_log_loss(probs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
+    assert!(probs.rows() == targets.rows() && probs.cols() == targets.cols(),
+            "probs and targets must have the same shape");
+    assert!(probs.rows() > 0, "probs and targets must not be empty");
+
+    let sum = probs.data()
+        .iter()
+        .zip(targets.data())
+        .fold(0f64, |s, (p, t)| s + t * p.max(LOG_LOSS_EPS).min(1f64 - LOG_LOSS_EPS).ln());
+    sum / probs.rows() as f64
+}
+
+/// Probabilities are clipped this far from 0 and 1 in the log loss.
+const LOG_LOSS_EPS: f64 = 1e-15;
+
+/// Returns the cumulative true and false positive counts at each
+/// distinct score, in decreasing order, with the scores.
+fn threshold_counts<'a, I>(scores: I, targets: I) -> (Vec<f64>, Vec<f64>, Vec<f64>)
+    where I: ExactSizeIterator<Item=&'a f64>
+{
+    assert!(scores.len() == targets.len(), "scores and targets must have the same length");
+
+    let mut pairs = scores.cloned().zip(targets.cloned()).collect::<Vec<_>>();
+    assert!(!pairs.is_empty(), "scores and targets must not be empty");
+    if pairs.iter().any(|&(_, t)| (t != 0f64) & (t != 1f64)) {
+        panic!("curves must be used for 2 class classification")
+    }
+    pairs.sort_by(|a, b| b.0.partial_cmp(&a.0).expect("scores must be comparable"));
+
+    let mut tps = Vec::new();
+    let mut fps = Vec::new();
+    let mut thresholds = Vec::new();
+    let (mut tp, mut fp) = (0f64, 0f64);
+    for (i, &(score, target)) in pairs.iter().enumerate() {
+        tp += target;
+        fp += 1f64 - target;
+        if i + 1 == pairs.len() || pairs[i + 1].0 != score {
+            tps.push(tp);
+            fps.push(fp);
+            thresholds.push(score);
+        }
+    }
+    (tps, fps, thresholds)
+}
+
+// ************************************
+// Regression Scores
+// ************************************
+
+// TODO: generalise to accept arbitrary iterators of diff-able things
+/// Returns the additive inverse of the mean-squared-error of the
+/// outputs. So higher is better, and the returned value is always
+/// negative.
+pub fn neg_mean_squared_error(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64
+{
+    // MeanSqError divides the actual mean squared error by two.
+    -2f64 * MeanSqError::cost(outputs, targets)
+}
+
+/// Returns the coefficient of determination of each output column.
+///
+/// R² is 1 - SS_res / SS_tot, see
+/// [Coefficient of determination](https://en.wikipedia.org/wiki/Coefficient_of_determination)
+/// for details. A constant target column scores 1 if predicted exactly and 0 otherwise.
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs and targets are empty
+pub fn r2_per_column(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> Vec<f64> {
+    per_column(outputs, targets, |o, t| {
+        let t_mean = mean(t);
+        let ss_res = o.iter().zip(t).fold(0f64, |s, (o, t)| s + (t - o) * (t - o));
+        let ss_tot = t.iter().fold(0f64, |s, t| s + (t - t_mean) * (t - t_mean));
+        explained_ratio(ss_res, ss_tot)
+    })
+}
+
+/// Returns the coefficient of determination averaged over the output columns.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::r2_score;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let outputs = Matrix::new(4, 1, vec![1., 2., 3., 5.]);
+/// let targets = Matrix::new(4, 1, vec![1., 2., 3., 4.]);
+///
+/// assert_eq!(r2_score(&outputs, &targets), 0.8);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs and targets are empty
+pub fn r2_score(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
+    mean(&r2_per_column(outputs, targets))
+}
+
+/// Returns the adjusted coefficient of determination of each output column.
+///
+/// Adjusted R² is 1 - (1 - R²)(n - 1) / (n - p - 1) for n samples and
+/// p input features, which penalises models using more features.
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - there are not more samples than `num_features + 1`
+pub fn adjusted_r2_per_column(outputs: &Matrix<f64>,
+                              targets: &Matrix<f64>,
+                              num_features: usize)
+                              -> Vec<f64> {
+    let n = outputs.rows();
+    assert!(n > num_features + 1,
+            "adjusted R² requires more samples than the number of features plus one");
+
+    let scale = (n - 1) as f64 / (n - num_features - 1) as f64;
+    r2_per_column(outputs, targets).into_iter().map(|r2| 1f64 - (1f64 - r2) * scale).collect()
+}
+
+/// Returns the adjusted coefficient of determination averaged over the output columns.
+///
+/// As the score depends on the number of input features it is used
+/// with `k_fold_validate` through a closure.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::adjusted_r2_score;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let outputs = Matrix::new(4, 1, vec![1., 2., 3., 5.]);
+/// let targets = Matrix::new(4, 1, vec![1., 2., 3., 4.]);
+///
+/// let score = |o: &Matrix<f64>, t: &Matrix<f64>| adjusted_r2_score(o, t, 1);
+/// assert!((score(&outputs, &targets) - 0.7).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - there are not more samples than `num_features + 1`
+pub fn adjusted_r2_score(outputs: &Matrix<f64>, targets: &Matrix<f64>, num_features: usize) -> f64 {
+    mean(&adjusted_r2_per_column(outputs, targets, num_features))
+}
+
+/// Returns the explained variance of each output column.
+///
+/// This is 1 - Var(targets - outputs) / Var(targets), which equals R²
+/// when the residuals have zero mean. A constant target column scores 1
+/// if the residuals are constant and 0 otherwise.
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs and targets are empty
+pub fn explained_variance_per_column(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> Vec<f64> {
+    per_column(outputs, targets, |o, t| {
+        let residuals = o.iter().zip(t).map(|(o, t)| t - o).collect::<Vec<_>>();
+        explained_ratio(variance(&residuals), variance(t))
+    })
+}
+
+/// Returns the explained variance averaged over the output columns.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::explained_variance_score;
+/// use rusty_machine::linalg::Matrix;
+///
+/// // A constant offset is fully explained.
+/// let outputs = Matrix::new(3, 1, vec![2., 3., 4.]);
+/// let targets = Matrix::new(3, 1, vec![1., 2., 3.]);
+///
+/// assert_eq!(explained_variance_score(&outputs, &targets), 1.);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs and targets are empty
+pub fn explained_variance_score(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
+    mean(&explained_variance_per_column(outputs, targets))
+}
+
+/// Returns the root-mean-squared-error of each output column.
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs and targets are empty
+pub fn rmse_per_column(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> Vec<f64> {
+    per_column(outputs, targets, |o, t| {
+        let sq_errors = o.iter().zip(t).map(|(o, t)| (t - o) * (t - o)).collect::<Vec<_>>();
+        mean(&sq_errors).sqrt()
+    })
+}
+
+/// Returns the additive inverse of the root-mean-squared-error
+/// averaged over the output columns.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::neg_root_mean_squared_error;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let outputs = Matrix::new(2, 2, vec![1., 2., 3., 4.]);
+/// let targets = Matrix::new(2, 2, vec![1., 4., 3., 0.]);
+///
+/// // The columns have errors 0 and sqrt(10).
+/// let score = neg_root_mean_squared_error(&outputs, &targets);
+/// assert!((score + 10f64.sqrt() / 2.).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs and targets are empty
+pub fn neg_root_mean_squared_error(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
+    -mean(&rmse_per_column(outputs, targets))
+}
+
+/// Returns the root-mean-squared-error of each output column
+/// divided by the range of the targets in that column.
+///
+/// A constant target column gives 0 if predicted exactly and infinity otherwise.
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs and targets are empty
+pub fn nrmse_per_column(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> Vec<f64> {
+    let ranges = per_column(outputs, targets, |_, t| {
+        let max = t.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let min = t.iter().cloned().fold(f64::INFINITY, f64::min);
+        max - min
+    });
+
+    rmse_per_column(outputs, targets)
+        .into_iter()
+        .zip(ranges)
+        .map(|(rmse, range)| if rmse == 0f64 { 0f64 } else { rmse / range })
+        .collect()
+}
+
+/// Returns the additive inverse of the range normalised
+/// root-mean-squared-error averaged over the output columns.
+///
+/// This allows comparing errors across targets of different scales.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::neg_normalized_root_mean_squared_error;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let outputs = Matrix::new(3, 2, vec![1., 10., 2., 20., 4., 40.]);
+/// let targets = Matrix::new(3, 2, vec![1., 10., 2., 20., 3., 30.]);
+///
+/// // Both columns have an error of half their range over sqrt(3).
+/// let score = neg_normalized_root_mean_squared_error(&outputs, &targets);
+/// assert!((score + 0.5 / 3f64.sqrt()).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs and targets are empty
+pub fn neg_normalized_root_mean_squared_error(outputs: &Matrix<f64>,
+                                              targets: &Matrix<f64>)
+                                              -> f64 {
+    -mean(&nrmse_per_column(outputs, targets))
+}
+
+/// Returns the mean-absolute-error of each output column.
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs and targets are empty
+pub fn mae_per_column(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> Vec<f64> {
+    per_column(outputs, targets, |o, t| {
+        mean(&o.iter().zip(t).map(|(o, t)| (t - o).abs()).collect::<Vec<_>>())
+    })
+}
+
+/// Returns the additive inverse of the mean-absolute-error
+/// averaged over the output columns.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::neg_mean_absolute_error;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let outputs = Matrix::new(4, 1, vec![1., 2., 3., 5.]);
+/// let targets = Matrix::new(4, 1, vec![1., 3., 3., 3.]);
+///
+/// assert_eq!(neg_mean_absolute_error(&outputs, &targets), -0.75);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs and targets are empty
+pub fn neg_mean_absolute_error(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
+    -mean(&mae_per_column(outputs, targets))
+}
+
+/// Returns the mean-absolute-percentage-error of each output column.
+///
+/// The error is given as a fraction rather than a percentage. Targets
+/// are bounded away from zero by machine epsilon, so targets close to
+/// zero give very large errors.
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs and targets are empty
+pub fn mape_per_column(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> Vec<f64> {
+    per_column(outputs, targets, |o, t| {
+        mean(&o.iter()
+            .zip(t)
+            .map(|(o, t)| (t - o).abs() / t.abs().max(f64::EPSILON))
+            .collect::<Vec<_>>())
+    })
+}
+
+/// Returns the additive inverse of the mean-absolute-percentage-error
+/// averaged over the output columns.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::neg_mean_absolute_percentage_error;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let outputs = Matrix::new(2, 1, vec![110., 45.]);
+/// let targets = Matrix::new(2, 1, vec![100., 50.]);
+///
+/// let score = neg_mean_absolute_percentage_error(&outputs, &targets);
+/// assert!((score + 0.1).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs and targets are empty
+pub fn neg_mean_absolute_percentage_error(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
+    -mean(&mape_per_column(outputs, targets))
+}
+
+/// Returns the symmetric mean-absolute-percentage-error of each output column.
+///
+/// Each error is divided by the mean magnitude of the output and target,
+/// so the result lies in [0, 2]. A zero output and target have no error.
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs and targets are empty
+pub fn smape_per_column(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> Vec<f64> {
+    per_column(outputs, targets, |o, t| {
+        mean(&o.iter()
+            .zip(t)
+            .map(|(o, t)| {
+                let scale = (o.abs() + t.abs()) / 2f64;
+                if scale == 0f64 { 0f64 } else { (t - o).abs() / scale }
+            })
+            .collect::<Vec<_>>())
+    })
+}
+
+/// Returns the additive inverse of the symmetric
+/// mean-absolute-percentage-error averaged over the output columns.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::neg_symmetric_mean_absolute_percentage_error;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let outputs = Matrix::new(2, 1, vec![3., 0.]);
+/// let targets = Matrix::new(2, 1, vec![1., 0.]);
+///
+/// let score = neg_symmetric_mean_absolute_percentage_error(&outputs, &targets);
+/// assert_eq!(score, -0.5);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs and targets are empty
+pub fn neg_symmetric_mean_absolute_percentage_error(outputs: &Matrix<f64>,
+                                                    targets: &Matrix<f64>)
+                                                    -> f64 {
+    -mean(&smape_per_column(outputs, targets))
+}
+
+/// Returns the largest absolute error of each output column.
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs and targets are empty
+pub fn max_error_per_column(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> Vec<f64> {
+    per_column(outputs, targets, |o, t| {
+        o.iter().zip(t).fold(0f64, |m, (o, t)| m.max((t - o).abs()))
+    })
+}
+
+/// Returns the additive inverse of the largest absolute error
+/// averaged over the output columns.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::neg_max_error;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let outputs = Matrix::new(3, 2, vec![1., 0., 2., 0., 3., 0.]);
+/// let targets = Matrix::new(3, 2, vec![1., 1., 4., 0., 3., 0.]);
+///
+/// assert_eq!(neg_max_error(&outputs, &targets), -1.5);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different shapes
+/// - outputs and targets are empty
+pub fn neg_max_error(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
+    -mean(&max_error_per_column(outputs, targets))
+}
+
+/// Applies a metric to each pair of output and target columns.
+fn per_column<F>(outputs: &Matrix<f64>, targets: &Matrix<f64>, metric: F) -> Vec<f64>
+    where F: Fn(&[f64], &[f64]) -> f64
+{
+    assert!(outputs.rows() == targets.rows() && outputs.cols() == targets.cols(),
+            "outputs and targets must have the same shape");
+    assert!(outputs.rows() > 0, "outputs and targets must not be empty");
+
+    let outputs = outputs.transpose();
+    let targets = targets.transpose();
+    outputs.row_iter()
+        .zip(targets.row_iter())
+        .map(|(o, t)| metric(o.raw_slice(), t.raw_slice()))
+        .collect()
+}
+
+/// Returns 1 - residual / total, treating a zero total as
+/// perfect if the residual is also zero.
+fn explained_ratio(residual: f64, total: f64) -> f64 {
+    if total == 0f64 {
+        if residual == 0f64 { 1f64 } else { 0f64 }
+    } else {
+        1f64 - residual / total
+    }
+}
+
+/// Returns the mean of the values.
+fn mean(xs: &[f64]) -> f64 {
+    xs.iter().fold(0f64, |s, x| s + x) / xs.len() as f64
+}
+
+/// Returns the population variance of the values.
+fn variance(xs: &[f64]) -> f64 {
+    let m = mean(xs);
+    xs.iter().fold(0f64, |s, x| s + (x - m) * (x - m)) / xs.len() as f64
+}
+
+// ************************************
+// Clustering Scores
+// ************************************
+
+/// A cluster assignment output by a clustering model.
+///
+/// This is implemented for the `usize` clusters of `KMeansClassifier` and
+/// the `Option<usize>` clusters of `DBSCAN`, where `None` marks noise.
+pub trait ClusterLabel {
+    /// The cluster of the point, or `None` if it is unassigned.
+    fn cluster(&self) -> Option<usize>;
+}
+
+impl ClusterLabel for usize {
+    fn cluster(&self) -> Option<usize> {
+        Some(*self)
+    }
+}
+
+impl ClusterLabel for Option<usize> {
+    fn cluster(&self) -> Option<usize> {
+        *self
+    }
+}
+
+/// Returns the most probable component of each row of membership
+/// probabilities, such as those predicted by `GaussianMixtureModel`.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::hard_labels;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let probs = Matrix::new(2, 2, vec![0.2, 0.8, 0.9, 0.1]);
+/// assert_eq!(hard_labels(&probs).into_vec(), vec![1, 0]);
+/// ```
+pub fn hard_labels(probs: &Matrix<f64>) -> Vector<usize> {
+    Vector::new(probs.row_iter()
+        .map(|r| utils::argmax(r.raw_slice()).0)
+        .collect::<Vec<_>>())
+}
+
+/// Returns the mean silhouette coefficient of a clustering.
+///
+/// The silhouette of a point is (b - a) / max(a, b), where a is its mean
+/// distance to the rest of its cluster and b its mean distance to the
+/// nearest other cluster. Points alone in their cluster score 0.
+/// The score lies in [-1, 1], see
+/// [Silhouette](https://en.wikipedia.org/wiki/Silhouette_(clustering)) for details.
+///
+/// Distances are Euclidean. Unassigned points are ignored.
+///
+/// # Arguments
+///
+/// * `inputs` - The clustered points, one per row.
+/// * `labels` - Iterator of the cluster of each point.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::silhouette_score;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let inputs = Matrix::new(4, 1, vec![0., 1., 10., 11.]);
+/// let labels = [0, 0, 1, 1];
+///
+/// let score = silhouette_score(&inputs, labels.iter());
+/// assert!(score > 0.85);
+/// ```
+///
+/// # Panics
+///
+/// - inputs and labels have different length
+/// - there are fewer than 2 clusters, or no fewer clusters than assigned points
+pub fn silhouette_score<'a, I, T>(inputs: &Matrix<f64>, labels: I) -> f64
+    where I: ExactSizeIterator<Item=&'a T>,
+          T: 'a + ClusterLabel
+{
+    assert!(inputs.rows() == labels.len(), "inputs and labels must have the same length");
+    let (points, k) = assigned_points(labels);
+    assert!(k >= 2 && k < points.len(),
+            "silhouette score requires between 2 and n - 1 clusters");
+
+    let mut sizes = vec![0f64; k];
+    for &(_, c) in &points {
+        sizes[c] += 1f64;
+    }
+
+    let mut total = 0f64;
+    for &(i, ci) in &points {
+        if sizes[ci] < 2f64 {
+            continue;
+        }
+
+        let mut dist_sums = vec![0f64; k];
+        for &(j, cj) in &points {
+            dist_sums[cj] += Euclidean.distance(inputs.row(i).raw_slice(),
+                                                inputs.row(j).raw_slice());
+        }
+
+        let a = dist_sums[ci] / (sizes[ci] - 1f64);
+        let b = (0..k)
+            .filter(|&c| c != ci)
+            .map(|c| dist_sums[c] / sizes[c])
+            .fold(f64::INFINITY, f64::min);
+        if a.max(b) > 0f64 {
+            total += (b - a) / a.max(b);
+        }
+    }
+    total / points.len() as f64
+}
+
+/// Returns the additive inverse of the Davies-Bouldin index of a clustering.
+///
+/// The index is the mean over clusters of the largest ratio of the summed
+/// scatter of the cluster and another to the distance between their
+/// centroids, see
+/// [Davies-Bouldin index](https://en.wikipedia.org/wiki/Davies%E2%80%93Bouldin_index)
+/// for details. So higher is better, and the returned value is never positive.
+///
+/// Distances are Euclidean. Unassigned points are ignored. Pairs of
+/// clusters with coinciding centroids are skipped, as their ratio is
+/// undefined. This follows scikit-learn, which scores two identical
+/// clusters as 0.
+///
+/// # Arguments
+///
+/// * `inputs` - The clustered points, one per row.
+/// * `labels` - Iterator of the cluster of each point.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::neg_davies_bouldin_score;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let inputs = Matrix::new(4, 1, vec![0., 1., 10., 11.]);
+/// let labels = [0, 0, 1, 1];
+///
+/// let score = neg_davies_bouldin_score(&inputs, labels.iter());
+/// assert!((score + 0.1).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - inputs and labels have different length
+/// - there are fewer than 2 clusters
+pub fn neg_davies_bouldin_score<'a, I, T>(inputs: &Matrix<f64>, labels: I) -> f64
+    where I: ExactSizeIterator<Item=&'a T>,
+          T: 'a + ClusterLabel
+{
+    assert!(inputs.rows() == labels.len(), "inputs and labels must have the same length");
+    let (points, k) = assigned_points(labels);
+    assert!(k >= 2, "Davies-Bouldin index requires at least 2 clusters");
+
+    let (centroids, sizes) = cluster_centroids(inputs, &points, k);
+    let mut scatter = vec![0f64; k];
+    for &(i, c) in &points {
+        scatter[c] += Euclidean.distance(inputs.row(i).raw_slice(), &centroids[c]);
+    }
+    for (s, size) in scatter.iter_mut().zip(&sizes) {
+        *s /= *size;
+    }
+
+    let total = (0..k)
+        .map(|a| {
+            (0..k)
+                .filter(|&b| b != a)
+                .map(|b| {
+                    let dist = Euclidean.distance(&centroids[a], &centroids[b]);
+                    if dist > 0f64 { (scatter[a] + scatter[b]) / dist } else { 0f64 }
+                })
+                .fold(0f64, f64::max)
+        })
+        .fold(0f64, |s, x| s + x);
+    -total / k as f64
+}
+
+/// Returns the Calinski-Harabasz index of a clustering.
+///
+/// The index is the ratio of the between-cluster dispersion to the
+/// within-cluster dispersion, each divided by its degrees of freedom, see
+/// [Calinski-Harabasz index](https://en.wikipedia.org/wiki/Calinski%E2%80%93Harabasz_index)
+/// for details. A clustering with no within-cluster dispersion scores 1.
+///
+/// Unassigned points are ignored.
+///
+/// # Arguments
+///
+/// * `inputs` - The clustered points, one per row.
+/// * `labels` - Iterator of the cluster of each point.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::calinski_harabasz_score;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let inputs = Matrix::new(4, 1, vec![0., 1., 10., 11.]);
+/// let labels = [0, 0, 1, 1];
+///
+/// assert_eq!(calinski_harabasz_score(&inputs, labels.iter()), 200.);
+/// ```
+///
+/// # Panics
+///
+/// - inputs and labels have different length
+/// - there are fewer than 2 clusters, or no fewer clusters than assigned points
+pub fn calinski_harabasz_score<'a, I, T>(inputs: &Matrix<f64>, labels: I) -> f64
+    where I: ExactSizeIterator<Item=&'a T>,
+          T: 'a + ClusterLabel
+{
+    assert!(inputs.rows() == labels.len(), "inputs and labels must have the same length");
+    let (points, k) = assigned_points(labels);
+    assert!(k >= 2 && k < points.len(),
+            "Calinski-Harabasz index requires between 2 and n - 1 clusters");
+
+    let (centroids, sizes) = cluster_centroids(inputs, &points, k);
+    let n = points.len() as f64;
+    let mut mean = vec![0f64; inputs.cols()];
+    for (centroid, size) in centroids.iter().zip(&sizes) {
+        for (m, x) in mean.iter_mut().zip(centroid) {
+            *m += x * size / n;
+        }
+    }
+
+    let between = centroids.iter()
+        .zip(&sizes)
+        .map(|(centroid, size)| size * Euclidean.distance(centroid, &mean).powi(2))
+        .fold(0f64, |s, x| s + x);
+    let within = points.iter()
+        .map(|&(i, c)| Euclidean.distance(inputs.row(i).raw_slice(), &centroids[c]).powi(2))
+        .fold(0f64, |s, x| s + x);
+
+    if within == 0f64 {
+        1f64
+    } else {
+        between * (n - k as f64) / (within * (k as f64 - 1f64))
+    }
+}
+
+/// Returns the adjusted Rand index between two clusterings.
+///
+/// The index counts the pairs of points on which the clusterings agree,
+/// adjusted for chance so that random labellings score close to 0 and
+/// identical ones score 1, see
+/// [Rand index](https://en.wikipedia.org/wiki/Rand_index#Adjusted_Rand_index)
+/// for details.
+///
+/// Unassigned points are grouped together as a cluster of their own.
+///
+/// # Arguments
+///
+/// * `outputs` - Iterator of output (predicted) clusters.
+/// * `targets` - Iterator of expected (actual) classes.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::adjusted_rand_score;
+/// let outputs = [0, 0, 1, 2];
+/// let targets = [0, 0, 1, 1];
+///
+/// let score = adjusted_rand_score(outputs.iter(), targets.iter());
+/// assert!((score - 4. / 7.).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different length
+pub fn adjusted_rand_score<'a, 'b, I1, I2, T1, T2>(outputs: I1, targets: I2) -> f64
+    where I1: ExactSizeIterator<Item=&'a T1>,
+          I2: ExactSizeIterator<Item=&'b T2>,
+          T1: 'a + ClusterLabel,
+          T2: 'b + ClusterLabel
+{
+    let (table, output_sums, target_sums) = contingency_table(outputs, targets);
+    let n = output_sums.iter().fold(0f64, |s, x| s + x);
+    if n < 2f64 {
+        return 1f64;
+    }
+
+    let pairs = |x: &f64| x * (x - 1f64) / 2f64;
+    let index = table.iter().flat_map(|r| r.iter()).map(&pairs).fold(0f64, |s, x| s + x);
+    let output_pairs = output_sums.iter().map(&pairs).fold(0f64, |s, x| s + x);
+    let target_pairs = target_sums.iter().map(&pairs).fold(0f64, |s, x| s + x);
+
+    let expected = output_pairs * target_pairs / pairs(&n);
+    let max = (output_pairs + target_pairs) / 2f64;
+    if max == expected {
+        1f64
+    } else {
+        (index - expected) / (max - expected)
+    }
+}
+
+/// Returns the normalized mutual information between two clusterings.
+///
+/// The mutual information is divided by the arithmetic mean of the
+/// entropies of the clusterings, so the score lies in [0, 1], see
+/// [Mutual information](https://en.wikipedia.org/wiki/Mutual_information)
+/// for details.
+///
+/// Unassigned points are grouped together as a cluster of their own.
+///
+/// # Arguments
+///
+/// * `outputs` - Iterator of output (predicted) clusters.
+/// * `targets` - Iterator of expected (actual) classes.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::normalized_mutual_info_score;
+/// let outputs = [1, 1, 0, 0];
+/// let targets = [0, 0, 1, 1];
+///
+/// let score = normalized_mutual_info_score(outputs.iter(), targets.iter());
+/// assert!((score - 1.).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different length
+pub fn normalized_mutual_info_score<'a, 'b, I1, I2, T1, T2>(outputs: I1, targets: I2) -> f64
+    where I1: ExactSizeIterator<Item=&'a T1>,
+          I2: ExactSizeIterator<Item=&'b T2>,
+          T1: 'a + ClusterLabel,
+          T2: 'b + ClusterLabel
+{
+    let (mi, output_entropy, target_entropy) = information(outputs, targets);
+    if output_entropy == 0f64 && target_entropy == 0f64 {
+        1f64
+    } else {
+        mi / ((output_entropy + target_entropy) / 2f64)
+    }
+}
+
+/// Returns the homogeneity of a clustering.
+///
+/// A clustering is homogeneous if each cluster contains only members of
+/// a single class. The score is the mutual information divided by the
+/// entropy of the classes, and lies in [0, 1], see
+/// [V-Measure](http://www.aclweb.org/anthology/D07-1043) for details.
+///
+/// Unassigned points are grouped together as a cluster of their own.
+///
+/// # Arguments
+///
+/// * `outputs` - Iterator of output (predicted) clusters.
+/// * `targets` - Iterator of expected (actual) classes.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::homogeneity_score;
+/// let outputs = [0, 1, 2, 3];
+/// let targets = [0, 0, 1, 1];
+///
+/// let score = homogeneity_score(outputs.iter(), targets.iter());
+/// assert!((score - 1.).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different length
+pub fn homogeneity_score<'a, 'b, I1, I2, T1, T2>(outputs: I1, targets: I2) -> f64
+    where I1: ExactSizeIterator<Item=&'a T1>,
+          I2: ExactSizeIterator<Item=&'b T2>,
+          T1: 'a + ClusterLabel,
+          T2: 'b + ClusterLabel
+{
+    let (mi, _, target_entropy) = information(outputs, targets);
+    if target_entropy == 0f64 {
+        1f64
+    } else {
+        mi / target_entropy
+    }
+}
+
+/// Returns the completeness of a clustering.
+///
+/// A clustering is complete if all members of each class are assigned
+/// to the same cluster. The score is the mutual information divided by
+/// the entropy of the clusters, and lies in [0, 1], see
+/// [V-Measure](http://www.aclweb.org/anthology/D07-1043) for details.
+///
+/// Unassigned points are grouped together as a cluster of their own.
+///
+/// # Arguments
+///
+/// * `outputs` - Iterator of output (predicted) clusters.
+/// * `targets` - Iterator of expected (actual) classes.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::completeness_score;
+/// let outputs = [0, 1, 2, 3];
+/// let targets = [0, 0, 1, 1];
+///
+/// let score = completeness_score(outputs.iter(), targets.iter());
+/// assert!((score - 0.5).abs() < 1e-12);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different length
+pub fn completeness_score<'a, 'b, I1, I2, T1, T2>(outputs: I1, targets: I2) -> f64
+    where I1: ExactSizeIterator<Item=&'a T1>,
+          I2: ExactSizeIterator<Item=&'b T2>,
+          T1: 'a + ClusterLabel,
+          T2: 'b + ClusterLabel
+{
+    let (mi, output_entropy, _) = information(outputs, targets);
+    if output_entropy == 0f64 {
+        1f64
+    } else {
+        mi / output_entropy
+    }
+}
+
+/// Returns the index and contiguous cluster of each assigned point,
+/// with the number of clusters.
+fn assigned_points<'a, I, T>(labels: I) -> (Vec<(usize, usize)>, usize)
+    where I: Iterator<Item=&'a T>,
+          T: 'a + ClusterLabel
+{
+    let mut clusters = HashMap::new();
+    let mut points = Vec::new();
+    for (i, label) in labels.enumerate() {
+        if let Some(c) = label.cluster() {
+            let next = clusters.len();
+            points.push((i, *clusters.entry(c).or_insert(next)));
+        }
+    }
+    let k = clusters.len();
+    (points, k)
+}
+
+/// Returns the centroid and size of each cluster.
+fn cluster_centroids(inputs: &Matrix<f64>,
+                     points: &[(usize, usize)],
+                     k: usize)
+                     -> (Vec<Vec<f64>>, Vec<f64>) {
+    let mut centroids = vec![vec![0f64; inputs.cols()]; k];
+    let mut sizes = vec![0f64; k];
+    for &(i, c) in points {
+        for (s, x) in centroids[c].iter_mut().zip(inputs.row(i).raw_slice()) {
+            *s += *x;
+        }
+        sizes[c] += 1f64;
+    }
+
+    for (centroid, size) in centroids.iter_mut().zip(&sizes) {
+        for s in centroid.iter_mut() {
+            *s /= *size;
+        }
+    }
+    (centroids, sizes)
+}
+
+/// Returns the contingency table of two clusterings, with its row and column sums.
+///
+/// Rows are output clusters and columns are target classes.
+fn contingency_table<'a, 'b, I1, I2, T1, T2>(outputs: I1,
+                                             targets: I2)
+                                             -> (Vec<Vec<f64>>, Vec<f64>, Vec<f64>)
+    where I1: ExactSizeIterator<Item=&'a T1>,
+          I2: ExactSizeIterator<Item=&'b T2>,
+          T1: 'a + ClusterLabel,
+          T2: 'b + ClusterLabel
+{
+    assert!(outputs.len() == targets.len(), "outputs and targets must have the same length");
+
+    let mut output_ids = HashMap::new();
+    let mut target_ids = HashMap::new();
+    let mut cells = Vec::with_capacity(outputs.len());
+    for (o, t) in outputs.zip(targets) {
+        let next = output_ids.len();
+        let row = *output_ids.entry(o.cluster()).or_insert(next);
+        let next = target_ids.len();
+        let col = *target_ids.entry(t.cluster()).or_insert(next);
+        cells.push((row, col));
+    }
+
+    let mut table = vec![vec![0f64; target_ids.len()]; output_ids.len()];
+    let mut output_sums = vec![0f64; output_ids.len()];
+    let mut target_sums = vec![0f64; target_ids.len()];
+    for (row, col) in cells {
+        table[row][col] += 1f64;
+        output_sums[row] += 1f64;
+        target_sums[col] += 1f64;
+    }
+    (table, output_sums, target_sums)
+}
+
+/// Returns the mutual information of two clusterings and their entropies.
+fn information<'a, 'b, I1, I2, T1, T2>(outputs: I1, targets: I2) -> (f64, f64, f64)
+    where I1: ExactSizeIterator<Item=&'a T1>,
+          I2: ExactSizeIterator<Item=&'b T2>,
+          T1: 'a + ClusterLabel,
+          T2: 'b + ClusterLabel
+{
+    let (table, output_sums, target_sums) = contingency_table(outputs, targets);
+    let n = output_sums.iter().fold(0f64, |s, x| s + x);
+    let entropy = |sums: &[f64]| {
+        -sums.iter()
+            .filter(|&&s| s > 0f64)
+            .map(|&s| s / n * (s / n).ln())
+            .fold(0f64, |s, x| s + x)
+    };
+
+    let mut mi = 0f64;
+    for (row, output_sum) in table.iter().zip(&output_sums) {
+        for (&count, target_sum) in row.iter().zip(&target_sums) {
+            if count > 0f64 {
+                mi += count / n * (n * count / (output_sum * target_sum)).ln();
+            }
+        }
+    }
+    // Guard against small negative values from rounding.
+    (mi.max(0f64), entropy(&output_sums[..]), entropy(&target_sums[..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use linalg::Matrix;
+    use super::{accuracy, precision, recall, f1, neg_brier_score, reliability_curve,
+                neg_mean_squared_error, hard_labels, silhouette_score,
+                neg_davies_bouldin_score, calinski_harabasz_score, adjusted_rand_score,
+                normalized_mutual_info_score, homogeneity_score, completeness_score,
+                r2_per_column, r2_score, adjusted_r2_per_column, adjusted_r2_score,
+                explained_variance_per_column, rmse_per_column, nrmse_per_column,
+                neg_normalized_root_mean_squared_error, mae_per_column, neg_mean_absolute_error,
+                max_error_per_column, mape_per_column, smape_per_column, Average,
+                classification_report, multiclass_precision, multiclass_recall, multiclass_f1,
+                roc_curve, roc_auc_score, precision_recall_curve, average_precision_score,
+                neg_log_loss, neg_row_log_loss};
+
+    #[test]
+    fn test_accuracy() {
+        let outputs = [1, 2, 3, 4, 5, 6];
+        let targets = [1, 2, 3, 3, 5, 1];
+        assert_eq!(accuracy(outputs.iter(), targets.iter()), 2f64/3f64);
+
+        let outputs = [1, 1, 1, 0, 0, 0];
+        let targets = [1, 1, 1, 0, 0, 1];
+        assert_eq!(accuracy(outputs.iter(), targets.iter()), 5.0f64 / 6.0f64);
+    }
+
+    #[test]
+    fn test_precision() {
+        let outputs = [1, 1, 1, 0, 0, 0];
+        let targets = [1, 1, 0, 0, 1, 1];
+        assert_eq!(precision(outputs.iter(), targets.iter()), 2.0f64 / 3.0f64);
+
+        let outputs = [1, 1, 1, 0, 1, 1];
+        let targets = [1, 1, 0, 0, 1, 1];
+        assert_eq!(precision(outputs.iter(), targets.iter()), 0.8);
+
+        let outputs = [0, 0, 0, 1, 1, 1];
+        let targets = [1, 1, 1, 1, 1, 0];
+        assert_eq!(precision(outputs.iter(), targets.iter()), 2.0f64 / 3.0f64);
+
+        let outputs = [1, 1, 1, 1, 1, 0];
+        let targets = [0, 0, 0, 1, 1, 1];
+        assert_eq!(precision(outputs.iter(), targets.iter()), 0.4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_precision_outputs_not_2class() {
+        let outputs = [1, 2, 1, 0, 0, 0];
+        let targets = [1, 1, 0, 0, 1, 1];
+        precision(outputs.iter(), targets.iter());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_precision_targets_not_2class() {
+        let outputs = [1, 0, 1, 0, 0, 0];
+        let targets = [1, 2, 0, 0, 1, 1];
+        precision(outputs.iter(), targets.iter());
+    }
+
+    #[test]
+    fn test_recall() {
+        let outputs = [1, 1, 1, 0, 0, 0];
+        let targets = [1, 1, 0, 0, 1, 1];
+        assert_eq!(recall(outputs.iter(), targets.iter()), 0.5);
+
+        let outputs = [1, 1, 1, 0, 1, 1];
+        let targets = [1, 1, 0, 0, 1, 1];
+        assert_eq!(recall(outputs.iter(), targets.iter()), 1.0);
+
+        let outputs = [0, 0, 0, 1, 1, 1];
+        let targets = [1, 1, 1, 1, 1, 0];
+        assert_eq!(recall(outputs.iter(), targets.iter()), 0.4);
+
+        let outputs = [1, 1, 1, 1, 1, 0];
+        let targets = [0, 0, 0, 1, 1, 1];
+        assert_eq!(recall(outputs.iter(), targets.iter()), 2.0f64 / 3.0f64);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_recall_outputs_not_2class() {
+        let outputs = [1, 2, 1, 0, 0, 0];
+        let targets = [1, 1, 0, 0, 1, 1];
+        recall(outputs.iter(), targets.iter());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_recall_targets_not_2class() {
+        let outputs = [1, 0, 1, 0, 0, 0];
+        let targets = [1, 2, 0, 0, 1, 1];
+        recall(outputs.iter(), targets.iter());
+    }
+
+    #[test]
+    fn test_f1() {
+        let outputs = [1, 1, 1, 0, 0, 0];
+        let targets = [1, 1, 0, 0, 1, 1];
+        assert_eq!(f1(outputs.iter(), targets.iter()), 0.5714285714285714);
+
+        let outputs = [1, 1, 1, 0, 1, 1];
+        let targets = [1, 1, 0, 0, 1, 1];
+        assert_eq!(f1(outputs.iter(), targets.iter()), 0.8888888888888888);
+
+        let outputs = [0, 0, 0, 1, 1, 1];
+        let targets = [1, 1, 1, 1, 1, 0];
+        assert_eq!(f1(outputs.iter(), targets.iter()), 0.5);
+
+        let outputs = [1, 1, 1, 1, 1, 0];
+        let targets = [0, 0, 0, 1, 1, 1];
+        assert_eq!(f1(outputs.iter(), targets.iter()), 0.5);
+    }
+
+
+    #[test]
+    #[should_panic]
+    fn test_f1_outputs_not_2class() {
+        let outputs = [1, 2, 1, 0, 0, 0];
+        let targets = [1, 1, 0, 0, 1, 1];
+        f1(outputs.iter(), targets.iter());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_f1_targets_not_2class() {
+        let outputs = [1, 0, 1, 0, 0, 0];
+        let targets = [1, 2, 0, 0, 1, 1];
+        f1(outputs.iter(), targets.iter());
+    }
+
+    #[test]
+    fn test_neg_brier_score() {
+        let probs = [1., 0., 1., 0.];
+        let targets = [1., 0., 1., 0.];
+        assert_eq!(neg_brier_score(probs.iter(), targets.iter()), 0.);
+
+        let probs = [0.5, 0.5];
+        let targets = [1., 0.];
+        assert_eq!(neg_brier_score(probs.iter(), targets.iter()), -0.25);
+
+        let probs = [0., 1.];
+        let targets = [1., 0.];
+        assert_eq!(neg_brier_score(probs.iter(), targets.iter()), -1.);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_neg_brier_score_targets_not_2class() {
+        let probs = [0.5, 0.5];
+        let targets = [1., 2.];
+        neg_brier_score(probs.iter(), targets.iter());
+    }
+
+    #[test]
+    fn test_reliability_curve() {
+        let probs = [0.05, 0.15, 0.55, 0.75, 1.];
+        let targets = [0., 1., 0., 1., 1.];
+
+        let (mean_probs, frac_pos) = reliability_curve(probs.iter(), targets.iter(), 10);
+        assert_eq!(mean_probs, vec![0.05, 0.15, 0.55, 0.75, 1.]);
+        assert_eq!(frac_pos, vec![0., 1., 0., 1., 1.]);
+
+        let (mean_probs, frac_pos) = reliability_curve(probs.iter(), targets.iter(), 1);
+        assert!((mean_probs[0] - 0.5).abs() < 1e-12);
+        assert_eq!(frac_pos, vec![0.6]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_reliability_curve_prob_out_of_range() {
+        let probs = [0.5, 1.5];
+        let targets = [1., 0.];
+        reliability_curve(probs.iter(), targets.iter(), 5);
+    }
+
+    #[test]
+    fn test_neg_mean_squared_error_1d() {
+        let outputs = Matrix::new(3, 1, vec![1f64, 2f64, 3f64]);
+        let targets = Matrix::new(3, 1, vec![2f64, 4f64, 3f64]);
+        assert_eq!(neg_mean_squared_error(&outputs, &targets), -5f64/3f64);
+    }
+
+    #[test]
+    fn test_neg_mean_squared_error_2d() {
+        let outputs = Matrix::new(3, 2, vec![
+            1f64, 2f64,
+            3f64, 4f64,
+            5f64, 6f64
+            ]);
+        let targets = Matrix::new(3, 2, vec![
+            1.5f64, 2.5f64,
+            5f64,   6f64,
+            5.5f64, 6.5f64
+            ]);
+        assert_eq!(neg_mean_squared_error(&outputs, &targets), -3f64);
+    }
+
+    #[test]
+    fn test_hard_labels() {
+        let probs = Matrix::new(3, 3, vec![0.2, 0.5, 0.3, 0.9, 0.05, 0.05, 0.1, 0.1, 0.8]);
+        assert_eq!(hard_labels(&probs).into_vec(), vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn test_silhouette_score() {
+        let inputs = Matrix::new(4, 1, vec![0., 1., 10., 11.]);
+        let labels = [0, 0, 1, 1];
+        let expected = (9.5 / 10.5 + 8.5 / 9.5) / 2.;
+        assert!((silhouette_score(&inputs, labels.iter()) - expected).abs() < 1e-12);
+
+        // Noise points are ignored.
+        let inputs = Matrix::new(5, 1, vec![0., 1., 5., 10., 11.]);
+        let labels = [Some(0), Some(0), None, Some(1), Some(1)];
+        assert!((silhouette_score(&inputs, labels.iter()) - expected).abs() < 1e-12);
+
+        // A bad clustering has a negative score.
+        let inputs = Matrix::new(4, 1, vec![0., 1., 10., 11.]);
+        let labels = [0, 1, 0, 1];
+        assert!(silhouette_score(&inputs, labels.iter()) < 0.);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_silhouette_score_single_cluster() {
+        let inputs = Matrix::new(3, 1, vec![0., 1., 2.]);
+        let labels = [0, 0, 0];
+        silhouette_score(&inputs, labels.iter());
+    }
+
+    #[test]
+    fn test_neg_davies_bouldin_score() {
+        let inputs = Matrix::new(4, 1, vec![0., 1., 10., 11.]);
+        let labels = [0, 0, 1, 1];
+        assert!((neg_davies_bouldin_score(&inputs, labels.iter()) + 0.1).abs() < 1e-12);
+
+        // Centroids 0.5, 2.5 and 10.5 with scatter 0.5 each.
+        let inputs = Matrix::new(6, 1, vec![0., 1., 2., 3., 10., 11.]);
+        let labels = [0, 0, 1, 1, 2, 2];
+        let score = neg_davies_bouldin_score(&inputs, labels.iter());
+        assert!((score + (0.5 + 0.5 + 1. / 8.) / 3.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_neg_davies_bouldin_score_identical_clusters() {
+        // Both clusters have the centroid 0.5, so the pair is skipped.
+        let inputs = Matrix::new(4, 1, vec![0., 1., 0., 1.]);
+        let labels = [0, 0, 1, 1];
+        assert_eq!(neg_davies_bouldin_score(&inputs, labels.iter()), 0.);
+
+        // Only the distinct pairs contribute, the third cluster is 10 away.
+        let inputs = Matrix::new(6, 1, vec![0., 1., 0., 1., 10., 11.]);
+        let labels = [0, 0, 1, 1, 2, 2];
+        let score = neg_davies_bouldin_score(&inputs, labels.iter());
+        assert!((score + 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_calinski_harabasz_score() {
+        let inputs = Matrix::new(4, 1, vec![0., 1., 10., 11.]);
+        let labels = [0, 0, 1, 1];
+        assert_eq!(calinski_harabasz_score(&inputs, labels.iter()), 200.);
+
+        let inputs = Matrix::new(4, 1, vec![0., 0., 10., 10.]);
+        assert_eq!(calinski_harabasz_score(&inputs, labels.iter()), 1.);
+    }
+
+    #[test]
+    fn test_adjusted_rand_score() {
+        let outputs = [0, 0, 1, 1];
+        let targets = [1, 1, 0, 0];
+        assert_eq!(adjusted_rand_score(outputs.iter(), targets.iter()), 1.);
+
+        let outputs = [0, 0, 1, 2];
+        let targets = [0, 0, 1, 1];
+        assert!((adjusted_rand_score(outputs.iter(), targets.iter()) - 4. / 7.).abs() < 1e-12);
+
+        let outputs = [0, 0, 1, 1];
+        let targets = [0, 1, 0, 1];
+        assert!(adjusted_rand_score(outputs.iter(), targets.iter()) < 0.);
+
+        let outputs = [Some(0), Some(0), None, None];
+        let targets = [0, 0, 1, 1];
+        assert_eq!(adjusted_rand_score(outputs.iter(), targets.iter()), 1.);
+    }
+
+    #[test]
+    fn test_normalized_mutual_info_score() {
+        let outputs = [1, 1, 0, 0];
+        let targets = [0, 0, 1, 1];
+        assert!((normalized_mutual_info_score(outputs.iter(), targets.iter()) - 1.).abs() < 1e-12);
+
+        let outputs = [0, 0, 1, 1];
+        let targets = [0, 1, 0, 1];
+        assert_eq!(normalized_mutual_info_score(outputs.iter(), targets.iter()), 0.);
+
+        // The mutual information is ln 2 and the entropies are ln 4 and ln 2.
+        let outputs = [0, 1, 2, 3];
+        let targets = [0, 0, 1, 1];
+        let score = normalized_mutual_info_score(outputs.iter(), targets.iter());
+        assert!((score - 2. / 3.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_homogeneity_completeness() {
+        let outputs = [0, 1, 2, 3];
+        let targets = [0, 0, 1, 1];
+        assert!((homogeneity_score(outputs.iter(), targets.iter()) - 1.).abs() < 1e-12);
+        assert!((completeness_score(outputs.iter(), targets.iter()) - 0.5).abs() < 1e-12);
+
+        let outputs = [0, 0, 0, 0];
+        assert_eq!(homogeneity_score(outputs.iter(), targets.iter()), 0.);
+        assert_eq!(completeness_score(outputs.iter(), targets.iter()), 1.);
+    }
+
+    #[test]
+    fn test_cluster_scores_well_separated() {
+        // Two well separated clusters, numbered the other way round from the truth.
+        let inputs = Matrix::new(6, 2, vec![59.59375, 270.6875,
+                                            51.59375, 307.6875,
+                                            86.59375, 286.6875,
+                                            319.59375, 145.6875,
+                                            314.59375, 174.6875,
+                                            350.59375, 161.6875]);
+        let labels = [1, 1, 1, 0, 0, 0];
+        let truth = [0, 0, 0, 1, 1, 1];
+
+        assert!(silhouette_score(&inputs, labels.iter()) > 0.8);
+        assert!(neg_davies_bouldin_score(&inputs, labels.iter()) > -0.2);
+        assert!(calinski_harabasz_score(&inputs, labels.iter()) > 10.);
+
+        assert_eq!(adjusted_rand_score(labels.iter(), truth.iter()), 1.);
+        assert!((normalized_mutual_info_score(labels.iter(), truth.iter()) - 1.).abs() < 1e-12);
+        assert!((homogeneity_score(labels.iter(), truth.iter()) - 1.).abs() < 1e-12);
+        assert!((completeness_score(labels.iter(), truth.iter()) - 1.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_r2_score() {
+        let outputs = Matrix::new(4, 2, vec![1., 0., 2., 0., 3., 0., 5., 0.]);
+        let targets = Matrix::new(4, 2, vec![1., 1., 2., 2., 3., 3., 4., 4.]);
+
+        // The second column has SS_res 30 and SS_tot 5.
+        assert_eq!(r2_per_column(&outputs, &targets), vec![0.8, -5.]);
+        assert!((r2_score(&outputs, &targets) + 2.1).abs() < 1e-12);
+
+        let adjusted = adjusted_r2_per_column(&outputs, &targets, 1);
+        assert!((adjusted[0] - 0.7).abs() < 1e-12);
+        assert!((adjusted[1] + 8.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_r2_constant_targets() {
+        let targets = Matrix::new(3, 1, vec![2., 2., 2.]);
+        assert_eq!(r2_score(&targets, &targets), 1.);
+
+        let outputs = Matrix::new(3, 1, vec![2., 2., 3.]);
+        assert_eq!(r2_score(&outputs, &targets), 0.);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_adjusted_r2_too_many_features() {
+        let outputs = Matrix::new(3, 1, vec![1., 2., 3.]);
+        adjusted_r2_score(&outputs, &outputs, 2);
+    }
+
+    #[test]
+    fn test_explained_variance() {
+        let outputs = Matrix::new(3, 2, vec![2., 1., 3., 2., 4., 4.]);
+        let targets = Matrix::new(3, 2, vec![1., 1., 2., 2., 3., 3.]);
+
+        // The second column residuals are 0, 0 and -1 with variance 2 / 9.
+        let scores = explained_variance_per_column(&outputs, &targets);
+        assert_eq!(scores[0], 1.);
+        assert!((scores[1] - (1. - (2. / 9.) / (2. / 3.))).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_rmse_and_nrmse() {
+        let outputs = Matrix::new(2, 2, vec![1., 2., 3., 4.]);
+        let targets = Matrix::new(2, 2, vec![1., 4., 3., 0.]);
+
+        let rmse = rmse_per_column(&outputs, &targets);
+        assert_eq!(rmse[0], 0.);
+        assert!((rmse[1] - 10f64.sqrt()).abs() < 1e-12);
+
+        let nrmse = nrmse_per_column(&outputs, &targets);
+        assert_eq!(nrmse[0], 0.);
+        assert!((nrmse[1] - 10f64.sqrt() / 4.).abs() < 1e-12);
+
+        // The range of the first column is 2 and of the second 4.
+        let score = neg_normalized_root_mean_squared_error(&outputs, &targets);
+        assert!((score + 10f64.sqrt() / 8.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_mae_and_max_error() {
+        let outputs = Matrix::new(3, 2, vec![1., 0., 2., 0., 3., 0.]);
+        let targets = Matrix::new(3, 2, vec![1., 1., 4., 0., 3., 0.]);
+
+        assert_eq!(mae_per_column(&outputs, &targets), vec![2. / 3., 1. / 3.]);
+        assert!((neg_mean_absolute_error(&outputs, &targets) + 0.5).abs() < 1e-12);
+        assert_eq!(max_error_per_column(&outputs, &targets), vec![2., 1.]);
+    }
+
+    #[test]
+    fn test_mape_and_smape() {
+        let outputs = Matrix::new(2, 1, vec![110., 45.]);
+        let targets = Matrix::new(2, 1, vec![100., 50.]);
+        let mape = mape_per_column(&outputs, &targets);
+        assert!((mape[0] - 0.1).abs() < 1e-12);
+
+        let smape = smape_per_column(&outputs, &targets);
+        assert!((smape[0] - (10. / 105. + 5. / 47.5) / 2.).abs() < 1e-12);
+
+        // Zero targets give very large percentage errors.
+        let outputs = Matrix::new(1, 1, vec![1.]);
+        let targets = Matrix::new(1, 1, vec![0.]);
+        assert!(mape_per_column(&outputs, &targets)[0] > 1e10);
+        assert_eq!(smape_per_column(&outputs, &targets), vec![2.]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_regression_score_shape_mismatch() {
+        let outputs = Matrix::new(2, 1, vec![1., 2.]);
+        let targets = Matrix::new(1, 2, vec![1., 2.]);
+        r2_score(&outputs, &targets);
+    }
+
+    #[test]
+    fn test_multiclass_averages() {
+        let outputs = [0, 0, 2, 2, 0, 2];
+        let targets = [2, 0, 2, 2, 0, 1];
+
+        let report = classification_report(&outputs, &targets);
+        assert_eq!(report.precision_per_class(), vec![2. / 3., 0., 2. / 3.]);
+        assert_eq!(report.recall_per_class(), vec![1., 0., 2. / 3.]);
+        assert_eq!(report.f1_per_class(), vec![0.8, 0., 2. / 3.]);
+        assert_eq!(report.support(), vec![2, 1, 3]);
+
+        assert!((multiclass_precision(&outputs, &targets, Average::Macro) - 4. / 9.).abs() < 1e-12);
+        assert!((multiclass_recall(&outputs, &targets, Average::Macro) - 5. / 9.).abs() < 1e-12);
+        assert!((multiclass_f1(&outputs, &targets, Average::Macro) - 22. / 45.).abs() < 1e-12);
+
+        let weighted = Average::Weighted;
+        assert!((multiclass_precision(&outputs, &targets, weighted) - 5. / 9.).abs() < 1e-12);
+        assert!((multiclass_recall(&outputs, &targets, weighted) - 2. / 3.).abs() < 1e-12);
+        assert!((multiclass_f1(&outputs, &targets, weighted) - 0.6).abs() < 1e-12);
+
+        // In single label classification all micro averages equal the accuracy.
+        let micro = Average::Micro;
+        assert!((multiclass_precision(&outputs, &targets, micro) - 2. / 3.).abs() < 1e-12);
+        assert!((multiclass_recall(&outputs, &targets, micro) - 2. / 3.).abs() < 1e-12);
+        assert!((multiclass_f1(&outputs, &targets, micro) - 2. / 3.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_multiclass_matches_binary() {
+        let outputs = [1, 1, 1, 0, 0, 0];
+        let targets = [1, 1, 0, 0, 1, 1];
+
+        let report = classification_report(&outputs, &targets);
+        assert_eq!(report.precision_per_class()[1], precision(outputs.iter(), targets.iter()));
+        assert_eq!(report.recall_per_class()[1], recall(outputs.iter(), targets.iter()));
+        assert!((report.f1_per_class()[1] - f1(outputs.iter(), targets.iter())).abs() < 1e-12);
+        assert_eq!(report.accuracy(), accuracy(outputs.iter(), targets.iter()));
+    }
+
+    #[test]
+    fn test_classification_report_display() {
+        let outputs = ["cat", "dog", "dog"];
+        let targets = ["cat", "cat", "dog"];
+
+        let report = classification_report(&outputs, &targets).to_string();
+        let lines = report.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "             precision    recall  f1-score   support");
+        assert_eq!(lines[2], "         cat      1.00      0.50      0.67         2");
+        assert_eq!(lines[3], "         dog      0.50      1.00      0.67         1");
+        assert_eq!(lines[5], "    accuracy                          0.67         3");
+        assert_eq!(lines[6], "   macro avg      0.75      0.75      0.67         3");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_classification_report_empty() {
+        let outputs: [usize; 0] = [];
+        classification_report(&outputs, &outputs);
+    }
+
+    #[test]
+    fn test_roc_curve_ties() {
+        let scores = [0.5, 0.5, 0.2];
+        let targets = [1., 0., 0.];
+
+        let (fpr, tpr, thresholds) = roc_curve(scores.iter(), targets.iter());
+        assert_eq!(fpr, vec![0., 0.5, 1.]);
+        assert_eq!(tpr, vec![0., 1., 1.]);
+        assert_eq!(&thresholds[1..], &[0.5, 0.2]);
+        assert_eq!(roc_auc_score(scores.iter(), targets.iter()), 0.75);
+    }
+
+    #[test]
+    fn test_roc_auc_perfect() {
+        let scores = [0.1, 0.2, 0.8, 0.9];
+        let targets = [0., 0., 1., 1.];
+        assert_eq!(roc_auc_score(scores.iter(), targets.iter()), 1.);
+        assert_eq!(average_precision_score(scores.iter(), targets.iter()), 1.);
+
+        let targets = [1., 1., 0., 0.];
+        assert_eq!(roc_auc_score(scores.iter(), targets.iter()), 0.);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_roc_curve_single_class() {
+        let scores = [0.1, 0.2];
+        let targets = [1., 1.];
+        roc_curve(scores.iter(), targets.iter());
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be empty")]
+    fn test_roc_curve_empty() {
+        let scores: [f64; 0] = [];
+        let targets: [f64; 0] = [];
+        roc_curve(scores.iter(), targets.iter());
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be empty")]
+    fn test_precision_recall_curve_empty() {
+        let scores: [f64; 0] = [];
+        let targets: [f64; 0] = [];
+        precision_recall_curve(scores.iter(), targets.iter());
+    }
+
+    #[test]
+    fn test_neg_log_loss() {
+        let probs = [1., 0.];
+        let targets = [1., 0.];
+        assert!(neg_log_loss(probs.iter(), targets.iter()).abs() < 1e-12);
+
+        // Confident mistakes are clipped to a finite loss.
+        let targets = [0., 1.];
+        let score = neg_log_loss(probs.iter(), targets.iter());
+        assert!(score.is_finite() && score < -30.);
+
+        let probs = Matrix::new(2, 2, vec![0.5, 0.5, 0.25, 0.75]);
+        let targets = Matrix::new(2, 2, vec![1., 0., 0., 1.]);
+        let expected = (0.5f64.ln() + 0.75f64.ln()) / 2.;
+        assert!((neg_row_log_loss(&probs, &targets) - expected).abs() < 1e-12);
     }
 }
@@ -0,0 +1,486 @@
+//! Agglomerative Clustering
+//!
+//! Provides an implementation of agglomerative hierarchical clustering.
+//!
+//! Each point starts in its own cluster and the two closest clusters are
+//! merged repeatedly until a single cluster remains. The distance between
+//! clusters is determined by the linkage:
+//!
+//! - `Single` : the distance between their closest points.
+//! - `Complete` : the distance between their farthest points.
+//! - `Average` : the mean distance between their points.
+//! - `Ward` : the increase in within-cluster variance caused by the merge.
+//!
+//! The full merge tree is available as a linkage matrix, and flat
+//! clusterings are found by cutting the tree at a number of clusters
+//! or a merge distance. The linkage matrix can be drawn with
+//! `plot::dendrogram::plot_dendrogram`.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::agglomerative::{AgglomerativeClustering, Linkage, Cut};
+//! use rusty_machine::learning::UnSupModel;
+//! use rusty_machine::linalg::Matrix;
+//!
+//! let inputs = Matrix::new(6, 2, vec![1.0, 2.0,
+//!                                     1.1, 2.2,
+//!                                     0.9, 1.9,
+//!                                     1.0, 2.1,
+//!                                     -2.0, 3.0,
+//!                                     -2.2, 3.1]);
+//!
+//! let mut model = AgglomerativeClustering::new(Linkage::Average, Cut::Clusters(2));
+//! model.train(&inputs).unwrap();
+//!
+//! let clusters = model.clusters().unwrap();
+//! assert_eq!(clusters.data(), &vec![0, 0, 0, 0, 1, 1]);
+//!
+//! // The tree can be cut again without retraining.
+//! let clusters = model.flat_clusters(Cut::Distance(0.5)).unwrap();
+//! assert_eq!(clusters.data(), &vec![0, 0, 0, 0, 1, 1]);
+//! ```
+
+use std::f64;
+
+use learning::{LearningResult, UnSupModel};
+use learning::error::{Error, ErrorKind};
+use learning::knn::{BruteForce, KNearestSearch};
+use learning::toolkit::distance::Metric;
+
+use linalg::{Matrix, Vector, BaseMatrix};
+
+/// The distance between clusters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Linkage {
+    /// The distance between the closest points of the clusters.
+    Single,
+    /// The distance between the farthest points of the clusters.
+    Complete,
+    /// The mean distance between the points of the clusters.
+    Average,
+    /// The increase in within-cluster variance caused by merging the
+    /// clusters, scaled to be a Euclidean distance.
+    Ward,
+}
+
+impl Linkage {
+    /// Compute the distance from the merge of clusters `a` and `b`
+    /// to cluster `l` using the Lance-Williams formula.
+    ///
+    /// Ward distances are squared.
+    fn update(&self, d_al: f64, d_bl: f64, d_ab: f64, n_a: usize, n_b: usize, n_l: usize) -> f64 {
+        let (n_a, n_b, n_l) = (n_a as f64, n_b as f64, n_l as f64);
+        match *self {
+            Linkage::Single => d_al.min(d_bl),
+            Linkage::Complete => d_al.max(d_bl),
+            Linkage::Average => (n_a * d_al + n_b * d_bl) / (n_a + n_b),
+            Linkage::Ward => {
+                ((n_a + n_l) * d_al + (n_b + n_l) * d_bl - n_l * d_ab) / (n_a + n_b + n_l)
+            }
+        }
+    }
+}
+
+/// The criterion used to cut the merge tree into flat clusters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cut {
+    /// Stop merging when this many clusters remain.
+    Clusters(usize),
+    /// Only apply merges at distances up to this threshold.
+    Distance(f64),
+}
+
+/// Agglomerative Clustering Model
+///
+/// Implements hierarchical clustering via the `UnSupModel` trait.
+#[derive(Debug)]
+pub struct AgglomerativeClustering {
+    linkage: Linkage,
+    cut: Cut,
+    searcher: BruteForce,
+    linkage_matrix: Option<Matrix<f64>>,
+    clusters: Option<Vector<usize>>,
+}
+
+/// Constructs an agglomerative clustering model with the
+/// following parameters:
+///
+/// - `linkage` : `Ward`
+/// - `cut` : `Clusters(2)`
+/// - `metric` : `Euclidean`
+impl Default for AgglomerativeClustering {
+    fn default() -> AgglomerativeClustering {
+        AgglomerativeClustering::new(Linkage::Ward, Cut::Clusters(2))
+    }
+}
+
+impl UnSupModel<Matrix<f64>, Vector<usize>> for AgglomerativeClustering {
+    /// Train the model using input data.
+    ///
+    /// This computes the full merge tree and cuts it using the model's criterion.
+    fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
+        let n = inputs.rows();
+        if n == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "There are no training inputs."));
+        }
+
+        let mut dist = Vec::with_capacity(n * n);
+        for a in inputs.row_iter() {
+            for b in inputs.row_iter() {
+                let d = self.searcher.metric().distance(a.raw_slice(), b.raw_slice());
+                dist.push(if self.linkage == Linkage::Ward { d * d } else { d });
+            }
+        }
+        if !dist.iter().all(|d| d.is_finite()) {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The inputs led to invalid distances between points."));
+        }
+
+        let merges = nn_chain(dist, n, self.linkage);
+        let linkage_matrix = linkage_matrix(n, merges);
+        let clusters = cut_tree(&linkage_matrix, self.cut)?;
+
+        self.searcher.build(inputs.clone());
+        self.linkage_matrix = Some(linkage_matrix);
+        self.clusters = Some(clusters);
+
+        Ok(())
+    }
+
+    /// Predict the clusters of new points.
+    ///
+    /// Each point is given the cluster of the closest training point.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<usize>> {
+        if let Some(ref clusters) = self.clusters {
+            let classes = self.searcher
+                .search_many(inputs, 1)?
+                .into_iter()
+                .map(|(idx, _)| clusters[idx[0]])
+                .collect::<Vec<_>>();
+
+            Ok(Vector::new(classes))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+impl AgglomerativeClustering {
+    /// Create a new agglomerative clustering model with the
+    /// given linkage and cut criterion.
+    ///
+    /// Distances are measured with the Euclidean metric.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::agglomerative::{AgglomerativeClustering, Linkage, Cut};
+    ///
+    /// let _ = AgglomerativeClustering::new(Linkage::Complete, Cut::Distance(1.5));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - The cut is at zero clusters or a negative distance.
+    pub fn new(linkage: Linkage, cut: Cut) -> AgglomerativeClustering {
+        check_cut(cut);
+
+        AgglomerativeClustering {
+            linkage: linkage,
+            cut: cut,
+            searcher: BruteForce::new(),
+            linkage_matrix: None,
+            clusters: None,
+        }
+    }
+
+    /// Create a new agglomerative clustering model with the
+    /// given linkage, cut criterion and distance metric.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::agglomerative::{AgglomerativeClustering, Linkage, Cut};
+    /// use rusty_machine::learning::toolkit::distance::Manhattan;
+    ///
+    /// let _ = AgglomerativeClustering::with_metric(Linkage::Average, Cut::Clusters(3), Manhattan);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - The linkage is `Ward`, which requires the Euclidean metric.
+    /// - The cut is at zero clusters or a negative distance.
+    pub fn with_metric<M: Metric + 'static>(linkage: Linkage,
+                                            cut: Cut,
+                                            metric: M)
+                                            -> AgglomerativeClustering {
+        assert!(linkage != Linkage::Ward, "Ward linkage requires the Euclidean metric.");
+        check_cut(cut);
+
+        AgglomerativeClustering {
+            linkage: linkage,
+            cut: cut,
+            searcher: BruteForce::with_metric(metric),
+            linkage_matrix: None,
+            clusters: None,
+        }
+    }
+
+    /// The linkage used to measure distances between clusters.
+    pub fn linkage(&self) -> Linkage {
+        self.linkage
+    }
+
+    /// The criterion used to cut the merge tree during training.
+    pub fn cut(&self) -> Cut {
+        self.cut
+    }
+
+    /// Return an Option pointing to the model clusters.
+    pub fn clusters(&self) -> Option<&Vector<usize>> {
+        self.clusters.as_ref()
+    }
+
+    /// The linkage matrix of the merge tree.
+    ///
+    /// The matrix has a row for each of the `n - 1` merges, in order of
+    /// increasing distance. Each row contains the two merged nodes, the
+    /// merge distance and the number of points in the new cluster. The
+    /// nodes `0..n` are the training points and the `i`th merge creates
+    /// node `n + i`. This matches the format used by SciPy.
+    pub fn linkage_matrix(&self) -> Option<&Matrix<f64>> {
+        self.linkage_matrix.as_ref()
+    }
+
+    /// Cut the trained merge tree into flat clusters.
+    ///
+    /// Clusters are labelled in order of their first training point.
+    ///
+    /// # Failures
+    ///
+    /// - The model has not been trained.
+    /// - The cut asks for more clusters than there are training points.
+    pub fn flat_clusters(&self, cut: Cut) -> LearningResult<Vector<usize>> {
+        check_cut(cut);
+
+        match self.linkage_matrix {
+            Some(ref linkage_matrix) => cut_tree(linkage_matrix, cut),
+            None => Err(Error::new_untrained()),
+        }
+    }
+}
+
+/// Check that a cut criterion is valid.
+fn check_cut(cut: Cut) {
+    match cut {
+        Cut::Clusters(k) => assert!(k > 0, "There must be at least one cluster."),
+        Cut::Distance(d) => assert!(d >= 0f64, "The distance threshold must be non-negative."),
+    }
+}
+
+/// Compute the merges using the nearest neighbour chain algorithm.
+///
+/// Takes the row-major matrix of distances between points, squared for
+/// Ward linkage. Returns the merges in the order they were found, each
+/// merged cluster identified by one of its points.
+fn nn_chain(mut dist: Vec<f64>, n: usize, linkage: Linkage) -> Vec<(usize, usize, f64)> {
+    let mut sizes = vec![1; n];
+    let mut active = vec![true; n];
+    let mut chain: Vec<usize> = Vec::with_capacity(n);
+    let mut merges = Vec::with_capacity(n - 1);
+
+    for _ in 1..n {
+        if chain.is_empty() {
+            chain.push(active.iter().position(|&a| a).expect("There are active clusters."));
+        }
+
+        loop {
+            let a = chain[chain.len() - 1];
+            let prev = if chain.len() > 1 { Some(chain[chain.len() - 2]) } else { None };
+
+            // Prefer the previous cluster in the chain on ties.
+            let (mut b, mut best) = match prev {
+                Some(p) => (p, dist[a * n + p]),
+                None => (a, f64::INFINITY),
+            };
+            for x in (0..n).filter(|&x| active[x] && x != a) {
+                if dist[a * n + x] < best {
+                    b = x;
+                    best = dist[a * n + x];
+                }
+            }
+
+            if Some(b) != prev {
+                chain.push(b);
+                continue;
+            }
+
+            // a and b are reciprocal nearest neighbours, merge a into b.
+            let new_len = chain.len() - 2;
+            chain.truncate(new_len);
+
+            for l in (0..n).filter(|&l| active[l] && l != a && l != b) {
+                let d = linkage.update(dist[a * n + l],
+                                       dist[b * n + l],
+                                       best,
+                                       sizes[a],
+                                       sizes[b],
+                                       sizes[l]);
+                dist[b * n + l] = d;
+                dist[l * n + b] = d;
+            }
+
+            active[a] = false;
+            sizes[b] += sizes[a];
+            merges.push((a, b, if linkage == Linkage::Ward { best.sqrt() } else { best }));
+            break;
+        }
+    }
+
+    merges
+}
+
+/// Find the root of a node, halving the path on the way.
+fn find_root(parents: &mut [usize], mut node: usize) -> usize {
+    while parents[node] != node {
+        parents[node] = parents[parents[node]];
+        node = parents[node];
+    }
+    node
+}
+
+/// Build the linkage matrix from merges between clusters identified by their points.
+fn linkage_matrix(n: usize, mut merges: Vec<(usize, usize, f64)>) -> Matrix<f64> {
+    merges.sort_by(|a, b| a.2.partial_cmp(&b.2).expect("Distances must be comparable."));
+
+    let mut parents = (0..2 * n - 1).collect::<Vec<_>>();
+    let mut sizes = vec![1; 2 * n - 1];
+    let mut data = Vec::with_capacity(4 * (n - 1));
+
+    for (i, (a, b, dist)) in merges.into_iter().enumerate() {
+        let (ra, rb) = (find_root(&mut parents, a), find_root(&mut parents, b));
+        let node = n + i;
+
+        parents[ra] = node;
+        parents[rb] = node;
+        sizes[node] = sizes[ra] + sizes[rb];
+        data.extend_from_slice(&[ra.min(rb) as f64, ra.max(rb) as f64, dist, sizes[node] as f64]);
+    }
+
+    Matrix::new(n - 1, 4, data)
+}
+
+/// Cut the merge tree described by a linkage matrix into flat clusters.
+fn cut_tree(linkage_matrix: &Matrix<f64>, cut: Cut) -> LearningResult<Vector<usize>> {
+    let n = linkage_matrix.rows() + 1;
+
+    let merge_count = match cut {
+        Cut::Clusters(k) if k > n => {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  format!("Number of clusters ({0}) exceeds number of data \
+                                           points ({1}).",
+                                          k,
+                                          n)));
+        }
+        Cut::Clusters(k) => n - k,
+        Cut::Distance(d) => {
+            linkage_matrix.row_iter().take_while(|row| row.raw_slice()[2] <= d).count()
+        }
+    };
+
+    let mut parents = (0..2 * n - 1).collect::<Vec<_>>();
+    for (i, row) in linkage_matrix.row_iter().take(merge_count).enumerate() {
+        parents[row.raw_slice()[0] as usize] = n + i;
+        parents[row.raw_slice()[1] as usize] = n + i;
+    }
+
+    let mut labels = vec![None; 2 * n - 1];
+    let mut next_label = 0;
+    let mut clusters = Vec::with_capacity(n);
+    for point in 0..n {
+        let root = find_root(&mut parents, point);
+        let label = *labels[root].get_or_insert_with(|| {
+            next_label += 1;
+            next_label - 1
+        });
+        clusters.push(label);
+    }
+
+    Ok(Vector::new(clusters))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Linkage, Cut, nn_chain, linkage_matrix, cut_tree};
+    use linalg::BaseMatrix;
+
+    // Points at 0, 1, 3 and 7 on a line.
+    fn line_distances(square: bool) -> Vec<f64> {
+        let points = [0f64, 1., 3., 7.];
+        let mut dist = Vec::new();
+        for a in &points {
+            for b in &points {
+                let d = (a - b).abs();
+                dist.push(if square { d * d } else { d });
+            }
+        }
+        dist
+    }
+
+    fn heights(linkage: Linkage) -> Vec<f64> {
+        let merges = nn_chain(line_distances(linkage == Linkage::Ward), 4, linkage);
+        let m = linkage_matrix(4, merges);
+        m.row_iter().map(|row| row.raw_slice()[2]).collect()
+    }
+
+    #[test]
+    fn test_single_linkage() {
+        assert_eq!(heights(Linkage::Single), vec![1., 2., 4.]);
+    }
+
+    #[test]
+    fn test_complete_linkage() {
+        assert_eq!(heights(Linkage::Complete), vec![1., 3., 7.]);
+    }
+
+    #[test]
+    fn test_average_linkage() {
+        // {0, 1} to 3 is (3 + 2) / 2, then {0, 1, 3} to 7 is (7 + 6 + 4) / 3.
+        assert_eq!(heights(Linkage::Average), vec![1., 2.5, 17. / 3.]);
+    }
+
+    #[test]
+    fn test_ward_linkage() {
+        // Ward distances are sqrt(2 n_a n_b / (n_a + n_b)) times the centroid distance.
+        let h = heights(Linkage::Ward);
+        let expected = [1., (4f64 / 3.).sqrt() * 2.5, (1.5f64).sqrt() * (7. - 4. / 3.)];
+        for (a, b) in h.iter().zip(expected.iter()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_linkage_matrix() {
+        let merges = nn_chain(line_distances(false), 4, Linkage::Single);
+        let m = linkage_matrix(4, merges);
+
+        assert_eq!(m.data(), &vec![0., 1., 1., 2.,
+                                   2., 4., 2., 3.,
+                                   3., 5., 4., 4.]);
+    }
+
+    #[test]
+    fn test_cut_tree() {
+        let merges = nn_chain(line_distances(false), 4, Linkage::Single);
+        let m = linkage_matrix(4, merges);
+
+        assert_eq!(cut_tree(&m, Cut::Clusters(1)).unwrap().into_vec(), vec![0, 0, 0, 0]);
+        assert_eq!(cut_tree(&m, Cut::Clusters(2)).unwrap().into_vec(), vec![0, 0, 0, 1]);
+        assert_eq!(cut_tree(&m, Cut::Clusters(4)).unwrap().into_vec(), vec![0, 1, 2, 3]);
+        assert!(cut_tree(&m, Cut::Clusters(5)).is_err());
+
+        assert_eq!(cut_tree(&m, Cut::Distance(1.5)).unwrap().into_vec(), vec![0, 0, 1, 2]);
+        assert_eq!(cut_tree(&m, Cut::Distance(0.5)).unwrap().into_vec(), vec![0, 1, 2, 3]);
+    }
+}
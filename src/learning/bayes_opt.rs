@@ -0,0 +1,417 @@
+//! Bayesian Optimization
+//!
+//! Provides Bayesian optimization of expensive black-box functions
+//! over box bounds.
+//!
+//! A `GaussianProcess` surrogate is fitted to the points evaluated so
+//! far. The next point to evaluate is the one maximising an acquisition
+//! function, which trades off exploring uncertain regions against
+//! exploiting regions with a high predicted value.
+//!
+//! The objective is *maximised*. To minimise a function negate it.
+//!
+//! # Usage
+//!
+//! ```
+//! # extern crate rand;
+//! # extern crate rusty_machine;
+//! use rusty_machine::learning::bayes_opt::{BayesianOptimizer, Acquisition};
+//! use rusty_machine::learning::toolkit::kernel::SquaredExp;
+//! use rusty_machine::benchmark_functions::analytic::Multimodal1D;
+//! use rand::{StdRng, SeedableRng};
+//!
+//! # fn main() {
+//! let mut optimizer = BayesianOptimizer::new(vec![(0., 1.2)],
+//!                                            SquaredExp::new(0.1, 1.),
+//!                                            Acquisition::ExpectedImprovement { xi: 0.01 });
+//!
+//! let seed: &[_] = &[1, 2, 3];
+//! let mut rng = StdRng::from_seed(seed);
+//!
+//! let (x, y) = optimizer.maximize(|x| x[0].multimodal1_d(), 3, 10, &mut rng).unwrap();
+//! println!("Found maximum {} at {}", y, x[0]);
+//! # }
+//! ```
+//!
+//! Alternatively the optimization loop can be driven manually with
+//! `optimizer.suggest()` and `optimizer.observe()`.
+
+use std::cmp;
+use std::f64;
+
+use rand::Rng;
+use rand::distributions::normal::StandardNormal;
+
+use learning::gp::{GaussianProcess, ConstMean};
+use learning::toolkit::kernel::Kernel;
+use learning::{LearningResult, SupModel};
+use learning::error::{Error, ErrorKind};
+use linalg::{Matrix, Vector};
+
+/// The number of local refinement rounds around the best candidate.
+const REFINE_ROUNDS: usize = 3;
+
+/// The number of points sampled in each local refinement round.
+const REFINE_POINTS: usize = 100;
+
+/// Acquisition functions used to choose the next point.
+///
+/// Each is evaluated on the standardised predictions of the surrogate.
+#[derive(Clone, Copy, Debug)]
+pub enum Acquisition {
+    /// The expected improvement over the best observation.
+    ///
+    /// `xi` is the minimum improvement worth having; larger values explore more.
+    ExpectedImprovement {
+        /// The improvement margin.
+        xi: f64,
+    },
+    /// The upper confidence bound, `mean + kappa * std_dev`.
+    ///
+    /// Larger values of `kappa` explore more.
+    UpperConfidenceBound {
+        /// The weight of the standard deviation.
+        kappa: f64,
+    },
+    /// The probability of improving on the best observation by at least `xi`.
+    ProbabilityOfImprovement {
+        /// The improvement margin.
+        xi: f64,
+    },
+}
+
+/// The default acquisition function.
+///
+/// Expected improvement with `xi = 0.01`.
+impl Default for Acquisition {
+    fn default() -> Acquisition {
+        Acquisition::ExpectedImprovement { xi: 0.01 }
+    }
+}
+
+impl Acquisition {
+    /// Evaluates the acquisition function.
+    ///
+    /// Takes the predictive mean and variance at a point and the best
+    /// observed value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::bayes_opt::Acquisition;
+    ///
+    /// let ucb = Acquisition::UpperConfidenceBound { kappa: 2. };
+    /// assert_eq!(ucb.evaluate(1., 4., 0.), 5.);
+    /// ```
+    pub fn evaluate(&self, mean: f64, variance: f64, best: f64) -> f64 {
+        let std_dev = variance.max(0f64).sqrt();
+
+        match *self {
+            Acquisition::ExpectedImprovement { xi } => {
+                let improvement = mean - best - xi;
+                if std_dev > 0f64 {
+                    let z = improvement / std_dev;
+                    improvement * std_normal_cdf(z) + std_dev * std_normal_pdf(z)
+                } else {
+                    improvement.max(0f64)
+                }
+            }
+            Acquisition::UpperConfidenceBound { kappa } => mean + kappa * std_dev,
+            Acquisition::ProbabilityOfImprovement { xi } => {
+                let improvement = mean - best - xi;
+                if std_dev > 0f64 {
+                    std_normal_cdf(improvement / std_dev)
+                } else if improvement > 0f64 {
+                    1f64
+                } else {
+                    0f64
+                }
+            }
+        }
+    }
+}
+
+/// The density of the standard normal distribution.
+fn std_normal_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2f64 * f64::consts::PI).sqrt()
+}
+
+/// The cumulative distribution function of the standard normal distribution.
+fn std_normal_cdf(x: f64) -> f64 {
+    0.5 * (1f64 + erf(x / f64::consts::SQRT_2))
+}
+
+/// The error function.
+///
+/// Uses the approximation 7.1.26 from Abramowitz and Stegun,
+/// which has an absolute error below 1.5e-7.
+fn erf(x: f64) -> f64 {
+    // The approximation is only accurate to 1e-9 at zero, where erf is exactly zero.
+    if x == 0f64 {
+        return 0f64;
+    }
+
+    let t = 1f64 / (1f64 + 0.3275911 * x.abs());
+    let poly = t *
+               (0.254829592 +
+                t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    x.signum() * (1f64 - poly * (-x * x).exp())
+}
+
+/// Bayesian optimizer over box bounds.
+///
+/// Keeps the evaluated points and fits a gaussian process surrogate
+/// with the given kernel to them. The targets are standardised before
+/// fitting, so the kernel amplitude should be on the order of one.
+#[derive(Debug)]
+pub struct BayesianOptimizer<T: Kernel + Clone> {
+    bounds: Vec<(f64, f64)>,
+    gp: GaussianProcess<T, ConstMean>,
+    /// The acquisition function used to choose the next point.
+    pub acquisition: Acquisition,
+    /// The number of random candidates the acquisition function is evaluated at.
+    pub num_candidates: usize,
+    /// Whether to fit the kernel hyperparameters to the observations.
+    pub hyperopt: bool,
+    hyperopt_error: Option<Error>,
+    inputs: Vec<f64>,
+    targets: Vec<f64>,
+}
+
+impl<T: Kernel + Clone> BayesianOptimizer<T> {
+    /// Constructs a new Bayesian optimizer.
+    ///
+    /// Takes the lower and upper bound of each input dimension,
+    /// the kernel of the surrogate and the acquisition function.
+    ///
+    /// The defaults are:
+    ///
+    /// - num_candidates = 1000
+    /// - hyperopt = true
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::bayes_opt::{BayesianOptimizer, Acquisition};
+    /// use rusty_machine::learning::toolkit::kernel::Matern52;
+    ///
+    /// let optimizer = BayesianOptimizer::new(vec![(-1., 1.), (0., 10.)],
+    ///                                        Matern52::default(),
+    ///                                        Acquisition::UpperConfidenceBound { kappa: 2. });
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - There are no bounds.
+    /// - A lower bound is not less than its upper bound.
+    pub fn new(bounds: Vec<(f64, f64)>, ker: T, acquisition: Acquisition) -> BayesianOptimizer<T> {
+        assert!(!bounds.is_empty(), "At least one input dimension is required.");
+        for &(lo, hi) in &bounds {
+            assert!(lo < hi, "The lower bound {} is not less than the upper bound {}.", lo, hi);
+        }
+
+        BayesianOptimizer {
+            bounds: bounds,
+            gp: GaussianProcess::new(ker, ConstMean::default(), 1e-6),
+            acquisition: acquisition,
+            num_candidates: 1000,
+            hyperopt: true,
+            hyperopt_error: None,
+            inputs: Vec::new(),
+            targets: Vec::new(),
+        }
+    }
+
+    /// The number of input dimensions.
+    pub fn dim(&self) -> usize {
+        self.bounds.len()
+    }
+
+    /// The number of observations.
+    pub fn num_observations(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// The error from fitting the kernel hyperparameters in the last `suggest`.
+    ///
+    /// When the fit fails the surrogate is trained with the kernel
+    /// hyperparameters it already has, and the error is kept here.
+    /// Returns `None` if the last fit succeeded or was not attempted.
+    pub fn hyperopt_error(&self) -> Option<&Error> {
+        self.hyperopt_error.as_ref()
+    }
+
+    /// Records the value of the objective at a point.
+    ///
+    /// # Panics
+    ///
+    /// - The point does not have one value per input dimension.
+    pub fn observe(&mut self, x: &[f64], y: f64) {
+        assert!(x.len() == self.dim(),
+                "The point has {} dimensions but the bounds have {}.",
+                x.len(),
+                self.dim());
+        self.inputs.extend_from_slice(x);
+        self.targets.push(y);
+    }
+
+    /// The best observed point and its value.
+    pub fn best(&self) -> Option<(&[f64], f64)> {
+        let dim = self.dim();
+        self.targets
+            .iter()
+            .enumerate()
+            .fold(None, |best: Option<(usize, f64)>, (i, &y)| match best {
+                Some((_, b)) if b >= y => best,
+                _ => Some((i, y)),
+            })
+            .map(|(i, y)| (&self.inputs[i * dim..(i + 1) * dim], y))
+    }
+
+    /// Proposes the next point to evaluate.
+    ///
+    /// Without any observations this is a uniformly random point.
+    /// Otherwise the surrogate is refitted and the acquisition function
+    /// is maximised by random search followed by local refinement.
+    ///
+    /// If fitting the kernel hyperparameters fails the previous ones are
+    /// kept, see `hyperopt_error`.
+    pub fn suggest<R: Rng>(&mut self, rng: &mut R) -> LearningResult<Vec<f64>> {
+        if self.targets.is_empty() {
+            return Ok(self.random_point(rng));
+        }
+
+        // Standardise the targets so the zero mean prior is sensible.
+        let n = self.targets.len();
+        let mean = self.targets.iter().sum::<f64>() / n as f64;
+        let var = self.targets.iter().map(|y| (y - mean) * (y - mean)).sum::<f64>() / n as f64;
+        let scale = if var > 0f64 { var.sqrt() } else { 1f64 };
+        let targets = Vector::new(self.targets.iter().map(|y| (y - mean) / scale).collect::<Vec<_>>());
+        let inputs = Matrix::new(n, self.dim(), self.inputs.clone());
+
+        self.hyperopt_error = if self.hyperopt {
+            self.gp.train_with_hyperopt(&inputs, &targets, 0).err()
+        } else {
+            None
+        };
+        if !self.hyperopt || self.hyperopt_error.is_some() {
+            self.gp.train(&inputs, &targets)?;
+        }
+
+        let best = targets.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+        let candidates = (0..cmp::max(self.num_candidates, 1))
+            .map(|_| self.random_point(rng))
+            .collect::<Vec<_>>();
+        let (mut best_x, mut best_acq) = self.best_candidate(candidates, best)?;
+
+        // Sample around the best candidate with a shrinking radius.
+        let mut radius = 0.1;
+        for _ in 0..REFINE_ROUNDS {
+            let candidates = (0..REFINE_POINTS)
+                .map(|_| self.perturb(&best_x, radius, rng))
+                .collect::<Vec<_>>();
+            let (x, acq) = self.best_candidate(candidates, best)?;
+            if acq > best_acq {
+                best_x = x;
+                best_acq = acq;
+            }
+            radius /= 4f64;
+        }
+
+        Ok(best_x)
+    }
+
+    /// Maximises the objective.
+    ///
+    /// Evaluates the objective at `init_points` random points and then
+    /// at `iters` points proposed by `suggest`. Returns the best point
+    /// and its value over all observations, including any made before.
+    ///
+    /// # Failures
+    ///
+    /// - No points were evaluated.
+    /// - The surrogate could not be trained.
+    pub fn maximize<F, R>(&mut self,
+                          mut objective: F,
+                          init_points: usize,
+                          iters: usize,
+                          rng: &mut R)
+                          -> LearningResult<(Vec<f64>, f64)>
+        where F: FnMut(&[f64]) -> f64,
+              R: Rng
+    {
+        for _ in 0..init_points {
+            let x = self.random_point(rng);
+            let y = objective(&x);
+            self.observe(&x, y);
+        }
+
+        for _ in 0..iters {
+            let x = self.suggest(rng)?;
+            let y = objective(&x);
+            self.observe(&x, y);
+        }
+
+        self.best()
+            .map(|(x, y)| (x.to_vec(), y))
+            .ok_or_else(|| {
+                Error::new(ErrorKind::InvalidState,
+                           "The objective has not been evaluated at any points.")
+            })
+    }
+
+    /// Draws a uniformly random point within the bounds.
+    fn random_point<R: Rng>(&self, rng: &mut R) -> Vec<f64> {
+        self.bounds.iter().map(|&(lo, hi)| rng.gen_range(lo, hi)).collect()
+    }
+
+    /// Perturbs a point by gaussian noise scaled to the bounds, clamped to the bounds.
+    fn perturb<R: Rng>(&self, x: &[f64], radius: f64, rng: &mut R) -> Vec<f64> {
+        x.iter()
+            .zip(&self.bounds)
+            .map(|(xi, &(lo, hi))| {
+                let StandardNormal(z) = rng.gen::<StandardNormal>();
+                (xi + z * radius * (hi - lo)).max(lo).min(hi)
+            })
+            .collect()
+    }
+
+    /// Finds the candidate with the largest acquisition value.
+    fn best_candidate(&self,
+                      candidates: Vec<Vec<f64>>,
+                      best: f64)
+                      -> LearningResult<(Vec<f64>, f64)> {
+        let num = candidates.len();
+        let flat = candidates.iter().flat_map(|c| c.iter().cloned()).collect::<Vec<_>>();
+        let (mean, var) = self.gp.predict_with_variance(&Matrix::new(num, self.dim(), flat))?;
+
+        let mut best_idx = 0;
+        let mut best_acq = f64::NEG_INFINITY;
+        for i in 0..num {
+            let acq = self.acquisition.evaluate(mean[i], var[i], best);
+            if acq > best_acq {
+                best_idx = i;
+                best_acq = acq;
+            }
+        }
+
+        Ok((candidates[best_idx].clone(), best_acq))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{erf, std_normal_cdf, std_normal_pdf};
+
+    #[test]
+    fn test_std_normal_functions() {
+        assert_eq!(erf(0f64), 0f64);
+        assert!((erf(1f64) - 0.8427007929).abs() < 1e-6);
+        assert!((erf(-1f64) + 0.8427007929).abs() < 1e-6);
+
+        assert!((std_normal_cdf(0f64) - 0.5).abs() < 1e-9);
+        assert!((std_normal_cdf(1.96) - 0.9750021049).abs() < 1e-6);
+        assert!((std_normal_pdf(0f64) - 0.3989422804).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,613 @@
+//! Probability Calibration
+//!
+//! Maps the scores of a binary classifier onto calibrated probabilities
+//! of the positive class.
+//!
+//! The scores output by models such as `LogisticRegressor`, SVMs and
+//! `NeuralNet`s trained with `BCECriterion` are often poor probability
+//! estimates. A calibrator is fitted to the scores of an already trained
+//! model on held-out data, which should not overlap the data the model
+//! was trained on.
+//!
+//! Two calibration methods are provided:
+//!
+//! - `PlattScaling` fits a sigmoid to the scores. It needs little data
+//!   but assumes the miscalibration has a sigmoid shape.
+//! - `IsotonicRegression` fits a non-decreasing step function to the
+//!   scores. It can correct any monotonic miscalibration but needs more
+//!   data to avoid overfitting.
+//!
+//! The `CalibratedClassifier` wraps any `SupModel` producing a single
+//! score column and applies either method to its outputs. Models whose
+//! predictions are class labels, such as the SVMs, should be calibrated on
+//! their decision values instead, which `CalibratedClassifier::with_scores`
+//! takes as a score function.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::calibration::{CalibratedClassifier, CalibrationMethod};
+//! use rusty_machine::learning::logistic_reg::LogisticRegressor;
+//! use rusty_machine::learning::SupModel;
+//! use rusty_machine::linalg::{Matrix, Vector};
+//!
+//! let inputs = Matrix::new(4, 1, vec![1., 3., 5., 7.]);
+//! let targets = Vector::new(vec![0., 0., 1., 1.]);
+//!
+//! let mut log_mod = LogisticRegressor::default();
+//! log_mod.train(&inputs, &targets).unwrap();
+//!
+//! // Calibrate the trained model on held-out data.
+//! let held_out = Matrix::new(6, 1, vec![0., 2., 4., 4.5, 6., 8.]);
+//! let held_out_targets = Vector::new(vec![0., 0., 0., 1., 1., 1.]);
+//!
+//! let mut calibrated = CalibratedClassifier::new(log_mod, CalibrationMethod::Isotonic);
+//! calibrated.train(&held_out, &held_out_targets).unwrap();
+//!
+//! let probs = calibrated.predict(&Matrix::new(2, 1, vec![0.5, 7.5])).unwrap();
+//! assert!(probs[0] < 0.5 && probs[1] > 0.5);
+//! ```
+
+use linalg::{Matrix, BaseMatrix, Vector};
+use learning::{LearningResult, SupModel};
+use learning::error::{Error, ErrorKind};
+
+/// The maximum number of Newton iterations used by Platt scaling.
+const PLATT_MAX_ITERS: usize = 100;
+/// The smallest line search step used by Platt scaling.
+const PLATT_MIN_STEP: f64 = 1e-10;
+/// The regularization added to the Hessian diagonal in Platt scaling.
+const PLATT_SIGMA: f64 = 1e-12;
+
+/// Model outputs which contain a single column of scores.
+pub trait ScoreColumn {
+    /// Converts the outputs into a vector of scores.
+    ///
+    /// Returns an error if the outputs do not have a single column.
+    fn into_scores(self) -> LearningResult<Vec<f64>>;
+}
+
+impl ScoreColumn for Vector<f64> {
+    fn into_scores(self) -> LearningResult<Vec<f64>> {
+        Ok(self.into_vec())
+    }
+}
+
+impl ScoreColumn for Matrix<f64> {
+    fn into_scores(self) -> LearningResult<Vec<f64>> {
+        if self.cols() != 1 {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The model outputs must have a single score column."));
+        }
+        Ok(self.into_vec())
+    }
+}
+
+/// Checks that the scores and binary targets can be used for calibration.
+fn check_calibration_data(scores: &[f64], targets: &[f64]) -> LearningResult<()> {
+    if scores.len() != targets.len() {
+        return Err(Error::new(ErrorKind::InvalidData,
+                              "The number of scores and targets do not match."));
+    }
+    if scores.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidData, "There is no calibration data."));
+    }
+    if scores.iter().any(|s| !s.is_finite()) {
+        return Err(Error::new(ErrorKind::InvalidData, "The scores must be finite."));
+    }
+    if targets.iter().any(|&t| t != 0f64 && t != 1f64) {
+        return Err(Error::new(ErrorKind::InvalidData, "The targets must be 0 or 1."));
+    }
+    Ok(())
+}
+
+/// Computes `1 / (1 + exp(x))` without overflow.
+fn neg_sigmoid(x: f64) -> f64 {
+    if x >= 0f64 {
+        let e = (-x).exp();
+        e / (1f64 + e)
+    } else {
+        1f64 / (1f64 + x.exp())
+    }
+}
+
+/// Platt scaling
+///
+/// Calibrates scores `s` with the sigmoid `1 / (1 + exp(a * s + b))`.
+///
+/// The parameters are fitted by Newton's method on the regularized
+/// targets suggested by Platt, following Lin, Lin and Weng,
+/// "A note on Platt's probabilistic outputs for support vector machines".
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlattScaling {
+    a: f64,
+    b: f64,
+}
+
+impl PlattScaling {
+    /// Fits the sigmoid to scores and binary targets.
+    ///
+    /// The targets must be 0 or 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::calibration::PlattScaling;
+    ///
+    /// let scores = [-2., -1., -0.5, 0.5, 1., 2.];
+    /// let targets = [0., 0., 1., 0., 1., 1.];
+    ///
+    /// let platt = PlattScaling::fit(&scores, &targets).unwrap();
+    /// assert!(platt.transform(2.) > platt.transform(-2.));
+    /// ```
+    pub fn fit(scores: &[f64], targets: &[f64]) -> LearningResult<PlattScaling> {
+        check_calibration_data(scores, targets)?;
+
+        let num_pos = targets.iter().filter(|&&t| t == 1f64).count() as f64;
+        let num_neg = targets.len() as f64 - num_pos;
+
+        let hi_target = (num_pos + 1f64) / (num_pos + 2f64);
+        let lo_target = 1f64 / (num_neg + 2f64);
+        let t = targets.iter()
+            .map(|&t| if t == 1f64 { hi_target } else { lo_target })
+            .collect::<Vec<_>>();
+
+        let objective = |a: f64, b: f64| {
+            scores.iter().zip(&t).fold(0f64, |acc, (s, t)| {
+                let f = s * a + b;
+                if f >= 0f64 {
+                    acc + t * f + (-f).exp().ln_1p()
+                } else {
+                    acc + (t - 1f64) * f + f.exp().ln_1p()
+                }
+            })
+        };
+
+        let mut a = 0f64;
+        let mut b = ((num_neg + 1f64) / (num_pos + 1f64)).ln();
+        let mut fval = objective(a, b);
+
+        for _ in 0..PLATT_MAX_ITERS {
+            let mut h11 = PLATT_SIGMA;
+            let mut h22 = PLATT_SIGMA;
+            let mut h21 = 0f64;
+            let mut g1 = 0f64;
+            let mut g2 = 0f64;
+
+            for (s, t) in scores.iter().zip(&t) {
+                let p = neg_sigmoid(s * a + b);
+                let d2 = p * (1f64 - p);
+                h11 += s * s * d2;
+                h22 += d2;
+                h21 += s * d2;
+
+                let d1 = t - p;
+                g1 += s * d1;
+                g2 += d1;
+            }
+
+            if g1.abs() < 1e-5 && g2.abs() < 1e-5 {
+                break;
+            }
+
+            let det = h11 * h22 - h21 * h21;
+            let da = -(h22 * g1 - h21 * g2) / det;
+            let db = -(h11 * g2 - h21 * g1) / det;
+            let gd = g1 * da + g2 * db;
+
+            let mut step = 1f64;
+            while step >= PLATT_MIN_STEP {
+                let (new_a, new_b) = (a + step * da, b + step * db);
+                let new_fval = objective(new_a, new_b);
+                if new_fval < fval + 1e-4 * step * gd {
+                    a = new_a;
+                    b = new_b;
+                    fval = new_fval;
+                    break;
+                }
+                step /= 2f64;
+            }
+
+            if step < PLATT_MIN_STEP {
+                break;
+            }
+        }
+
+        Ok(PlattScaling { a: a, b: b })
+    }
+
+    /// The slope `a` of the fitted sigmoid.
+    ///
+    /// This is negative when larger scores are more likely to be positive.
+    pub fn slope(&self) -> f64 {
+        self.a
+    }
+
+    /// The intercept `b` of the fitted sigmoid.
+    pub fn intercept(&self) -> f64 {
+        self.b
+    }
+
+    /// The calibrated probability of the positive class for a score.
+    pub fn transform(&self, score: f64) -> f64 {
+        neg_sigmoid(self.a * score + self.b)
+    }
+}
+
+/// Isotonic regression
+///
+/// Calibrates scores with the non-decreasing function which best fits
+/// the targets in the least squares sense, found by the pool adjacent
+/// violators algorithm.
+///
+/// Scores between the training scores are linearly interpolated and
+/// scores outside of them are clipped to the nearest end.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IsotonicRegression {
+    thresholds: Vec<f64>,
+    values: Vec<f64>,
+}
+
+/// A run of pooled scores with a common fitted value.
+struct Block {
+    min: f64,
+    max: f64,
+    sum: f64,
+    weight: f64,
+}
+
+impl Block {
+    fn value(&self) -> f64 {
+        self.sum / self.weight
+    }
+}
+
+impl IsotonicRegression {
+    /// Fits the isotonic function to scores and binary targets.
+    ///
+    /// The targets must be 0 or 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::calibration::IsotonicRegression;
+    ///
+    /// let scores = [1., 2., 3., 4.];
+    /// let targets = [0., 1., 0., 1.];
+    ///
+    /// let iso = IsotonicRegression::fit(&scores, &targets).unwrap();
+    /// assert_eq!(iso.transform(1.), 0.);
+    /// assert_eq!(iso.transform(2.5), 0.5);
+    /// assert_eq!(iso.transform(10.), 1.);
+    /// ```
+    pub fn fit(scores: &[f64], targets: &[f64]) -> LearningResult<IsotonicRegression> {
+        check_calibration_data(scores, targets)?;
+
+        let mut pairs = scores.iter().cloned().zip(targets.iter().cloned()).collect::<Vec<_>>();
+        pairs.sort_by(|x, y| x.0.partial_cmp(&y.0).expect("Scores are finite."));
+
+        let mut blocks: Vec<Block> = Vec::new();
+        for (s, t) in pairs {
+            // Equal scores always share a block.
+            let tied = blocks.last().map_or(false, |b| b.max == s);
+            if tied {
+                let last = blocks.last_mut().expect("A tied block exists.");
+                last.sum += t;
+                last.weight += 1f64;
+            } else {
+                blocks.push(Block {
+                    min: s,
+                    max: s,
+                    sum: t,
+                    weight: 1f64,
+                });
+            }
+
+            // Pool the adjacent violators.
+            while blocks.len() > 1 &&
+                  blocks[blocks.len() - 2].value() >= blocks[blocks.len() - 1].value() {
+                let last = blocks.pop().expect("There are at least two blocks.");
+                let prev = blocks.last_mut().expect("There are at least two blocks.");
+                prev.max = last.max;
+                prev.sum += last.sum;
+                prev.weight += last.weight;
+            }
+        }
+
+        let mut thresholds = Vec::with_capacity(2 * blocks.len());
+        let mut values = Vec::with_capacity(2 * blocks.len());
+        for block in &blocks {
+            thresholds.push(block.min);
+            values.push(block.value());
+            if block.max > block.min {
+                thresholds.push(block.max);
+                values.push(block.value());
+            }
+        }
+
+        Ok(IsotonicRegression {
+            thresholds: thresholds,
+            values: values,
+        })
+    }
+
+    /// The increasing scores at which the fitted function changes slope.
+    pub fn thresholds(&self) -> &[f64] {
+        &self.thresholds
+    }
+
+    /// The fitted probabilities at each of the thresholds.
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// The calibrated probability of the positive class for a score.
+    pub fn transform(&self, score: f64) -> f64 {
+        let last = self.thresholds.len() - 1;
+        if score <= self.thresholds[0] {
+            return self.values[0];
+        }
+        if score >= self.thresholds[last] {
+            return self.values[last];
+        }
+
+        let search = self.thresholds
+            .binary_search_by(|x| x.partial_cmp(&score).expect("Thresholds are finite."));
+        match search {
+            Ok(i) => self.values[i],
+            Err(i) => {
+                let (x0, x1) = (self.thresholds[i - 1], self.thresholds[i]);
+                let (y0, y1) = (self.values[i - 1], self.values[i]);
+                y0 + (y1 - y0) * (score - x0) / (x1 - x0)
+            }
+        }
+    }
+}
+
+/// The method used to calibrate scores.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CalibrationMethod {
+    /// Sigmoid calibration with `PlattScaling`.
+    Platt,
+    /// Non-parametric calibration with `IsotonicRegression`.
+    Isotonic,
+}
+
+/// A fitted calibration function.
+#[derive(Clone, Debug)]
+enum Calibrator {
+    Platt(PlattScaling),
+    Isotonic(IsotonicRegression),
+}
+
+impl Calibrator {
+    fn fit(method: CalibrationMethod,
+           scores: &[f64],
+           targets: &[f64])
+           -> LearningResult<Calibrator> {
+        match method {
+            CalibrationMethod::Platt => PlattScaling::fit(scores, targets).map(Calibrator::Platt),
+            CalibrationMethod::Isotonic => {
+                IsotonicRegression::fit(scores, targets).map(Calibrator::Isotonic)
+            }
+        }
+    }
+
+    fn transform(&self, score: f64) -> f64 {
+        match *self {
+            Calibrator::Platt(ref platt) => platt.transform(score),
+            Calibrator::Isotonic(ref iso) => iso.transform(score),
+        }
+    }
+}
+
+/// Calibrated Classifier
+///
+/// Wraps a trained binary classifier whose scores, of type `T`, are a
+/// single column. Larger scores should indicate the positive class.
+///
+/// The scores are the model predictions by default. A different score
+/// function can be given with `with_scores`.
+///
+/// Training the wrapper fits the calibration on held-out data and leaves
+/// the wrapped model unchanged, so the model must be trained beforehand.
+/// The targets are 0 or 1 and the predictions are calibrated probabilities
+/// of the positive class.
+#[derive(Debug)]
+pub struct CalibratedClassifier<M, T> {
+    model: M,
+    method: CalibrationMethod,
+    scores: fn(&M, &Matrix<f64>) -> LearningResult<T>,
+    calibrator: Option<Calibrator>,
+}
+
+impl<M, T> CalibratedClassifier<M, T>
+    where M: SupModel<Matrix<f64>, T>,
+          T: ScoreColumn
+{
+    /// Constructs an uncalibrated wrapper around a trained model.
+    ///
+    /// The model predictions are used as the scores.
+    pub fn new(model: M, method: CalibrationMethod) -> CalibratedClassifier<M, T> {
+        CalibratedClassifier::with_scores(model, method, M::predict)
+    }
+}
+
+impl<M, T: ScoreColumn> CalibratedClassifier<M, T> {
+    /// Constructs an uncalibrated wrapper around a trained model
+    /// which is scored by the given function.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::calibration::{CalibratedClassifier, CalibrationMethod};
+    /// use rusty_machine::learning::svm::SoftMarginSVM;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let inputs = Matrix::new(4, 1, vec![1., 3., 5., 7.]);
+    /// let targets = Vector::new(vec![-1., -1., 1., 1.]);
+    ///
+    /// let mut svm = SoftMarginSVM::default();
+    /// svm.train(&inputs, &targets).unwrap();
+    ///
+    /// // The SVM predicts labels, so calibrate its decision values.
+    /// let mut calibrated = CalibratedClassifier::with_scores(svm,
+    ///                                                        CalibrationMethod::Platt,
+    ///                                                        SoftMarginSVM::decision_function);
+    /// let held_out = Matrix::new(4, 1, vec![0., 2., 6., 8.]);
+    /// calibrated.train(&held_out, &Vector::new(vec![0., 0., 1., 1.])).unwrap();
+    /// ```
+    pub fn with_scores(model: M,
+                       method: CalibrationMethod,
+                       scores: fn(&M, &Matrix<f64>) -> LearningResult<T>)
+                       -> CalibratedClassifier<M, T> {
+        CalibratedClassifier {
+            model: model,
+            method: method,
+            scores: scores,
+            calibrator: None,
+        }
+    }
+
+    /// The wrapped model.
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    /// A mutable reference to the wrapped model.
+    ///
+    /// Retraining the model invalidates the calibration, which
+    /// should be refitted.
+    pub fn model_mut(&mut self) -> &mut M {
+        &mut self.model
+    }
+
+    /// The calibration method.
+    pub fn method(&self) -> CalibrationMethod {
+        self.method
+    }
+
+    /// The fitted Platt scaling, if that is the calibration method.
+    pub fn platt_scaling(&self) -> Option<&PlattScaling> {
+        match self.calibrator {
+            Some(Calibrator::Platt(ref platt)) => Some(platt),
+            _ => None,
+        }
+    }
+
+    /// The fitted isotonic regression, if that is the calibration method.
+    pub fn isotonic_regression(&self) -> Option<&IsotonicRegression> {
+        match self.calibrator {
+            Some(Calibrator::Isotonic(ref iso)) => Some(iso),
+            _ => None,
+        }
+    }
+}
+
+impl<M, T: ScoreColumn> SupModel<Matrix<f64>, Vector<f64>> for CalibratedClassifier<M, T> {
+    /// Predicts the calibrated probability of the positive class.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if let Some(ref calibrator) = self.calibrator {
+            let scores = (self.scores)(&self.model, inputs)?.into_scores()?;
+            Ok(Vector::new(scores.into_iter()
+                .map(|s| calibrator.transform(s))
+                .collect::<Vec<_>>()))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// Fits the calibration to the model's scores on held-out data.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        let scores = (self.scores)(&self.model, inputs)?.into_scores()?;
+        self.calibrator = Some(Calibrator::fit(self.method, &scores, targets.data())?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PlattScaling, IsotonicRegression, ScoreColumn};
+    use linalg::Matrix;
+
+    #[test]
+    fn test_platt_gradient_vanishes() {
+        let scores = [-3., -2., -1.5, -0.5, 0.2, 0.4, 1., 2.5, 3.];
+        let targets = [0., 0., 1., 0., 0., 1., 1., 1., 1.];
+
+        let platt = PlattScaling::fit(&scores, &targets).unwrap();
+        assert!(platt.slope() < 0f64);
+
+        let num_pos = 5f64;
+        let num_neg = 4f64;
+        let (mut g1, mut g2) = (0f64, 0f64);
+        for (&s, &t) in scores.iter().zip(targets.iter()) {
+            let t = if t == 1f64 { (num_pos + 1.) / (num_pos + 2.) } else { 1. / (num_neg + 2.) };
+            let d = t - platt.transform(s);
+            g1 += s * d;
+            g2 += d;
+        }
+        assert!(g1.abs() < 1e-4);
+        assert!(g2.abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_platt_probabilities_in_range() {
+        let scores = [-100., 0., 100.];
+        let targets = [0., 1., 1.];
+
+        let platt = PlattScaling::fit(&scores, &targets).unwrap();
+        for &s in &[-1e6, -1., 0., 1., 1e6] {
+            let p = platt.transform(s);
+            assert!(p >= 0f64 && p <= 1f64);
+        }
+    }
+
+    #[test]
+    fn test_isotonic_pools_violators() {
+        let scores = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6];
+        let targets = [0., 1., 0., 0., 1., 1.];
+
+        let iso = IsotonicRegression::fit(&scores, &targets).unwrap();
+        assert_eq!(iso.thresholds(), &[0.1, 0.2, 0.4, 0.5, 0.6]);
+        assert_eq!(iso.values(), &[0., 1. / 3., 1. / 3., 1., 1.]);
+
+        assert_eq!(iso.transform(0.3), 1. / 3.);
+        assert!((iso.transform(0.45) - 2. / 3.).abs() < 1e-12);
+        assert_eq!(iso.transform(-1.), 0.);
+    }
+
+    #[test]
+    fn test_isotonic_ties() {
+        let scores = [1., 1., 2., 2.];
+        let targets = [0., 1., 1., 1.];
+
+        let iso = IsotonicRegression::fit(&scores, &targets).unwrap();
+        assert_eq!(iso.thresholds(), &[1., 2.]);
+        assert_eq!(iso.values(), &[0.5, 1.]);
+    }
+
+    #[test]
+    fn test_isotonic_is_monotonic() {
+        let scores = [0.9, 0.1, 0.5, 0.3, 0.7, 0.2, 0.8, 0.4, 0.6];
+        let targets = [1., 0., 1., 1., 0., 0., 1., 0., 1.];
+
+        let iso = IsotonicRegression::fit(&scores, &targets).unwrap();
+        assert!(iso.values().windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_invalid_calibration_data() {
+        assert!(PlattScaling::fit(&[0., 1.], &[0., 2.]).is_err());
+        assert!(PlattScaling::fit(&[0., 1.], &[0.]).is_err());
+        assert!(IsotonicRegression::fit(&[], &[]).is_err());
+        assert!(IsotonicRegression::fit(&[0., ::std::f64::NAN], &[0., 1.]).is_err());
+    }
+
+    #[test]
+    fn test_matrix_score_column() {
+        assert!(Matrix::new(2, 1, vec![0., 1.]).into_scores().is_ok());
+        assert!(Matrix::new(1, 2, vec![0., 1.]).into_scores().is_err());
+    }
+}
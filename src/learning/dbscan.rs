@@ -16,6 +16,12 @@
 //! If a point is not within distance `eps` of a cluster it will be classified
 //! as noise. This means that it will be set to `None` in the clusters `Vector`.
 //!
+//! Distances are Euclidean by default, other metrics can be used
+//! by constructing the model with `DBSCAN::with_metric`. Neighbourhoods
+//! are found with radius queries on a `BallTree` over the training data,
+//! or by brute force for distances which are not true metrics (like
+//! `Cosine`) as the tree pruning would miss neighbours.
+//!
 //! # Examples
 //!
 //! ```
@@ -38,10 +44,10 @@
 
 use learning::{LearningResult, UnSupModel};
 use learning::error::{Error, ErrorKind};
+use learning::knn::{KNearestSearch, MetricSearch};
+use learning::toolkit::distance::{Metric, Euclidean};
 
 use linalg::{Matrix, Vector, BaseMatrix};
-use rulinalg::utils;
-use rulinalg::matrix::Row;
 
 /// DBSCAN Model
 ///
@@ -51,10 +57,10 @@ use rulinalg::matrix::Row;
 pub struct DBSCAN {
     eps: f64,
     min_points: usize,
+    searcher: MetricSearch,
     clusters: Option<Vector<Option<usize>>>,
     predictive: bool,
     _visited: Vec<bool>,
-    _cluster_data: Option<Matrix<f64>>,
 }
 
 /// Constructs a non-predictive DBSCAN model with the
@@ -62,15 +68,16 @@ pub struct DBSCAN {
 ///
 /// - `eps` : `0.5`
 /// - `min_points` : `5`
+/// - `metric` : `Euclidean`
 impl Default for DBSCAN {
     fn default() -> DBSCAN {
         DBSCAN {
             eps: 0.5,
             min_points: 5,
+            searcher: MetricSearch::default(),
             clusters: None,
             predictive: false,
             _visited: Vec::new(),
-            _cluster_data: None,
         }
     }
 }
@@ -78,7 +85,12 @@ impl Default for DBSCAN {
 impl UnSupModel<Matrix<f64>, Vector<Option<usize>>> for DBSCAN {
     /// Train the classifier using input data.
     fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
+        if inputs.rows() == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "There are no training inputs."));
+        }
+
         self.init_params(inputs.rows());
+        self.searcher.build(inputs.clone());
         let mut cluster = 0;
 
         for (idx, point) in inputs.row_iter().enumerate() {
@@ -87,40 +99,26 @@ impl UnSupModel<Matrix<f64>, Vector<Option<usize>>> for DBSCAN {
             if !visited {
                 self._visited[idx] = true;
 
-                let neighbours = self.region_query(point, inputs);
+                let neighbours = self.region_query(point.raw_slice())?;
 
                 if neighbours.len() >= self.min_points {
-                    self.expand_cluster(inputs, idx, neighbours, cluster);
+                    self.expand_cluster(inputs, idx, neighbours, cluster)?;
                     cluster += 1;
                 }
             }
         }
 
-        if self.predictive {
-            self._cluster_data = Some(inputs.clone());
-        }
-
         Ok(())
     }
 
     fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<Option<usize>>> {
         if self.predictive {
-            if let (&Some(ref cluster_data), &Some(ref clusters)) = (&self._cluster_data,
-                                                                     &self.clusters) {
+            if let Some(ref clusters) = self.clusters {
                 let mut classes = Vec::with_capacity(inputs.rows());
 
-                for input_point in inputs.row_iter() {
-                    let mut distances = Vec::with_capacity(cluster_data.rows());
-
-                    for cluster_point in cluster_data.row_iter() {
-                        let point_distance =
-                            utils::vec_bin_op(input_point.raw_slice(), cluster_point.raw_slice(), |x, y| x - y);
-                        distances.push(utils::dot(&point_distance, &point_distance).sqrt());
-                    }
-
-                    let (closest_idx, closest_dist) = utils::argmin(&distances);
-                    if closest_dist < self.eps {
-                        classes.push(clusters[closest_idx]);
+                for (idx, dist) in self.searcher.search_many(inputs, 1)? {
+                    if dist[0] < self.eps {
+                        classes.push(clusters[idx[0]]);
                     } else {
                         classes.push(None);
                     }
@@ -140,24 +138,41 @@ impl UnSupModel<Matrix<f64>, Vector<Option<usize>>> for DBSCAN {
 impl DBSCAN {
     /// Create a new DBSCAN model with a given
     /// distance episilon and minimum points per cluster.
+    ///
+    /// Distances are measured with the Euclidean metric.
     pub fn new(eps: f64, min_points: usize) -> DBSCAN {
+        DBSCAN::with_metric(eps, min_points, Euclidean)
+    }
+
+    /// Create a new DBSCAN model with a given distance
+    /// episilon, minimum points per cluster and distance metric.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::dbscan::DBSCAN;
+    /// use rusty_machine::learning::toolkit::distance::Manhattan;
+    ///
+    /// let _ = DBSCAN::with_metric(1.0, 3, Manhattan);
+    /// ```
+    pub fn with_metric<M: Metric + 'static>(eps: f64, min_points: usize, metric: M) -> DBSCAN {
         assert!(eps > 0f64, "The model epsilon must be positive.");
 
         DBSCAN {
             eps: eps,
             min_points: min_points,
+            searcher: MetricSearch::with_metric(metric),
             clusters: None,
             predictive: false,
             _visited: Vec::new(),
-            _cluster_data: None,
         }
     }
 
     /// Set predictive to true if the model is to be used
     /// to classify future points.
     ///
-    /// If the model is set as predictive then the input data
-    /// will be cloned during training.
+    /// Future points are classified using the search tree
+    /// built over the training data.
     pub fn set_predictive(&mut self, predictive: bool) {
         self.predictive = predictive;
     }
@@ -171,7 +186,8 @@ impl DBSCAN {
                       inputs: &Matrix<f64>,
                       point_idx: usize,
                       neighbour_pts: Vec<usize>,
-                      cluster: usize) {
+                      cluster: usize)
+                      -> LearningResult<()> {
         debug_assert!(point_idx < inputs.rows(),
                       "Point index too large for inputs");
         debug_assert!(neighbour_pts.iter().all(|x| *x < inputs.rows()),
@@ -184,32 +200,22 @@ impl DBSCAN {
             if !visited {
                 self._visited[*data_point_idx] = true;
                 let data_point_row = unsafe { inputs.row_unchecked(*data_point_idx) };
-                let sub_neighbours = self.region_query(data_point_row, inputs);
+                let sub_neighbours = self.region_query(data_point_row.raw_slice())?;
 
                 if sub_neighbours.len() >= self.min_points {
-                    self.expand_cluster(inputs, *data_point_idx, sub_neighbours, cluster);
+                    self.expand_cluster(inputs, *data_point_idx, sub_neighbours, cluster)?;
                 }
             }
         }
-    }
-
-
-    fn region_query(&self, point: Row<f64>, inputs: &Matrix<f64>) -> Vec<usize> {
-        debug_assert!(point.cols() == inputs.cols(),
-                      "point must be of same dimension as inputs");
 
-        let mut in_neighbourhood = Vec::new();
-        for (idx, data_point) in inputs.row_iter().enumerate() {
-            //TODO: Use `MatrixMetric` when rulinalg#154 is fixed.
-            let point_distance = utils::vec_bin_op(data_point.raw_slice(), point.raw_slice(), |x, y| x - y);
-            let dist = utils::dot(&point_distance, &point_distance).sqrt();
-
-            if dist < self.eps {
-                in_neighbourhood.push(idx);
-            }
-        }
+        Ok(())
+    }
 
-        in_neighbourhood
+    /// Find the training points within distance `eps` of the point.
+    ///
+    /// The search tree must be built.
+    fn region_query(&self, point: &[f64]) -> LearningResult<Vec<usize>> {
+        Ok(self.searcher.search_radius(point, self.eps)?.0)
     }
 
     fn init_params(&mut self, total_points: usize) {
@@ -229,31 +235,66 @@ impl DBSCAN {
 #[cfg(test)]
 mod tests {
     use super::DBSCAN;
-    use linalg::{Matrix, BaseMatrix};
+    use linalg::Matrix;
+    use learning::knn::{KNearestSearch, MetricSearch};
+    use learning::toolkit::distance::{Chebyshev, Manhattan, Cosine};
 
     #[test]
     fn test_region_query() {
-        let model = DBSCAN::new(1.0, 3);
+        let mut model = DBSCAN::new(1.0, 3);
 
         let inputs = Matrix::new(3, 2, vec![1.0, 1.0, 1.1, 1.9, 3.0, 3.0]);
+        model.searcher.build(inputs);
 
-        let m = matrix![1.0, 1.0];
-        let row = m.row(0);
-        let neighbours = model.region_query(row, &inputs);
+        let neighbours = model.region_query(&[1.0, 1.0]).unwrap();
 
         assert!(neighbours.len() == 2);
     }
 
     #[test]
     fn test_region_query_small_eps() {
-        let model = DBSCAN::new(0.01, 3);
+        let mut model = DBSCAN::new(0.01, 3);
 
         let inputs = Matrix::new(3, 2, vec![1.0, 1.0, 1.1, 1.9, 1.1, 1.1]);
+        model.searcher.build(inputs);
 
-        let m = matrix![1.0, 1.0];
-        let row = m.row(0);
-        let neighbours = model.region_query(row, &inputs);
+        let neighbours = model.region_query(&[1.0, 1.0]).unwrap();
 
         assert!(neighbours.len() == 1);
     }
+
+    #[test]
+    fn test_region_query_metric() {
+        // (1.1, 1.9) is within 0.95 of (1, 1) under Chebyshev but not Manhattan.
+        let inputs = Matrix::new(3, 2, vec![1.0, 1.0, 1.1, 1.9, 3.0, 3.0]);
+
+        let mut model = DBSCAN::with_metric(0.95, 3, Chebyshev);
+        model.searcher.build(inputs.clone());
+        assert_eq!(model.region_query(&[1.0, 1.0]).unwrap(), vec![0, 1]);
+
+        let mut model = DBSCAN::with_metric(0.95, 3, Manhattan);
+        model.searcher.build(inputs);
+        assert_eq!(model.region_query(&[1.0, 1.0]).unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn test_non_metric_uses_brute_force() {
+        // The points lie on three rays, only the angle matters to the cosine distance.
+        let inputs = Matrix::new(4, 2, vec![1.0, 0.0, 10.0, 0.0, 0.0, 1.0, 10.0, 10.0]);
+
+        let mut model = DBSCAN::with_metric(0.1, 2, Cosine);
+        match model.searcher {
+            MetricSearch::Brute(_) => {}
+            MetricSearch::Tree(_) => panic!("Expected a brute force search."),
+        }
+
+        model.searcher.build(inputs);
+        assert_eq!(model.region_query(&[5.0, 0.0]).unwrap(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_region_query_unbuilt() {
+        let model = DBSCAN::new(1.0, 3);
+        assert!(model.region_query(&[1.0, 1.0]).is_err());
+    }
 }
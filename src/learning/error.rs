@@ -30,7 +30,9 @@ pub enum ErrorKind {
     /// The model has not been trained
     UntrainedModel,
     /// Linear algebra related error
-    LinearAlgebra
+    LinearAlgebra,
+    /// The optimization diverged and produced non-finite values
+    Divergence,
 }
 
 impl Error {
@@ -30,11 +30,20 @@
 //! // Probabilities that each point comes from each Gaussian.
 //! println!("{:?}", post_probs.data());
 //! ```
+//!
+//! The number of components can be chosen by fitting a range of models
+//! and comparing an information criterion, see `select_comp_count`.
+use std::f64;
+use std::f64::consts::PI;
+
 use linalg::{Matrix, MatrixSlice, Vector, BaseMatrix, BaseMatrixMut, Axes};
-use rulinalg::utils;
-use rulinalg::matrix::decomposition::{PartialPivLu};
+use rulinalg::matrix::decomposition::{Decomposition, PartialPivLu, Cholesky};
+
+use rand::Rng;
+use rand::distributions::normal::StandardNormal;
 
 use learning::{LearningResult, UnSupModel};
+use learning::k_means::{Initializer, KPlusPlus};
 use learning::toolkit::rand_utils;
 use learning::error::{Error, ErrorKind};
 
@@ -43,6 +52,8 @@ use learning::error::{Error, ErrorKind};
 /// - Full : The full covariance structure.
 /// - Regularized : Adds a regularization constant to the covariance diagonal.
 /// - Diagonal : Only the diagonal covariance structure.
+/// - Tied : A single full covariance shared by all of the Gaussians.
+/// - Spherical : A single variance for each Gaussian, shared by all features.
 #[derive(Clone, Copy, Debug)]
 pub enum CovOption {
     /// The full covariance structure.
@@ -51,9 +62,46 @@ pub enum CovOption {
     Regularized(f64),
     /// Only the diagonal covariance structure.
     Diagonal,
+    /// A single full covariance shared by all of the Gaussians.
+    Tied,
+    /// A single variance for each Gaussian, shared by all features.
+    Spherical,
+}
+
+/// Information criteria for choosing the number of GMM components.
+///
+/// Both penalise the log-likelihood of the data by the number of free
+/// parameters in the model. Lower values are better.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectionCriterion {
+    /// The Bayesian Information Criterion.
+    BIC,
+    /// The Akaike Information Criterion.
+    AIC,
 }
 
 
+/// Initialization options for the GMM means.
+///
+/// - Random : The means are a random sample of the inputs.
+/// - KPlusPlus : The means are chosen by the k-means++ scheme.
+#[derive(Clone, Copy, Debug)]
+pub enum MeanInit {
+    /// The means are a random sample of the inputs.
+    Random,
+    /// The means are chosen by the k-means++ scheme.
+    KPlusPlus,
+}
+
+/// The fitted parameters from a single run of the EM algorithm.
+struct EmFit {
+    log_lik: f64,
+    iterations: usize,
+    mix_weights: Vector<f64>,
+    means: Matrix<f64>,
+    covars: Vec<Matrix<f64>>,
+}
+
 /// A Gaussian Mixture Model
 #[derive(Debug)]
 pub struct GaussianMixtureModel {
@@ -61,14 +109,22 @@ pub struct GaussianMixtureModel {
     mix_weights: Vector<f64>,
     model_means: Option<Matrix<f64>>,
     model_covars: Option<Vec<Matrix<f64>>>,
-    log_lik: f64,
+    log_lik: Option<f64>,
+    iterations: Option<usize>,
     max_iters: usize,
+    tol: f64,
+    n_init: usize,
     /// The covariance options for the GMM.
     pub cov_option: CovOption,
+    /// The initialization option for the GMM means.
+    pub mean_init: MeanInit,
 }
 
 impl UnSupModel<Matrix<f64>, Matrix<f64>> for GaussianMixtureModel {
     /// Train the model using inputs.
+    ///
+    /// The EM algorithm is run from `n_init` initializations and the
+    /// parameters with the highest log-likelihood are kept.
     fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
         let reg_value = if inputs.rows() > 1 {
             1f64 / (inputs.rows() - 1) as f64
@@ -76,32 +132,29 @@ impl UnSupModel<Matrix<f64>, Matrix<f64>> for GaussianMixtureModel {
             return Err(Error::new(ErrorKind::InvalidData, "Only one row of data provided."));
         };
 
-        // Initialization:
-        let k = self.comp_count;
-
-        self.model_covars = {
-            let cov_mat = self.initialize_covariances(inputs, reg_value)?;
-            Some(vec![cov_mat; k])
-        };
-
-        let random_rows: Vec<usize> =
-            rand_utils::reservoir_sample(&(0..inputs.rows()).collect::<Vec<usize>>(), k);
-        self.model_means = Some(inputs.select_rows(&random_rows));
+        // Each initialization starts from the prior mixture weights.
+        let prior_weights = self.mix_weights.clone();
+        let mut best: Option<EmFit> = None;
 
-        for _ in 0..self.max_iters {
-            let log_lik_0 = self.log_lik;
+        for _ in 0..self.n_init {
+            self.mix_weights = prior_weights.clone();
+            let fit = self.run_em(inputs, reg_value)?;
 
-            let (weights, log_lik_1) = self.membership_weights(inputs)?;
-
-            if (log_lik_1 - log_lik_0).abs() < 1e-15 {
-                break;
+            // A NaN log-likelihood is only kept if nothing better is found.
+            let better = best.as_ref()
+                .map_or(true, |b| b.log_lik.is_nan() || fit.log_lik > b.log_lik);
+            if better {
+                best = Some(fit);
             }
-
-            self.log_lik = log_lik_1;
-
-            self.update_params(inputs, weights);
         }
 
+        let best = best.expect("There is at least one initialization.");
+        self.mix_weights = best.mix_weights;
+        self.model_means = Some(best.means);
+        self.model_covars = Some(best.covars);
+        self.log_lik = Some(best.log_lik);
+        self.iterations = Some(best.iterations);
+
         Ok(())
     }
 
@@ -119,8 +172,8 @@ impl UnSupModel<Matrix<f64>, Matrix<f64>> for GaussianMixtureModel {
 impl GaussianMixtureModel {
     /// Constructs a new Gaussian Mixture Model
     ///
-    /// Defaults to 100 maximum iterations and
-    /// full covariance structure.
+    /// Defaults to 100 maximum iterations, a relative tolerance of 1e-6,
+    /// a single random initialization and full covariance structure.
     ///
     /// # Examples
     /// ```
@@ -134,9 +187,13 @@ impl GaussianMixtureModel {
             mix_weights: Vector::ones(k) / (k as f64),
             model_means: None,
             model_covars: None,
-            log_lik: 0f64,
+            log_lik: None,
+            iterations: None,
             max_iters: 100,
+            tol: 1e-6,
+            n_init: 1,
             cov_option: CovOption::Full,
+            mean_init: MeanInit::Random,
         }
     }
 
@@ -176,9 +233,13 @@ impl GaussianMixtureModel {
                 mix_weights: normalized_weights,
                 model_means: None,
                 model_covars: None,
-                log_lik: 0f64,
+                log_lik: None,
+                iterations: None,
                 max_iters: 100,
+                tol: 1e-6,
+                n_init: 1,
                 cov_option: CovOption::Full,
+                mean_init: MeanInit::Random,
             })
         }
     }
@@ -224,6 +285,239 @@ impl GaussianMixtureModel {
         self.max_iters = iters;
     }
 
+    /// Sets the relative convergence tolerance for the EM algorithm.
+    ///
+    /// Training stops when the log-likelihood changes by less than
+    /// this fraction between iterations.
+    ///
+    /// # Panics
+    ///
+    /// - `tol` is negative.
+    pub fn set_tol(&mut self, tol: f64) {
+        assert!(tol >= 0f64, "The tolerance must be non-negative.");
+        self.tol = tol;
+    }
+
+    /// Sets the number of initializations for the EM algorithm.
+    ///
+    /// The model is trained from each initialization and the
+    /// parameters with the highest log-likelihood are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gmm::{GaussianMixtureModel, MeanInit};
+    ///
+    /// let mut gmm = GaussianMixtureModel::new(2);
+    /// gmm.set_n_init(5);
+    /// gmm.mean_init = MeanInit::KPlusPlus;
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `n_init` is zero.
+    pub fn set_n_init(&mut self, n_init: usize) {
+        assert!(n_init > 0, "There must be at least one initialization.");
+        self.n_init = n_init;
+    }
+
+    /// The number of EM iterations run for the trained parameters.
+    ///
+    /// Returns `None` if the model has not been trained.
+    pub fn iterations(&self) -> Option<usize> {
+        self.iterations
+    }
+
+    /// The log-likelihood of the training data under the trained model.
+    ///
+    /// Returns `None` if the model has not been trained.
+    pub fn log_likelihood(&self) -> Option<f64> {
+        self.log_lik
+    }
+
+    /// The log probability density of each input under the model.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::linalg::Matrix;
+    /// use rusty_machine::learning::gmm::GaussianMixtureModel;
+    /// use rusty_machine::learning::UnSupModel;
+    ///
+    /// let inputs = Matrix::new(4, 1, vec![0., 0.2, 0.4, 0.6]);
+    ///
+    /// let mut gmm = GaussianMixtureModel::new(1);
+    /// gmm.train(&inputs).unwrap();
+    ///
+    /// let log_dens = gmm.score_samples(&Matrix::new(2, 1, vec![0.3, 5.])).unwrap();
+    /// assert!(log_dens[0] > log_dens[1]);
+    /// ```
+    pub fn score_samples(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        let log_dens = self.weighted_log_densities(inputs)?;
+        Ok(Vector::new(log_dens.row_iter()
+            .map(|row| log_sum_exp(row.raw_slice()))
+            .collect::<Vec<_>>()))
+    }
+
+    /// The total log-likelihood of the inputs under the model.
+    pub fn score(&self, inputs: &Matrix<f64>) -> LearningResult<f64> {
+        Ok(self.score_samples(inputs)?.sum())
+    }
+
+    /// The number of free parameters in the model.
+    ///
+    /// This counts the mixture weights, means and covariances of
+    /// the Gaussians for inputs with `dim` features.
+    pub fn num_parameters(&self, dim: usize) -> usize {
+        let k = self.comp_count;
+        let cov_params = match self.cov_option {
+            CovOption::Full | CovOption::Regularized(_) => k * dim * (dim + 1) / 2,
+            CovOption::Diagonal => k * dim,
+            CovOption::Tied => dim * (dim + 1) / 2,
+            CovOption::Spherical => k,
+        };
+        (k - 1) + k * dim + cov_params
+    }
+
+    /// The Bayesian Information Criterion of the model on the inputs.
+    ///
+    /// Computed as `-2 ln L + p ln n` for `p` free parameters and `n`
+    /// inputs. Lower is better.
+    pub fn bic(&self, inputs: &Matrix<f64>) -> LearningResult<f64> {
+        let p = self.num_parameters(inputs.cols()) as f64;
+        Ok(-2f64 * self.score(inputs)? + p * (inputs.rows() as f64).ln())
+    }
+
+    /// The Akaike Information Criterion of the model on the inputs.
+    ///
+    /// Computed as `-2 ln L + 2p` for `p` free parameters. Lower is better.
+    pub fn aic(&self, inputs: &Matrix<f64>) -> LearningResult<f64> {
+        let p = self.num_parameters(inputs.cols()) as f64;
+        Ok(-2f64 * self.score(inputs)? + 2f64 * p)
+    }
+
+    /// Draws samples from the trained mixture, one per row.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate rand;
+    /// # extern crate rusty_machine;
+    /// use rusty_machine::linalg::{Matrix, BaseMatrix};
+    /// use rusty_machine::learning::gmm::GaussianMixtureModel;
+    /// use rusty_machine::learning::UnSupModel;
+    /// use rand::{StdRng, SeedableRng};
+    ///
+    /// # fn main() {
+    /// let inputs = Matrix::new(4, 2, vec![0., 0., 0.1, 0.3, 5., 5., 5.2, 4.9]);
+    ///
+    /// let mut gmm = GaussianMixtureModel::new(1);
+    /// gmm.train(&inputs).unwrap();
+    ///
+    /// let seed: &[_] = &[1, 2, 3];
+    /// let mut rng = StdRng::from_seed(seed);
+    /// let samples = gmm.sample(10, &mut rng).unwrap();
+    ///
+    /// assert_eq!(samples.rows(), 10);
+    /// assert_eq!(samples.cols(), 2);
+    /// # }
+    /// ```
+    pub fn sample<R: Rng>(&self, num_samples: usize, rng: &mut R) -> LearningResult<Matrix<f64>> {
+        if let (&Some(ref means), &Some(ref covars)) = (&self.model_means, &self.model_covars) {
+            let d = means.cols();
+
+            let mut chols = Vec::with_capacity(self.comp_count);
+            for cov in covars {
+                chols.push(Cholesky::decompose(cov.clone())?.unpack());
+            }
+
+            let mut samples = Vec::with_capacity(num_samples * d);
+            for _ in 0..num_samples {
+                // Choose a component by inverting the cumulative mixture weights.
+                let u = rng.gen::<f64>();
+                let mut cumulative = 0f64;
+                let mut comp = self.comp_count - 1;
+                for (j, w) in self.mix_weights.iter().enumerate() {
+                    cumulative += *w;
+                    if u < cumulative {
+                        comp = j;
+                        break;
+                    }
+                }
+
+                let z = (0..d)
+                    .map(|_| {
+                        let StandardNormal(x) = rng.gen::<StandardNormal>();
+                        x
+                    })
+                    .collect::<Vec<_>>();
+                let x = &chols[comp] * Vector::new(z);
+                samples.extend(x.iter().zip(means.row(comp).raw_slice()).map(|(x, m)| x + m));
+            }
+
+            Ok(Matrix::new(num_samples, d, samples))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// Runs the EM algorithm from a new initialization.
+    fn run_em(&mut self, inputs: &Matrix<f64>, reg_value: f64) -> LearningResult<EmFit> {
+        let k = self.comp_count;
+
+        self.model_covars = {
+            let cov_mat = self.initialize_covariances(inputs, reg_value)?;
+            Some(vec![cov_mat; k])
+        };
+        self.model_means = Some(self.initialize_means(inputs)?);
+
+        let mut log_lik_0 = f64::NEG_INFINITY;
+        let mut iterations = 0;
+        while iterations < self.max_iters {
+            let (weights, log_lik_1) = self.membership_weights(inputs)?;
+
+            if (log_lik_1 - log_lik_0).abs() <= self.tol * log_lik_1.abs() {
+                break;
+            }
+
+            log_lik_0 = log_lik_1;
+
+            self.update_params(inputs, weights);
+            iterations += 1;
+        }
+
+        // The log-likelihood of the final parameters.
+        let log_lik = self.membership_weights(inputs)?.1;
+
+        Ok(EmFit {
+            log_lik: log_lik,
+            iterations: iterations,
+            mix_weights: self.mix_weights.clone(),
+            means: self.model_means.take().expect("The means were initialized."),
+            covars: self.model_covars.take().expect("The covariances were initialized."),
+        })
+    }
+
+    fn initialize_means(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        let k = self.comp_count;
+        if k > inputs.rows() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  format!("Number of components ({0}) exceeds number of data \
+                                           points ({1}).",
+                                          k,
+                                          inputs.rows())));
+        }
+
+        match self.mean_init {
+            MeanInit::Random => {
+                let random_rows: Vec<usize> =
+                    rand_utils::reservoir_sample(&(0..inputs.rows()).collect::<Vec<usize>>(), k);
+                Ok(inputs.select_rows(&random_rows))
+            }
+            MeanInit::KPlusPlus => KPlusPlus.init_centroids(k, inputs),
+        }
+    }
+
     fn initialize_covariances(&self, inputs: &Matrix<f64>, reg_value: f64) -> LearningResult<Matrix<f64>> {
         match self.cov_option {
             CovOption::Diagonal => {
@@ -231,7 +525,13 @@ impl GaussianMixtureModel {
                 Ok(Matrix::from_diag(variance.data()) * reg_value.sqrt())
             }
 
-            CovOption::Full | CovOption::Regularized(_) => {
+            CovOption::Spherical => {
+                let variance = inputs.variance(Axes::Row)?;
+                let mean_var = variance.sum() / variance.size() as f64;
+                Ok(Matrix::<f64>::identity(inputs.cols()) * mean_var)
+            }
+
+            CovOption::Full | CovOption::Regularized(_) | CovOption::Tied => {
                 let means = inputs.mean(Axes::Row);
                 let mut cov_mat = Matrix::zeros(inputs.cols(), inputs.cols());
                 for (j, mut row) in cov_mat.row_iter_mut().enumerate() {
@@ -250,55 +550,70 @@ impl GaussianMixtureModel {
         }
     }
 
-    fn membership_weights(&self, inputs: &Matrix<f64>) -> LearningResult<(Matrix<f64>, f64)> {
-        let n = inputs.rows();
-
-        let mut member_weights_data = Vec::with_capacity(n * self.comp_count);
+    /// Computes the log of the weighted density of each Gaussian at each input.
+    ///
+    /// The output has a row for each input and a column for each Gaussian.
+    fn weighted_log_densities(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        if let (&Some(ref means), &Some(ref covars)) = (&self.model_means, &self.model_covars) {
+            let n = inputs.rows();
+            let d = inputs.cols();
+
+            if d != means.cols() {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Input data has different number of columns to model."));
+            }
 
-        // We compute the determinants and inverses now
-        let mut cov_sqrt_dets = Vec::with_capacity(self.comp_count);
-        let mut cov_invs = Vec::with_capacity(self.comp_count);
+            // We compute the log determinants and inverses now
+            let mut cov_log_dets = Vec::with_capacity(self.comp_count);
+            let mut cov_invs = Vec::with_capacity(self.comp_count);
 
-        if let Some(ref covars) = self.model_covars {
             for cov in covars {
-                let lup = PartialPivLu::decompose(cov.clone()).expect("Covariance could not be lup decomposed");
+                let lup = PartialPivLu::decompose(cov.clone())?;
                 let covar_det = lup.det();
                 // TODO: We can probably remove this inverse for a more stable solve elsewhere.
                 let covar_inv = lup.inverse().map_err(Error::from)?;
 
-                cov_sqrt_dets.push(covar_det.sqrt());
+                cov_log_dets.push(covar_det.ln());
                 cov_invs.push(covar_inv);
             }
-        }
 
-        let mut log_lik = 0f64;
+            let log_norm = -0.5 * d as f64 * (2f64 * PI).ln();
 
-        // Now we compute the membership weights
-        if let Some(ref means) = self.model_means {
+            let mut log_dens = Vec::with_capacity(n * self.comp_count);
             for i in 0..n {
-                let mut pdfs = Vec::with_capacity(self.comp_count);
-                let x_i = MatrixSlice::from_matrix(inputs, [i, 0], 1, inputs.cols());
+                let x_i = MatrixSlice::from_matrix(inputs, [i, 0], 1, d);
 
                 for j in 0..self.comp_count {
-                    let mu_j = MatrixSlice::from_matrix(means, [j, 0], 1, means.cols());
+                    let mu_j = MatrixSlice::from_matrix(means, [j, 0], 1, d);
                     let diff = x_i - mu_j;
 
-                    let pdf = (&diff * &cov_invs[j] * diff.transpose() * -0.5).into_vec()[0]
-                        .exp() / cov_sqrt_dets[j];
-                    pdfs.push(pdf);
+                    let quad = (&diff * &cov_invs[j] * diff.transpose()).into_vec()[0];
+                    log_dens.push(self.mix_weights[j].ln() + log_norm - 0.5 * cov_log_dets[j] -
+                                  0.5 * quad);
                 }
+            }
 
-                let weighted_pdf_sum = utils::dot(&pdfs, self.mix_weights.data());
+            Ok(Matrix::new(n, self.comp_count, log_dens))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
 
-                for (idx, pdf) in pdfs.iter().enumerate() {
-                    member_weights_data.push(self.mix_weights[idx] * pdf / (weighted_pdf_sum));
-                }
+    fn membership_weights(&self, inputs: &Matrix<f64>) -> LearningResult<(Matrix<f64>, f64)> {
+        let mut member_weights = self.weighted_log_densities(inputs)?;
+
+        let mut log_lik = 0f64;
 
-                log_lik += weighted_pdf_sum.ln();
+        // Normalize the weighted densities of each input in log space.
+        for mut row in member_weights.row_iter_mut() {
+            let log_sum = log_sum_exp(row.raw_slice());
+            for w in row.raw_slice_mut() {
+                *w = (*w - log_sum).exp();
             }
+            log_lik += log_sum;
         }
 
-        Ok((Matrix::new(n, self.comp_count, member_weights_data), log_lik))
+        Ok((member_weights, log_lik))
     }
 
     fn update_params(&mut self, inputs: &Matrix<f64>, membership_weights: Matrix<f64>) {
@@ -331,8 +646,19 @@ impl GaussianMixtureModel {
                 cov_mat += Matrix::<f64>::identity(cov_mat.cols()) * eps;
             }
 
-            new_covs.push(cov_mat / sum_weights[k]);
+            new_covs.push(cov_mat);
+        }
 
+        if let CovOption::Tied = self.cov_option {
+            // The shared covariance pools the scatter of every Gaussian.
+            let tied_cov = new_covs.into_iter()
+                .fold(Matrix::zeros(d, d), |acc, cov| acc + cov) / (n as f64);
+            new_covs = vec![tied_cov; self.comp_count];
+        } else {
+            new_covs = new_covs.into_iter()
+                .zip(sum_weights.iter())
+                .map(|(cov, w)| cov / *w)
+                .collect();
         }
 
         self.model_means = Some(new_means);
@@ -341,16 +667,99 @@ impl GaussianMixtureModel {
 
     fn compute_cov(&self, diff: Matrix<f64>, weight: f64) -> Matrix<f64> {
         match self.cov_option {
-            CovOption::Full | CovOption::Regularized(_) => (diff.transpose() * diff) * weight,
+            CovOption::Full | CovOption::Regularized(_) | CovOption::Tied => {
+                (diff.transpose() * diff) * weight
+            }
             CovOption::Diagonal => Matrix::from_diag(&diff.elemul(&diff).into_vec()) * weight,
+            CovOption::Spherical => {
+                let d = diff.cols();
+                let sq_norm = diff.elemul(&diff).sum();
+                Matrix::<f64>::identity(d) * (sq_norm * weight / d as f64)
+            }
+        }
+    }
+}
+
+/// Computes `ln(sum(exp(x)))` without overflow.
+fn log_sum_exp(xs: &[f64]) -> f64 {
+    let max = xs.iter().fold(f64::NEG_INFINITY, |m, &x| m.max(x));
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+    max + xs.iter().map(|x| (x - max).exp()).sum::<f64>().ln()
+}
+
+/// Fits a GMM for each number of components and returns the best by the criterion.
+///
+/// Each model uses the given covariance option and the default number of
+/// iterations. The criterion is evaluated on the training inputs. Models
+/// which fail to train, for example because a covariance became singular,
+/// are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::linalg::Matrix;
+/// use rusty_machine::learning::gmm::{self, CovOption, SelectionCriterion};
+///
+/// let inputs = Matrix::new(6, 1, vec![0., 0.1, 0.2, 5., 5.1, 5.2]);
+///
+/// let model = gmm::select_comp_count(&inputs, 1..3, CovOption::Diagonal,
+///                                    SelectionCriterion::BIC).unwrap();
+/// println!("{:?}", model.means());
+/// ```
+///
+/// # Failures
+///
+/// - No component counts are given.
+/// - No model could be trained.
+pub fn select_comp_count<I>(inputs: &Matrix<f64>,
+                            comp_counts: I,
+                            cov_option: CovOption,
+                            criterion: SelectionCriterion)
+                            -> LearningResult<GaussianMixtureModel>
+    where I: IntoIterator<Item = usize>
+{
+    let mut best: Option<(f64, GaussianMixtureModel)> = None;
+    let mut last_err = None;
+
+    for k in comp_counts {
+        let mut model = GaussianMixtureModel::new(k);
+        model.cov_option = cov_option;
+
+        let value = model.train(inputs).and_then(|_| match criterion {
+            SelectionCriterion::BIC => model.bic(inputs),
+            SelectionCriterion::AIC => model.aic(inputs),
+        });
+
+        match value {
+            // NaN criteria, from degenerate fits, are never chosen.
+            Ok(v) if !v.is_nan() => {
+                if best.as_ref().map_or(true, |&(b, _)| v < b) {
+                    best = Some((v, model));
+                }
+            }
+            Ok(_) => {}
+            Err(e) => last_err = Some(e),
+        }
+    }
+
+    match best {
+        Some((_, model)) => Ok(model),
+        None => {
+            Err(last_err.unwrap_or_else(|| {
+                Error::new(ErrorKind::InvalidParameters,
+                           "At least one component count must be given.")
+            }))
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::GaussianMixtureModel;
-    use linalg::Vector;
+    use super::{GaussianMixtureModel, CovOption, log_sum_exp};
+    use linalg::{Matrix, Vector};
+    use std::f64;
 
     #[test]
     fn test_means_none() {
@@ -379,4 +788,34 @@ mod tests {
         let gmm_res = GaussianMixtureModel::with_weights(3, mix_weights);
         assert!(gmm_res.is_err());
     }
+
+    #[test]
+    fn test_log_likelihood_none() {
+        let model = GaussianMixtureModel::new(2);
+
+        assert_eq!(model.log_likelihood(), None);
+        assert!(model.score_samples(&Matrix::new(1, 1, vec![0.])).is_err());
+    }
+
+    #[test]
+    fn test_num_parameters() {
+        let mut model = GaussianMixtureModel::new(3);
+        assert_eq!(model.num_parameters(2), 2 + 6 + 9);
+
+        model.cov_option = CovOption::Diagonal;
+        assert_eq!(model.num_parameters(2), 2 + 6 + 6);
+
+        model.cov_option = CovOption::Tied;
+        assert_eq!(model.num_parameters(2), 2 + 6 + 3);
+
+        model.cov_option = CovOption::Spherical;
+        assert_eq!(model.num_parameters(2), 2 + 6 + 3);
+    }
+
+    #[test]
+    fn test_log_sum_exp() {
+        assert!((log_sum_exp(&[0., 0.]) - 2f64.ln()).abs() < 1e-12);
+        assert!((log_sum_exp(&[1000., 1000.]) - (1000. + 2f64.ln())).abs() < 1e-9);
+        assert_eq!(log_sum_exp(&[f64::NEG_INFINITY]), f64::NEG_INFINITY);
+    }
 }
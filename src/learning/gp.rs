@@ -25,13 +25,40 @@
 //! Alternatively one could use `gaussp.get_posterior()` which would return both
 //! the predictive mean and covariance. However, this is likely to change in
 //! a future release.
+//!
+//! When only pointwise uncertainty is needed `gaussp.predict_with_variance()`
+//! returns the predictive mean and variance without forming the covariance
+//! matrix. Functions can be drawn from the posterior with `gaussp.sample_posterior()`.
+//!
+//! The kernel hyperparameters and noise can be fitted to the data by maximising
+//! the log marginal likelihood with `gaussp.train_with_hyperopt()`.
+//!
+//! Independent GPs sharing a kernel can be fitted to each column of a target
+//! matrix with the `MultiOutputGaussianProcess`.
+
+use std::f64;
+use std::f64::consts::PI;
 
-use learning::toolkit::kernel::{Kernel, SquaredExp};
+use rand::{thread_rng, Rng};
+use rand::distributions::normal::StandardNormal;
+
+use learning::toolkit::kernel::{Kernel, SquaredExp, kernel_matrix};
+use learning::optim::{Optimizable, OptimAlgorithm};
+use learning::optim::fmincg::ConjugateGD;
 use linalg::{Matrix, BaseMatrix, Decomposition, Cholesky};
 use linalg::Vector;
 use learning::{LearningResult, SupModel};
 use learning::error::{Error, ErrorKind};
 
+/// The smallest noise used as a starting point for hyperparameter optimization.
+const MIN_NOISE: f64 = 1e-6;
+
+/// The width of the uniform perturbation of the log hyperparameters for random restarts.
+const RESTART_SCALE: f64 = 2f64;
+
+/// The jitter added to the posterior covariance when drawing samples.
+const SAMPLE_JITTER: f64 = 1e-8;
+
 /// Trait for GP mean functions.
 pub trait MeanFunc {
     /// Compute the mean function applied elementwise to a matrix.
@@ -122,20 +149,258 @@ impl<T: Kernel, U: MeanFunc> GaussianProcess<T, U> {
 
     /// Construct a kernel matrix
     fn ker_mat(&self, m1: &Matrix<f64>, m2: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
-        if m1.cols() != m2.cols() {
-            Err(Error::new(ErrorKind::InvalidState,
-                           "Inputs to kernel matrices have different column counts."))
-        } else {
-            let dim1 = m1.rows();
-            let dim2 = m2.rows();
+        kernel_matrix(&self.ker, m1, m2)
+    }
+
+    /// Computes the log marginal likelihood of the targets.
+    ///
+    /// This is the log probability of the targets given the inputs under
+    /// the GP prior with the current kernel, mean and noise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gp::GaussianProcess;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let mut gp = GaussianProcess::default();
+    /// gp.noise = 0.1;
+    ///
+    /// let inputs = Matrix::new(4, 1, vec![0., 1., 2., 3.]);
+    /// let targets = Vector::new(vec![0., 1., 0., -1.]);
+    ///
+    /// let lml = gp.log_marginal_likelihood(&inputs, &targets).unwrap();
+    /// assert!(lml < 0.);
+    /// ```
+    pub fn log_marginal_likelihood(&self,
+                                   inputs: &Matrix<f64>,
+                                   targets: &Vector<f64>)
+                                   -> LearningResult<f64> {
+        log_marginal_likelihood(&self.ker, &self.mean, self.noise, inputs, targets, false)
+            .map(|(lml, _)| lml)
+    }
+
+    /// Computes the log marginal likelihood and its gradient.
+    ///
+    /// The gradient is taken with respect to the log hyperparameters of
+    /// the kernel (in the order given by `Kernel::hyperparams`) followed
+    /// by the log of the noise.
+    pub fn log_marginal_likelihood_grad(&self,
+                                        inputs: &Matrix<f64>,
+                                        targets: &Vector<f64>)
+                                        -> LearningResult<(f64, Vec<f64>)> {
+        log_marginal_likelihood(&self.ker, &self.mean, self.noise, inputs, targets, true)
+    }
+}
+
+impl<T: Kernel + Clone, U: MeanFunc> GaussianProcess<T, U> {
+    /// Fits the hyperparameters and then trains the model.
+    ///
+    /// The kernel hyperparameters and the noise are chosen by maximising the
+    /// log marginal likelihood with `ConjugateGD`. The optimization starts
+    /// from the current hyperparameters and is repeated from `restarts`
+    /// random perturbations of them, keeping the best result. Restarts
+    /// whose optimization fails are skipped.
+    ///
+    /// The perturbations are drawn from `thread_rng`, see
+    /// `train_with_hyperopt_with_rng` to use a seeded generator.
+    ///
+    /// Returns the log marginal likelihood of the chosen hyperparameters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gp::GaussianProcess;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let mut gp = GaussianProcess::default();
+    /// gp.noise = 0.1;
+    ///
+    /// let inputs = Matrix::new(6, 1, vec![0., 1., 2., 3., 4., 5.]);
+    /// let targets = Vector::new(vec![0., 0.8, 0.9, 0.1, -0.7, -1.]);
+    ///
+    /// let lml = gp.train_with_hyperopt(&inputs, &targets, 2).unwrap();
+    /// ```
+    pub fn train_with_hyperopt(&mut self,
+                               inputs: &Matrix<f64>,
+                               targets: &Vector<f64>,
+                               restarts: usize)
+                               -> LearningResult<f64> {
+        self.train_with_hyperopt_with_rng(inputs, targets, restarts, &mut thread_rng())
+    }
+
+    /// Fits the hyperparameters and then trains the model, drawing the
+    /// random restarts from `rng`.
+    ///
+    /// See `train_with_hyperopt`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rand;
+    /// # extern crate rusty_machine;
+    /// use rand::{SeedableRng, StdRng};
+    /// use rusty_machine::learning::gp::GaussianProcess;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// # fn main() {
+    /// let inputs = Matrix::new(6, 1, vec![0., 1., 2., 3., 4., 5.]);
+    /// let targets = Vector::new(vec![0., 0.8, 0.9, 0.1, -0.7, -1.]);
+    ///
+    /// let seed: &[_] = &[1, 2, 3];
+    /// let fit = || {
+    ///     let mut gp = GaussianProcess::default();
+    ///     gp.noise = 0.1;
+    ///     gp.train_with_hyperopt_with_rng(&inputs, &targets, 2, &mut StdRng::from_seed(seed))
+    ///         .unwrap()
+    /// };
+    ///
+    /// assert_eq!(fit(), fit());
+    /// # }
+    /// ```
+    pub fn train_with_hyperopt_with_rng<R: Rng>(&mut self,
+                                                inputs: &Matrix<f64>,
+                                                targets: &Vector<f64>,
+                                                restarts: usize,
+                                                rng: &mut R)
+                                                -> LearningResult<f64> {
+        let mut start = self.ker.hyperparams();
+        start.push(self.noise.max(MIN_NOISE).ln());
+
+        let (neg_lml, params) = {
+            let objective = HyperObjective {
+                ker: &self.ker,
+                mean: &self.mean,
+            };
+            let optimizer = ConjugateGD::default();
+
+            let mut best: Option<(f64, Vec<f64>)> = None;
+            for restart in 0..restarts + 1 {
+                let init = if restart == 0 {
+                    start.clone()
+                } else {
+                    start.iter()
+                        .map(|p| p + rng.gen_range(-RESTART_SCALE, RESTART_SCALE))
+                        .collect::<Vec<_>>()
+                };
+
+                let params = match optimizer.optimize(&objective, &init, inputs, targets) {
+                    Ok(params) => params,
+                    Err(_) => continue,
+                };
+                let (cost, _) = objective.compute_grad(&params, inputs, targets);
+
+                let improved = match best {
+                    Some((best_cost, _)) => cost < best_cost,
+                    None => true,
+                };
+                if cost.is_finite() && improved {
+                    best = Some((cost, params));
+                }
+            }
+
+            best.ok_or_else(|| {
+                    Error::new(ErrorKind::InvalidState,
+                               "Could not find hyperparameters with a finite marginal likelihood.")
+                })?
+        };
 
-            let mut ker_data = Vec::with_capacity(dim1 * dim2);
-            ker_data.extend(m1.row_iter().flat_map(|row1| {
-                m2.row_iter()
-                    .map(move |row2| self.ker.kernel(row1.raw_slice(), row2.raw_slice()))
-            }));
+        let split = params.len() - 1;
+        self.ker.set_hyperparams(&params[..split]);
+        self.noise = params[split].exp();
+        self.train(inputs, targets)?;
 
-            Ok(Matrix::new(dim1, dim2, ker_data))
+        Ok(-neg_lml)
+    }
+}
+
+/// Computes the log marginal likelihood, and optionally its gradient
+/// with respect to the log kernel hyperparameters and log noise.
+fn log_marginal_likelihood<K: Kernel, M: MeanFunc>(ker: &K,
+                                                   mean: &M,
+                                                   noise: f64,
+                                                   inputs: &Matrix<f64>,
+                                                   targets: &Vector<f64>,
+                                                   with_grad: bool)
+                                                   -> LearningResult<(f64, Vec<f64>)> {
+    let n = inputs.rows();
+    if targets.size() != n {
+        return Err(Error::new(ErrorKind::InvalidData,
+                              "The number of inputs and targets do not match."));
+    }
+
+    let cov = kernel_matrix(ker, inputs, inputs)? + Matrix::identity(n) * noise;
+    let l = Cholesky::decompose(cov).map_err(|_| {
+            Error::new(ErrorKind::InvalidState,
+                       "Could not compute Cholesky decomposition.")
+        })?
+        .unpack();
+
+    let y = targets - mean.func(inputs.clone());
+    let z = l.solve_l_triangular(y)?;
+    let alpha = l.transpose().solve_u_triangular(z.clone())?;
+
+    let log_det = (0..n).map(|i| l[[i, i]].ln()).sum::<f64>();
+    let lml = -0.5 * z.dot(&z) - log_det - 0.5 * (n as f64) * (2f64 * PI).ln();
+
+    if !with_grad {
+        return Ok((lml, Vec::new()));
+    }
+
+    // The columns of L^-1, so that K^-1 = L^-T L^-1.
+    let mut l_inv_t = Vec::with_capacity(n * n);
+    for j in 0..n {
+        let mut e_j = Vector::zeros(n);
+        e_j[j] = 1f64;
+        l_inv_t.extend(l.solve_l_triangular(e_j)?.into_vec());
+    }
+    let l_inv_t = Matrix::new(n, n, l_inv_t);
+    let cov_inv = &l_inv_t * l_inv_t.transpose();
+
+    // The gradient is 0.5 * tr((aa^T - K^-1) dK/dp)
+    let rows = inputs.row_iter().map(|r| r.raw_slice()).collect::<Vec<_>>();
+    let num_params = ker.hyperparams().len();
+    let mut grad = vec![0f64; num_params + 1];
+
+    for i in 0..n {
+        for j in 0..i + 1 {
+            let w = alpha[i] * alpha[j] - cov_inv[[i, j]];
+            // Off diagonal terms appear twice in the trace.
+            let scale = if i == j { 0.5 * w } else { w };
+            for (g, dk) in grad.iter_mut().zip(ker.hyperparam_grad(rows[i], rows[j])) {
+                *g += scale * dk;
+            }
+        }
+        grad[num_params] += 0.5 * noise * (alpha[i] * alpha[i] - cov_inv[[i, i]]);
+    }
+
+    Ok((lml, grad))
+}
+
+/// The negative log marginal likelihood as a function of the
+/// log kernel hyperparameters followed by the log noise.
+struct HyperObjective<'a, T: 'a, U: 'a> {
+    ker: &'a T,
+    mean: &'a U,
+}
+
+impl<'a, T: Kernel + Clone, U: MeanFunc> Optimizable for HyperObjective<'a, T, U> {
+    type Inputs = Matrix<f64>;
+    type Targets = Vector<f64>;
+
+    fn compute_grad(&self,
+                    params: &[f64],
+                    inputs: &Matrix<f64>,
+                    targets: &Vector<f64>)
+                    -> (f64, Vec<f64>) {
+        let split = params.len() - 1;
+        let mut ker = self.ker.clone();
+        ker.set_hyperparams(&params[..split]);
+
+        match log_marginal_likelihood(&ker, self.mean, params[split].exp(), inputs, targets, true) {
+            Ok((lml, grad)) => (-lml, grad.into_iter().map(|g| -g).collect()),
+            // Hyperparameters giving a singular covariance are infinitely unlikely.
+            Err(_) => (f64::INFINITY, vec![0f64; params.len()]),
         }
     }
 }
@@ -156,14 +421,7 @@ impl<T: Kernel, U: MeanFunc> SupModel<Matrix<f64>, Vector<f64>> for GaussianProc
 
     /// Train the model using data and outputs.
     fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
-        let noise_mat = Matrix::identity(inputs.rows()) * self.noise;
-
-        let ker_mat = self.ker_mat(inputs, inputs).unwrap();
-
-        let train_mat = Cholesky::decompose(ker_mat + noise_mat).map_err(|_| {
-            Error::new(ErrorKind::InvalidState,
-                       "Could not compute Cholesky decomposition.")
-        })?.unpack();
+        let train_mat = train_cholesky(&self.ker, self.noise, inputs)?;
 
         let x = train_mat.solve_l_triangular(targets - self.mean.func(inputs.clone())).unwrap();
         let alpha = train_mat.transpose().solve_u_triangular(x).unwrap();
@@ -209,3 +467,295 @@ impl<T: Kernel, U: MeanFunc> GaussianProcess<T, U> {
         }
     }
 }
+
+impl<T: Kernel, U: MeanFunc> GaussianProcess<T, U> {
+    /// Compute the posterior mean and variance at each input.
+    ///
+    /// Requires the model to be trained first.
+    ///
+    /// This avoids forming the full posterior covariance matrix
+    /// and so is preferable for large prediction grids.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gp::GaussianProcess;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let mut gp = GaussianProcess::default();
+    /// gp.noise = 0.1;
+    ///
+    /// let inputs = Matrix::new(4, 1, vec![0., 1., 2., 3.]);
+    /// let targets = Vector::new(vec![0., 1., 0., -1.]);
+    /// gp.train(&inputs, &targets).unwrap();
+    ///
+    /// let (mean, var) = gp.predict_with_variance(&Matrix::new(2, 1, vec![1.5, 10.])).unwrap();
+    ///
+    /// // Far from the data the variance returns to the prior variance.
+    /// assert!(var[0] < var[1]);
+    /// ```
+    pub fn predict_with_variance(&self,
+                                 inputs: &Matrix<f64>)
+                                 -> LearningResult<(Vector<f64>, Vector<f64>)> {
+        if let (&Some(ref t_mat), &Some(ref alpha), &Some(ref t_data)) = (&self.train_mat,
+                                                                          &self.alpha,
+                                                                          &self.train_data) {
+            let test_mat = self.ker_mat(inputs, t_data)?;
+            let post_mean = self.mean.func(inputs.clone()) + &test_mat * alpha;
+            let post_var = predictive_variance(&self.ker, t_mat, inputs, &test_mat)?;
+
+            Ok((post_mean, post_var))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// Draws functions from the posterior distribution.
+    ///
+    /// Requires the model to be trained first.
+    ///
+    /// Each row of the returned matrix is one sample of the function
+    /// values at the inputs. A small jitter is added to the posterior
+    /// covariance for numerical stability.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate rand;
+    /// # extern crate rusty_machine;
+    /// use rusty_machine::learning::gp::GaussianProcess;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{Matrix, Vector, BaseMatrix};
+    /// use rand::{StdRng, SeedableRng};
+    ///
+    /// # fn main() {
+    /// let mut gp = GaussianProcess::default();
+    /// gp.noise = 0.1;
+    ///
+    /// let inputs = Matrix::new(4, 1, vec![0., 1., 2., 3.]);
+    /// let targets = Vector::new(vec![0., 1., 0., -1.]);
+    /// gp.train(&inputs, &targets).unwrap();
+    ///
+    /// let seed: &[_] = &[1, 2, 3];
+    /// let mut rng = StdRng::from_seed(seed);
+    /// let samples = gp.sample_posterior(&inputs, 5, &mut rng).unwrap();
+    ///
+    /// assert_eq!(samples.rows(), 5);
+    /// assert_eq!(samples.cols(), 4);
+    /// # }
+    /// ```
+    pub fn sample_posterior<R: Rng>(&self,
+                                    inputs: &Matrix<f64>,
+                                    num_samples: usize,
+                                    rng: &mut R)
+                                    -> LearningResult<Matrix<f64>> {
+        let (mean, cov) = self.get_posterior(inputs)?;
+        sample_gaussian(&mean, cov, num_samples, rng)
+    }
+}
+
+/// Multi-output Gaussian Process struct
+///
+/// Independent gaussian processes for each column of the targets
+/// which share a kernel, mean function and noise. The kernel matrix
+/// is decomposed once for all of the outputs.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::learning::gp::MultiOutputGaussianProcess;
+/// use rusty_machine::learning::SupModel;
+/// use rusty_machine::linalg::{Matrix, BaseMatrix};
+///
+/// let mut gp = MultiOutputGaussianProcess::default();
+/// gp.noise = 0.1;
+///
+/// let inputs = Matrix::new(4, 1, vec![0., 1., 2., 3.]);
+/// let targets = Matrix::new(4, 2, vec![0., 1., 1., 0., 0., -1., -1., 0.]);
+/// gp.train(&inputs, &targets).unwrap();
+///
+/// let outputs = gp.predict(&Matrix::new(2, 1, vec![0.5, 2.5])).unwrap();
+/// assert_eq!(outputs.cols(), 2);
+/// ```
+#[derive(Debug)]
+pub struct MultiOutputGaussianProcess<T: Kernel, U: MeanFunc> {
+    ker: T,
+    mean: U,
+    /// The observation noise of the GP.
+    pub noise: f64,
+    alpha: Option<Matrix<f64>>,
+    train_mat: Option<Matrix<f64>>,
+    train_data: Option<Matrix<f64>>,
+}
+
+/// Construct a default Multi-output Gaussian Process
+///
+/// The defaults are:
+///
+/// - Squared Exponential kernel.
+/// - Zero-mean function.
+/// - Zero noise.
+impl Default for MultiOutputGaussianProcess<SquaredExp, ConstMean> {
+    fn default() -> MultiOutputGaussianProcess<SquaredExp, ConstMean> {
+        MultiOutputGaussianProcess::new(SquaredExp::default(), ConstMean::default(), 0f64)
+    }
+}
+
+impl<T: Kernel, U: MeanFunc> MultiOutputGaussianProcess<T, U> {
+    /// Construct a new Multi-output Gaussian Process.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gp;
+    /// use rusty_machine::learning::toolkit::kernel;
+    ///
+    /// let ker = kernel::SquaredExp::default();
+    /// let mean = gp::ConstMean::default();
+    /// let gaussp = gp::MultiOutputGaussianProcess::new(ker, mean, 1e-3f64);
+    /// ```
+    pub fn new(ker: T, mean: U, noise: f64) -> MultiOutputGaussianProcess<T, U> {
+        MultiOutputGaussianProcess {
+            ker: ker,
+            mean: mean,
+            noise: noise,
+            alpha: None,
+            train_mat: None,
+            train_data: None,
+        }
+    }
+
+    /// The prior mean added to every output column.
+    fn add_mean(&self, inputs: &Matrix<f64>, mut outputs: Matrix<f64>) -> Matrix<f64> {
+        let mean = self.mean.func(inputs.clone());
+        let cols = outputs.cols();
+        for (row, m) in outputs.mut_data().chunks_mut(cols).zip(mean.iter()) {
+            for x in row {
+                *x += *m;
+            }
+        }
+        outputs
+    }
+
+    /// Compute the posterior mean of each output and the shared variance at each input.
+    ///
+    /// Requires the model to be trained first.
+    ///
+    /// As the outputs share a kernel and noise their predictive
+    /// variances are equal.
+    pub fn predict_with_variance(&self,
+                                 inputs: &Matrix<f64>)
+                                 -> LearningResult<(Matrix<f64>, Vector<f64>)> {
+        if let (&Some(ref t_mat), &Some(ref alpha), &Some(ref t_data)) = (&self.train_mat,
+                                                                          &self.alpha,
+                                                                          &self.train_data) {
+            let test_mat = kernel_matrix(&self.ker, inputs, t_data)?;
+            let post_mean = self.add_mean(inputs, &test_mat * alpha);
+            let post_var = predictive_variance(&self.ker, t_mat, inputs, &test_mat)?;
+
+            Ok((post_mean, post_var))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+impl<T: Kernel, U: MeanFunc> SupModel<Matrix<f64>, Matrix<f64>> for MultiOutputGaussianProcess<T, U> {
+    /// Predict each output from inputs.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        if let (&Some(ref alpha), &Some(ref t_data)) = (&self.alpha, &self.train_data) {
+            let test_mat = kernel_matrix(&self.ker, inputs, t_data)?;
+            Ok(self.add_mean(inputs, test_mat * alpha))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// Train the model using data and a column of targets for each output.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Matrix<f64>) -> LearningResult<()> {
+        if inputs.rows() != targets.rows() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets do not match."));
+        }
+
+        let train_mat = train_cholesky(&self.ker, self.noise, inputs)?;
+        let mean = self.mean.func(inputs.clone());
+
+        let mut alpha_t = Vec::with_capacity(targets.rows() * targets.cols());
+        for j in 0..targets.cols() {
+            let y = Vector::new(targets.select_cols(&[j]).into_vec()) - &mean;
+            let x = train_mat.solve_l_triangular(y)?;
+            alpha_t.extend(train_mat.transpose().solve_u_triangular(x)?.into_vec());
+        }
+
+        self.alpha = Some(Matrix::new(targets.cols(), targets.rows(), alpha_t).transpose());
+        self.train_mat = Some(train_mat);
+        self.train_data = Some(inputs.clone());
+
+        Ok(())
+    }
+}
+
+/// Computes the Cholesky factor of the noisy kernel matrix of the training inputs.
+fn train_cholesky<K: Kernel>(ker: &K,
+                             noise: f64,
+                             inputs: &Matrix<f64>)
+                             -> LearningResult<Matrix<f64>> {
+    let noise_mat = Matrix::identity(inputs.rows()) * noise;
+    let ker_mat = kernel_matrix(ker, inputs, inputs)?;
+
+    Cholesky::decompose(ker_mat + noise_mat)
+        .map(|c| c.unpack())
+        .map_err(|_| {
+            Error::new(ErrorKind::InvalidState,
+                       "Could not compute Cholesky decomposition.")
+        })
+}
+
+/// Computes the posterior variance at each input.
+///
+/// Takes the Cholesky factor of the training matrix and the kernel
+/// matrix between the inputs and the training data.
+fn predictive_variance<K: Kernel>(ker: &K,
+                                  train_mat: &Matrix<f64>,
+                                  inputs: &Matrix<f64>,
+                                  test_mat: &Matrix<f64>)
+                                  -> LearningResult<Vector<f64>> {
+    let mut var = Vec::with_capacity(inputs.rows());
+    for (x, k) in inputs.row_iter().zip(test_mat.row_iter()) {
+        let v = train_mat.solve_l_triangular(Vector::new(k.raw_slice()))?;
+        let prior = ker.kernel(x.raw_slice(), x.raw_slice());
+        // Rounding can leave tiny negative variances near the training data.
+        var.push((prior - v.dot(&v)).max(0f64));
+    }
+    Ok(Vector::new(var))
+}
+
+/// Draws samples from a multivariate gaussian, one per row.
+fn sample_gaussian<R: Rng>(mean: &Vector<f64>,
+                           cov: Matrix<f64>,
+                           num_samples: usize,
+                           rng: &mut R)
+                           -> LearningResult<Matrix<f64>> {
+    let n = mean.size();
+    let chol = Cholesky::decompose(cov + Matrix::identity(n) * SAMPLE_JITTER)
+        .map_err(|_| {
+            Error::new(ErrorKind::InvalidState,
+                       "Could not compute Cholesky decomposition of the posterior covariance.")
+        })?
+        .unpack();
+
+    let mut samples = Vec::with_capacity(num_samples * n);
+    for _ in 0..num_samples {
+        let z = (0..n)
+            .map(|_| {
+                let StandardNormal(x) = rng.gen::<StandardNormal>();
+                x
+            })
+            .collect::<Vec<_>>();
+        samples.extend((&chol * Vector::new(z) + mean).into_vec());
+    }
+
+    Ok(Matrix::new(num_samples, n, samples))
+}
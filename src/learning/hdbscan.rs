@@ -0,0 +1,626 @@
+//! HDBSCAN Clustering
+//!
+//! Provides an implementation of HDBSCAN (Hierarchical DBSCAN) clustering.
+//!
+//! HDBSCAN builds the hierarchy of DBSCAN clusterings over all values of
+//! `eps` and selects the most stable clusters from it, so it can find
+//! clusters of varying density. The only required parameter is the
+//! minimum cluster size.
+//!
+//! The algorithm works in the following steps:
+//!
+//! 1. The core distance of each point is the distance to its
+//!    `min_samples`-th nearest neighbour, counting itself.
+//! 2. The mutual reachability distance between two points is the largest
+//!    of their distance and their two core distances. A minimum spanning
+//!    tree of the points under this distance is built.
+//! 3. The spanning tree is turned into a single linkage hierarchy, which is
+//!    condensed by treating splits that remove fewer than `min_cluster_size`
+//!    points as points falling out of a cluster.
+//! 4. The clusters of the condensed tree with the largest stability are
+//!    selected, where the stability measures how long the points of a
+//!    cluster persist as `eps` shrinks.
+//!
+//! Points outside the selected clusters are noise, and are set to `None`
+//! in the clusters `Vector`. The model also computes the GLOSH outlier
+//! score of each training point, which lies between 0 for points in the
+//! densest part of their cluster and 1 for outliers.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::hdbscan::HDBSCAN;
+//! use rusty_machine::learning::UnSupModel;
+//! use rusty_machine::linalg::Matrix;
+//!
+//! // A dense cluster, a sparse cluster and an outlier.
+//! let inputs = Matrix::new(9, 1, vec![0.0, 0.1, 0.2, 0.3,
+//!                                     10.0, 11.0, 12.0, 13.0,
+//!                                     100.0]);
+//!
+//! let mut model = HDBSCAN::new(3);
+//! model.set_min_samples(2);
+//! model.train(&inputs).unwrap();
+//!
+//! let clustering = model.clusters().unwrap();
+//! assert!(clustering[0].is_some());
+//! assert!(clustering[4].is_some());
+//! assert!(clustering[0] != clustering[4]);
+//! assert_eq!(clustering[8], None);
+//! ```
+
+use std::cmp;
+use std::collections::VecDeque;
+use std::f64;
+
+use learning::{LearningResult, UnSupModel};
+use learning::error::{Error, ErrorKind};
+use learning::knn::{KNearestSearch, MetricSearch};
+use learning::toolkit::distance::{Metric, Euclidean};
+
+use linalg::{Matrix, Vector, BaseMatrix};
+
+/// An edge of the condensed cluster tree.
+///
+/// Clusters are labelled from `n`, the number of training points, with
+/// the root cluster labelled `n`. Children with a size of one are the
+/// training points, which are labelled by their index.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CondensedEdge {
+    /// The parent cluster.
+    pub parent: usize,
+    /// The child cluster or point.
+    pub child: usize,
+    /// The inverse distance at which the child leaves the parent.
+    pub lambda: f64,
+    /// The number of points in the child.
+    pub size: usize,
+}
+
+/// HDBSCAN Model
+///
+/// Implements clustering using the HDBSCAN algorithm
+/// via the `UnSupModel` trait.
+#[derive(Debug)]
+pub struct HDBSCAN {
+    min_cluster_size: usize,
+    min_samples: usize,
+    searcher: MetricSearch,
+    core_distances: Option<Vector<f64>>,
+    condensed_tree: Option<Vec<CondensedEdge>>,
+    clusters: Option<Vector<Option<usize>>>,
+    outlier_scores: Option<Vector<f64>>,
+}
+
+/// Constructs an HDBSCAN model with the
+/// following parameters:
+///
+/// - `min_cluster_size` : `5`
+/// - `min_samples` : `5`
+/// - `metric` : `Euclidean`
+impl Default for HDBSCAN {
+    fn default() -> HDBSCAN {
+        HDBSCAN::new(5)
+    }
+}
+
+impl UnSupModel<Matrix<f64>, Vector<Option<usize>>> for HDBSCAN {
+    /// Train the model using input data.
+    fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
+        let n = inputs.rows();
+        if n == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "There are no training inputs."));
+        }
+
+        self.searcher.build(inputs.clone());
+
+        let k = cmp::min(self.min_samples, n);
+        let core = self.searcher
+            .search_many(inputs, k)?
+            .into_iter()
+            .map(|(_, dist)| dist[k - 1])
+            .collect::<Vec<_>>();
+
+        let edges = mutual_reachability_mst(inputs, &core, self.searcher.metric());
+        let merges = single_linkage(n, edges);
+        let tree = condense_tree(&merges, n, self.min_cluster_size);
+
+        let (clusters, outlier_scores) = {
+            let hierarchy = ClusterHierarchy::new(&tree, n);
+            let selected = hierarchy.select_clusters();
+            (hierarchy.label_points(&selected), hierarchy.outlier_scores())
+        };
+
+        self.core_distances = Some(Vector::new(core));
+        self.condensed_tree = Some(tree);
+        self.clusters = Some(Vector::new(clusters));
+        self.outlier_scores = Some(Vector::new(outlier_scores));
+
+        Ok(())
+    }
+
+    /// Predict the clusters of new points.
+    ///
+    /// Each point is given the cluster of the closest training point
+    /// if it lies within the core distance of that point, otherwise it
+    /// is noise. This approximates the clustering which would be found
+    /// by training with the new points.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<Option<usize>>> {
+        if let (&Some(ref clusters), &Some(ref core)) = (&self.clusters, &self.core_distances) {
+            let classes = self.searcher
+                .search_many(inputs, 1)?
+                .into_iter()
+                .map(|(idx, dist)| if dist[0] <= core[idx[0]] { clusters[idx[0]] } else { None })
+                .collect::<Vec<_>>();
+
+            Ok(Vector::new(classes))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+impl HDBSCAN {
+    /// Create a new HDBSCAN model with a given minimum cluster size.
+    ///
+    /// The number of samples used for core distances defaults to the
+    /// minimum cluster size, and distances are measured with the
+    /// Euclidean metric.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::hdbscan::HDBSCAN;
+    ///
+    /// let _ = HDBSCAN::new(10);
+    /// ```
+    pub fn new(min_cluster_size: usize) -> HDBSCAN {
+        HDBSCAN::with_metric(min_cluster_size, Euclidean)
+    }
+
+    /// Create a new HDBSCAN model with a given minimum
+    /// cluster size and distance metric.
+    ///
+    /// Neighbours are found with a `BallTree`, or by brute force for
+    /// distances which are not true metrics (like `Cosine`) as the tree
+    /// pruning would miss neighbours.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::hdbscan::HDBSCAN;
+    /// use rusty_machine::learning::toolkit::distance::Manhattan;
+    ///
+    /// let _ = HDBSCAN::with_metric(10, Manhattan);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `min_cluster_size` is less than 2.
+    pub fn with_metric<M: Metric + 'static>(min_cluster_size: usize, metric: M) -> HDBSCAN {
+        assert!(min_cluster_size >= 2, "The minimum cluster size must be at least 2.");
+
+        HDBSCAN {
+            min_cluster_size: min_cluster_size,
+            min_samples: min_cluster_size,
+            searcher: MetricSearch::with_metric(metric),
+            core_distances: None,
+            condensed_tree: None,
+            clusters: None,
+            outlier_scores: None,
+        }
+    }
+
+    /// Set the number of samples used to compute core distances.
+    ///
+    /// Larger values make the clustering more conservative,
+    /// with more points declared as noise.
+    ///
+    /// # Panics
+    ///
+    /// - `min_samples` is zero.
+    pub fn set_min_samples(&mut self, min_samples: usize) {
+        assert!(min_samples > 0, "The number of samples must be positive.");
+        self.min_samples = min_samples;
+    }
+
+    /// Return an Option pointing to the model clusters.
+    pub fn clusters(&self) -> Option<&Vector<Option<usize>>> {
+        self.clusters.as_ref()
+    }
+
+    /// The core distance of each training point.
+    pub fn core_distances(&self) -> Option<&Vector<f64>> {
+        self.core_distances.as_ref()
+    }
+
+    /// The condensed cluster tree of the training points.
+    pub fn condensed_tree(&self) -> Option<&[CondensedEdge]> {
+        self.condensed_tree.as_ref().map(|t| &t[..])
+    }
+
+    /// The GLOSH outlier score of each training point.
+    ///
+    /// The score compares the distance at which a point leaves its
+    /// cluster with the distance at which the densest points of the
+    /// cluster do, and lies between 0 and 1.
+    pub fn outlier_scores(&self) -> Option<&Vector<f64>> {
+        self.outlier_scores.as_ref()
+    }
+}
+
+/// Compute the minimum spanning tree under the mutual reachability
+/// distance using Prim's algorithm.
+///
+/// Returns the edges as pairs of points and their distance.
+fn mutual_reachability_mst(inputs: &Matrix<f64>,
+                           core: &[f64],
+                           metric: &dyn Metric)
+                           -> Vec<(usize, usize, f64)> {
+    let n = inputs.rows();
+    let mut in_tree = vec![false; n];
+    let mut best = vec![f64::INFINITY; n];
+    let mut from = vec![0; n];
+    let mut edges = Vec::with_capacity(n - 1);
+
+    let mut current = 0;
+    in_tree[current] = true;
+
+    for _ in 1..n {
+        let row = inputs.row(current);
+        let point = row.raw_slice();
+        for j in (0..n).filter(|&j| !in_tree[j]) {
+            let d = metric.distance(point, inputs.row(j).raw_slice())
+                .max(core[current])
+                .max(core[j]);
+            if d < best[j] {
+                best[j] = d;
+                from[j] = current;
+            }
+        }
+
+        let next = (0..n)
+            .filter(|&j| !in_tree[j])
+            .fold(None, |b: Option<usize>, j| match b {
+                Some(b) if best[b] <= best[j] => Some(b),
+                _ => Some(j),
+            })
+            .expect("There are points outside the tree.");
+
+        in_tree[next] = true;
+        edges.push((from[next], next, best[next]));
+        current = next;
+    }
+
+    edges
+}
+
+/// Find the root of a node, halving the path on the way.
+fn find_root(parents: &mut [usize], mut node: usize) -> usize {
+    while parents[node] != node {
+        parents[node] = parents[parents[node]];
+        node = parents[node];
+    }
+    node
+}
+
+/// Build the single linkage hierarchy from the spanning tree edges.
+///
+/// The points are the nodes `0..n` and the `i`th merge creates node
+/// `n + i`. Returns the merged nodes, distance and size of each merge.
+fn single_linkage(n: usize,
+                  mut edges: Vec<(usize, usize, f64)>)
+                  -> Vec<(usize, usize, f64, usize)> {
+    edges.sort_by(|a, b| a.2.partial_cmp(&b.2).expect("Distances must be comparable."));
+
+    let mut parents = (0..2 * n - 1).collect::<Vec<_>>();
+    let mut sizes = vec![1; 2 * n - 1];
+    let mut merges = Vec::with_capacity(n - 1);
+
+    for (i, (a, b, dist)) in edges.into_iter().enumerate() {
+        let (ra, rb) = (find_root(&mut parents, a), find_root(&mut parents, b));
+        let node = n + i;
+
+        parents[ra] = node;
+        parents[rb] = node;
+        sizes[node] = sizes[ra] + sizes[rb];
+        merges.push((ra, rb, dist, sizes[node]));
+    }
+
+    merges
+}
+
+/// Collect the points below a node of the single linkage hierarchy.
+fn leaves(merges: &[(usize, usize, f64, usize)], n: usize, node: usize) -> Vec<usize> {
+    let mut stack = vec![node];
+    let mut points = Vec::new();
+
+    while let Some(node) = stack.pop() {
+        if node < n {
+            points.push(node);
+        } else {
+            stack.push(merges[node - n].0);
+            stack.push(merges[node - n].1);
+        }
+    }
+
+    points
+}
+
+/// Condense the single linkage hierarchy.
+///
+/// Splits in which both sides have at least `min_cluster_size` points
+/// create two new clusters. Otherwise the smaller sides fall out of
+/// the cluster as individual points.
+fn condense_tree(merges: &[(usize, usize, f64, usize)],
+                 n: usize,
+                 min_cluster_size: usize)
+                 -> Vec<CondensedEdge> {
+    let mut tree = Vec::new();
+    if n < 2 {
+        return tree;
+    }
+
+    let size = |node: usize| if node < n { 1 } else { merges[node - n].3 };
+
+    let mut labels = vec![0; 2 * n - 1];
+    labels[2 * n - 2] = n;
+    let mut next_label = n + 1;
+
+    let mut queue = VecDeque::new();
+    queue.push_back(2 * n - 2);
+
+    while let Some(node) = queue.pop_front() {
+        let (left, right, dist, _) = merges[node - n];
+        let lambda = if dist > 0f64 { 1f64 / dist } else { f64::INFINITY };
+        let parent = labels[node];
+
+        let (left_big, right_big) = (size(left) >= min_cluster_size,
+                                     size(right) >= min_cluster_size);
+
+        for &(child, big) in &[(left, left_big), (right, right_big)] {
+            if left_big && right_big {
+                labels[child] = next_label;
+                next_label += 1;
+                tree.push(CondensedEdge {
+                    parent: parent,
+                    child: labels[child],
+                    lambda: lambda,
+                    size: size(child),
+                });
+                queue.push_back(child);
+            } else if big {
+                // The cluster continues through its larger side.
+                labels[child] = parent;
+                queue.push_back(child);
+            } else {
+                for point in leaves(merges, n, child) {
+                    tree.push(CondensedEdge {
+                        parent: parent,
+                        child: point,
+                        lambda: lambda,
+                        size: 1,
+                    });
+                }
+            }
+        }
+    }
+
+    tree
+}
+
+/// The difference between two lambdas, which is zero if both are infinite.
+fn lambda_diff(a: f64, b: f64) -> f64 {
+    if a == b { 0f64 } else { a - b }
+}
+
+/// The clusters of a condensed tree, indexed from the root.
+struct ClusterHierarchy<'a> {
+    tree: &'a [CondensedEdge],
+    n: usize,
+    // The parent of each cluster, the root is its own parent.
+    parents: Vec<usize>,
+    children: Vec<Vec<usize>>,
+    stability: Vec<f64>,
+}
+
+impl<'a> ClusterHierarchy<'a> {
+    fn new(tree: &'a [CondensedEdge], n: usize) -> ClusterHierarchy<'a> {
+        // Child clusters are labelled after their parents.
+        let count = tree.iter()
+            .map(|e| if e.size > 1 { e.child } else { e.parent })
+            .max()
+            .map_or(1, |label| label - n + 1);
+
+        let mut parents = (0..count).collect::<Vec<_>>();
+        let mut children = vec![Vec::new(); count];
+        let mut birth = vec![0f64; count];
+
+        for e in tree.iter().filter(|e| e.size > 1) {
+            parents[e.child - n] = e.parent - n;
+            children[e.parent - n].push(e.child - n);
+            birth[e.child - n] = e.lambda;
+        }
+
+        let mut stability = vec![0f64; count];
+        for e in tree {
+            let c = e.parent - n;
+            stability[c] += lambda_diff(e.lambda, birth[c]) * e.size as f64;
+        }
+
+        ClusterHierarchy {
+            tree: tree,
+            n: n,
+            parents: parents,
+            children: children,
+            stability: stability,
+        }
+    }
+
+    /// Select the clusters maximizing the total stability,
+    /// excluding the root.
+    fn select_clusters(&self) -> Vec<bool> {
+        let count = self.parents.len();
+        let mut stability = self.stability.clone();
+        let mut selected = vec![false; count];
+
+        // Children are labelled after their parents.
+        for c in (1..count).rev() {
+            let child_stability = self.children[c].iter().fold(0f64, |s, &ch| s + stability[ch]);
+
+            if self.children[c].is_empty() || stability[c] >= child_stability {
+                selected[c] = true;
+
+                let mut stack = self.children[c].clone();
+                while let Some(d) = stack.pop() {
+                    selected[d] = false;
+                    stack.extend(self.children[d].iter().cloned());
+                }
+            } else {
+                stability[c] = child_stability;
+            }
+        }
+
+        selected
+    }
+
+    /// Label each point with the selected cluster containing it.
+    fn label_points(&self, selected: &[bool]) -> Vec<Option<usize>> {
+        let mut label_of = vec![None; selected.len()];
+        let mut next_label = 0;
+        for (c, _) in selected.iter().enumerate().filter(|&(_, &s)| s) {
+            label_of[c] = Some(next_label);
+            next_label += 1;
+        }
+
+        let mut labels = vec![None; self.n];
+        for e in self.tree.iter().filter(|e| e.size == 1) {
+            let mut c = e.parent - self.n;
+            loop {
+                if selected[c] {
+                    labels[e.child] = label_of[c];
+                    break;
+                }
+                if self.parents[c] == c {
+                    break;
+                }
+                c = self.parents[c];
+            }
+        }
+
+        labels
+    }
+
+    /// Compute the GLOSH outlier score of each point.
+    fn outlier_scores(&self) -> Vec<f64> {
+        // The largest lambda at which points leave each cluster or its descendants.
+        let mut max_lambda = vec![0f64; self.parents.len()];
+        for e in self.tree.iter().filter(|e| e.size == 1) {
+            let c = e.parent - self.n;
+            max_lambda[c] = max_lambda[c].max(e.lambda);
+        }
+        for c in (1..self.parents.len()).rev() {
+            let p = self.parents[c];
+            max_lambda[p] = max_lambda[p].max(max_lambda[c]);
+        }
+
+        let mut scores = vec![0f64; self.n];
+        for e in self.tree.iter().filter(|e| e.size == 1) {
+            let max = max_lambda[e.parent - self.n];
+            scores[e.child] = if max == 0f64 || e.lambda == max {
+                0f64
+            } else if max.is_infinite() {
+                1f64
+            } else {
+                (max - e.lambda) / max
+            };
+        }
+
+        scores
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HDBSCAN, CondensedEdge, ClusterHierarchy, single_linkage, condense_tree, leaves};
+    use learning::knn::MetricSearch;
+    use learning::toolkit::distance::{Cosine, Euclidean};
+
+    #[test]
+    fn test_single_linkage() {
+        let edges = vec![(0, 1, 1.0), (2, 3, 0.5), (1, 2, 3.0)];
+        let merges = single_linkage(4, edges);
+
+        assert_eq!(merges, vec![(2, 3, 0.5, 2), (0, 1, 1.0, 2), (5, 4, 3.0, 4)]);
+
+        let mut points = leaves(&merges, 4, 6);
+        points.sort();
+        assert_eq!(points, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_condense_tree() {
+        // Two pairs which split at distance 4, with point 4 joining at 5.
+        let edges = vec![(0, 1, 1.0), (2, 3, 2.0), (1, 2, 4.0), (3, 4, 5.0)];
+        let merges = single_linkage(5, edges);
+        let tree = condense_tree(&merges, 5, 2);
+
+        assert_eq!(tree,
+                   vec![CondensedEdge { parent: 5, child: 4, lambda: 0.2, size: 1 },
+                        CondensedEdge { parent: 5, child: 6, lambda: 0.25, size: 2 },
+                        CondensedEdge { parent: 5, child: 7, lambda: 0.25, size: 2 },
+                        CondensedEdge { parent: 6, child: 0, lambda: 1.0, size: 1 },
+                        CondensedEdge { parent: 6, child: 1, lambda: 1.0, size: 1 },
+                        CondensedEdge { parent: 7, child: 2, lambda: 0.5, size: 1 },
+                        CondensedEdge { parent: 7, child: 3, lambda: 0.5, size: 1 }]);
+    }
+
+    #[test]
+    fn test_select_and_label() {
+        let edges = vec![(0, 1, 1.0), (2, 3, 2.0), (1, 2, 4.0), (3, 4, 5.0)];
+        let merges = single_linkage(5, edges);
+        let tree = condense_tree(&merges, 5, 2);
+        let hierarchy = ClusterHierarchy::new(&tree, 5);
+
+        // (1 - 0.25) * 2 and (0.5 - 0.25) * 2
+        assert!((hierarchy.stability[1] - 1.5).abs() < 1e-12);
+        assert!((hierarchy.stability[2] - 0.5).abs() < 1e-12);
+
+        let selected = hierarchy.select_clusters();
+        assert_eq!(selected, vec![false, true, true]);
+        assert_eq!(hierarchy.label_points(&selected),
+                   vec![Some(0), Some(0), Some(1), Some(1), None]);
+
+        let scores = hierarchy.outlier_scores();
+        assert_eq!(scores[0], 0.);
+        assert_eq!(scores[2], 0.);
+        assert!((scores[4] - 0.8).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_infinite_lambdas() {
+        // Duplicate points merge at distance zero.
+        let edges = vec![(0, 1, 0.0), (1, 2, 0.0), (2, 3, 1.0)];
+        let merges = single_linkage(4, edges);
+        let tree = condense_tree(&merges, 4, 2);
+        let hierarchy = ClusterHierarchy::new(&tree, 4);
+
+        assert!(hierarchy.stability.iter().all(|s| !s.is_nan()));
+        let scores = hierarchy.outlier_scores();
+        assert_eq!(scores[0], 0.);
+        assert_eq!(scores[3], 1.);
+        assert!(scores.iter().all(|s| !s.is_nan()));
+    }
+
+    #[test]
+    fn test_non_metric_uses_brute_force() {
+        match HDBSCAN::with_metric(2, Cosine).searcher {
+            MetricSearch::Brute(_) => {}
+            MetricSearch::Tree(_) => panic!("Expected a brute force search."),
+        }
+        match HDBSCAN::with_metric(2, Euclidean).searcher {
+            MetricSearch::Tree(_) => {}
+            MetricSearch::Brute(_) => panic!("Expected a ball tree search."),
+        }
+    }
+}
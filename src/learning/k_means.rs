@@ -23,7 +23,9 @@
 //! ```
 //!
 //! Additionally you can control the initialization
-//! algorithm and max number of iterations.
+//! algorithm, max number of iterations, convergence
+//! tolerance, number of restarts and the distance metric
+//! used to assign points to centroids.
 //!
 //! # Initializations
 //!
@@ -43,12 +45,14 @@
 //! The [k-means++](https://en.wikipedia.org/wiki/K-means%2B%2B) scheme.
 
 use linalg::{Matrix, MatrixSlice, Axes, Vector, BaseMatrix};
+use rulinalg::utils;
 use learning::{LearningResult, UnSupModel};
 use learning::error::{Error, ErrorKind};
+use learning::toolkit::distance::{Metric, Euclidean};
 
 use rand::{Rng, thread_rng};
-use libnum::abs;
 
+use std::f64;
 use std::fmt::Debug;
 
 /// K-Means Classification model.
@@ -70,8 +74,18 @@ pub struct KMeansClassifier<InitAlg: Initializer> {
     iters: usize,
     /// The number of classes.
     k: usize,
+    /// The relative change in inertia at which training stops.
+    tol: f64,
+    /// The number of initializations to try.
+    n_init: usize,
+    /// The metric used to assign points to centroids.
+    metric: Box<dyn Metric>,
     /// The fitted centroids .
     centroids: Option<Matrix<f64>>,
+    /// The inertia of the fitted centroids.
+    inertia: Option<f64>,
+    /// The number of iterations run for the fitted centroids.
+    iterations: Option<usize>,
     /// The initial algorithm to use.
     init_algorithm: InitAlg,
 }
@@ -82,30 +96,38 @@ impl<InitAlg: Initializer> UnSupModel<Matrix<f64>, Vector<usize>> for KMeansClas
     /// Model must be trained.
     fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<usize>> {
         if let Some(ref centroids) = self.centroids {
-            Ok(KMeansClassifier::<InitAlg>::find_closest_centroids(centroids.as_slice(), inputs).0)
+            Ok(KMeansClassifier::<InitAlg>::find_closest_centroids(centroids.as_slice(),
+                                                                    inputs,
+                                                                    &*self.metric)
+                .0)
         } else {
             Err(Error::new_untrained())
         }
     }
 
     /// Train the classifier using input data.
+    ///
+    /// The algorithm is run from `n_init` initializations and the
+    /// centroids with the lowest inertia are kept.
     fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
-        self.init_centroids(inputs)?;
-        let mut cost = 0.0;
-        let eps = 1e-14;
+        let mut best: Option<(f64, usize, Matrix<f64>)> = None;
 
-        for _i in 0..self.iters {
-            let (idx, distances) = self.get_closest_centroids(inputs)?;
-            self.update_centroids(inputs, idx);
+        for _ in 0..self.n_init {
+            self.init_centroids(inputs)?;
+            let iterations = self.run_iterations(inputs)?;
+            let inertia = self.get_closest_centroids(inputs)?.1.sum();
 
-            let cost_i = distances.sum();
-            if abs(cost - cost_i) < eps {
-                break;
+            if best.as_ref().map_or(true, |&(b, _, _)| inertia < b) {
+                let centroids = self.centroids.take().expect("Centroids were initialized.");
+                best = Some((inertia, iterations, centroids));
             }
-
-            cost = cost_i;
         }
 
+        let (inertia, iterations, centroids) = best.expect("There is at least one initialization.");
+        self.centroids = Some(centroids);
+        self.inertia = Some(inertia);
+        self.iterations = Some(iterations);
+
         Ok(())
     }
 }
@@ -114,7 +136,9 @@ impl KMeansClassifier<KPlusPlus> {
     /// Constructs untrained k-means classifier model.
     ///
     /// Requires number of classes to be specified.
-    /// Defaults to 100 iterations and kmeans++ initialization.
+    /// Defaults to 100 iterations, a relative tolerance of 1e-4,
+    /// a single initialization, the Euclidean metric and kmeans++
+    /// initialization.
     ///
     /// # Examples
     ///
@@ -124,12 +148,7 @@ impl KMeansClassifier<KPlusPlus> {
     /// let model = KMeansClassifier::new(5);
     /// ```
     pub fn new(k: usize) -> KMeansClassifier<KPlusPlus> {
-        KMeansClassifier {
-            iters: 100,
-            k: k,
-            centroids: None,
-            init_algorithm: KPlusPlus,
-        }
+        KMeansClassifier::new_specified(k, 100, KPlusPlus)
     }
 }
 
@@ -139,6 +158,9 @@ impl<InitAlg: Initializer> KMeansClassifier<InitAlg> {
     /// Requires number of classes, number of iterations, and
     /// the initialization algorithm to use.
     ///
+    /// Defaults to a relative tolerance of 1e-4, a single initialization
+    /// and the Euclidean metric.
+    ///
     /// # Examples
     ///
     /// ```
@@ -150,7 +172,12 @@ impl<InitAlg: Initializer> KMeansClassifier<InitAlg> {
         KMeansClassifier {
             iters: iters,
             k: k,
+            tol: 1e-4,
+            n_init: 1,
+            metric: Box::new(Euclidean),
             centroids: None,
+            inertia: None,
+            iterations: None,
             init_algorithm: algo,
         }
     }
@@ -175,11 +202,123 @@ impl<InitAlg: Initializer> KMeansClassifier<InitAlg> {
         &self.centroids
     }
 
+    /// Get the relative convergence tolerance.
+    pub fn tol(&self) -> f64 {
+        self.tol
+    }
+
+    /// Get the number of initializations.
+    pub fn n_init(&self) -> usize {
+        self.n_init
+    }
+
+    /// Get the metric used to assign points to centroids.
+    pub fn metric(&self) -> &dyn Metric {
+        &*self.metric
+    }
+
+    /// Get the inertia of the fitted centroids.
+    ///
+    /// This is the sum of squared distances, under the model's
+    /// metric, from each training point to its closest centroid.
+    pub fn inertia(&self) -> Option<f64> {
+        self.inertia
+    }
+
+    /// Get the number of iterations run for the fitted centroids.
+    pub fn iterations(&self) -> Option<usize> {
+        self.iterations
+    }
+
     /// Set the number of iterations.
     pub fn set_iters(&mut self, iters: usize) {
         self.iters = iters;
     }
 
+    /// Set the relative convergence tolerance.
+    ///
+    /// Training stops when the inertia changes by less than
+    /// this fraction between iterations.
+    ///
+    /// # Panics
+    ///
+    /// - `tol` is negative.
+    pub fn set_tol(&mut self, tol: f64) {
+        assert!(tol >= 0f64, "The tolerance must be non-negative.");
+        self.tol = tol;
+    }
+
+    /// Set the number of initializations.
+    ///
+    /// The model is trained from each initialization and the
+    /// centroids with the lowest inertia are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::linalg::Matrix;
+    /// use rusty_machine::learning::k_means::KMeansClassifier;
+    /// use rusty_machine::learning::UnSupModel;
+    ///
+    /// let inputs = Matrix::new(4, 1, vec![0.0, 0.5, 5.0, 5.5]);
+    ///
+    /// let mut model = KMeansClassifier::new(2);
+    /// model.set_n_init(10);
+    /// model.train(&inputs).unwrap();
+    ///
+    /// assert!((model.inertia().unwrap() - 0.25).abs() < 1e-12);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `n_init` is zero.
+    pub fn set_n_init(&mut self, n_init: usize) {
+        assert!(n_init > 0, "There must be at least one initialization.");
+        self.n_init = n_init;
+    }
+
+    /// Set the metric used to assign points to centroids.
+    ///
+    /// The centroids are still updated to the mean of their assigned
+    /// points, which only minimizes the inertia for the Euclidean metric.
+    /// The k-means++ initialization always uses Euclidean distances.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::k_means::KMeansClassifier;
+    /// use rusty_machine::learning::toolkit::distance::Manhattan;
+    ///
+    /// let mut model = KMeansClassifier::new(3);
+    /// model.set_metric(Manhattan);
+    /// ```
+    pub fn set_metric<M: Metric + 'static>(&mut self, metric: M) {
+        self.metric = Box::new(metric);
+    }
+
+    /// Run the iterations of the algorithm from the current centroids.
+    ///
+    /// Returns the number of iterations run.
+    fn run_iterations(&mut self, inputs: &Matrix<f64>) -> LearningResult<usize> {
+        let mut cost = f64::INFINITY;
+        let mut iterations = 0;
+
+        while iterations < self.iters {
+            let (idx, distances) = self.get_closest_centroids(inputs)?;
+            self.update_centroids(inputs, idx);
+            iterations += 1;
+
+            let cost_i = distances.sum();
+            if (cost - cost_i).abs() <= self.tol * cost_i {
+                break;
+            }
+
+            cost = cost_i;
+        }
+
+        Ok(iterations)
+    }
+
     /// Initialize the centroids.
     ///
     /// Used internally within model.
@@ -209,6 +348,8 @@ impl<InitAlg: Initializer> KMeansClassifier<InitAlg> {
 
     /// Updated the centroids by computing means of assigned classes.
     ///
+    /// A centroid with no assigned points is left where it is.
+    ///
     /// Used internally within model.
     fn update_centroids(&mut self, inputs: &Matrix<f64>, classes: Vector<usize>) {
         let mut new_centroids = Vec::with_capacity(self.k * inputs.cols());
@@ -218,9 +359,14 @@ impl<InitAlg: Initializer> KMeansClassifier<InitAlg> {
             row_indexes.get_mut(c as usize).map(|v| v.push(i));
         }
 
-        for vec_i in row_indexes {
-            let mat_i = inputs.select_rows(&vec_i);
-            new_centroids.extend(mat_i.mean(Axes::Row).into_vec());
+        let old_centroids = self.centroids.as_ref().expect("Centroids were initialized.");
+        for (vec_i, old_centroid) in row_indexes.into_iter().zip(old_centroids.row_iter()) {
+            if vec_i.is_empty() {
+                new_centroids.extend_from_slice(old_centroid.raw_slice());
+            } else {
+                let mat_i = inputs.select_rows(&vec_i);
+                new_centroids.extend(mat_i.mean(Axes::Row).into_vec());
+            }
         }
 
         self.centroids = Some(Matrix::new(self.k, inputs.cols(), new_centroids));
@@ -230,7 +376,9 @@ impl<InitAlg: Initializer> KMeansClassifier<InitAlg> {
                              inputs: &Matrix<f64>)
                              -> LearningResult<(Vector<usize>, Vector<f64>)> {
         if let Some(ref c) = self.centroids {
-            Ok(KMeansClassifier::<InitAlg>::find_closest_centroids(c.as_slice(), inputs))
+            Ok(KMeansClassifier::<InitAlg>::find_closest_centroids(c.as_slice(),
+                                                                    inputs,
+                                                                    &*self.metric))
         } else {
             Err(Error::new(ErrorKind::InvalidState,
                            "Centroids not correctly initialized."))
@@ -240,20 +388,24 @@ impl<InitAlg: Initializer> KMeansClassifier<InitAlg> {
     /// Find the centroid closest to each data point.
     ///
     /// Used internally within model.
-    /// Returns the index of the closest centroid and the distance to it.
+    /// Returns the index of the closest centroid and the squared distance to it.
     fn find_closest_centroids(centroids: MatrixSlice<f64>,
-                              inputs: &Matrix<f64>)
+                              inputs: &Matrix<f64>,
+                              metric: &dyn Metric)
                               -> (Vector<usize>, Vector<f64>) {
         let mut idx = Vec::with_capacity(inputs.rows());
         let mut distances = Vec::with_capacity(inputs.rows());
 
-        for i in 0..inputs.rows() {
-            // This works like repmat pulling out row i repeatedly.
-            let centroid_diff = centroids - inputs.select_rows(&vec![i; centroids.rows()]);
-            let dist = &centroid_diff.elemul(&centroid_diff).sum_cols();
+        for point in inputs.row_iter() {
+            let dist = centroids.row_iter()
+                .map(|c| {
+                    let d = metric.distance(point.raw_slice(), c.raw_slice());
+                    d * d
+                })
+                .collect::<Vec<_>>();
 
             // Now take argmin and this is the centroid.
-            let (min_idx, min_dist) = dist.argmin();
+            let (min_idx, min_dist) = utils::argmin(&dist);
             idx.push(min_idx);
             distances.push(min_dist);
         }
@@ -340,7 +492,9 @@ impl Initializer for KPlusPlus {
                                                                  inputs.cols(),
                                                                  inputs.cols());
                 let (_, dist) =
-                    KMeansClassifier::<KPlusPlus>::find_closest_centroids(temp_centroids, inputs);
+                    KMeansClassifier::<KPlusPlus>::find_closest_centroids(temp_centroids,
+                                                                          inputs,
+                                                                          &Euclidean);
 
                 // A relatively cheap way to validate our input data
                 if !dist.data().iter().all(|x| x.is_finite()) {
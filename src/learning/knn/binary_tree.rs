@@ -4,14 +4,17 @@ use std::collections::VecDeque;
 
 use linalg::{Matrix, BaseMatrix, Vector};
 use learning::error::Error;
+use learning::toolkit::distance::{Metric, MinkowskiMetric, Euclidean};
 
-use super::{KNearest, KNearestSearch, get_distances, dist};
+use super::{KNearest, KNearestSearch, get_distances, sorted_pairs};
 
 /// Binary tree
 #[derive(Debug)]
 pub struct BinaryTree<B: BinarySplit> {
     // Binary tree leaf size
     leafsize: usize,
+    // Distance metric
+    metric: Box<dyn Metric>,
     // Search data
     data: Option<Matrix<f64>>,
     // Binary tree
@@ -20,7 +23,8 @@ pub struct BinaryTree<B: BinarySplit> {
 
 impl<B: BinarySplit> Default for BinaryTree<B> {
     /// Constructs default binary-tree (kd-tree or ball-tree) seach.
-    /// Each leaf contains 30 elements at maximum.
+    /// Each leaf contains 30 elements at maximum and the
+    /// Euclidean metric is used.
     ///
     /// # Examples
     ///
@@ -32,6 +36,7 @@ impl<B: BinarySplit> Default for BinaryTree<B> {
     fn default() -> Self {
         BinaryTree {
             leafsize: 30,
+            metric: Box::new(Euclidean),
             data: None,
             root: None
         }
@@ -44,7 +49,7 @@ pub trait BinarySplit: Sized {
     /// Build branch from passed args
     fn build(data: &Matrix<f64>, remains: Vec<usize>,
              dim: usize, split: f64, min: Vector<f64>, max: Vector<f64>,
-             left: Node<Self>, right: Node<Self>)
+             left: Node<Self>, right: Node<Self>, metric: &dyn Metric)
         -> Node<Self>;
 
     /// Return a tuple of left and right node. First node is likely to be
@@ -53,7 +58,7 @@ pub trait BinarySplit: Sized {
         -> (&'s Node<Self>, &'s Node<Self>);
 
     /// Return distance between the point and myself
-    fn dist(&self, point: &[f64]) -> f64;
+    fn dist(&self, point: &[f64], metric: &dyn Metric) -> f64;
 
     /// Return left node
     fn left(&self) -> &Node<Self>;
@@ -109,7 +114,7 @@ impl BinarySplit for KDTreeBranch {
 
     fn build(_: &Matrix<f64>, _: Vec<usize>,
              dim: usize, split: f64, min: Vector<f64>, max: Vector<f64>,
-             left: Node<Self>, right: Node<Self>) -> Node<Self> {
+             left: Node<Self>, right: Node<Self>, _: &dyn Metric) -> Node<Self> {
 
         let b = KDTreeBranch {
             dim: dim,
@@ -132,19 +137,24 @@ impl BinarySplit for KDTreeBranch {
         }
     }
 
-    fn dist(&self, point: &[f64]) -> f64 {
-        let mut d = 0.;
-        for ((&p, &mi), &ma) in point.iter()
+    fn dist(&self, point: &[f64], metric: &dyn Metric) -> f64 {
+        // closest point in the hyper-rectangle, this is only a lower bound
+        // of the distance to its contents for Minkowski metrics
+        let closest: Vec<f64> = point.iter()
                                      .zip(self.min.iter())
-                                     .zip(self.max.iter()) {
-            if p < mi {
-                d += (mi - p) * (mi - p);
-            } else if ma < p {
-                d += (ma - p) * (ma - p);
-            }
-            // otherwise included in the hyper-rectangle
-        }
-        d.sqrt()
+                                     .zip(self.max.iter())
+                                     .map(|((&p, &mi), &ma)| {
+                                         if p < mi {
+                                             mi
+                                         } else if ma < p {
+                                             ma
+                                         } else {
+                                             // included in the hyper-rectangle
+                                             p
+                                         }
+                                     })
+                                     .collect();
+        metric.distance(point, &closest)
     }
 
     fn left(&self) -> &Node<Self> {
@@ -160,7 +170,7 @@ impl BinarySplit for BallTreeBranch {
 
     fn build(data: &Matrix<f64>, remains: Vec<usize>,
              dim: usize, split: f64, _: Vector<f64>, _: Vector<f64>,
-             left: Node<Self>, right: Node<Self>) -> Node<Self> {
+             left: Node<Self>, right: Node<Self>, metric: &dyn Metric) -> Node<Self> {
 
         // calculate centroid (mean)
         // TODO: cleanup using .row()
@@ -178,7 +188,7 @@ impl BinarySplit for BallTreeBranch {
         let mut radius = 0.;
         for &i in &remains {
             let row: Vec<f64> = data.select_rows(&[i]).into_vec();
-            let d = dist(&center, &row);
+            let d = metric.distance(&center, &row);
             if d > radius {
                 radius = d;
             }
@@ -205,8 +215,8 @@ impl BinarySplit for BallTreeBranch {
         }
     }
 
-    fn dist(&self, point: &[f64]) -> f64 {
-        let d = dist(self.center.data(), point);
+    fn dist(&self, point: &[f64], metric: &dyn Metric) -> f64 {
+        let d = metric.distance(self.center.data(), point);
         if d < self.radius {
             0.
         } else {
@@ -261,11 +271,17 @@ impl<B: BinarySplit> BinaryTree<B> {
     pub fn new(leafsize: usize) -> Self {
         BinaryTree {
             leafsize: leafsize,
+            metric: Box::new(Euclidean),
             data: None,
             root: None
         }
     }
 
+    /// Return the metric used by the search
+    pub fn metric(&self) -> &dyn Metric {
+        &*self.metric
+    }
+
     /// Select next split dimension and value. Returns tuple with 6 elements
     /// - split dim
     /// - split value
@@ -273,9 +289,11 @@ impl<B: BinarySplit> BinaryTree<B> {
     /// - remains for right node
     /// - updated max for left node
     /// - updated min for right node
+    ///
+    /// Returns None if the remains can't be split, i.e. they are all identical.
     fn select_split(&self, data: &Matrix<f64>, mut remains: Vec<usize>,
                     mut dmin: Vector<f64>, mut dmax: Vector<f64>)
-        -> (usize, f64, Vec<usize>, Vec<usize>, Vector<f64>, Vector<f64>){
+        -> Option<(usize, f64, Vec<usize>, Vec<usize>, Vector<f64>, Vector<f64>)> {
 
         // avoid recursive call
         loop {
@@ -290,6 +308,11 @@ impl<B: BinarySplit> BinaryTree<B> {
                 dmin.data().get_unchecked(dim) + d / 2.0
             };
 
+            // the hyper-rectangle has shrunk to a point
+            if !(dmin[dim] < split && split < dmax[dim]) {
+                return None;
+            }
+
             // split remains
             let mut l_remains: Vec<usize> = Vec::with_capacity(remains.len());
             let mut r_remains: Vec<usize> = Vec::with_capacity(remains.len());
@@ -323,7 +346,7 @@ impl<B: BinarySplit> BinaryTree<B> {
                 let mut r_min = dmin.clone();
                 r_min[dim] = split;
 
-                return (dim, split, l_remains, r_remains, l_max, r_min);
+                return Some((dim, split, l_remains, r_remains, l_max, r_min));
             }
         };
     }
@@ -333,16 +356,17 @@ impl<B: BinarySplit> BinaryTree<B> {
              dmin: Vector<f64>, dmax: Vector<f64>) -> Node<B> {
 
         if remains.len() < self.leafsize {
-            Node::Leaf(Leaf::new(remains))
-        } else {
-
-            // ToDo: avoid this clone
-            let (dim, split, l_remains, r_remains, l_max, r_min) =
-                self.select_split(data, remains.clone(), dmin.clone(), dmax.clone());
+            return Node::Leaf(Leaf::new(remains));
+        }
 
-            let l_node = self.split(data, l_remains, dmin.clone(), l_max);
-            let g_node = self.split(data, r_remains, r_min, dmax.clone());
-            B::build(data, remains, dim, split, dmin, dmax, l_node, g_node)
+        // ToDo: avoid this clone
+        match self.select_split(data, remains.clone(), dmin.clone(), dmax.clone()) {
+            Some((dim, split, l_remains, r_remains, l_max, r_min)) => {
+                let l_node = self.split(data, l_remains, dmin.clone(), l_max);
+                let g_node = self.split(data, r_remains, r_min, dmax.clone());
+                B::build(data, remains, dim, split, dmin, dmax, l_node, g_node, &*self.metric)
+            },
+            None => Node::Leaf(Leaf::new(remains))
         }
     }
 
@@ -360,7 +384,7 @@ impl<B: BinarySplit> BinaryTree<B> {
                 let current: &Node<B> = queue.pop_front().unwrap();
                 match *current {
                     Node::Leaf(ref l) => {
-                        let distances = get_distances(data, point, &l.children);
+                        let distances = get_distances(data, point, &l.children, &*self.metric);
                         let kn = KNearest::new(k, l.children.clone(), distances);
                         return Ok((kn, queue));
                     },
@@ -382,6 +406,58 @@ impl<B: BinarySplit> BinaryTree<B> {
     }
 }
 
+impl BinaryTree<KDTreeBranch> {
+    /// Constructs kd-tree search using the specified metric.
+    ///
+    /// The kd-tree bounds distances to its hyper-rectangles, which
+    /// is only valid for metrics of the Minkowski family.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::knn::KDTree;
+    /// use rusty_machine::learning::toolkit::distance::Manhattan;
+    /// let _ = KDTree::with_metric(10, Manhattan);
+    /// ```
+    pub fn with_metric<M: MinkowskiMetric + 'static>(leafsize: usize, metric: M) -> Self {
+        BinaryTree {
+            leafsize: leafsize,
+            metric: Box::new(metric),
+            data: None,
+            root: None
+        }
+    }
+}
+
+impl BinaryTree<BallTreeBranch> {
+    /// Constructs ball-tree search using the specified metric.
+    ///
+    /// The search is exact for any metric satisfying the triangle inequality.
+    /// Other distances, like `Cosine`, should be searched with `BruteForce`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::knn::BallTree;
+    /// use rusty_machine::learning::toolkit::distance::Chebyshev;
+    /// let _ = BallTree::with_metric(10, Chebyshev);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - The metric does not satisfy the triangle inequality, see `Metric::is_true_metric`.
+    pub fn with_metric<M: Metric + 'static>(leafsize: usize, metric: M) -> Self {
+        assert!(metric.is_true_metric(),
+                "The ball tree requires a metric satisfying the triangle inequality.");
+        BinaryTree {
+            leafsize: leafsize,
+            metric: Box::new(metric),
+            data: None,
+            root: None
+        }
+    }
+}
+
 /// Can search k-nearest items
 impl<B: BinarySplit> KNearestSearch for BinaryTree<B> {
 
@@ -403,7 +479,7 @@ impl<B: BinarySplit> KNearestSearch for BinaryTree<B> {
 
                 match *current {
                     Node::Leaf(ref l) => {
-                        let distances = get_distances(data, point, &l.children);
+                        let distances = get_distances(data, point, &l.children, &*self.metric);
                         let mut current_dist = query.dist();
 
                         for (&i, d) in l.children.iter().zip(distances.into_iter()) {
@@ -413,7 +489,7 @@ impl<B: BinarySplit> KNearestSearch for BinaryTree<B> {
                         }
                     },
                     Node::Branch(ref b) => {
-                        let d = b.dist(point);
+                        let d = b.dist(point, &*self.metric);
                         if d < query.dist() {
                             queue.push_back(b.left());
                             queue.push_back(b.right());
@@ -426,6 +502,35 @@ impl<B: BinarySplit> KNearestSearch for BinaryTree<B> {
             Err(Error::new_untrained())
         }
     }
+
+    /// Search items within the radius of the point
+    fn search_radius(&self, point: &[f64], radius: f64) -> Result<(Vec<usize>, Vec<f64>), Error> {
+        if let (&Some(ref root), &Some(ref data)) = (&self.root, &self.data) {
+            let mut pairs: Vec<(usize, f64)> = Vec::new();
+            let mut stack: Vec<&Node<B>> = vec![root];
+
+            while let Some(current) = stack.pop() {
+                match *current {
+                    Node::Leaf(ref l) => {
+                        let distances = get_distances(data, point, &l.children, &*self.metric);
+                        pairs.extend(l.children.iter()
+                                               .cloned()
+                                               .zip(distances.into_iter())
+                                               .filter(|&(_, d)| d < radius));
+                    },
+                    Node::Branch(ref b) => {
+                        if b.dist(point, &*self.metric) < radius {
+                            stack.push(b.left());
+                            stack.push(b.right());
+                        }
+                    }
+                }
+            }
+            Ok(sorted_pairs(pairs))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
 }
 
 
@@ -468,7 +573,7 @@ fn max(data: &Matrix<f64>) -> Vector<f64> {
 #[cfg(test)]
 mod tests {
 
-    use linalg::{Vector, Matrix, BaseMatrix};
+    use linalg::{Vector, Matrix};
     use super::super::KNearestSearch;
     use super::{KDTree, BallTree, min, max};
 
@@ -671,6 +776,45 @@ mod tests {
         assert_eq!(blr.children, vec![1, 3]);
     }
 
+    #[test]
+    fn test_kdtree_search_radius() {
+        let m = Matrix::new(5, 2, vec![1., 2.,
+                                       8., 0.,
+                                       6., 10.,
+                                       3., 6.,
+                                       0., 3.]);
+        let mut tree = KDTree::new(3);
+        tree.build(m);
+
+        let (ind, dist) = tree.search_radius(&vec![3., 4.9], 3.6).unwrap();
+        assert_eq!(ind, vec![3, 0, 4]);
+        assert_eq!(dist, vec![1.0999999999999996, 3.5227829907617076, 3.551056180912941]);
+
+        let (ind, _) = tree.search_radius(&vec![3., 4.9], 1.).unwrap();
+        assert!(ind.is_empty());
+
+        let (ind, _) = tree.search_radius(&vec![3., 4.9], 100.).unwrap();
+        assert_eq!(ind, vec![3, 0, 4, 2, 1]);
+    }
+
+    #[test]
+    fn test_kdtree_duplicates() {
+        let m = Matrix::new(5, 2, vec![1., 2.,
+                                       1., 2.,
+                                       1., 2.,
+                                       1., 2.,
+                                       3., 2.]);
+        let mut tree = KDTree::new(2);
+        tree.build(m);
+
+        let (ind, dist) = tree.search(&vec![1., 2.], 2).unwrap();
+        assert_eq!(ind.len(), 2);
+        assert_eq!(dist, vec![0., 0.]);
+
+        let (ind, _) = tree.search_radius(&vec![1., 2.], 1.).unwrap();
+        assert_eq!(ind, vec![0, 1, 2, 3]);
+    }
+
     #[test]
     fn test_kdtree_untrained() {
         let tree = KDTree::default();
@@ -680,6 +824,9 @@ mod tests {
 
         let e = tree.search(&vec![3., 4.9], 1);
         assert!(e.is_err());
+
+        let e = tree.search_radius(&vec![3., 4.9], 1.);
+        assert!(e.is_err());
     }
 
     #[test]
@@ -816,6 +963,24 @@ mod tests {
         assert_eq!(blr.children, vec![1, 3]);
     }
 
+    #[test]
+    fn test_balltree_search_radius() {
+        let m = Matrix::new(5, 2, vec![1., 2.,
+                                       8., 0.,
+                                       6., 10.,
+                                       3., 6.,
+                                       0., 3.]);
+        let mut tree = BallTree::new(3);
+        tree.build(m);
+
+        let (ind, dist) = tree.search_radius(&vec![3., 4.9], 3.6).unwrap();
+        assert_eq!(ind, vec![3, 0, 4]);
+        assert_eq!(dist, vec![1.0999999999999996, 3.5227829907617076, 3.551056180912941]);
+
+        let (ind, _) = tree.search_radius(&vec![3., 4.9], 1.).unwrap();
+        assert!(ind.is_empty());
+    }
+
     #[test]
     fn test_balltree_untrained() {
         let tree = BallTree::default();
@@ -825,6 +990,9 @@ mod tests {
 
         let e = tree.search(&vec![3., 4.9], 1);
         assert!(e.is_err());
+
+        let e = tree.search_radius(&vec![3., 4.9], 1.);
+        assert!(e.is_err());
     }
 
     #[test]
@@ -835,4 +1003,11 @@ mod tests {
         assert_eq!(min(&data), Vector::new(vec![1., 1.]));
         assert_eq!(max(&data), Vector::new(vec![3., 4.]));
     }
+
+    #[test]
+    #[should_panic]
+    fn test_ball_tree_non_metric() {
+        use learning::toolkit::distance::Cosine;
+        let _ = BallTree::with_metric(10, Cosine);
+    }
 }
@@ -1,17 +1,21 @@
 //! Bruteforce search implementations
+use std::cmp;
+
 use linalg::{Matrix, BaseMatrix};
 use learning::error::Error;
+use learning::toolkit::distance::{Metric, Euclidean};
 
-use super::{KNearest, KNearestSearch, get_distances, dist};
+use super::{KNearest, KNearestSearch, get_distances, sorted_pairs};
 
 /// Perform brute-force search
 #[derive(Debug)]
 pub struct BruteForce {
+    metric: Box<dyn Metric>,
     data: Option<Matrix<f64>>,
 }
 
 impl Default for BruteForce {
-    /// Constructs new brute-force search
+    /// Constructs new brute-force search using the Euclidean metric
     ///
     /// # Examples
     ///
@@ -21,6 +25,7 @@ impl Default for BruteForce {
     /// ```
     fn default() -> Self {
         BruteForce {
+            metric: Box::new(Euclidean),
             data: None
         }
     }
@@ -39,6 +44,27 @@ impl BruteForce {
     pub fn new() -> Self {
         BruteForce::default()
     }
+
+    /// Constructs new brute-force search using the specified metric.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::knn::BruteForce;
+    /// use rusty_machine::learning::toolkit::distance::Chebyshev;
+    /// let _ = BruteForce::with_metric(Chebyshev);
+    /// ```
+    pub fn with_metric<M: Metric + 'static>(metric: M) -> Self {
+        BruteForce {
+            metric: Box::new(metric),
+            data: None
+        }
+    }
+
+    /// Return the metric used by the search
+    pub fn metric(&self) -> &dyn Metric {
+        &*self.metric
+    }
 }
 
 /// Can search K-nearest items
@@ -52,15 +78,16 @@ impl KNearestSearch for BruteForce {
     /// Serch k-nearest items close to the point
     fn search(&self, point: &[f64], k: usize) -> Result<(Vec<usize>, Vec<f64>), Error> {
         if let Some(ref data) = self.data {
+            let k = cmp::min(k, data.rows());
             let indices: Vec<usize> = (0..k).collect();
-            let distances = get_distances(data, point, &indices);
+            let distances = get_distances(data, point, &indices, &*self.metric);
 
             let mut query = KNearest::new(k, indices, distances);
             let mut current_dist = query.dist();
 
             let mut i = k;
             for row in data.row_iter().skip(k) {
-                let d = dist(point, row.raw_slice());
+                let d = self.metric.distance(point, row.raw_slice());
                 if d < current_dist {
                     current_dist = query.add(i, d);
                 }
@@ -71,6 +98,20 @@ impl KNearestSearch for BruteForce {
             Err(Error::new_untrained())
         }
     }
+
+    /// Search items within the radius of the point
+    fn search_radius(&self, point: &[f64], radius: f64) -> Result<(Vec<usize>, Vec<f64>), Error> {
+        if let Some(ref data) = self.data {
+            let pairs = data.row_iter()
+                            .map(|row| self.metric.distance(point, row.raw_slice()))
+                            .enumerate()
+                            .filter(|&(_, d)| d < radius)
+                            .collect();
+            Ok(sorted_pairs(pairs))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
 }
 
 #[cfg(test)]
@@ -79,6 +120,7 @@ mod tests {
     use linalg::Matrix;
     use super::super::KNearestSearch;
     use super::BruteForce;
+    use learning::toolkit::distance::Manhattan;
 
     #[test]
     fn test_bruteforce_search() {
@@ -103,10 +145,47 @@ mod tests {
         assert_eq!(dist, vec![1.0999999999999996, 3.5227829907617076, 3.551056180912941]);
     }
 
+    #[test]
+    fn test_bruteforce_search_metric() {
+        let m = Matrix::new(5, 2, vec![1., 2.,
+                                       8., 0.,
+                                       6., 10.,
+                                       3., 6.,
+                                       0., 3.]);
+        let mut b = BruteForce::with_metric(Manhattan);
+        b.build(m);
+
+        let (ind, dist) = b.search(&vec![0.5, 4.], 3).unwrap();
+        assert_eq!(ind, vec![4, 0, 3]);
+        assert_eq!(dist, vec![1.5, 2.5, 4.5]);
+    }
+
+    #[test]
+    fn test_bruteforce_search_radius() {
+        let m = Matrix::new(5, 2, vec![1., 2.,
+                                       8., 0.,
+                                       6., 10.,
+                                       3., 6.,
+                                       0., 3.]);
+        let mut b = BruteForce::new();
+        b.build(m);
+
+        let (ind, dist) = b.search_radius(&vec![3., 4.9], 3.6).unwrap();
+        assert_eq!(ind, vec![3, 0, 4]);
+        assert_eq!(dist, vec![1.0999999999999996, 3.5227829907617076, 3.551056180912941]);
+
+        let (ind, dist) = b.search_radius(&vec![3., 4.9], 1.).unwrap();
+        assert!(ind.is_empty());
+        assert!(dist.is_empty());
+    }
+
     #[test]
     fn test_bruteforce_untrained() {
         let b = BruteForce::new();
         let e = b.search(&vec![3., 4.9], 1);
         assert!(e.is_err());
+
+        let e = b.search_radius(&vec![3., 4.9], 1.);
+        assert!(e.is_err());
     }
 }
@@ -26,12 +26,17 @@
 //! assert_eq!(res, Vector::new(vec![1, 0]));
 //! # }
 //! ```
+//!
+//! `KNNRegressor` predicts the mean target of the neighbors instead,
+//! and both models can weight the neighbors by inverse distance.
 use std::f64;
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
 
 use linalg::{Matrix, BaseMatrix, Vector};
 use learning::{LearningResult, SupModel};
 use learning::error::{Error, ErrorKind};
+use learning::toolkit::distance::Metric;
 
 mod binary_tree;
 mod brute_force;
@@ -39,13 +44,42 @@ mod brute_force;
 pub use self::binary_tree::{KDTree, BallTree};
 pub use self::brute_force::BruteForce;
 
+/// Weighting of the k-nearest neighbors in a prediction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Weighting {
+    /// All neighbors contribute equally.
+    Uniform,
+    /// Neighbors contribute in proportion to the inverse of their distance.
+    ///
+    /// If any neighbors coincide with the point, only they contribute.
+    Distance,
+}
+
+impl Weighting {
+    /// Computes the weight of each neighbor from its distance.
+    fn weights(&self, distances: &[f64]) -> Vec<f64> {
+        match *self {
+            Weighting::Uniform => vec![1.; distances.len()],
+            Weighting::Distance => {
+                if distances.iter().any(|&d| d == 0.) {
+                    distances.iter().map(|&d| if d == 0. { 1. } else { 0. }).collect()
+                } else {
+                    distances.iter().map(|&d| 1. / d).collect()
+                }
+            }
+        }
+    }
+}
+
 /// k-Nearest Neighbor Classifier
 #[derive(Debug)]
 pub struct KNNClassifier<S: KNearestSearch> {
     k: usize,
+    weighting: Weighting,
 
     searcher: S,
     target: Option<Vector<usize>>,
+    classes: Option<Vec<usize>>,
 }
 
 impl Default for KNNClassifier<KDTree> {
@@ -58,11 +92,7 @@ impl Default for KNNClassifier<KDTree> {
     /// let _ = KNNClassifier::default();
     /// ```
     fn default() -> Self {
-        KNNClassifier {
-            k: 5,
-            searcher: KDTree::default(),
-            target: None
-        }
+        KNNClassifier::new(5)
     }
 }
 
@@ -77,11 +107,7 @@ impl KNNClassifier<KDTree> {
     /// let _ = KNNClassifier::new(3);
     /// ```
     pub fn new(k: usize) -> Self {
-        KNNClassifier {
-            k: k,
-            searcher: KDTree::default(),
-            target: None
-        }
+        KNNClassifier::new_specified(k, KDTree::default())
     }
 }
 
@@ -98,8 +124,96 @@ impl<S: KNearestSearch> KNNClassifier<S> {
     pub fn new_specified(k: usize, searcher: S) -> Self {
         KNNClassifier {
             k: k,
+            weighting: Weighting::Uniform,
             searcher: searcher,
-            target: None
+            target: None,
+            classes: None,
+        }
+    }
+
+    /// Returns the weighting of the neighbors' votes.
+    pub fn weighting(&self) -> Weighting {
+        self.weighting
+    }
+
+    /// Sets the weighting of the neighbors' votes.
+    ///
+    /// Defaults to `Weighting::Uniform`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::knn::{KNNClassifier, Weighting};
+    ///
+    /// let mut knn = KNNClassifier::new(3);
+    /// knn.set_weighting(Weighting::Distance);
+    /// ```
+    pub fn set_weighting(&mut self, weighting: Weighting) {
+        self.weighting = weighting;
+    }
+
+    /// Returns the sorted classes seen in training.
+    ///
+    /// These label the columns of `predict_proba`.
+    pub fn classes(&self) -> Option<&[usize]> {
+        self.classes.as_ref().map(|c| &c[..])
+    }
+
+    /// Predicts the frequency of each class among the neighbors of each input.
+    ///
+    /// The output has a row for each input and a column for each of
+    /// `classes`. The frequencies are weighted by the `weighting` and
+    /// each row sums to one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use] extern crate rulinalg; extern crate rusty_machine; fn main() {
+    /// use rusty_machine::learning::knn::KNNClassifier;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::Vector;
+    ///
+    /// let data = matrix![0.; 1.; 2.; 10.];
+    /// let target = Vector::new(vec![0, 0, 1, 1]);
+    ///
+    /// let mut knn = KNNClassifier::new(3);
+    /// knn.train(&data, &target).unwrap();
+    ///
+    /// let proba = knn.predict_proba(&matrix![0.5]).unwrap();
+    /// assert_eq!(proba.data(), &vec![2. / 3., 1. / 3.]);
+    /// # }
+    /// ```
+    pub fn predict_proba(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        match self.classes {
+            Some(ref classes) => {
+                let mut proba = Vec::with_capacity(inputs.rows() * classes.len());
+                for row in inputs.row_iter() {
+                    let (uniques, weights) = self.neighbor_votes(row.raw_slice())?;
+                    let total: f64 = weights.iter().sum();
+
+                    let mut row_proba = vec![0.; classes.len()];
+                    for (label, w) in uniques.iter().zip(weights) {
+                        let col = classes.binary_search(label)
+                            .expect("Neighbor labels are training classes.");
+                        row_proba[col] = w / total;
+                    }
+                    proba.extend(row_proba);
+                }
+                Ok(Matrix::new(inputs.rows(), classes.len(), proba))
+            }
+            None => Err(Error::new_untrained()),
+        }
+    }
+
+    /// Returns the distinct labels of the neighbors of the point and their total weights.
+    fn neighbor_votes(&self, point: &[f64]) -> LearningResult<(Vec<usize>, Vec<f64>)> {
+        match self.target {
+            Some(ref target) => {
+                let (idx, distances) = self.searcher.search(point, self.k)?;
+                let res = target.select(&idx);
+                Ok(weighted_freq(res.data(), &self.weighting.weights(&distances)))
+            }
+            None => Err(Error::new_untrained()),
         }
     }
 }
@@ -107,25 +221,160 @@ impl<S: KNearestSearch> KNNClassifier<S> {
 impl<S: KNearestSearch> SupModel<Matrix<f64>, Vector<usize>> for KNNClassifier<S> {
 
     fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<usize>> {
+        let mut results: Vec<usize> = Vec::with_capacity(inputs.rows());
+        for row in inputs.row_iter() {
+            let (uniques, weights) = self.neighbor_votes(row.raw_slice())?;
+            // ties go to the smallest label
+            let (id, _) = weights.iter()
+                .enumerate()
+                .fold((0, f64::NEG_INFINITY),
+                      |(bi, bw), (i, &w)| if w > bw { (i, w) } else { (bi, bw) });
+            results.push(uniques[id]);
+        }
+        Ok(Vector::new(results))
+    }
+
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<usize>) -> LearningResult<()> {
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "inputs and targets must be the same length"));
+        }
+        if inputs.rows() < self.k {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "inputs number of rows must be equal or learger than k"));
+        }
+        let mut classes = targets.data().clone();
+        classes.sort_unstable();
+        classes.dedup();
+
+        self.searcher.build(inputs.clone());
+        self.target = Some(targets.clone());
+        self.classes = Some(classes);
+        Ok(())
+    }
+}
+
+/// k-Nearest Neighbor Regressor
+///
+/// Predicts the mean of the targets of the k-nearest neighbors,
+/// weighted by the `weighting`. The targets may be a `Vector` or
+/// a `Matrix` with a column for each output.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate rulinalg; extern crate rusty_machine; fn main() {
+/// use rusty_machine::learning::knn::KNNRegressor;
+/// use rusty_machine::learning::SupModel;
+/// use rusty_machine::linalg::Vector;
+///
+/// let data = matrix![0.; 1.; 2.; 3.];
+/// let target = Vector::new(vec![0., 2., 4., 6.]);
+///
+/// let mut knn = KNNRegressor::new(2);
+/// knn.train(&data, &target).unwrap();
+///
+/// // The output type selects between single and multiple outputs.
+/// let res: Vector<f64> = knn.predict(&matrix![0.4; 2.6]).unwrap();
+/// assert_eq!(res, Vector::new(vec![1., 5.]));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct KNNRegressor<S: KNearestSearch> {
+    k: usize,
+    weighting: Weighting,
+
+    searcher: S,
+    target: Option<Matrix<f64>>,
+}
+
+impl Default for KNNRegressor<KDTree> {
+    /// Constructs an untrained KNN Regressor with searching 5 neighbors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::knn::KNNRegressor;
+    /// let _ = KNNRegressor::default();
+    /// ```
+    fn default() -> Self {
+        KNNRegressor::new(5)
+    }
+}
+
+impl KNNRegressor<KDTree> {
+    /// Constructs an untrained KNN Regressor with specified
+    /// number of search neighbors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::knn::KNNRegressor;
+    /// let _ = KNNRegressor::new(3);
+    /// ```
+    pub fn new(k: usize) -> Self {
+        KNNRegressor::new_specified(k, KDTree::default())
+    }
+}
+
+impl<S: KNearestSearch> KNNRegressor<S> {
+    /// Constructs an untrained KNN Regressor with specified
+    /// k and searcher.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::knn::{KNNRegressor, BruteForce};
+    /// let _ = KNNRegressor::new_specified(3, BruteForce::new());
+    /// ```
+    pub fn new_specified(k: usize, searcher: S) -> Self {
+        KNNRegressor {
+            k: k,
+            weighting: Weighting::Uniform,
+            searcher: searcher,
+            target: None,
+        }
+    }
+
+    /// Returns the weighting of the neighbors' targets.
+    pub fn weighting(&self) -> Weighting {
+        self.weighting
+    }
+
+    /// Sets the weighting of the neighbors' targets.
+    ///
+    /// Defaults to `Weighting::Uniform`.
+    pub fn set_weighting(&mut self, weighting: Weighting) {
+        self.weighting = weighting;
+    }
+
+    /// Predicts the weighted mean of the neighbors' targets, one row per input.
+    fn predict_rows(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
         match self.target {
             Some(ref target) => {
-
-                let mut results: Vec<usize> = Vec::with_capacity(inputs.rows());
+                let cols = target.cols();
+                let mut results = Vec::with_capacity(inputs.rows() * cols);
                 for row in inputs.row_iter() {
-                    let (idx, _) = self.searcher.search(row.raw_slice(), self.k)?;
-                    let res = target.select(&idx);
-                    let (uniques, counts) = freq(res.data());
-                    let (id, _) = counts.argmax();
-                    results.push(uniques[id]);
+                    let (idx, distances) = self.searcher.search(row.raw_slice(), self.k)?;
+                    let weights = self.weighting.weights(&distances);
+                    let total: f64 = weights.iter().sum();
+
+                    let mut mean = vec![0.; cols];
+                    for (&i, w) in idx.iter().zip(weights) {
+                        for (m, t) in mean.iter_mut().zip(target.row(i).raw_slice()) {
+                            *m += w * t;
+                        }
+                    }
+                    results.extend(mean.into_iter().map(|m| m / total));
                 }
-                Ok(Vector::new(results))
-            },
-            _ => Err(Error::new_untrained())
+                Ok(Matrix::new(inputs.rows(), cols, results))
+            }
+            None => Err(Error::new_untrained()),
         }
     }
 
-    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<usize>) -> LearningResult<()> {
-        if inputs.rows() != targets.size() {
+    fn train_rows(&mut self, inputs: &Matrix<f64>, targets: Matrix<f64>) -> LearningResult<()> {
+        if inputs.rows() != targets.rows() {
             return Err(Error::new(ErrorKind::InvalidData,
                                   "inputs and targets must be the same length"));
         }
@@ -134,11 +383,38 @@ impl<S: KNearestSearch> SupModel<Matrix<f64>, Vector<usize>> for KNNClassifier<S
                                   "inputs number of rows must be equal or learger than k"));
         }
         self.searcher.build(inputs.clone());
-        self.target = Some(targets.clone());
+        self.target = Some(targets);
         Ok(())
     }
 }
 
+impl<S: KNearestSearch> SupModel<Matrix<f64>, Vector<f64>> for KNNRegressor<S> {
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if let Some(ref target) = self.target {
+            if target.cols() != 1 {
+                return Err(Error::new(ErrorKind::InvalidState,
+                                      "model was trained with multiple outputs"));
+            }
+        }
+        Ok(Vector::new(self.predict_rows(inputs)?.into_vec()))
+    }
+
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        let n = targets.size();
+        self.train_rows(inputs, Matrix::new(n, 1, targets.data().clone()))
+    }
+}
+
+impl<S: KNearestSearch> SupModel<Matrix<f64>, Matrix<f64>> for KNNRegressor<S> {
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        self.predict_rows(inputs)
+    }
+
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Matrix<f64>) -> LearningResult<()> {
+        self.train_rows(inputs, targets.clone())
+    }
+}
+
 /// Container for k-Nearest search results
 struct KNearest {
     // number to search
@@ -158,7 +434,7 @@ impl KNearest {
                                                 .zip(distances.into_iter())
                                                 .collect();
         // sort by distance, take k elements
-        pairs.sort_by(|x, y| x.1.partial_cmp(&y.1).unwrap());
+        pairs.sort_by(|x, y| cmp_dist(x.1, y.1));
         pairs.truncate(k);
 
         KNearest {
@@ -240,28 +516,114 @@ pub trait KNearestSearch: Default{
     /// Serch k-nearest items close to the point
     /// Returns a tuple of searched item index and its distances
     fn search(&self, point: &[f64], k: usize) -> Result<(Vec<usize>, Vec<f64>), Error>;
+
+    /// Search all items whose distance to the point is less than the radius
+    /// Returns a tuple of searched item index and its distances, sorted by distance
+    ///
+    /// The default implementation repeats `search` with a doubling `k`
+    /// until an item outside the radius is found.
+    fn search_radius(&self, point: &[f64], radius: f64) -> Result<(Vec<usize>, Vec<f64>), Error> {
+        let mut k = 1;
+        loop {
+            let (indices, distances) = self.search(point, k)?;
+            if indices.len() < k || distances.last().map_or(true, |&d| !(d < radius)) {
+                let pairs = indices.into_iter()
+                                   .zip(distances.into_iter())
+                                   .filter(|&(_, d)| d < radius)
+                                   .collect();
+                return Ok(sorted_pairs(pairs));
+            }
+            k *= 2;
+        }
+    }
+
+    /// Search k-nearest items close to each row of the points
+    /// Returns a tuple of searched item index and its distances for each row
+    fn search_many(&self, points: &Matrix<f64>, k: usize)
+        -> Result<Vec<(Vec<usize>, Vec<f64>)>, Error> {
+        points.row_iter().map(|row| self.search(row.raw_slice(), k)).collect()
+    }
+}
+
+/// A neighbour search chosen to suit the distance metric.
+///
+/// `BallTree` pruning relies on the triangle inequality, so other
+/// distances are searched by brute force.
+#[derive(Debug)]
+pub(crate) enum MetricSearch {
+    Tree(BallTree),
+    Brute(BruteForce),
+}
+
+impl MetricSearch {
+    /// Uses a `BallTree` if the metric is a true metric, otherwise `BruteForce`.
+    pub(crate) fn with_metric<M: Metric + 'static>(metric: M) -> MetricSearch {
+        if metric.is_true_metric() {
+            MetricSearch::Tree(BallTree::with_metric(30, metric))
+        } else {
+            MetricSearch::Brute(BruteForce::with_metric(metric))
+        }
+    }
+
+    /// Return the metric used by the search
+    pub(crate) fn metric(&self) -> &dyn Metric {
+        match *self {
+            MetricSearch::Tree(ref s) => s.metric(),
+            MetricSearch::Brute(ref s) => s.metric(),
+        }
+    }
+}
+
+impl Default for MetricSearch {
+    fn default() -> MetricSearch {
+        MetricSearch::Tree(BallTree::default())
+    }
 }
 
-/// Count target label frequencies
+impl KNearestSearch for MetricSearch {
+    fn build(&mut self, data: Matrix<f64>) {
+        match *self {
+            MetricSearch::Tree(ref mut s) => s.build(data),
+            MetricSearch::Brute(ref mut s) => s.build(data),
+        }
+    }
+
+    fn search(&self, point: &[f64], k: usize) -> Result<(Vec<usize>, Vec<f64>), Error> {
+        match *self {
+            MetricSearch::Tree(ref s) => s.search(point, k),
+            MetricSearch::Brute(ref s) => s.search(point, k),
+        }
+    }
+
+    fn search_radius(&self, point: &[f64], radius: f64) -> Result<(Vec<usize>, Vec<f64>), Error> {
+        match *self {
+            MetricSearch::Tree(ref s) => s.search_radius(point, radius),
+            MetricSearch::Brute(ref s) => s.search_radius(point, radius),
+        }
+    }
+}
+
+/// Sum the weights of each target label, returning the sorted labels
 /// TODO: Used in decisition tree, move impl to somewhere
-fn freq(labels: &[usize]) -> (Vector<usize>, Vector<usize>) {
-    let mut map: BTreeMap<usize, usize> = BTreeMap::new();
-    for l in labels {
-        let e = map.entry(*l).or_insert(0);
-        *e += 1;
+fn weighted_freq(labels: &[usize], weights: &[f64]) -> (Vec<usize>, Vec<f64>) {
+    let mut map: BTreeMap<usize, f64> = BTreeMap::new();
+    for (l, w) in labels.iter().zip(weights) {
+        let e = map.entry(*l).or_insert(0.);
+        *e += *w;
     }
 
     let mut uniques: Vec<usize> = Vec::with_capacity(map.len());
-    let mut counts: Vec<usize> = Vec::with_capacity(map.len());
+    let mut totals: Vec<f64> = Vec::with_capacity(map.len());
     for (&k, &v) in &map {
         uniques.push(k);
-        counts.push(v);
+        totals.push(v);
     }
-    (Vector::new(uniques), Vector::new(counts))
+    (uniques, totals)
 }
 
 /// Return distances between given point and data specified with row ids
-fn get_distances(data: &Matrix<f64>, point: &[f64], ids: &[usize]) -> Vec<f64> {
+fn get_distances(data: &Matrix<f64>, point: &[f64], ids: &[usize],
+                 metric: &dyn Metric) -> Vec<f64> {
     assert!(!ids.is_empty(), "target ids is empty");
 
     let mut distances: Vec<f64> = Vec::with_capacity(ids.len());
@@ -269,26 +631,56 @@ fn get_distances(data: &Matrix<f64>, point: &[f64], ids: &[usize]) -> Vec<f64> {
         // ToDo: use .row(*id)
         let row: Vec<f64> = data.select_rows(&[*id]).into_vec();
         // let row: Vec<f64> = self.data.row(*id).into_vec();
-        let d = dist(point, &row);
+        let d = metric.distance(point, &row);
         distances.push(d);
     }
     distances
 }
 
-fn dist(v1: &[f64], v2: &[f64]) -> f64 {
-    // ToDo: use metrics
-    let d: f64 = v1.iter()
-                   .zip(v2.iter())
-                   .map(|(&x, &y)| (x - y) * (x - y))
-                   .fold(0., |s, v| s + v);
-    d.sqrt()
+/// Compare distances, ordering NaN after every other value
+fn cmp_dist(x: f64, y: f64) -> Ordering {
+    x.partial_cmp(&y).unwrap_or_else(|| x.is_nan().cmp(&y.is_nan()))
+}
+
+/// Split index and distance pairs, sorted by distance and then index
+///
+/// NaN distances are sorted last.
+fn sorted_pairs(mut pairs: Vec<(usize, f64)>) -> (Vec<usize>, Vec<f64>) {
+    pairs.sort_by(|x, y| cmp_dist(x.1, y.1).then(x.0.cmp(&y.0)));
+    pairs.into_iter().unzip()
 }
 
 #[cfg(test)]
 mod tests {
 
     use std::f64;
-    use super::KNearest;
+    use super::{KNearest, Weighting, weighted_freq, sorted_pairs};
+
+    #[test]
+    fn test_sorted_pairs() {
+        let (ind, dist) = sorted_pairs(vec![(3, 2.), (0, 1.), (2, 1.), (1, 0.5)]);
+        assert_eq!(ind, vec![1, 0, 2, 3]);
+        assert_eq!(dist, vec![0.5, 1., 1., 2.]);
+
+        let (ind, dist) = sorted_pairs(vec![(0, f64::NAN), (1, 2.), (2, 1.)]);
+        assert_eq!(ind, vec![2, 1, 0]);
+        assert!(dist[2].is_nan());
+    }
+
+    #[test]
+    fn test_weighted_freq() {
+        let (uniques, totals) = weighted_freq(&[2, 0, 2, 1], &[1., 0.5, 2., 0.25]);
+        assert_eq!(uniques, vec![0, 1, 2]);
+        assert_eq!(totals, vec![0.5, 0.25, 3.]);
+    }
+
+    #[test]
+    fn test_distance_weights() {
+        assert_eq!(Weighting::Uniform.weights(&[1., 2.]), vec![1., 1.]);
+        assert_eq!(Weighting::Distance.weights(&[1., 4.]), vec![1., 0.25]);
+        // coinciding neighbors take all of the weight
+        assert_eq!(Weighting::Distance.weights(&[0., 1., 0.]), vec![1., 0., 1.]);
+    }
 
     #[test]
     fn test_knearest() {
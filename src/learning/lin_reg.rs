@@ -142,20 +142,24 @@ impl LinRegressor {
     /// let mut lin_mod = LinRegressor::default();
     ///
     /// // Train the model
-    /// lin_mod.train_with_optimization(&inputs, &targets);
+    /// lin_mod.train_with_optimization(&inputs, &targets).unwrap();
     ///
     /// // Now we'll predict a new point
     /// let new_point = Matrix::new(1,1,vec![10.]);
     /// let _ = lin_mod.predict(&new_point).unwrap();
     /// ```
-    pub fn train_with_optimization(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) {
+    pub fn train_with_optimization(&mut self,
+                                   inputs: &Matrix<f64>,
+                                   targets: &Vector<f64>)
+                                   -> LearningResult<()> {
         let ones = Matrix::<f64>::ones(inputs.rows(), 1);
         let full_inputs = ones.hcat(inputs);
 
         let initial_params = vec![0.; full_inputs.cols()];
 
         let gd = GradientDesc::default();
-        let optimal_w = gd.optimize(self, &initial_params[..], &full_inputs, targets);
+        let optimal_w = gd.optimize(self, &initial_params[..], &full_inputs, targets)?;
         self.parameters = Some(Vector::new(optimal_w));
+        Ok(())
     }
 }
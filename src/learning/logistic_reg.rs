@@ -109,7 +109,7 @@ impl<A> SupModel<Matrix<f64>, Vector<f64>> for LogisticRegressor<A>
     ///
     /// let mut logistic_mod = LogisticRegressor::default();
     /// let inputs = Matrix::new(3,2, vec![1.0, 2.0, 1.0, 3.0, 1.0, 4.0]);
-    /// let targets = Vector::new(vec![5.0, 6.0, 7.0]);
+    /// let targets = Vector::new(vec![0.0, 0.0, 1.0]);
     ///
     /// logistic_mod.train(&inputs, &targets).unwrap();
     /// ```
@@ -119,7 +119,7 @@ impl<A> SupModel<Matrix<f64>, Vector<f64>> for LogisticRegressor<A>
 
         let initial_params = vec![0.5; full_inputs.cols()];
 
-        let optimal_w = self.alg.optimize(&self.base, &initial_params[..], &full_inputs, targets);
+        let optimal_w = self.alg.optimize(&self.base, &initial_params[..], &full_inputs, targets)?;
         self.base.set_parameters(Vector::new(optimal_w));
         Ok(())
     }
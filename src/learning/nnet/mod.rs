@@ -23,8 +23,8 @@
 //! // Choose the BCE criterion with L2 regularization (`lambda=0.1`).
 //! let criterion = BCECriterion::new(Regularization::L2(0.1));
 //!
-//! // We will create a multilayer perceptron and just use the default stochastic gradient descent.
-//! let mut model = NeuralNet::mlp(layers, criterion, StochasticGD::default(), Sigmoid);
+//! // We will create a multilayer perceptron trained with stochastic gradient descent.
+//! let mut model = NeuralNet::mlp(layers, criterion, StochasticGD::new(0.1, 0.1, 20), Sigmoid);
 //!
 //! // Train the model!
 //! model.train(&inputs, &targets).unwrap();
@@ -44,8 +44,9 @@
 
 
 pub mod net_layer;
+pub mod param_store;
 
-use linalg::{Matrix, MatrixSlice};
+use linalg::{Matrix, MatrixSlice, BaseMatrix};
 use rulinalg::utils;
 
 use learning::{LearningResult, SupModel};
@@ -57,8 +58,21 @@ use learning::toolkit::cost_fn::CostFunc;
 use learning::toolkit::regularization::Regularization;
 use learning::optim::{Optimizable, OptimAlgorithm};
 use learning::optim::grad_desc::StochasticGD;
+use learning::optim::grad_check::{self, GradCheck};
 
 use self::net_layer::NetLayer;
+use self::param_store::ParamStore;
+
+pub use learning::toolkit::parallel::MaybeSync;
+
+#[cfg(feature = "parallel")]
+use std::cmp;
+#[cfg(feature = "parallel")]
+use learning::toolkit::parallel;
+
+/// The number of rows in each chunk of a batch when computing gradients in parallel.
+#[cfg(feature = "parallel")]
+const PARALLEL_CHUNK_ROWS: usize = 64;
 
 /// Neural Network Model
 ///
@@ -86,9 +100,14 @@ impl<T, A> SupModel<Matrix<f64>, Matrix<f64>> for NeuralNet<T, A>
     }
 
     /// Train the model using gradient optimization and back propagation.
+    ///
+    /// The shapes of the inputs and targets are checked against the
+    /// network layers before optimizing.
     fn train(&mut self, inputs: &Matrix<f64>, targets: &Matrix<f64>) -> LearningResult<()> {
-        let optimal_w = self.alg.optimize(&self.base, &self.base.weights, inputs, targets);
-        self.base.weights = optimal_w;
+        self.base.check_shapes(inputs, targets)?;
+        let start = self.base.params.to_vec();
+        let optimal_w = self.alg.optimize(&self.base, &start, inputs, targets)?;
+        self.base.params.set_values(&optimal_w)?;
         Ok(())
     }
 }
@@ -161,7 +180,7 @@ impl<T, A> NeuralNet<T, A>
     /// let mut net = NeuralNet::mlp(layers, BCECriterion::default(), StochasticGD::default(), Sigmoid);
     /// ```
     pub fn mlp<U>(layer_sizes: &[usize], criterion: T, alg: A, activ_fn: U) -> NeuralNet<T, A> 
-        where U: ActivationFunc + 'static {
+        where U: ActivationFunc + MaybeSync + 'static {
         NeuralNet {
             base: BaseNeuralNet::mlp(layer_sizes, criterion, activ_fn),
             alg: alg,
@@ -237,7 +256,49 @@ impl<T, A> NeuralNet<T, A>
     /// assert_eq!(w.cols(), 3);
     /// ```
     pub fn get_net_weights(&self, idx: usize) -> MatrixSlice<f64> {
-        self.base.get_layer_weights(&self.base.weights[..], idx)
+        self.base.params.layer(idx)
+    }
+
+    /// Checks the back propagated gradients against finite differences.
+    ///
+    /// The check is carried out at the current weights of the network.
+    /// Returns the maximum relative error for the parameters of each layer,
+    /// in the order the layers were added.
+    ///
+    /// Note that the criteria compute per sample gradients, so the check
+    /// is only meaningful for single row inputs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::nnet::{NeuralNet, MSECriterion};
+    /// use rusty_machine::learning::nnet::net_layer::Linear;
+    /// use rusty_machine::learning::toolkit::activ_fn::Sigmoid;
+    /// use rusty_machine::learning::optim::grad_desc::StochasticGD;
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// let mut net = NeuralNet::new(MSECriterion::default(), StochasticGD::default());
+    /// net.add(Box::new(Linear::new(2, 3)))
+    ///    .add(Box::new(Sigmoid))
+    ///    .add(Box::new(Linear::new(3, 1)));
+    ///
+    /// let inputs = Matrix::new(1, 2, vec![0.5, -0.5]);
+    /// let targets = Matrix::new(1, 1, vec![1.0]);
+    ///
+    /// let check = net.gradient_check(&inputs, &targets, 1e-6).unwrap();
+    /// assert!(check.passes(1e-5));
+    /// ```
+    pub fn gradient_check(&self,
+                          inputs: &Matrix<f64>,
+                          targets: &Matrix<f64>,
+                          eps: f64)
+                          -> LearningResult<GradCheck> {
+        grad_check::gradient_check(&self.base,
+                                   &self.base.params.to_vec(),
+                                   inputs,
+                                   targets,
+                                   &self.base.params.layer_sizes(),
+                                   eps)
     }
 }
 
@@ -247,7 +308,7 @@ impl<T, A> NeuralNet<T, A>
 #[derive(Debug)]
 pub struct BaseNeuralNet<T: Criterion> {
     layers: Vec<Box<dyn NetLayer>>,
-    weights: Vec<f64>,
+    params: ParamStore,
     criterion: T,
 }
 
@@ -255,7 +316,7 @@ pub struct BaseNeuralNet<T: Criterion> {
 impl BaseNeuralNet<BCECriterion> {
     /// Creates a base neural network with the specified layer sizes.
     fn default<U>(layer_sizes: &[usize], activ_fn: U) -> BaseNeuralNet<BCECriterion>
-        where U: ActivationFunc + 'static {
+        where U: ActivationFunc + MaybeSync + 'static {
         BaseNeuralNet::mlp(layer_sizes, BCECriterion::default(), activ_fn)
     }
 }
@@ -266,30 +327,38 @@ impl<T: Criterion> BaseNeuralNet<T> {
     fn new(criterion: T) -> BaseNeuralNet<T> {
         BaseNeuralNet {
             layers: Vec::new(),
-            weights: Vec::new(),
+            params: ParamStore::new(),
             criterion: criterion
         }
     } 
 
     /// Create a multilayer perceptron with the specified layer sizes.
     fn mlp<U>(layer_sizes: &[usize], criterion: T, activ_fn: U) -> BaseNeuralNet<T> 
-        where U: ActivationFunc + 'static {
+        where U: ActivationFunc + MaybeSync + 'static {
         let mut mlp = BaseNeuralNet {
             layers: Vec::with_capacity(2*(layer_sizes.len()-1)),
-            weights: Vec::new(),
+            params: ParamStore::new(),
             criterion: criterion
         };
         for shape in layer_sizes.windows(2) {
             mlp.add(Box::new(net_layer::Linear::new(shape[0], shape[1])));
             mlp.add(Box::new(activ_fn.clone()));
         }
-        mlp.add(Box::new(net_layer::Linear::new(10, 1)));
         mlp
     }
 
     /// Adds the specified layer to the end of the network
+    ///
+    /// Panics if the layer's `num_params` disagrees with its `param_shape`.
     fn add<'a>(&'a mut self, layer: Box<dyn NetLayer>) -> &'a mut BaseNeuralNet<T> {
-        self.weights.extend_from_slice(&layer.default_params());
+        let shape = layer.param_shape();
+        assert!(layer.num_params() == shape.0 * shape.1,
+                "Layer {} has {} parameters but a parameter shape of {}x{}.",
+                self.layers.len(),
+                layer.num_params(),
+                shape.0,
+                shape.1);
+        self.params.push(shape.0, shape.1, layer.default_params());
         self.layers.push(layer);
         self
     }
@@ -304,24 +373,40 @@ impl<T: Criterion> BaseNeuralNet<T> {
         self
     }
 
-    /// Gets matrix of weights for the specified layer for the weights.
-    fn get_layer_weights(&self, weights: &[f64], idx: usize) -> MatrixSlice<f64> {
-        debug_assert!(idx < self.layers.len());
-
-        // Check that the weights are the right size.
-        let full_size: usize = self.layers.iter().map(|l| l.num_params()).sum();
+    /// Checks that the inputs and targets fit the network.
+    ///
+    /// A single row of the inputs is propagated through the layers so that
+    /// any mismatch is reported with the index of the offending layer.
+    fn check_shapes(&self, inputs: &Matrix<f64>, targets: &Matrix<f64>) -> LearningResult<()> {
+        if inputs.rows() != targets.rows() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  format!("The inputs have {} rows but the targets have {} rows.",
+                                          inputs.rows(),
+                                          targets.rows())));
+        }
 
-        debug_assert_eq!(full_size, weights.len());
+        if inputs.rows() == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "The inputs have no rows."));
+        }
 
-        let start: usize = self.layers.iter().take(idx).map(|l| l.num_params()).sum();
+        let mut output = inputs.select_rows(&[0]);
+        for (i, layer) in self.layers.iter().enumerate() {
+            output = layer.forward(&output, self.params.layer(i)).map_err(|e| {
+                Error::new(ErrorKind::InvalidData,
+                           format!("Layer {} does not accept its input: {}", i, e))
+            })?;
+        }
 
-        let shape = self.layers[idx].param_shape();
-        unsafe {
-            MatrixSlice::from_raw_parts(weights.as_ptr().offset(start as isize),
-                                        shape.0,
-                                        shape.1,
-                                        shape.1)
+        if output.cols() != targets.cols() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  format!("The output of layer {} has {} columns but the \
+                                           targets have {} columns.",
+                                          self.layers.len().saturating_sub(1),
+                                          output.cols(),
+                                          targets.cols())));
         }
+
+        Ok(())
     }
 
     /// Compute the gradient using the back propagation algorithm.
@@ -329,68 +414,108 @@ impl<T: Criterion> BaseNeuralNet<T> {
                     weights: &[f64],
                     inputs: &Matrix<f64>,
                     targets: &Matrix<f64>)
-                    -> (f64, Vec<f64>) {
-        let mut gradients = Vec::with_capacity(weights.len());
-        unsafe {
-            gradients.set_len(weights.len());
+                    -> LearningResult<(f64, Vec<f64>)> {
+        let params = self.params.with_values(weights)?;
+
+        #[cfg(not(feature = "parallel"))]
+        let (mut cost, mut gradients) = self.batch_grad(&params, inputs, targets)?;
+        #[cfg(feature = "parallel")]
+        let (mut cost, mut gradients) = self.par_batch_grad(&params, inputs, targets)?;
+
+        if self.criterion.is_regularized() {
+            let all_params = Matrix::new(weights.len(), 1, weights.to_vec());
+            utils::in_place_vec_bin_op(&mut gradients,
+                                       self.criterion.reg_cost_grad(all_params.as_slice()).data(),
+                                       |x, &y| *x = *x + y);
+            cost += self.criterion.reg_cost(all_params.as_slice());
         }
+        Ok((cost, gradients))
+    }
+
+    /// Compute the unregularized cost and gradient over a batch of data.
+    fn batch_grad(&self,
+                  params: &ParamStore,
+                  inputs: &Matrix<f64>,
+                  targets: &Matrix<f64>)
+                  -> LearningResult<(f64, Vec<f64>)> {
         // activations[i] is the output of layer[i]
         let mut activations = Vec::with_capacity(self.layers.len());
-        // params[i] is the weights for layer[i]
-        let mut params = Vec::with_capacity(self.layers.len());
 
         // Forward propagation
-        
-        let mut index = 0;
-        for (i, layer) in self.layers.iter().enumerate() {
-            let shape = layer.param_shape();
-
-            let slice = unsafe {
-                MatrixSlice::from_raw_parts(weights.as_ptr().offset(index as isize),
-                                            shape.0,
-                                            shape.1,
-                                            shape.1)
-            };
 
+        for (i, layer) in self.layers.iter().enumerate() {
             let output = if i == 0 {
-                layer.forward(inputs, slice).unwrap()
+                layer.forward(inputs, params.layer(i))?
             } else {
-                layer.forward(activations.last().unwrap(), slice).unwrap()
+                layer.forward(activations.last().unwrap(), params.layer(i))?
             };
 
             activations.push(output);
-            params.push(slice);
-            index += layer.num_params();
         }
-        let output = activations.last().unwrap();
+        let output = activations.last().unwrap_or(inputs);
 
         // Backward propagation
-        
+
+        // layer_grads[i] is the gradient for the weights of layer[layers.len() - 1 - i]
+        let mut layer_grads = Vec::with_capacity(self.layers.len());
         // The gradient with respect to the current layer's output
         let mut out_grad = self.criterion.cost_grad(output, targets);
-        // at this point index == weights.len()
         for (i, layer) in self.layers.iter().enumerate().rev() {
             let activation = if i == 0 {inputs} else {&activations[i-1]};
             let result = &activations[i];
-            index -= layer.num_params();
 
-            let grad_params = &mut gradients[index..index+layer.num_params()];
-            grad_params.copy_from_slice(layer.back_params(&out_grad, activation, result, params[i]).data());
-            
-            out_grad = layer.back_input(&out_grad, activation, result, params[i]);
+            layer_grads.push(layer.back_params(&out_grad, activation, result, params.layer(i)));
+            out_grad = layer.back_input(&out_grad, activation, result, params.layer(i));
         }
 
-        let mut cost = self.criterion.cost(output, targets);
-        if self.criterion.is_regularized() {
-            let all_params = unsafe {
-                MatrixSlice::from_raw_parts(weights.as_ptr(), weights.len(), 1, 1)
-            };
-            utils::in_place_vec_bin_op(&mut gradients,
-                                       self.criterion.reg_cost_grad(all_params).data(),
-                                       |x, &y| *x = *x + y);
-            cost += self.criterion.reg_cost(all_params);
+        let mut gradients = Vec::with_capacity(params.num_params());
+        for grad in layer_grads.iter().rev() {
+            gradients.extend_from_slice(grad.data());
+        }
+        if gradients.len() != params.num_params() {
+            return Err(Error::new(ErrorKind::InvalidState,
+                                  "The layer gradients do not match the network's parameters."));
         }
-        (cost, gradients)
+
+        Ok((self.criterion.cost(output, targets), gradients))
+    }
+
+    /// Compute the unregularized cost and gradient with the batch split across threads.
+    ///
+    /// The batch is split into chunks of a fixed number of rows, independent
+    /// of the thread count, and the chunk results are reduced in order. This
+    /// keeps the result identical for any number of threads.
+    #[cfg(feature = "parallel")]
+    fn par_batch_grad(&self,
+                      params: &ParamStore,
+                      inputs: &Matrix<f64>,
+                      targets: &Matrix<f64>)
+                      -> LearningResult<(f64, Vec<f64>)> {
+        let num_samples = inputs.rows();
+        if num_samples <= PARALLEL_CHUNK_ROWS {
+            return self.batch_grad(params, inputs, targets);
+        }
+
+        let chunks = (0..num_samples)
+            .step_by(PARALLEL_CHUNK_ROWS)
+            .map(|start| (start, cmp::min(start + PARALLEL_CHUNK_ROWS, num_samples)))
+            .collect::<Vec<_>>();
+
+        let partials = parallel::par_map(&chunks, |&(start, end)| {
+            let rows = (start..end).collect::<Vec<_>>();
+            self.batch_grad(params, &inputs.select_rows(&rows), &targets.select_rows(&rows))
+        });
+
+        // The costs are means over each chunk while the gradients are sums over rows.
+        let mut cost = 0f64;
+        let mut gradients = vec![0f64; params.num_params()];
+        for (&(start, end), partial) in chunks.iter().zip(partials) {
+            let (chunk_cost, chunk_grad) = partial?;
+            cost += chunk_cost * (end - start) as f64;
+            utils::in_place_vec_bin_op(&mut gradients, &chunk_grad, |x, &y| *x = *x + y);
+        }
+
+        Ok((cost / num_samples as f64, gradients))
     }
 
     /// Forward propagation of the model weights to get the outputs.
@@ -399,33 +524,14 @@ impl<T: Criterion> BaseNeuralNet<T> {
             return Ok(inputs.clone());
         }
 
-        let mut outputs = unsafe {
-            let shape = self.layers[0].param_shape();
-            let slice = MatrixSlice::from_raw_parts(self.weights.as_ptr(),
-                                                    shape.0,
-                                                    shape.1,
-                                                    shape.1);
-            self.layers[0].forward(inputs, slice)?
-        };
-
-        let mut index = self.layers[0].num_params();
-        for layer in self.layers.iter().skip(1) {
-            let shape = layer.param_shape();
+        let mut outputs = self.layers[0].forward(inputs, self.params.layer(0))?;
 
-            let slice = unsafe {
-                MatrixSlice::from_raw_parts(self.weights.as_ptr().offset(index as isize),
-                                            shape.0,
-                                            shape.1,
-                                            shape.1)
-            };
-            
-            outputs = match layer.forward(&outputs, slice) {
+        for (i, layer) in self.layers.iter().enumerate().skip(1) {
+            outputs = match layer.forward(&outputs, self.params.layer(i)) {
                 Ok(act) => act,
-                Err(_) => {return Err(Error::new(ErrorKind::InvalidParameters,
-                    "The network's layers do not line up correctly."))}
+                Err(e) => {return Err(Error::new(ErrorKind::InvalidParameters,
+                    format!("The network's layers do not line up correctly at layer {}: {}", i, e)))}
             };
-
-            index += layer.num_params();
         }
         Ok(outputs)
     }
@@ -438,19 +544,30 @@ impl<T: Criterion> Optimizable for BaseNeuralNet<T> {
     type Targets = Matrix<f64>;
 
     /// Compute the gradient of the neural network.
+    ///
+    /// `NeuralNet::train` checks the shapes before optimizing, so this
+    /// should not fail. If it does the cost and gradient are `NaN`, which
+    /// the optimization algorithms report as a divergence error.
     fn compute_grad(&self,
                     params: &[f64],
                     inputs: &Matrix<f64>,
                     targets: &Matrix<f64>)
                     -> (f64, Vec<f64>) {
         self.compute_grad(params, inputs, targets)
+            .unwrap_or_else(|_| (f64::NAN, vec![f64::NAN; params.len()]))
     }
 }
 
 /// Criterion for Neural Networks
 ///
 /// Specifies an activation function and a cost function.
-pub trait Criterion {
+///
+/// The cost should be the mean over the rows of the outputs, while
+/// the gradient is not scaled by the number of rows.
+///
+/// With the `parallel` feature criteria must also be `Send + Sync`,
+/// see `MaybeSync`.
+pub trait Criterion: MaybeSync {
     /// The cost function for the criterion.
     type Cost: CostFunc<Matrix<f64>>;
 
@@ -587,3 +704,182 @@ impl MSECriterion {
         MSECriterion { regularization: regularization }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{NeuralNet, MSECriterion};
+    use super::net_layer::Linear;
+    use learning::{LearningResult, SupModel};
+    use learning::error::ErrorKind;
+    use learning::optim::Optimizable;
+    use learning::optim::grad_desc::StochasticGD;
+    use learning::toolkit::activ_fn::Sigmoid;
+    use linalg::{BaseMatrix, Matrix};
+
+    fn small_net() -> NeuralNet<MSECriterion, StochasticGD> {
+        let mut net = NeuralNet::new(MSECriterion::default(), StochasticGD::new(0.1, 0.1, 5));
+        net.add(Box::new(Linear::new(2, 3)))
+            .add(Box::new(Sigmoid))
+            .add(Box::new(Linear::new(3, 1)));
+        net
+    }
+
+    fn assert_invalid_data<T>(res: LearningResult<T>, msg_part: &str) {
+        match res {
+            Ok(_) => panic!("Expected an error."),
+            Err(e) => {
+                match *e.kind() {
+                    ErrorKind::InvalidData => {}
+                    _ => panic!("Expected InvalidData but got {:?}", e.kind()),
+                }
+                let msg = format!("{}", e);
+                assert!(msg.contains(msg_part), "Unexpected message: {}", msg);
+            }
+        }
+    }
+
+    #[test]
+    fn test_train_valid_shapes() {
+        let mut net = small_net();
+        let inputs = Matrix::new(3, 2, vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6]);
+        let targets = Matrix::new(3, 1, vec![0.1, 0.2, 0.3]);
+
+        assert!(net.train(&inputs, &targets).is_ok());
+    }
+
+    #[test]
+    fn test_compute_grad_shape_errors() {
+        let net = small_net();
+        let inputs = Matrix::new(3, 2, vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6]);
+        let wide_inputs = Matrix::new(2, 3, vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6]);
+        let targets = Matrix::new(3, 1, vec![0.1, 0.2, 0.3]);
+        let weights = net.base.params.to_vec();
+
+        assert!(net.base.compute_grad(&weights[1..], &inputs, &targets).is_err());
+        assert!(net.base.compute_grad(&weights, &wide_inputs, &targets).is_err());
+
+        // The optimizers see a non-finite cost and gradient instead.
+        let (cost, grad) = Optimizable::compute_grad(&net.base, &weights[1..], &inputs, &targets);
+        assert!(cost.is_nan());
+        assert!(grad.iter().all(|x| x.is_nan()));
+    }
+
+    #[test]
+    fn test_train_wrong_input_width() {
+        let mut net = small_net();
+        let inputs = Matrix::new(2, 3, vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6]);
+        let targets = Matrix::new(2, 1, vec![0.1, 0.2]);
+
+        assert_invalid_data(net.train(&inputs, &targets), "Layer 0");
+    }
+
+    #[test]
+    fn test_train_wrong_target_width() {
+        let mut net = small_net();
+        let inputs = Matrix::new(3, 2, vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6]);
+        let targets = Matrix::new(3, 2, vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6]);
+
+        assert_invalid_data(net.train(&inputs, &targets), "layer 2 has 1 columns");
+    }
+
+    #[test]
+    fn test_train_mismatched_rows() {
+        let mut net = small_net();
+        let inputs = Matrix::new(3, 2, vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6]);
+        let targets = Matrix::new(2, 1, vec![0.1, 0.2]);
+
+        assert_invalid_data(net.train(&inputs, &targets), "3 rows");
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_grad_independent_of_threads() {
+        use learning::toolkit::parallel;
+
+        let net = small_net();
+        let inputs = Matrix::new(200, 2, (0..400).map(|x| (x as f64 * 0.37).sin())
+                                                  .collect::<Vec<_>>());
+        let targets = Matrix::new(200, 1, (0..200).map(|x| (x as f64 * 0.11).cos())
+                                                   .collect::<Vec<_>>());
+        let weights = net.base.params.to_vec();
+
+        let grad = || net.base.compute_grad(&weights, &inputs, &targets).unwrap();
+        let single = parallel::with_num_threads(1, &grad);
+        let multi = parallel::with_num_threads(3, &grad);
+
+        assert_eq!(single.0, multi.0);
+        assert_eq!(single.1, multi.1);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_grad_matches_batch_grad() {
+        // Not a multiple of the chunk size, so the last chunk is smaller.
+        let rows = 2 * super::PARALLEL_CHUNK_ROWS + 7;
+        let net = small_net();
+        let inputs = Matrix::new(rows, 2, (0..2 * rows).map(|x| (x as f64 * 0.37).sin())
+                                                       .collect::<Vec<_>>());
+        let targets = Matrix::new(rows, 1, (0..rows).map(|x| (x as f64 * 0.11).cos())
+                                                    .collect::<Vec<_>>());
+
+        let (cost, grad) = net.base.batch_grad(&net.base.params, &inputs, &targets).unwrap();
+        let (par_cost, par_grad) = net.base.par_batch_grad(&net.base.params, &inputs, &targets)
+            .unwrap();
+
+        assert!((cost - par_cost).abs() < 1e-10);
+        for (x, y) in grad.iter().zip(par_grad.iter()) {
+            assert!((x - y).abs() < 1e-10, "{} != {}", x, y);
+        }
+    }
+
+    // Without the `parallel` feature layers need not be thread safe.
+    #[cfg(not(feature = "parallel"))]
+    #[test]
+    fn test_unsync_layer() {
+        use std::marker::PhantomData;
+        use std::rc::Rc;
+        use learning::toolkit::activ_fn::ActivationFunc;
+
+        #[derive(Clone, Debug)]
+        struct Shared(PhantomData<Rc<()>>);
+
+        impl ActivationFunc for Shared {
+            fn func(x: f64) -> f64 { x }
+            fn func_grad(_: f64) -> f64 { 1.0 }
+            fn func_grad_from_output(_: f64) -> f64 { 1.0 }
+            fn func_inv(x: f64) -> f64 { x }
+        }
+
+        let mut net = small_net();
+        net.add(Box::new(Shared(PhantomData)));
+        let inputs = Matrix::new(2, 2, vec![0.1, 0.2, 0.3, 0.4]);
+        let targets = Matrix::new(2, 1, vec![0.1, 0.2]);
+        net.train(&inputs, &targets).unwrap();
+    }
+
+    #[test]
+    fn test_train_misaligned_layers() {
+        let mut net = NeuralNet::new(MSECriterion::default(), StochasticGD::default());
+        net.add(Box::new(Linear::new(2, 3)))
+            .add(Box::new(Linear::new(4, 1)));
+        let inputs = Matrix::new(1, 2, vec![0.1, 0.2]);
+        let targets = Matrix::new(1, 1, vec![0.1]);
+
+        assert_invalid_data(net.train(&inputs, &targets), "Layer 1");
+    }
+
+    #[test]
+    fn test_mlp_layers() {
+        let mut net = NeuralNet::mlp(&[2, 4, 3],
+                                     MSECriterion::default(),
+                                     StochasticGD::new(0.1, 0.1, 5),
+                                     Sigmoid);
+        // A linear layer and an activation for each pair of layer sizes.
+        assert_eq!(net.base.layers.len(), 4);
+
+        let inputs = Matrix::new(2, 2, vec![0.1, 0.2, 0.3, 0.4]);
+        let targets = Matrix::new(2, 3, vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6]);
+        net.train(&inputs, &targets).unwrap();
+        assert_eq!(net.predict(&inputs).unwrap().cols(), 3);
+    }
+}
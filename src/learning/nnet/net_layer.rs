@@ -5,6 +5,7 @@ use linalg::{Matrix, MatrixSlice, BaseMatrix};
 use learning::LearningResult;
 use learning::error::{Error, ErrorKind};
 use learning::toolkit::activ_fn::ActivationFunc;
+use learning::toolkit::parallel::MaybeSync;
 
 use rand::thread_rng;
 use rand::distributions::Sample;
@@ -13,7 +14,10 @@ use rand::distributions::normal::Normal;
 use std::fmt::Debug;
 
 /// Trait for neural net layers
-pub trait NetLayer : Debug {
+///
+/// With the `parallel` feature layers must also be `Send + Sync`,
+/// see `MaybeSync`.
+pub trait NetLayer : Debug + MaybeSync {
     /// The result of propogating data forward through this layer
     fn forward(&self, input: &Matrix<f64>, params: MatrixSlice<f64>) -> LearningResult<Matrix<f64>>;
 
@@ -105,13 +109,17 @@ impl NetLayer for Linear {
     fn forward(&self, input: &Matrix<f64>, params: MatrixSlice<f64>) -> LearningResult<Matrix<f64>> {
         if self.has_bias {
             if input.cols()+1 != params.rows() {
-                Err(Error::new(ErrorKind::InvalidData, "The input had the wrong number of columns"))
+                Err(Error::new(ErrorKind::InvalidData,
+                               format!("The input had {} columns but {} were expected",
+                                       input.cols(), params.rows() - 1)))
             } else {
                 Ok(&Matrix::ones(input.rows(), 1).hcat(input) * &params)
             }
         } else {
             if input.cols() != params.rows() {
-                Err(Error::new(ErrorKind::InvalidData, "The input had the wrong number of columns"))
+                Err(Error::new(ErrorKind::InvalidData,
+                               format!("The input had {} columns but {} were expected",
+                                       input.cols(), params.rows())))
             } else {
                 Ok(input * &params)
             }
@@ -153,7 +161,7 @@ impl NetLayer for Linear {
     }
 }
 
-impl<T: ActivationFunc> NetLayer for T {
+impl<T: ActivationFunc + MaybeSync> NetLayer for T {
     /// Applies the activation function to each element of the input
     fn forward(&self, input: &Matrix<f64>, _: MatrixSlice<f64>) -> LearningResult<Matrix<f64>> {
         let mut output = Vec::with_capacity(input.rows()*input.cols());
@@ -0,0 +1,205 @@
+//! Neural Network Parameter Storage
+//!
+//! Contains the `ParamStore` which holds the parameters of each
+//! layer in a network as its own matrix. The optimizers work on a
+//! single flat parameter vector, the store converts between the two
+//! and hands out views of each layer's parameters.
+
+use linalg::{Matrix, MatrixSlice, BaseMatrix};
+
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
+
+/// Typed storage for the parameters of a network's layers.
+///
+/// Each layer owns a matrix of parameters with the shape given by
+/// `NetLayer::param_shape`. The flat ordering used by the optimizers
+/// is the row-major data of each layer's matrix, in layer order.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::learning::nnet::param_store::ParamStore;
+/// use rusty_machine::linalg::BaseMatrix;
+///
+/// let mut store = ParamStore::new();
+/// store.push(2, 3, vec![1.0; 6]);
+/// store.push(0, 0, vec![]);
+///
+/// assert_eq!(store.num_layers(), 2);
+/// assert_eq!(store.num_params(), 6);
+/// assert_eq!(store.layer(0).cols(), 3);
+/// assert!(store.get(2).is_none());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ParamStore {
+    params: Vec<Matrix<f64>>,
+}
+
+impl ParamStore {
+    /// Constructs an empty parameter store.
+    pub fn new() -> ParamStore {
+        ParamStore { params: Vec::new() }
+    }
+
+    /// Adds the parameters for a new layer to the end of the store.
+    ///
+    /// # Panics
+    ///
+    /// - The length of the data does not match `rows * cols`.
+    pub fn push(&mut self, rows: usize, cols: usize, data: Vec<f64>) {
+        assert!(rows * cols == data.len(),
+                "The layer parameters do not match the shape {}x{}.",
+                rows,
+                cols);
+        self.params.push(Matrix::new(rows, cols, data));
+    }
+
+    /// The number of layers in the store.
+    pub fn num_layers(&self) -> usize {
+        self.params.len()
+    }
+
+    /// The total number of parameters over all layers.
+    pub fn num_params(&self) -> usize {
+        self.params.iter().map(|p| p.data().len()).sum()
+    }
+
+    /// The number of parameters of each layer.
+    pub fn layer_sizes(&self) -> Vec<usize> {
+        self.params.iter().map(|p| p.data().len()).collect()
+    }
+
+    /// Returns a view of the parameters of the layer at `idx`.
+    ///
+    /// Returns `None` if there is no such layer.
+    pub fn get(&self, idx: usize) -> Option<MatrixSlice<'_, f64>> {
+        self.params.get(idx).map(|p| p.as_slice())
+    }
+
+    /// Returns a view of the parameters of the layer at `idx`.
+    ///
+    /// # Panics
+    ///
+    /// - `idx` is not less than the number of layers.
+    pub fn layer(&self, idx: usize) -> MatrixSlice<'_, f64> {
+        self.get(idx).expect("The layer index is out of range.")
+    }
+
+    /// Returns the parameters of all layers as a flat vector.
+    pub fn to_vec(&self) -> Vec<f64> {
+        let mut flat = Vec::with_capacity(self.num_params());
+        for p in &self.params {
+            flat.extend_from_slice(p.data());
+        }
+        flat
+    }
+
+    /// Returns a store with the same layer shapes holding the given values.
+    ///
+    /// The values are copied straight into the new layer matrices, so
+    /// the parameters of this store are never cloned.
+    ///
+    /// # Failures
+    ///
+    /// - The number of values does not match the number of parameters.
+    pub fn with_values(&self, values: &[f64]) -> LearningResult<ParamStore> {
+        self.check_len(values.len())?;
+
+        let mut start = 0;
+        let params = self.params
+            .iter()
+            .map(|p| {
+                let end = start + p.data().len();
+                let layer = Matrix::new(p.rows(), p.cols(), values[start..end].to_vec());
+                start = end;
+                layer
+            })
+            .collect();
+        Ok(ParamStore { params: params })
+    }
+
+    /// Overwrites the parameters of all layers from a flat vector.
+    ///
+    /// # Failures
+    ///
+    /// - The number of values does not match the number of parameters.
+    pub fn set_values(&mut self, values: &[f64]) -> LearningResult<()> {
+        self.check_len(values.len())?;
+
+        let mut start = 0;
+        for p in &mut self.params {
+            let end = start + p.data().len();
+            p.mut_data().copy_from_slice(&values[start..end]);
+            start = end;
+        }
+        Ok(())
+    }
+
+    /// Checks that a flat vector has one value for each parameter.
+    fn check_len(&self, len: usize) -> LearningResult<()> {
+        if len != self.num_params() {
+            Err(Error::new(ErrorKind::InvalidParameters,
+                           format!("Expected {} parameters but got {}.",
+                                   self.num_params(),
+                                   len)))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParamStore;
+    use linalg::BaseMatrix;
+
+    #[test]
+    fn test_flat_round_trip() {
+        let mut store = ParamStore::new();
+        store.push(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+        store.push(0, 0, vec![]);
+        store.push(3, 1, vec![5.0, 6.0, 7.0]);
+
+        assert_eq!(store.to_vec(), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+        assert_eq!(store.layer_sizes(), vec![4, 0, 3]);
+
+        let other = store.with_values(&[7.0, 6.0, 5.0, 4.0, 3.0, 2.0, 1.0]).unwrap();
+        let layer = other.layer(2);
+        assert_eq!(layer.rows(), 3);
+        assert_eq!(layer.iter().cloned().collect::<Vec<_>>(), vec![3.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn test_with_values_keeps_shapes() {
+        let mut store = ParamStore::new();
+        store.push(2, 2, vec![0.0; 4]);
+        store.push(0, 0, vec![]);
+        store.push(1, 3, vec![0.0; 3]);
+
+        let other = store.with_values(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]).unwrap();
+        assert_eq!(other.num_layers(), 3);
+        assert_eq!(other.layer(0)[[1, 0]], 3.0);
+        assert_eq!(other.layer(1).rows(), 0);
+        assert_eq!(other.layer(2).iter().cloned().collect::<Vec<_>>(), vec![5.0, 6.0, 7.0]);
+
+        // The original parameters are left untouched.
+        assert_eq!(store.to_vec(), vec![0.0; 7]);
+    }
+
+    #[test]
+    fn test_wrong_value_count() {
+        let mut store = ParamStore::new();
+        store.push(2, 2, vec![1.0; 4]);
+
+        assert!(store.with_values(&[1.0; 3]).is_err());
+        assert!(store.set_values(&[1.0; 5]).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_push_wrong_shape() {
+        let mut store = ParamStore::new();
+        store.push(2, 2, vec![1.0; 3]);
+    }
+}
@@ -0,0 +1,460 @@
+//! OPTICS Clustering
+//!
+//! Provides an implementation of OPTICS (Ordering Points To Identify
+//! the Clustering Structure) clustering.
+//!
+//! Like DBSCAN the algorithm is controlled by a `min_points` parameter,
+//! but rather than a single clustering it computes an ordering of the
+//! points along with their reachability distances. Clusters are valleys
+//! in the reachability plot, so a single ordering describes the
+//! DBSCAN clusterings for every `eps` up to `max_eps`.
+//!
+//! Training extracts the clusters at the model's `eps`, matching DBSCAN
+//! up to the assignment of border points. Other clusterings can be
+//! extracted from the trained model with `extract_dbscan`.
+//!
+//! Noise points are set to `None` in the clusters `Vector`. The model
+//! also computes the local outlier factor of each training point, which
+//! is close to 1 within clusters and larger for outliers.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::optics::OPTICS;
+//! use rusty_machine::learning::UnSupModel;
+//! use rusty_machine::linalg::Matrix;
+//!
+//! let inputs = Matrix::new(6, 2, vec![1.0, 2.0,
+//!                                     1.1, 2.2,
+//!                                     0.9, 1.9,
+//!                                     1.0, 2.1,
+//!                                     -2.0, 3.0,
+//!                                     -2.2, 3.1]);
+//!
+//! let mut model = OPTICS::new(0.5, 2);
+//! model.train(&inputs).unwrap();
+//!
+//! let clustering = model.clusters().unwrap();
+//! assert_eq!(clustering[0], clustering[3]);
+//!
+//! // Extract a coarser clustering without retraining.
+//! let coarse = model.extract_dbscan(5.0).unwrap();
+//! assert!(coarse.iter().all(|c| *c == Some(0)));
+//! ```
+
+use std::cmp::{self, Ordering};
+use std::collections::BinaryHeap;
+use std::f64;
+
+use learning::{LearningResult, UnSupModel};
+use learning::error::{Error, ErrorKind};
+use learning::knn::{KNearestSearch, MetricSearch};
+use learning::toolkit::distance::{Metric, Euclidean};
+
+use linalg::{Matrix, Vector, BaseMatrix};
+
+/// Small constant added to mean reachability distances when
+/// computing local reachability densities, to allow duplicate points.
+const LOF_EPS: f64 = 1e-10;
+
+/// OPTICS Model
+///
+/// Implements clustering using the OPTICS algorithm
+/// via the `UnSupModel` trait.
+#[derive(Debug)]
+pub struct OPTICS {
+    eps: f64,
+    max_eps: f64,
+    min_points: usize,
+    searcher: MetricSearch,
+    ordering: Option<Vec<usize>>,
+    reachability: Option<Vector<f64>>,
+    core_distances: Option<Vector<f64>>,
+    clusters: Option<Vector<Option<usize>>>,
+    outlier_scores: Option<Vector<f64>>,
+}
+
+/// Constructs an OPTICS model with the
+/// following parameters:
+///
+/// - `eps` : `0.5`
+/// - `max_eps` : `f64::INFINITY`
+/// - `min_points` : `5`
+/// - `metric` : `Euclidean`
+impl Default for OPTICS {
+    fn default() -> OPTICS {
+        OPTICS::new(0.5, 5)
+    }
+}
+
+/// A point waiting to be processed, ordered so that
+/// the heap pops the smallest reachability first.
+#[derive(Debug, PartialEq)]
+struct Seed {
+    reach: f64,
+    idx: usize,
+}
+
+impl Eq for Seed {}
+
+impl PartialOrd for Seed {
+    fn partial_cmp(&self, other: &Seed) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Seed {
+    fn cmp(&self, other: &Seed) -> Ordering {
+        other.reach
+            .partial_cmp(&self.reach)
+            .unwrap_or(Ordering::Equal)
+            .then(other.idx.cmp(&self.idx))
+    }
+}
+
+impl UnSupModel<Matrix<f64>, Vector<Option<usize>>> for OPTICS {
+    /// Train the model using input data.
+    fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
+        let n = inputs.rows();
+        if n == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "There are no training inputs."));
+        }
+
+        self.searcher.build(inputs.clone());
+
+        // The nearest neighbours of each point, including itself.
+        let neighbours = self.searcher.search_many(inputs, cmp::min(self.min_points + 1, n))?;
+
+        let core = neighbours.iter()
+            .map(|&(_, ref dist)| {
+                if self.min_points <= n && dist[self.min_points - 1] < self.max_eps {
+                    dist[self.min_points - 1]
+                } else {
+                    f64::INFINITY
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut reach = vec![f64::INFINITY; n];
+        let mut processed = vec![false; n];
+        let mut ordering = Vec::with_capacity(n);
+
+        for start in 0..n {
+            if processed[start] {
+                continue;
+            }
+
+            let mut seeds = BinaryHeap::new();
+            seeds.push(Seed {
+                reach: f64::INFINITY,
+                idx: start,
+            });
+
+            while let Some(Seed { idx: p, .. }) = seeds.pop() {
+                // Outdated seeds are popped after the point is processed.
+                if processed[p] {
+                    continue;
+                }
+                processed[p] = true;
+                ordering.push(p);
+
+                if core[p].is_finite() {
+                    let (idx, dist) = self.searcher
+                        .search_radius(inputs.row(p).raw_slice(), self.max_eps)?;
+
+                    for (o, d) in idx.into_iter().zip(dist.into_iter()) {
+                        let new_reach = core[p].max(d);
+                        if !processed[o] && new_reach < reach[o] {
+                            reach[o] = new_reach;
+                            seeds.push(Seed {
+                                reach: new_reach,
+                                idx: o,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        self.clusters = Some(extract_clusters(&ordering, &reach, &core, self.eps));
+        self.outlier_scores = Some(Vector::new(local_outlier_factors(neighbours,
+                                                                     self.min_points)));
+        self.ordering = Some(ordering);
+        self.reachability = Some(Vector::new(reach));
+        self.core_distances = Some(Vector::new(core));
+
+        Ok(())
+    }
+
+    /// Predict the clusters of new points.
+    ///
+    /// Each point is given the cluster of the closest training
+    /// point if it lies within `eps` of it, otherwise it is noise.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<Option<usize>>> {
+        if let Some(ref clusters) = self.clusters {
+            let classes = self.searcher
+                .search_many(inputs, 1)?
+                .into_iter()
+                .map(|(idx, dist)| if dist[0] < self.eps { clusters[idx[0]] } else { None })
+                .collect::<Vec<_>>();
+
+            Ok(Vector::new(classes))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+impl OPTICS {
+    /// Create a new OPTICS model with a given cluster extraction
+    /// distance and minimum points per cluster.
+    ///
+    /// Distances are measured with the Euclidean metric.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optics::OPTICS;
+    ///
+    /// let _ = OPTICS::new(0.5, 5);
+    /// ```
+    pub fn new(eps: f64, min_points: usize) -> OPTICS {
+        OPTICS::with_metric(eps, min_points, Euclidean)
+    }
+
+    /// Create a new OPTICS model with a given cluster extraction
+    /// distance, minimum points per cluster and distance metric.
+    ///
+    /// Neighbours are found with a `BallTree`, or by brute force for
+    /// distances which are not true metrics (like `Cosine`) as the tree
+    /// pruning would miss neighbours.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optics::OPTICS;
+    /// use rusty_machine::learning::toolkit::distance::Manhattan;
+    ///
+    /// let _ = OPTICS::with_metric(0.5, 5, Manhattan);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `eps` is not positive.
+    /// - `min_points` is zero.
+    pub fn with_metric<M: Metric + 'static>(eps: f64, min_points: usize, metric: M) -> OPTICS {
+        assert!(eps > 0f64, "The model epsilon must be positive.");
+        assert!(min_points > 0, "The minimum number of points must be positive.");
+
+        OPTICS {
+            eps: eps,
+            max_eps: f64::INFINITY,
+            min_points: min_points,
+            searcher: MetricSearch::with_metric(metric),
+            ordering: None,
+            reachability: None,
+            core_distances: None,
+            clusters: None,
+            outlier_scores: None,
+        }
+    }
+
+    /// Set the maximum distance at which points are considered neighbours.
+    ///
+    /// Smaller values speed up training, but clusters can only be
+    /// extracted at distances up to `max_eps`. Defaults to infinity.
+    ///
+    /// # Panics
+    ///
+    /// - `max_eps` is smaller than `eps`.
+    pub fn set_max_eps(&mut self, max_eps: f64) {
+        assert!(max_eps >= self.eps, "The maximum epsilon must be at least epsilon.");
+        self.max_eps = max_eps;
+    }
+
+    /// Return an Option pointing to the model clusters.
+    pub fn clusters(&self) -> Option<&Vector<Option<usize>>> {
+        self.clusters.as_ref()
+    }
+
+    /// The indices of the training points in the order they were processed.
+    pub fn ordering(&self) -> Option<&[usize]> {
+        self.ordering.as_ref().map(|o| &o[..])
+    }
+
+    /// The reachability distance of each training point.
+    ///
+    /// This is infinite for the first point processed in each
+    /// connected component.
+    pub fn reachability(&self) -> Option<&Vector<f64>> {
+        self.reachability.as_ref()
+    }
+
+    /// The core distance of each training point.
+    ///
+    /// This is the distance to its `min_points`-th nearest neighbour,
+    /// counting itself, and is infinite if it exceeds `max_eps`.
+    pub fn core_distances(&self) -> Option<&Vector<f64>> {
+        self.core_distances.as_ref()
+    }
+
+    /// The local outlier factor of each training point.
+    ///
+    /// The factor compares the density of a point with the density of
+    /// its `min_points` nearest neighbours. Values close to 1 indicate
+    /// inliers and larger values indicate outliers.
+    pub fn outlier_scores(&self) -> Option<&Vector<f64>> {
+        self.outlier_scores.as_ref()
+    }
+
+    /// Extract the DBSCAN clustering at the given distance
+    /// from the trained ordering.
+    ///
+    /// # Failures
+    ///
+    /// - The model has not been trained.
+    /// - `eps` is larger than `max_eps`.
+    pub fn extract_dbscan(&self, eps: f64) -> LearningResult<Vector<Option<usize>>> {
+        if eps > self.max_eps {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "Clusters cannot be extracted beyond the maximum epsilon."));
+        }
+
+        if let (&Some(ref ordering), &Some(ref reach), &Some(ref core)) =
+               (&self.ordering, &self.reachability, &self.core_distances) {
+            Ok(extract_clusters(ordering, reach.data(), core.data(), eps))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+/// Extract the DBSCAN clustering at distance `eps` from an ordering.
+///
+/// A point which is not reachable from its predecessors starts a
+/// new cluster if it is a core point, and is noise otherwise.
+fn extract_clusters(ordering: &[usize],
+                    reach: &[f64],
+                    core: &[f64],
+                    eps: f64)
+                    -> Vector<Option<usize>> {
+    let mut clusters = vec![None; ordering.len()];
+    let mut next_cluster = 0;
+    let mut current = None;
+
+    for &p in ordering {
+        if reach[p] < eps {
+            clusters[p] = current;
+        } else if core[p] < eps {
+            current = Some(next_cluster);
+            next_cluster += 1;
+            clusters[p] = current;
+        }
+    }
+
+    Vector::new(clusters)
+}
+
+/// Compute the local outlier factor of each point from its nearest
+/// neighbours, which include the point itself.
+fn local_outlier_factors(neighbours: Vec<(Vec<usize>, Vec<f64>)>, k: usize) -> Vec<f64> {
+    // Remove each point from its own neighbours.
+    let neighbours = neighbours.into_iter()
+        .enumerate()
+        .map(|(i, (mut idx, mut dist))| {
+            let pos = idx.iter().position(|&j| j == i).unwrap_or(idx.len() - 1);
+            idx.remove(pos);
+            dist.remove(pos);
+            idx.truncate(k);
+            dist.truncate(k);
+            (idx, dist)
+        })
+        .collect::<Vec<_>>();
+
+    let k_dist = neighbours.iter()
+        .map(|&(_, ref dist)| dist.last().cloned().unwrap_or(0f64))
+        .collect::<Vec<_>>();
+
+    let lrd = neighbours.iter()
+        .map(|&(ref idx, ref dist)| {
+            if idx.is_empty() {
+                return 1f64;
+            }
+            let total = idx.iter().zip(dist.iter()).fold(0f64, |s, (&o, &d)| s + k_dist[o].max(d));
+            1f64 / (total / idx.len() as f64 + LOF_EPS)
+        })
+        .collect::<Vec<_>>();
+
+    neighbours.iter()
+        .zip(lrd.iter())
+        .map(|(&(ref idx, _), &l)| {
+            if idx.is_empty() {
+                1f64
+            } else {
+                idx.iter().fold(0f64, |s, &o| s + lrd[o]) / (idx.len() as f64 * l)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BinaryHeap;
+    use std::f64;
+
+    use super::{OPTICS, Seed, extract_clusters, local_outlier_factors};
+    use learning::knn::MetricSearch;
+    use learning::toolkit::distance::{Cosine, Euclidean};
+
+    #[test]
+    fn test_seed_order() {
+        let mut heap = BinaryHeap::new();
+        heap.push(Seed { reach: 2.0, idx: 0 });
+        heap.push(Seed { reach: f64::INFINITY, idx: 1 });
+        heap.push(Seed { reach: 1.0, idx: 3 });
+        heap.push(Seed { reach: 1.0, idx: 2 });
+
+        let order = (0..4).map(|_| heap.pop().unwrap().idx).collect::<Vec<_>>();
+        assert_eq!(order, vec![2, 3, 0, 1]);
+    }
+
+    #[test]
+    fn test_extract_clusters() {
+        let ordering = [0, 1, 2, 3, 4, 5];
+        let reach = [f64::INFINITY, 0.2, 0.3, 2.0, 0.1, 3.0];
+        let core = [0.2, 0.2, 0.5, 0.1, 0.1, 2.0];
+
+        let clusters = extract_clusters(&ordering, &reach, &core, 1.0);
+        assert_eq!(clusters.into_vec(), vec![Some(0), Some(0), Some(0), Some(1), Some(1), None]);
+
+        let clusters = extract_clusters(&ordering, &reach, &core, 2.5);
+        assert_eq!(clusters.into_vec(),
+                   vec![Some(0), Some(0), Some(0), Some(0), Some(0), Some(1)]);
+    }
+
+    #[test]
+    fn test_local_outlier_factors() {
+        // Points at 0, 1, 2 and 10 with their two nearest neighbours.
+        let neighbours = vec![(vec![0, 1, 2], vec![0., 1., 2.]),
+                              (vec![1, 0, 2], vec![0., 1., 1.]),
+                              (vec![2, 1, 0], vec![0., 1., 2.]),
+                              (vec![3, 2, 1], vec![0., 8., 9.])];
+        let lof = local_outlier_factors(neighbours, 2);
+
+        assert!((lof[1] - 4. / 3.).abs() < 1e-6);
+        assert!(lof[3] > 4.);
+        assert!(lof.iter().take(3).all(|&l| l < 1.5));
+    }
+
+    #[test]
+    fn test_non_metric_uses_brute_force() {
+        match OPTICS::with_metric(0.5, 2, Cosine).searcher {
+            MetricSearch::Brute(_) => {}
+            MetricSearch::Tree(_) => panic!("Expected a brute force search."),
+        }
+        match OPTICS::with_metric(0.5, 2, Euclidean).searcher {
+            MetricSearch::Tree(_) => {}
+            MetricSearch::Brute(_) => panic!("Expected a ball tree search."),
+        }
+    }
+}
@@ -27,14 +27,65 @@
 //!
 //! - Conversion to Rust.
 //! - Length hard defaults to the max iterations.
+//! - Halve the step when the cost or gradient at a trial point is
+//!   non-finite, as in the later `minimize.m`.
+//! - Stop with an `ErrorKind::Divergence` error when the accepted cost
+//!   or the search direction becomes non-finite.
 
 use learning::optim::{Optimizable, OptimAlgorithm};
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
 use linalg::Vector;
 
 use std::cmp;
 use std::f64;
 
 
+/// Returns an error if the cost or search direction is non-finite.
+fn check_divergence(cost: f64, values: &Vector<f64>, iter: usize) -> LearningResult<()> {
+    if !cost.is_finite() {
+        Err(Error::new(ErrorKind::Divergence,
+                       format!("The optimization diverged: the cost became {} in iteration {}.",
+                               cost,
+                               iter)))
+    } else if !values.data().iter().all(|x| x.is_finite()) {
+        Err(Error::new(ErrorKind::Divergence,
+                       format!("The optimization diverged: the search direction \
+                                became non-finite in iteration {}.",
+                               iter)))
+    } else {
+        Ok(())
+    }
+}
+
+/// Takes a step of `z` along `s` from `x` and evaluates the model there.
+///
+/// While the cost or gradient is non-finite the step is halved, using up
+/// to `m` further evaluations. Returns the cost, the gradient and the step
+/// actually taken.
+fn step_and_eval<M: Optimizable>(model: &M,
+                                 inputs: &M::Inputs,
+                                 targets: &M::Targets,
+                                 x: &mut Vector<f64>,
+                                 s: &Vector<f64>,
+                                 mut z: f64,
+                                 m: &mut i32)
+                                 -> (f64, Vector<f64>, f64) {
+    *x += s * z;
+    loop {
+        let (f, df) = model.compute_grad(x.data(), inputs, targets);
+        let df = Vector::new(df);
+        if *m <= 0 || (f.is_finite() && df.data().iter().all(|x| x.is_finite())) {
+            return (f, df, z);
+        }
+
+        // Move back to the middle of the step and try again.
+        *m -= 1;
+        z /= 2f64;
+        *x += s * -z;
+    }
+}
+
 /// Conjugate Gradient Descent algorithm
 #[derive(Clone, Copy, Debug)]
 pub struct ConjugateGD {
@@ -86,7 +137,7 @@ impl<M: Optimizable> OptimAlgorithm<M> for ConjugateGD {
                 start: &[f64],
                 inputs: &M::Inputs,
                 targets: &M::Targets)
-                -> Vec<f64> {
+                -> LearningResult<Vec<f64>> {
         let mut i = 0usize;
         let mut ls_failed = false;
 
@@ -99,6 +150,7 @@ impl<M: Optimizable> OptimAlgorithm<M> for ConjugateGD {
         let length = self.iters as i32;
 
         let mut s = -df1.clone();
+        check_divergence(f1, &s, i)?;
         let mut d1 = -s.dot(&s);
         let mut z1 = red / (1f64 - d1);
 
@@ -113,11 +165,18 @@ impl<M: Optimizable> OptimAlgorithm<M> for ConjugateGD {
 
             let (x0, f0) = (x.clone(), f1);
 
-            x += &s * z1;
+            // The backoffs of the first step share the line search budget.
+            let mut m = if length > 0 {
+                self.max as i32
+            } else {
+                // The first step below counts as an evaluation.
+                cmp::min(self.max as i32, -length - (i as i32) - 1)
+            };
 
-            let cost = model.compute_grad(x.data(), inputs, targets);
+            let cost = step_and_eval(model, inputs, targets, &mut x, &s, z1, &mut m);
             f2 = cost.0;
-            df2 = Vector::new(cost.1);
+            df2 = cost.1;
+            z1 = cost.2;
 
             if length < 0 {
                 i += 1;
@@ -127,19 +186,14 @@ impl<M: Optimizable> OptimAlgorithm<M> for ConjugateGD {
 
             let (mut f3, mut d3, mut z3) = (f1, d1, -z1);
 
-            let mut m = if length > 0 {
-                self.max as i32
-            } else {
-                cmp::min(self.max as i32, -length - (i as i32))
-            };
-
             let mut success = false;
             let mut limit = -1f64;
 
             loop {
                 let mut z2: f64;
 
-                while ((f2 > (f1 + z1 * self.rho * d1)) || (d2 > -self.sig * d1)) && (m > 0i32) {
+                while (!f2.is_finite() || (f2 > (f1 + z1 * self.rho * d1)) ||
+                       (d2 > -self.sig * d1)) && (m > 0i32) {
 
                     limit = z1;
 
@@ -165,13 +219,13 @@ impl<M: Optimizable> OptimAlgorithm<M> for ConjugateGD {
                         z2 = self.int * z3;
                     }
 
-                    z1 += z2;
-                    x += &s * z2;
-                    let cost_grad = model.compute_grad(x.data(), inputs, targets);
+                    m -= 1i32;
+                    let cost_grad = step_and_eval(model, inputs, targets, &mut x, &s, z2, &mut m);
                     f2 = cost_grad.0;
-                    df2 = Vector::new(cost_grad.1);
+                    df2 = cost_grad.1;
+                    z2 = cost_grad.2;
+                    z1 += z2;
 
-                    m -= 1i32;
                     if length < 0 {
                         i += 1;
                     }
@@ -180,7 +234,7 @@ impl<M: Optimizable> OptimAlgorithm<M> for ConjugateGD {
                     z3 -= z2;
                 }
 
-                if f2 > f1 + z1 * self.rho * d1 || d2 > -self.sig * d1 {
+                if !f2.is_finite() || f2 > f1 + z1 * self.rho * d1 || d2 > -self.sig * d1 {
                     break;
                 } else if d2 > self.sig * d1 {
                     success = true;
@@ -211,15 +265,15 @@ impl<M: Optimizable> OptimAlgorithm<M> for ConjugateGD {
 
                 f3 = f2;
                 d3 = d2;
-                z3 = -z2;
-                z1 += z2;
-                x += &s * z2;
 
-                let cost_grad = model.compute_grad(x.data(), inputs, targets);
+                m -= 1;
+                let cost_grad = step_and_eval(model, inputs, targets, &mut x, &s, z2, &mut m);
                 f2 = cost_grad.0;
-                df2 = Vector::new(cost_grad.1);
+                df2 = cost_grad.1;
+                z2 = cost_grad.2;
+                z3 = -z2;
+                z1 += z2;
 
-                m -= 1;
                 if length < 0 {
                     i += 1;
                 }
@@ -239,6 +293,7 @@ impl<M: Optimizable> OptimAlgorithm<M> for ConjugateGD {
                     s = -&df1;
                     d2 = -s.dot(&s);
                 }
+                check_divergence(f1, &s, i)?;
 
                 let ratio = d1 / (d2 - f64::MIN_POSITIVE);
                 if self.ratio < ratio {
@@ -260,6 +315,7 @@ impl<M: Optimizable> OptimAlgorithm<M> for ConjugateGD {
                 df1 = df2;
 
                 s = -&df1;
+                check_divergence(f1, &s, i)?;
                 d1 = -s.dot(&s);
 
                 z1 = 1f64 / (1f64 - d1);
@@ -267,6 +323,6 @@ impl<M: Optimizable> OptimAlgorithm<M> for ConjugateGD {
             }
 
         }
-        x.into_vec()
+        Ok(x.into_vec())
     }
 }
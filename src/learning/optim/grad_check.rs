@@ -0,0 +1,399 @@
+//! Gradient Checking
+//!
+//! Utilities for verifying analytic gradients against central
+//! finite differences. Module contains the `gradient_check` function
+//! for models implementing the `Optimizable` trait and the
+//! `layer_gradient_check` function for individual `NetLayer`s.
+//!
+//! These are intended to be used when implementing new models,
+//! layers or criteria - a silent bug in the back propagation will
+//! show up as a large relative error in the affected parameter block.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::optim::grad_check::layer_gradient_check;
+//! use rusty_machine::learning::nnet::net_layer::Linear;
+//! use rusty_machine::linalg::Matrix;
+//!
+//! let layer = Linear::new(3, 2);
+//! let input = Matrix::new(2, 3, vec![0.1, -0.4, 0.3, 0.7, 0.2, -0.5]);
+//! let params = vec![0.2, -0.1, 0.5, 0.3, -0.2, 0.4, 0.1, -0.3];
+//!
+//! let check = layer_gradient_check(&layer, &input, &params, 1e-6).unwrap();
+//! assert!(check.max_error() < 1e-6);
+//! ```
+
+use learning::optim::Optimizable;
+use learning::nnet::net_layer::NetLayer;
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
+use linalg::{Matrix, BaseMatrix};
+
+/// Gradients smaller than this are compared absolutely rather than relatively.
+const ZERO_GRAD_FLOOR: f64 = 1e-8;
+
+/// The result of a gradient check.
+///
+/// Holds the maximum relative error between the analytic
+/// and finite difference gradients for each parameter block.
+#[derive(Clone, Debug)]
+pub struct GradCheck {
+    /// The maximum relative error within each parameter block.
+    pub block_errors: Vec<f64>,
+}
+
+impl GradCheck {
+    /// The maximum relative error over all parameter blocks.
+    ///
+    /// Returns `0` if there are no blocks.
+    pub fn max_error(&self) -> f64 {
+        self.block_errors.iter().fold(0f64, |acc, &x| acc.max(x))
+    }
+
+    /// Returns `true` if every block has relative error below `tol`.
+    pub fn passes(&self, tol: f64) -> bool {
+        self.block_errors.iter().all(|&x| x < tol)
+    }
+}
+
+/// The relative error between an analytic and numerical gradient.
+fn relative_error(analytic: f64, numerical: f64) -> f64 {
+    let scale = analytic.abs().max(numerical.abs()).max(ZERO_GRAD_FLOOR);
+    (analytic - numerical).abs() / scale
+}
+
+/// The max relative error of each block of consecutive gradients.
+fn block_errors(analytic: &[f64], numerical: &[f64], block_sizes: &[usize]) -> Vec<f64> {
+    let mut start = 0;
+    block_sizes.iter()
+        .map(|&size| {
+            let err = analytic[start..start + size]
+                .iter()
+                .zip(&numerical[start..start + size])
+                .fold(0f64, |acc, (&a, &n)| acc.max(relative_error(a, n)));
+            start += size;
+            err
+        })
+        .collect()
+}
+
+/// Compares `Optimizable::compute_grad` against central finite differences.
+///
+/// The parameters are split into consecutive blocks of the given sizes
+/// and the maximum relative error is reported for each block. The block
+/// sizes must sum to the number of parameters.
+///
+/// `eps` is the step size used for the finite differences.
+///
+/// # Failures
+///
+/// - The block sizes do not sum to the number of parameters.
+/// - `eps` is not positive.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::learning::optim::Optimizable;
+/// use rusty_machine::learning::optim::grad_check::gradient_check;
+/// use rusty_machine::linalg::Matrix;
+///
+/// // The cost (x - 3)^2 + y^2
+/// struct Quadratic;
+///
+/// impl Optimizable for Quadratic {
+///     type Inputs = Matrix<f64>;
+///     type Targets = Matrix<f64>;
+///
+///     fn compute_grad(&self, p: &[f64], _: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
+///         ((p[0] - 3.0) * (p[0] - 3.0) + p[1] * p[1],
+///          vec![2.0 * (p[0] - 3.0), 2.0 * p[1]])
+///     }
+/// }
+///
+/// let data = Matrix::zeros(1, 1);
+/// let check = gradient_check(&Quadratic, &[1.0, 2.0], &data, &data, &[1, 1], 1e-6).unwrap();
+///
+/// assert_eq!(check.block_errors.len(), 2);
+/// assert!(check.passes(1e-6));
+/// ```
+pub fn gradient_check<M: Optimizable>(model: &M,
+                                      params: &[f64],
+                                      inputs: &M::Inputs,
+                                      targets: &M::Targets,
+                                      block_sizes: &[usize],
+                                      eps: f64)
+                                      -> LearningResult<GradCheck> {
+    if eps <= 0f64 {
+        return Err(Error::new(ErrorKind::InvalidParameters,
+                              "The finite difference step (eps) must be positive."));
+    }
+
+    if block_sizes.iter().sum::<usize>() != params.len() {
+        return Err(Error::new(ErrorKind::InvalidParameters,
+                              "The block sizes must sum to the number of parameters."));
+    }
+
+    let (_, analytic) = model.compute_grad(params, inputs, targets);
+
+    let mut perturbed = params.to_vec();
+    let mut numerical = Vec::with_capacity(params.len());
+
+    for i in 0..params.len() {
+        perturbed[i] = params[i] + eps;
+        let (cost_plus, _) = model.compute_grad(&perturbed, inputs, targets);
+        perturbed[i] = params[i] - eps;
+        let (cost_minus, _) = model.compute_grad(&perturbed, inputs, targets);
+        perturbed[i] = params[i];
+
+        numerical.push((cost_plus - cost_minus) / (2f64 * eps));
+    }
+
+    Ok(GradCheck { block_errors: block_errors(&analytic, &numerical, block_sizes) })
+}
+
+/// Compares the `back_input` and `back_params` gradients of a layer against
+/// central finite differences.
+///
+/// The layer output is reduced to a scalar by a fixed weighted sum, whose
+/// gradient with respect to the output is passed to the layer's backward
+/// methods.
+///
+/// The returned `GradCheck` has two blocks: the first for the gradient
+/// with respect to the input and the second for the gradient with
+/// respect to the parameters. Layers without parameters always report
+/// zero error for the second block.
+///
+/// # Failures
+///
+/// - `eps` is not positive.
+/// - The parameters do not match the layer's `param_shape`.
+/// - The layer cannot propagate the input forward.
+pub fn layer_gradient_check(layer: &dyn NetLayer,
+                            input: &Matrix<f64>,
+                            params: &[f64],
+                            eps: f64)
+                            -> LearningResult<GradCheck> {
+    if eps <= 0f64 {
+        return Err(Error::new(ErrorKind::InvalidParameters,
+                              "The finite difference step (eps) must be positive."));
+    }
+
+    let shape = layer.param_shape();
+    if shape.0 * shape.1 != params.len() {
+        return Err(Error::new(ErrorKind::InvalidParameters,
+                              "The parameters do not match the layer's parameter shape."));
+    }
+
+    let param_mat = Matrix::new(shape.0, shape.1, params.to_vec());
+    let output = layer.forward(input, param_mat.as_slice())?;
+
+    // The objective is sum(out_weights .* output) so its output gradient is out_weights.
+    let out_weights = Matrix::new(output.rows(),
+                                  output.cols(),
+                                  (0..output.rows() * output.cols())
+                                      .map(|i| 0.5 + ((i % 7) as f64) / 7f64)
+                                      .collect::<Vec<_>>());
+    let objective = |out: &Matrix<f64>| -> f64 {
+        out.data().iter().zip(out_weights.data()).map(|(x, w)| x * w).sum()
+    };
+
+    let input_grad = layer.back_input(&out_weights, input, &output, param_mat.as_slice());
+    let param_grad = layer.back_params(&out_weights, input, &output, param_mat.as_slice());
+
+    let mut num_input_grad = Vec::with_capacity(input.data().len());
+    let mut perturbed = input.clone();
+    for i in 0..input.data().len() {
+        let x = input.data()[i];
+        perturbed.mut_data()[i] = x + eps;
+        let plus = objective(&layer.forward(&perturbed, param_mat.as_slice())?);
+        perturbed.mut_data()[i] = x - eps;
+        let minus = objective(&layer.forward(&perturbed, param_mat.as_slice())?);
+        perturbed.mut_data()[i] = x;
+
+        num_input_grad.push((plus - minus) / (2f64 * eps));
+    }
+
+    let mut num_param_grad = Vec::with_capacity(params.len());
+    let mut perturbed = param_mat.clone();
+    for i in 0..params.len() {
+        let p = params[i];
+        perturbed.mut_data()[i] = p + eps;
+        let plus = objective(&layer.forward(input, perturbed.as_slice())?);
+        perturbed.mut_data()[i] = p - eps;
+        let minus = objective(&layer.forward(input, perturbed.as_slice())?);
+        perturbed.mut_data()[i] = p;
+
+        num_param_grad.push((plus - minus) / (2f64 * eps));
+    }
+
+    let input_err = block_errors(input_grad.data(), &num_input_grad, &[num_input_grad.len()]);
+    let param_err = block_errors(param_grad.data(), &num_param_grad, &[num_param_grad.len()]);
+
+    Ok(GradCheck { block_errors: vec![input_err[0], param_err[0]] })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gradient_check, layer_gradient_check, relative_error};
+    use learning::optim::Optimizable;
+    use learning::nnet::{NeuralNet, MSECriterion, BCECriterion};
+    use learning::nnet::net_layer::{NetLayer, Linear};
+    use learning::optim::grad_desc::StochasticGD;
+    use learning::toolkit::activ_fn::{Sigmoid, Tanh, Exp};
+    use learning::toolkit::activ_fn;
+    use linalg::Matrix;
+
+    fn test_input() -> Matrix<f64> {
+        Matrix::new(3, 2, vec![0.3, -0.7, 1.2, 0.4, -0.5, 0.9])
+    }
+
+    struct Quadratic;
+
+    impl Optimizable for Quadratic {
+        type Inputs = Matrix<f64>;
+        type Targets = Matrix<f64>;
+
+        fn compute_grad(&self, p: &[f64], _: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
+            (p[0] * p[0] + 3.0 * p[1] * p[1] * p[1],
+             vec![2.0 * p[0], 9.0 * p[1] * p[1]])
+        }
+    }
+
+    struct WrongGrad;
+
+    impl Optimizable for WrongGrad {
+        type Inputs = Matrix<f64>;
+        type Targets = Matrix<f64>;
+
+        fn compute_grad(&self, p: &[f64], _: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
+            (p[0] * p[0] + p[1] * p[1], vec![2.0 * p[0], p[1]])
+        }
+    }
+
+    #[test]
+    fn test_relative_error() {
+        assert_eq!(relative_error(0.0, 0.0), 0.0);
+        assert!((relative_error(1.0, 0.5) - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_correct_gradient_passes() {
+        let data = Matrix::zeros(1, 1);
+        let check = gradient_check(&Quadratic, &[1.5, -0.5], &data, &data, &[1, 1], 1e-6)
+            .unwrap();
+
+        assert_eq!(check.block_errors.len(), 2);
+        assert!(check.passes(1e-6));
+    }
+
+    #[test]
+    fn test_wrong_gradient_fails() {
+        let data = Matrix::zeros(1, 1);
+        let check = gradient_check(&WrongGrad, &[1.5, -0.5], &data, &data, &[1, 1], 1e-6)
+            .unwrap();
+
+        assert!(check.block_errors[0] < 1e-6);
+        assert!(check.block_errors[1] > 0.1);
+    }
+
+    #[test]
+    fn test_bad_block_sizes() {
+        let data = Matrix::zeros(1, 1);
+        assert!(gradient_check(&Quadratic, &[1.5, -0.5], &data, &data, &[1], 1e-6).is_err());
+        assert!(gradient_check(&Quadratic, &[1.5, -0.5], &data, &data, &[2], 0.0).is_err());
+    }
+
+    #[test]
+    fn test_linear_layer_gradients() {
+        let layer = Linear::new(2, 3);
+        let params = layer.default_params();
+        let check = layer_gradient_check(&layer, &test_input(), &params, 1e-6).unwrap();
+
+        assert!(check.passes(1e-6), "{:?}", check);
+    }
+
+    #[test]
+    fn test_linear_without_bias_gradients() {
+        let layer = Linear::without_bias(2, 4);
+        let params = layer.default_params();
+        let check = layer_gradient_check(&layer, &test_input(), &params, 1e-6).unwrap();
+
+        assert!(check.passes(1e-6), "{:?}", check);
+    }
+
+    #[test]
+    fn test_activation_layer_gradients() {
+        let sig = layer_gradient_check(&Sigmoid, &test_input(), &[], 1e-6).unwrap();
+        assert!(sig.passes(1e-6), "{:?}", sig);
+
+        let tanh = layer_gradient_check(&Tanh, &test_input(), &[], 1e-6).unwrap();
+        assert!(tanh.passes(1e-6), "{:?}", tanh);
+
+        let exp = layer_gradient_check(&Exp, &test_input(), &[], 1e-6).unwrap();
+        assert!(exp.passes(1e-6), "{:?}", exp);
+
+        let lin = layer_gradient_check(&activ_fn::Linear, &test_input(), &[], 1e-6).unwrap();
+        assert!(lin.passes(1e-6), "{:?}", lin);
+    }
+
+    #[test]
+    fn test_layer_wrong_param_count() {
+        let layer = Linear::new(2, 3);
+        assert!(layer_gradient_check(&layer, &test_input(), &[0.1; 4], 1e-6).is_err());
+    }
+
+    #[test]
+    fn test_neural_net_gradients() {
+        let mut net = NeuralNet::new(MSECriterion::default(), StochasticGD::default());
+        net.add(Box::new(Linear::new(2, 4)))
+            .add(Box::new(Tanh))
+            .add(Box::new(Linear::new(4, 1)));
+
+        // The criteria gradients are per sample so we check on a single row.
+        let inputs = Matrix::new(1, 2, vec![0.3, -0.7]);
+        let targets = Matrix::new(1, 1, vec![0.2]);
+
+        let check = net.gradient_check(&inputs, &targets, 1e-6).unwrap();
+
+        assert_eq!(check.block_errors.len(), 3);
+        assert!(check.passes(1e-5), "{:?}", check);
+    }
+
+    #[test]
+    fn test_bce_neural_net_gradients() {
+        let mut net = NeuralNet::new(BCECriterion::default(), StochasticGD::default());
+        net.add(Box::new(Linear::new(2, 3)))
+            .add(Box::new(Sigmoid))
+            .add(Box::new(Linear::new(3, 2)))
+            .add(Box::new(Sigmoid));
+
+        let inputs = Matrix::new(1, 2, vec![0.3, -0.7]);
+        let targets = Matrix::new(1, 2, vec![1.0, 0.0]);
+
+        let check = net.gradient_check(&inputs, &targets, 1e-6).unwrap();
+
+        assert_eq!(check.block_errors.len(), 4);
+        assert!(check.passes(1e-5), "{:?}", check);
+    }
+
+    #[test]
+    fn test_neural_net_multi_row_gradients() {
+        let mut net = NeuralNet::new(MSECriterion::default(), StochasticGD::default());
+        net.add(Box::new(Linear::new(2, 4)))
+            .add(Box::new(Tanh))
+            .add(Box::new(Linear::new(4, 1)));
+
+        // Three copies of one row have the same mean cost as the row alone,
+        // but the per sample gradients sum to three times its gradient.
+        let inputs = Matrix::new(3, 2, vec![0.3, -0.7, 0.3, -0.7, 0.3, -0.7]);
+        let targets = Matrix::new(3, 1, vec![0.2, 0.2, 0.2]);
+
+        let check = net.gradient_check(&inputs, &targets, 1e-6).unwrap();
+
+        // The relative error of `3g` against `g` is `2/3`.
+        for err in check.block_errors {
+            assert!((err - 2.0 / 3.0).abs() < 1e-4, "{}", err);
+        }
+    }
+}
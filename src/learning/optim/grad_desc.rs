@@ -7,16 +7,110 @@
 //! Currently standard batch gradient descent is the only implemented
 //! optimization algorithm but there is flexibility to introduce new
 //! algorithms and git them into the same scheme easily.
+//!
+//! All of the algorithms support optional gradient clipping through
+//! `GradClip`, and stop with an `ErrorKind::Divergence` error if the
+//! cost or the gradient becomes non-finite.
 
 use learning::optim::{Optimizable, OptimAlgorithm};
 use linalg::Vector;
 use linalg::{Matrix, BaseMatrix};
 use rulinalg::utils;
 
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
 use learning::toolkit::rand_utils;
 
 const LEARNING_EPS: f64 = 1e-20;
 
+/// Gradient clipping applied before each parameter update.
+///
+/// Clipping keeps a single large gradient from throwing the
+/// parameters far from the current solution.
+#[derive(Clone, Copy, Debug)]
+pub enum GradClip {
+    /// No clipping.
+    None,
+    /// Rescale the gradient so that its Euclidean norm is at most the given value.
+    Norm(f64),
+    /// Clamp each component of the gradient to `[-v, v]`.
+    Value(f64),
+}
+
+impl GradClip {
+    /// Clip the gradient in place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::grad_desc::GradClip;
+    ///
+    /// let mut grad = vec![3.0, -4.0];
+    /// GradClip::Norm(1.0).clip(&mut grad);
+    ///
+    /// assert!((grad[0] - 0.6).abs() < 1e-12);
+    /// assert!((grad[1] + 0.8).abs() < 1e-12);
+    /// ```
+    pub fn clip(&self, grad: &mut [f64]) {
+        match *self {
+            GradClip::None => {}
+            GradClip::Norm(max_norm) => {
+                let norm = grad.iter().map(|x| x * x).sum::<f64>().sqrt();
+                if norm > max_norm {
+                    let scale = max_norm / norm;
+                    for x in grad.iter_mut() {
+                        *x *= scale;
+                    }
+                }
+            }
+            GradClip::Value(max_val) => {
+                for x in grad.iter_mut() {
+                    *x = x.max(-max_val).min(max_val);
+                }
+            }
+        }
+    }
+
+    /// Checks that the clipping threshold is positive.
+    fn validate(&self) {
+        match *self {
+            GradClip::None => {}
+            GradClip::Norm(x) | GradClip::Value(x) => {
+                assert!(x > 0f64, "The clipping threshold must be greater than 0.")
+            }
+        }
+    }
+}
+
+/// Returns an error if the cost or the gradient has become non-finite.
+///
+/// This must be checked before clipping, which would hide non-finite gradients.
+fn check_divergence(cost: f64, grad: &[f64], iter: usize) -> LearningResult<()> {
+    if !cost.is_finite() {
+        Err(Error::new(ErrorKind::Divergence,
+                       format!("The optimization diverged: the cost became {} in iteration {}.",
+                               cost,
+                               iter)))
+    } else if !grad.iter().all(|x| x.is_finite()) {
+        Err(Error::new(ErrorKind::Divergence,
+                       format!("The optimization diverged: the gradient became non-finite \
+                                in iteration {}.",
+                               iter)))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns an error if any of the final parameters are non-finite.
+fn check_params(params: Vector<f64>) -> LearningResult<Vec<f64>> {
+    if params.data().iter().all(|x| x.is_finite()) {
+        Ok(params.into_vec())
+    } else {
+        Err(Error::new(ErrorKind::Divergence,
+                       "The optimization diverged: the parameters became non-finite."))
+    }
+}
+
 /// Batch Gradient Descent algorithm
 #[derive(Clone, Copy, Debug)]
 pub struct GradientDesc {
@@ -24,6 +118,8 @@ pub struct GradientDesc {
     alpha: f64,
     /// The number of iterations to run.
     iters: usize,
+    /// The gradient clipping applied before each step.
+    clip: GradClip,
 }
 
 /// The default gradient descent algorithm.
@@ -37,6 +133,7 @@ impl Default for GradientDesc {
         GradientDesc {
             alpha: 0.003,
             iters: 1000,
+            clip: GradClip::None,
         }
     }
 }
@@ -61,8 +158,24 @@ impl GradientDesc {
         GradientDesc {
             alpha: alpha,
             iters: iters,
+            clip: GradClip::None,
         }
     }
+
+    /// Sets the gradient clipping used by the algorithm.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::grad_desc::{GradientDesc, GradClip};
+    ///
+    /// let gd = GradientDesc::new(0.3, 10000).with_clipping(GradClip::Norm(5.0));
+    /// ```
+    pub fn with_clipping(mut self, clip: GradClip) -> GradientDesc {
+        clip.validate();
+        self.clip = clip;
+        self
+    }
 }
 
 impl<M: Optimizable> OptimAlgorithm<M> for GradientDesc {
@@ -71,16 +184,18 @@ impl<M: Optimizable> OptimAlgorithm<M> for GradientDesc {
                 start: &[f64],
                 inputs: &M::Inputs,
                 targets: &M::Targets)
-                -> Vec<f64> {
+                -> LearningResult<Vec<f64>> {
 
         // Create the initial optimal parameters
         let mut optimizing_val = Vector::new(start.to_vec());
         // The cost at the start of each iteration
         let mut start_iter_cost = 0f64;
 
-        for _ in 0..self.iters {
+        for iter in 0..self.iters {
             // Compute the cost and gradient for the current parameters
-            let (cost, grad) = model.compute_grad(optimizing_val.data(), inputs, targets);
+            let (cost, mut grad) = model.compute_grad(optimizing_val.data(), inputs, targets);
+            check_divergence(cost, &grad, iter)?;
+            self.clip.clip(&mut grad);
 
             // Early stopping
             if (start_iter_cost - cost).abs() < LEARNING_EPS {
@@ -92,7 +207,7 @@ impl<M: Optimizable> OptimAlgorithm<M> for GradientDesc {
                 start_iter_cost = cost;
             }
         }
-        optimizing_val.into_vec()
+        check_params(optimizing_val)
     }
 }
 
@@ -107,6 +222,8 @@ pub struct StochasticGD {
     mu: f64,
     /// The number of passes through the data.
     iters: usize,
+    /// The gradient clipping applied before each step.
+    clip: GradClip,
 }
 
 /// The default Stochastic GD algorithm.
@@ -122,6 +239,7 @@ impl Default for StochasticGD {
             alpha: 10.0,
             mu: 10.0,
             iters: 100,
+            clip: GradClip::None,
         }
     }
 }
@@ -149,8 +267,26 @@ impl StochasticGD {
             alpha: alpha,
             mu: mu,
             iters: iters,
+            clip: GradClip::None,
         }
     }
+
+    /// Sets the gradient clipping used by the algorithm.
+    ///
+    /// The clipping is applied to each stochastic gradient.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::grad_desc::{StochasticGD, GradClip};
+    ///
+    /// let sgd = StochasticGD::default().with_clipping(GradClip::Value(1.0));
+    /// ```
+    pub fn with_clipping(mut self, clip: GradClip) -> StochasticGD {
+        clip.validate();
+        self.clip = clip;
+        self
+    }
 }
 
 impl<M> OptimAlgorithm<M> for StochasticGD
@@ -161,7 +297,7 @@ impl<M> OptimAlgorithm<M> for StochasticGD
                 start: &[f64],
                 inputs: &M::Inputs,
                 targets: &M::Targets)
-                -> Vec<f64> {
+                -> LearningResult<Vec<f64>> {
 
         // Create the initial optimal parameters
         let mut optimizing_val = Vector::new(start.to_vec());
@@ -173,16 +309,18 @@ impl<M> OptimAlgorithm<M> for StochasticGD
         // The cost at the start of each iteration
         let mut start_iter_cost = 0f64;
 
-        for _ in 0..self.iters {
+        for iter in 0..self.iters {
             // The cost at the end of each stochastic gd pass
             let mut end_cost = 0f64;
             // Permute the indices
             rand_utils::in_place_fisher_yates(&mut permutation);
             for i in &permutation {
                 // Compute the cost and gradient for this data pair
-                let (cost, vec_data) = model.compute_grad(optimizing_val.data(),
-                                                          &inputs.select_rows(&[*i]),
-                                                          &targets.select_rows(&[*i]));
+                let (cost, mut vec_data) = model.compute_grad(optimizing_val.data(),
+                                                              &inputs.select_rows(&[*i]),
+                                                              &targets.select_rows(&[*i]));
+                check_divergence(cost, &vec_data, iter)?;
+                self.clip.clip(&mut vec_data);
 
                 // Backup previous velocity
                 let prev_w = delta_w.clone();
@@ -205,7 +343,7 @@ impl<M> OptimAlgorithm<M> for StochasticGD
                 start_iter_cost = end_cost;
             }
         }
-        optimizing_val.into_vec()
+        check_params(optimizing_val)
     }
 }
 
@@ -217,6 +355,7 @@ pub struct AdaGrad {
     alpha: f64,
     tau: f64,
     iters: usize,
+    clip: GradClip,
 }
 
 impl AdaGrad {
@@ -240,8 +379,27 @@ impl AdaGrad {
             alpha: alpha,
             tau: tau,
             iters: iters,
+            clip: GradClip::None,
         }
     }
+
+    /// Sets the gradient clipping used by the algorithm.
+    ///
+    /// The clipping is applied to each stochastic gradient before
+    /// it is added to the adaptive scaling.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::grad_desc::{AdaGrad, GradClip};
+    ///
+    /// let gd = AdaGrad::new(0.5, 1.0, 100).with_clipping(GradClip::Norm(10.0));
+    /// ```
+    pub fn with_clipping(mut self, clip: GradClip) -> AdaGrad {
+        clip.validate();
+        self.clip = clip;
+        self
+    }
 }
 
 impl Default for AdaGrad {
@@ -250,6 +408,7 @@ impl Default for AdaGrad {
             alpha: 1f64,
             tau: 3f64,
             iters: 100,
+            clip: GradClip::None,
         }
     }
 }
@@ -260,7 +419,7 @@ impl<M: Optimizable<Inputs = Matrix<f64>, Targets = Matrix<f64>>> OptimAlgorithm
                 start: &[f64],
                 inputs: &M::Inputs,
                 targets: &M::Targets)
-                -> Vec<f64> {
+                -> LearningResult<Vec<f64>> {
 
         // Initialize the adaptive scaling
         let mut ada_s = Vector::zeros(start.len());
@@ -272,7 +431,7 @@ impl<M: Optimizable<Inputs = Matrix<f64>, Targets = Matrix<f64>>> OptimAlgorithm
         // The cost at the start of each iteration
         let mut start_iter_cost = 0f64;
 
-        for _ in 0..self.iters {
+        for iter in 0..self.iters {
             // The cost at the end of each stochastic gd pass
             let mut end_cost = 0f64;
             // Permute the indices
@@ -282,6 +441,8 @@ impl<M: Optimizable<Inputs = Matrix<f64>, Targets = Matrix<f64>>> OptimAlgorithm
                 let (cost, mut vec_data) = model.compute_grad(optimizing_val.data(),
                                                               &inputs.select_rows(&[*i]),
                                                               &targets.select_rows(&[*i]));
+                check_divergence(cost, &vec_data, iter)?;
+                self.clip.clip(&mut vec_data);
                 // Update the adaptive scaling by adding the gradient squared
                 utils::in_place_vec_bin_op(ada_s.mut_data(), &vec_data, |x, &y| *x += y * y);
 
@@ -304,7 +465,7 @@ impl<M: Optimizable<Inputs = Matrix<f64>, Targets = Matrix<f64>>> OptimAlgorithm
                 start_iter_cost = end_cost;
             }
         }
-        optimizing_val.into_vec()
+        check_params(optimizing_val)
     }
 }
 
@@ -321,6 +482,8 @@ pub struct RMSProp {
     epsilon: f64,
     /// The number of passes through the data
     iters: usize,
+    /// The gradient clipping applied before each step
+    clip: GradClip,
 }
 
 /// The default RMSProp configuration
@@ -337,7 +500,8 @@ impl Default for RMSProp {
             learning_rate: 11.0, //0.01
             decay_rate: 0.9,
             epsilon: 1.0e-5,
-            iters: 100
+            iters: 100,
+            clip: GradClip::None,
         }
     }
 }
@@ -363,9 +527,25 @@ impl RMSProp {
             decay_rate: decay_rate,
             learning_rate: learning_rate,
             epsilon: epsilon,
-            iters: iters
+            iters: iters,
+            clip: GradClip::None,
         }
     }
+
+    /// Sets the gradient clipping used by the algorithm.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::grad_desc::{RMSProp, GradClip};
+    ///
+    /// let rms = RMSProp::default().with_clipping(GradClip::Value(0.5));
+    /// ```
+    pub fn with_clipping(mut self, clip: GradClip) -> RMSProp {
+        clip.validate();
+        self.clip = clip;
+        self
+    }
 }
 
 impl<M> OptimAlgorithm<M> for RMSProp
@@ -375,7 +555,7 @@ impl<M> OptimAlgorithm<M> for RMSProp
                 start: &[f64],
                 inputs: &M::Inputs,
                 targets: &M::Targets)
-                -> Vec<f64> {
+                -> LearningResult<Vec<f64>> {
         // Initial parameters
         let mut params = Vector::new(start.to_vec());
         // Running average of squared gradients
@@ -386,15 +566,17 @@ impl<M> OptimAlgorithm<M> for RMSProp
         // The cost from the previous iteration
         let mut prev_cost = 0f64;
 
-        for _ in 0..self.iters {
+        for iter in 0..self.iters {
             // The cost at end of each pass
             let mut end_cost = 0f64;
             // Permute the vertices
             rand_utils::in_place_fisher_yates(&mut permutation);
             for i in &permutation {
-                let (cost, grad) = model.compute_grad(params.data(),
-                                                      &inputs.select_rows(&[*i]),
-                                                      &targets.select_rows(&[*i]));
+                let (cost, mut grad) = model.compute_grad(params.data(),
+                                                          &inputs.select_rows(&[*i]),
+                                                          &targets.select_rows(&[*i]));
+                check_divergence(cost, &grad, iter)?;
+                self.clip.clip(&mut grad);
 
                 let mut grad = Vector::new(grad);
                 let grad_squared = grad.clone().apply(&|x| x*x);
@@ -417,14 +599,14 @@ impl<M> OptimAlgorithm<M> for RMSProp
                 prev_cost = end_cost;
             }
         }
-        params.into_vec()
+        check_params(params)
     }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use super::{GradientDesc, StochasticGD, AdaGrad, RMSProp};
+    use super::{GradientDesc, StochasticGD, AdaGrad, RMSProp, GradClip};
 
     #[test]
     #[should_panic]
@@ -473,4 +655,34 @@ mod tests {
     fn rmsprop_neg_learning_rate() {
         let _ = RMSProp::new(0.5, -0.005, 1.0e-5, 0);
     }
+
+    #[test]
+    #[should_panic]
+    fn gd_neg_clip_norm() {
+        let _ = GradientDesc::default().with_clipping(GradClip::Norm(-1.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn stochastic_gd_zero_clip_value() {
+        let _ = StochasticGD::default().with_clipping(GradClip::Value(0.0));
+    }
+
+    #[test]
+    fn clip_by_norm() {
+        let mut grad = vec![3.0, 4.0];
+        GradClip::Norm(10.0).clip(&mut grad);
+        assert_eq!(grad, vec![3.0, 4.0]);
+
+        GradClip::Norm(2.5).clip(&mut grad);
+        assert!((grad[0] - 1.5).abs() < 1e-12);
+        assert!((grad[1] - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn clip_by_value() {
+        let mut grad = vec![-3.0, 0.5, 4.0];
+        GradClip::Value(1.0).clip(&mut grad);
+        assert_eq!(grad, vec![-1.0, 0.5, 1.0]);
+    }
 }
@@ -0,0 +1,274 @@
+//! Sparse Gaussian Processes
+//!
+//! Provides an inducing point approximation to Gaussian process
+//! regression for large datasets.
+//!
+//! The full `GaussianProcess` decomposes an n×n kernel matrix which
+//! costs O(n<sup>3</sup>). This model instead summarises the training data
+//! through m inducing inputs using the Fully Independent Training
+//! Conditional (FITC) approximation, reducing the cost of training to
+//! O(nm<sup>2</sup>).
+//!
+//! The inducing inputs can be a random subset of the training inputs,
+//! the centroids found by k-means, or supplied directly.
+//!
+//! # Usage
+//!
+//! ```
+//! use rusty_machine::learning::sparse_gp::{SparseGaussianProcess, InducingPoints};
+//! use rusty_machine::learning::gp::ConstMean;
+//! use rusty_machine::learning::toolkit::kernel;
+//! use rusty_machine::learning::SupModel;
+//! use rusty_machine::linalg::{Matrix, Vector};
+//!
+//! let inputs = Matrix::new(8, 1, vec![0., 1., 2., 3., 4., 5., 6., 7.]);
+//! let targets = Vector::new(vec![0., 1., 2., 3., 3., 2., 1., 0.]);
+//!
+//! // A sparse GP with 4 inducing points chosen by k-means.
+//! let mut gaussp = SparseGaussianProcess::new(kernel::SquaredExp::default(),
+//!                                             ConstMean::default(),
+//!                                             0.1,
+//!                                             InducingPoints::KMeans(4));
+//!
+//! gaussp.train(&inputs, &targets).unwrap();
+//!
+//! let test_inputs = Matrix::new(3, 1, vec![1.5, 3.5, 5.5]);
+//! let outputs = gaussp.predict(&test_inputs).unwrap();
+//! let (mean, cov) = gaussp.get_posterior(&test_inputs).unwrap();
+//! ```
+
+use learning::gp::MeanFunc;
+use learning::k_means::KMeansClassifier;
+use learning::toolkit::kernel::{Kernel, kernel_matrix};
+use learning::toolkit::rand_utils;
+use linalg::{Matrix, BaseMatrix, Decomposition, Cholesky};
+use linalg::Vector;
+use learning::{LearningResult, SupModel, UnSupModel};
+use learning::error::{Error, ErrorKind};
+
+/// The jitter added to the diagonal of the inducing kernel matrix.
+const JITTER: f64 = 1e-8;
+
+/// The method used to choose the inducing inputs.
+#[derive(Clone, Debug)]
+pub enum InducingPoints {
+    /// A random subset of the training inputs of the given size.
+    ///
+    /// All of the training inputs are used if there are fewer of them.
+    Subset(usize),
+    /// The centroids found by k-means with the given number of clusters.
+    KMeans(usize),
+    /// Fixed inducing inputs, one per row.
+    Fixed(Matrix<f64>),
+}
+
+/// Sparse Gaussian Process struct
+///
+/// Gaussian process regression using the FITC inducing point approximation
+/// with generic kernel and deterministic mean function.
+#[derive(Debug)]
+pub struct SparseGaussianProcess<T: Kernel, U: MeanFunc> {
+    ker: T,
+    mean: U,
+    /// The observation noise of the GP.
+    pub noise: f64,
+    /// The method used to choose the inducing inputs.
+    pub inducing: InducingPoints,
+    inducing_inputs: Option<Matrix<f64>>,
+    inducing_chol_inv: Option<Matrix<f64>>,
+    reduced_chol_inv: Option<Matrix<f64>>,
+    alpha: Option<Vector<f64>>,
+}
+
+impl<T: Kernel, U: MeanFunc> SparseGaussianProcess<T, U> {
+    /// Construct a new Sparse Gaussian Process.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gp::ConstMean;
+    /// use rusty_machine::learning::sparse_gp::{SparseGaussianProcess, InducingPoints};
+    /// use rusty_machine::learning::toolkit::kernel;
+    ///
+    /// let ker = kernel::SquaredExp::default();
+    /// let mean = ConstMean::default();
+    /// let gaussp = SparseGaussianProcess::new(ker, mean, 1e-3f64, InducingPoints::Subset(50));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - The number of inducing points is zero.
+    pub fn new(ker: T, mean: U, noise: f64, inducing: InducingPoints) -> SparseGaussianProcess<T, U> {
+        match inducing {
+            InducingPoints::Subset(m) |
+            InducingPoints::KMeans(m) => assert!(m > 0, "At least one inducing point is required."),
+            InducingPoints::Fixed(ref z) => {
+                assert!(z.rows() > 0, "At least one inducing point is required.")
+            }
+        }
+
+        SparseGaussianProcess {
+            ker: ker,
+            mean: mean,
+            noise: noise,
+            inducing: inducing,
+            inducing_inputs: None,
+            inducing_chol_inv: None,
+            reduced_chol_inv: None,
+            alpha: None,
+        }
+    }
+
+    /// The inducing inputs used by the trained model.
+    pub fn inducing_inputs(&self) -> Option<&Matrix<f64>> {
+        self.inducing_inputs.as_ref()
+    }
+
+    /// Chooses the inducing inputs for the given training inputs.
+    fn select_inducing(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        match self.inducing {
+            InducingPoints::Subset(m) => {
+                if m >= inputs.rows() {
+                    Ok(inputs.clone())
+                } else {
+                    let idxs = (0..inputs.rows()).collect::<Vec<_>>();
+                    let sample = rand_utils::reservoir_sample(&idxs, m);
+                    Ok(inputs.select_rows(&sample))
+                }
+            }
+            InducingPoints::KMeans(m) => {
+                let mut model = KMeansClassifier::new(m);
+                model.train(inputs)?;
+                model.centroids()
+                    .clone()
+                    .ok_or_else(Error::new_untrained)
+            }
+            InducingPoints::Fixed(ref z) => Ok(z.clone()),
+        }
+    }
+}
+
+/// Computes the inverse of a lower triangular matrix.
+fn lower_tri_inverse(l: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+    let n = l.rows();
+    let mut inv_t = Vec::with_capacity(n * n);
+    for j in 0..n {
+        let mut e_j = Vector::zeros(n);
+        e_j[j] = 1f64;
+        inv_t.extend(l.solve_l_triangular(e_j)?.into_vec());
+    }
+    Ok(Matrix::new(n, n, inv_t).transpose())
+}
+
+/// Computes the Cholesky factor of a positive definite matrix.
+fn cholesky(mat: Matrix<f64>) -> LearningResult<Matrix<f64>> {
+    Cholesky::decompose(mat)
+        .map(|c| c.unpack())
+        .map_err(|_| {
+            Error::new(ErrorKind::InvalidState,
+                       "Could not compute Cholesky decomposition.")
+        })
+}
+
+impl<T: Kernel, U: MeanFunc> SupModel<Matrix<f64>, Vector<f64>> for SparseGaussianProcess<T, U> {
+    /// Predict output from inputs.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if let (&Some(ref z), &Some(ref alpha)) = (&self.inducing_inputs, &self.alpha) {
+            let mean = self.mean.func(inputs.clone());
+            Ok(mean + kernel_matrix(&self.ker, inputs, z)? * alpha)
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// Train the model using data and outputs.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        let n = inputs.rows();
+        if targets.size() != n {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets do not match."));
+        }
+
+        let z = self.select_inducing(inputs)?;
+        let m = z.rows();
+
+        let inducing_mat = kernel_matrix(&self.ker, &z, &z)? + Matrix::identity(m) * JITTER;
+        let lm_inv = lower_tri_inverse(&cholesky(inducing_mat)?)?;
+
+        // V = L_m^-1 K_mn, so that Q_nn = V^T V.
+        let v = &lm_inv * kernel_matrix(&self.ker, &z, inputs)?;
+
+        // The FITC diagonal, diag(K_nn - Q_nn) + noise.
+        let mut lambda = Vec::with_capacity(n);
+        for (i, row) in inputs.row_iter().enumerate() {
+            let q = (0..m).map(|j| v[[j, i]] * v[[j, i]]).sum::<f64>();
+            let l = self.ker.kernel(row.raw_slice(), row.raw_slice()) - q + self.noise;
+            if l <= 0f64 {
+                return Err(Error::new(ErrorKind::InvalidState,
+                                      "The FITC diagonal is not positive. Try increasing the \
+                                       noise."));
+            }
+            lambda.push(l);
+        }
+
+        // Scale the columns of V and the residuals by Lambda^-1/2.
+        let mut v_scaled = v.into_vec();
+        for row in v_scaled.chunks_mut(n) {
+            for (x, l) in row.iter_mut().zip(&lambda) {
+                *x /= l.sqrt();
+            }
+        }
+        let v_scaled = Matrix::new(m, n, v_scaled);
+
+        let residuals = targets - self.mean.func(inputs.clone());
+        let r = Vector::new(residuals.iter()
+            .zip(&lambda)
+            .map(|(y, l)| y / l.sqrt())
+            .collect::<Vec<_>>());
+
+        let reduced_mat = Matrix::identity(m) + &v_scaled * v_scaled.transpose();
+        let lb_inv = lower_tri_inverse(&cholesky(reduced_mat)?)?;
+
+        let b = &lb_inv * (&v_scaled * r);
+        let alpha = lm_inv.transpose() * (lb_inv.transpose() * b);
+
+        self.inducing_inputs = Some(z);
+        self.inducing_chol_inv = Some(lm_inv);
+        self.reduced_chol_inv = Some(lb_inv);
+        self.alpha = Some(alpha);
+
+        Ok(())
+    }
+}
+
+impl<T: Kernel, U: MeanFunc> SparseGaussianProcess<T, U> {
+    /// Compute the posterior distribution [UNSTABLE]
+    ///
+    /// Requires the model to be trained first.
+    ///
+    /// Outputs the posterior mean and covariance matrix.
+    pub fn get_posterior(&self,
+                         inputs: &Matrix<f64>)
+                         -> LearningResult<(Vector<f64>, Matrix<f64>)> {
+        if let (&Some(ref z), &Some(ref lm_inv), &Some(ref lb_inv), &Some(ref alpha)) =
+               (&self.inducing_inputs,
+                &self.inducing_chol_inv,
+                &self.reduced_chol_inv,
+                &self.alpha) {
+            let mean = self.mean.func(inputs.clone());
+            let test_mat = kernel_matrix(&self.ker, inputs, z)?;
+
+            let post_mean = mean + &test_mat * alpha;
+
+            let w = lm_inv * test_mat.transpose();
+            let u = lb_inv * &w;
+
+            let post_var = kernel_matrix(&self.ker, inputs, inputs)? - w.transpose() * &w +
+                           u.transpose() * &u;
+
+            Ok((post_mean, post_var))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
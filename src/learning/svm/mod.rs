@@ -0,0 +1,344 @@
+//! Support Vector Machine Module
+//!
+//! Contains implementation of Support Vector Machine using the
+//! [Pegasos training algorithm](http://ttic.uchicago.edu/~nati/Publications/PegasosMPB.pdf).
+//!
+//! Also contains the `SoftMarginSVM` which solves the soft margin dual
+//! problem exactly with Sequential Minimal Optimization (SMO). It keeps
+//! only the support vectors and exposes the decision values.
+//!
+//! These SVM models only support binary classification. Multi-class
+//! classification is provided by the wrappers in the `multiclass` module
+//! and regression by the `svr` module.
+//! The model inputs should be a matrix and the training targets are
+//! in the form of a vector of `-1`s and `1`s.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::svm::SVM;
+//! use rusty_machine::learning::SupModel;
+//!
+//! use rusty_machine::linalg::Matrix;
+//! use rusty_machine::linalg::Vector;
+//!
+//! let inputs = Matrix::new(4,1,vec![1.0,3.0,5.0,7.0]);
+//! let targets = Vector::new(vec![-1.,-1.,1.,1.]);
+//!
+//! let mut svm_mod = SVM::default();
+//!
+//! // Train the model
+//! svm_mod.train(&inputs, &targets).unwrap();
+//!
+//! // Now we'll predict a new point
+//! let new_point = Matrix::new(1,1,vec![10.]);
+//! let output = svm_mod.predict(&new_point).unwrap();
+//!
+//! // Hopefully we classified our new point correctly!
+//! assert!(output[0] == 1f64, "Our classifier isn't very good!");
+//! ```
+//!
+//! The `SoftMarginSVM` is used in the same way.
+//!
+//! ```
+//! use rusty_machine::learning::svm::SoftMarginSVM;
+//! use rusty_machine::learning::toolkit::kernel::Linear;
+//! use rusty_machine::learning::SupModel;
+//!
+//! use rusty_machine::linalg::{BaseMatrix, Matrix};
+//! use rusty_machine::linalg::Vector;
+//!
+//! let inputs = Matrix::new(4,1,vec![1.0,3.0,5.0,7.0]);
+//! let targets = Vector::new(vec![-1.,-1.,1.,1.]);
+//!
+//! let mut svm_mod = SoftMarginSVM::new(Linear::default(), 10.);
+//! svm_mod.train(&inputs, &targets).unwrap();
+//!
+//! // Only the points on the margin are kept.
+//! println!("{} support vectors", svm_mod.support_vectors().unwrap().rows());
+//!
+//! // The signed distance from the boundary, scaled by the margin.
+//! let decision = svm_mod.decision_function(&Matrix::new(2,1,vec![4.,5.])).unwrap();
+//! assert!(decision[0].abs() < 1e-2);
+//! assert!((decision[1] - 1.).abs() < 1e-2);
+//! ```
+
+pub mod multiclass;
+pub mod svr;
+
+mod smo;
+
+
+use linalg::{Matrix, BaseMatrix};
+use linalg::Vector;
+
+use self::smo::{KernelCache, ClassQ};
+
+use learning::toolkit::kernel::{Kernel, SquaredExp, kernel_matrix};
+use learning::{LearningResult, SupModel};
+use learning::error::{Error, ErrorKind};
+
+use rand;
+use rand::Rng;
+
+/// Support Vector Machine
+#[derive(Debug)]
+pub struct SVM<K: Kernel> {
+    ker: K,
+    alpha: Option<Vector<f64>>,
+    train_inputs: Option<Matrix<f64>>,
+    train_targets: Option<Vector<f64>>,
+    lambda: f64,
+    /// Number of iterations for training.
+    pub optim_iters: usize,
+}
+
+/// The default Support Vector Machine.
+///
+/// The defaults are:
+///
+/// - `ker` = `SquaredExp::default()`
+/// - `lambda` = `0.3`
+/// - `optim_iters` = `100`
+impl Default for SVM<SquaredExp> {
+    fn default() -> SVM<SquaredExp> {
+        SVM {
+            ker: SquaredExp::default(),
+            alpha: None,
+            train_inputs: None,
+            train_targets: None,
+            lambda: 0.3f64,
+            optim_iters: 100,
+        }
+    }
+}
+
+impl<K: Kernel> SVM<K> {
+    /// Constructs an untrained SVM with specified
+    /// kernel and lambda which determins the hardness
+    /// of the margin.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::svm::SVM;
+    /// use rusty_machine::learning::toolkit::kernel::SquaredExp;
+    ///
+    /// let _ = SVM::new(SquaredExp::default(), 0.3);
+    /// ```
+    pub fn new(ker: K, lambda: f64) -> SVM<K> {
+        SVM {
+            ker: ker,
+            alpha: None,
+            train_inputs: None,
+            train_targets: None,
+            lambda: lambda,
+            optim_iters: 100,
+        }
+    }
+}
+
+/// Train the model using the Pegasos algorithm and
+/// predict the model output from new data.
+impl<K: Kernel> SupModel<Matrix<f64>, Vector<f64>> for SVM<K> {
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+        let full_inputs = ones.hcat(inputs);
+
+        if let (&Some(ref alpha), &Some(ref train_inputs), &Some(ref train_targets)) =
+               (&self.alpha, &self.train_inputs, &self.train_targets) {
+            let ker_mat = kernel_matrix(&self.ker, &full_inputs, train_inputs)?;
+            let weight_vec = alpha.elemul(train_targets) / self.lambda;
+
+            let plane_dist = ker_mat * weight_vec;
+
+            Ok(plane_dist.apply(&|d| d.signum()))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        let n = inputs.rows();
+
+        let mut rng = rand::thread_rng();
+
+        let mut alpha = vec![0f64; n];
+
+        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+        let full_inputs = ones.hcat(inputs);
+
+        for t in 0..self.optim_iters {
+            let i = rng.gen_range(0, n);
+            let row_i = full_inputs.select_rows(&[i]);
+            let sum = full_inputs.row_iter()
+                .fold(0f64, |sum, row| sum + self.ker.kernel(row_i.data(), row.raw_slice())) *
+                      targets[i] / (self.lambda * (t as f64));
+
+            if sum < 1f64 {
+                alpha[i] += 1f64;
+            }
+        }
+
+        self.alpha = Some(Vector::new(alpha) / (self.optim_iters as f64));
+        self.train_inputs = Some(full_inputs);
+        self.train_targets = Some(targets.clone());
+
+        Ok(())
+    }
+}
+
+/// Soft margin Support Vector Machine
+///
+/// Trained by solving the dual problem with Sequential Minimal
+/// Optimization. Only the support vectors, the training points
+/// with non-zero dual coefficients, are kept after training.
+#[derive(Clone, Debug)]
+pub struct SoftMarginSVM<K: Kernel> {
+    ker: K,
+    /// The penalty for points violating the margin.
+    pub c: f64,
+    /// The tolerance on the optimality conditions at which training stops.
+    pub tol: f64,
+    /// The maximum number of SMO iterations.
+    pub max_iters: usize,
+    /// The number of kernel matrix rows to cache during training.
+    pub cache_size: usize,
+    support_vectors: Option<Matrix<f64>>,
+    dual_coefs: Option<Vector<f64>>,
+    bias: Option<f64>,
+    iterations: Option<usize>,
+}
+
+/// The default soft margin Support Vector Machine.
+///
+/// The defaults are:
+///
+/// - `ker` = `SquaredExp::default()`
+/// - `c` = `1`
+/// - `tol` = `1e-3`
+/// - `max_iters` = `100000`
+/// - `cache_size` = `1000`
+impl Default for SoftMarginSVM<SquaredExp> {
+    fn default() -> SoftMarginSVM<SquaredExp> {
+        SoftMarginSVM::new(SquaredExp::default(), 1f64)
+    }
+}
+
+impl<K: Kernel> SoftMarginSVM<K> {
+    /// Constructs an untrained soft margin SVM with the specified
+    /// kernel and margin penalty `c`.
+    ///
+    /// Larger values of `c` give a harder margin.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::svm::SoftMarginSVM;
+    /// use rusty_machine::learning::toolkit::kernel::SquaredExp;
+    ///
+    /// let _ = SoftMarginSVM::new(SquaredExp::default(), 10.);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `c` is not positive.
+    pub fn new(ker: K, c: f64) -> SoftMarginSVM<K> {
+        assert!(c > 0f64, "The margin penalty must be positive.");
+
+        SoftMarginSVM {
+            ker: ker,
+            c: c,
+            tol: 1e-3,
+            max_iters: 100000,
+            cache_size: 1000,
+            support_vectors: None,
+            dual_coefs: None,
+            bias: None,
+            iterations: None,
+        }
+    }
+
+    /// The support vectors of the trained model, one per row.
+    pub fn support_vectors(&self) -> Option<&Matrix<f64>> {
+        self.support_vectors.as_ref()
+    }
+
+    /// The dual coefficients `y_i α_i` of each support vector.
+    pub fn dual_coefs(&self) -> Option<&Vector<f64>> {
+        self.dual_coefs.as_ref()
+    }
+
+    /// The bias term of the decision function.
+    pub fn bias(&self) -> Option<f64> {
+        self.bias
+    }
+
+    /// The number of SMO iterations taken by the last training.
+    ///
+    /// This is equal to `max_iters` if training did not converge.
+    pub fn iterations(&self) -> Option<usize> {
+        self.iterations
+    }
+
+    /// Computes the decision function at each input.
+    ///
+    /// This is `Σ y_i α_i k(x_i, x) + b` over the support vectors. The sign
+    /// gives the predicted class and values with magnitude less than one
+    /// lie within the margin.
+    pub fn decision_function(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if let (&Some(ref sv), &Some(ref coefs), Some(bias)) =
+               (&self.support_vectors, &self.dual_coefs, self.bias) {
+            let ker_mat = kernel_matrix(&self.ker, inputs, sv)?;
+            Ok((ker_mat * coefs).apply(&|d| d + bias))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+/// Train the model using SMO and predict the class from new data.
+impl<K: Kernel> SupModel<Matrix<f64>, Vector<f64>> for SoftMarginSVM<K> {
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        self.decision_function(inputs).map(|d| d.apply(&|x| if x >= 0f64 { 1f64 } else { -1f64 }))
+    }
+
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        let n = inputs.rows();
+        if targets.size() != n {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets do not match."));
+        }
+        if targets.iter().any(|&y| y != 1f64 && y != -1f64) {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The targets must all be 1 or -1."));
+        }
+        if !(targets.iter().any(|&y| y > 0f64) && targets.iter().any(|&y| y < 0f64)) {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The targets must contain both classes."));
+        }
+
+        let y = targets.data();
+        let solution = {
+            let cache = KernelCache::new(&self.ker, inputs, self.cache_size);
+            let mut q = ClassQ::new(cache, y);
+            smo::solve(&mut q,
+                       &vec![-1f64; n],
+                       y,
+                       &vec![self.c; n],
+                       vec![0f64; n],
+                       self.tol,
+                       self.max_iters)
+        };
+
+        let support = (0..n).filter(|&i| solution.alpha[i] > 0f64).collect::<Vec<_>>();
+        let coefs = support.iter().map(|&i| y[i] * solution.alpha[i]).collect::<Vec<_>>();
+
+        self.support_vectors = Some(inputs.select_rows(&support));
+        self.dual_coefs = Some(Vector::new(coefs));
+        self.bias = Some(-solution.rho);
+        self.iterations = Some(solution.iterations);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+//! Multi-class Support Vector Machines
+//!
+//! Wrappers which combine binary `SoftMarginSVM`s into multi-class
+//! classifiers. The targets are class indices.
+//!
+//! - `OneVsRestSVM` trains one model per class, separating it from all
+//!   other classes, and predicts the class with the largest decision value.
+//! - `OneVsOneSVM` trains one model per pair of classes and predicts the
+//!   class with the most votes.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::svm::SoftMarginSVM;
+//! use rusty_machine::learning::svm::multiclass::OneVsRestSVM;
+//! use rusty_machine::learning::toolkit::kernel::SquaredExp;
+//! use rusty_machine::learning::SupModel;
+//! use rusty_machine::linalg::{Matrix, Vector};
+//!
+//! let inputs = Matrix::new(6, 1, vec![0., 0.5, 5., 5.5, 10., 10.5]);
+//! let targets = Vector::new(vec![0, 0, 1, 1, 2, 2]);
+//!
+//! let mut model = OneVsRestSVM::new(SoftMarginSVM::new(SquaredExp::new(2., 1.), 10.));
+//! model.train(&inputs, &targets).unwrap();
+//!
+//! let classes = model.predict(&Matrix::new(2, 1, vec![0.2, 9.8])).unwrap();
+//! assert_eq!(classes.into_vec(), vec![0, 2]);
+//! ```
+
+use std::f64;
+
+use linalg::{Matrix, BaseMatrix, Vector};
+use learning::{LearningResult, SupModel};
+use learning::error::{Error, ErrorKind};
+use learning::toolkit::kernel::Kernel;
+
+use super::SoftMarginSVM;
+
+/// The sorted distinct classes of the targets.
+fn distinct_classes(inputs: &Matrix<f64>, targets: &Vector<usize>) -> LearningResult<Vec<usize>> {
+    if inputs.rows() != targets.size() {
+        return Err(Error::new(ErrorKind::InvalidData,
+                              "The number of inputs and targets do not match."));
+    }
+
+    let mut classes = targets.data().clone();
+    classes.sort_unstable();
+    classes.dedup();
+
+    if classes.len() < 2 {
+        return Err(Error::new(ErrorKind::InvalidData,
+                              "The targets must contain at least two classes."));
+    }
+    Ok(classes)
+}
+
+/// One-vs-rest multi-class Support Vector Machine
+///
+/// Trains a copy of the given binary model for each class.
+#[derive(Debug)]
+pub struct OneVsRestSVM<K: Kernel + Clone> {
+    base: SoftMarginSVM<K>,
+    classes: Option<Vec<usize>>,
+    models: Vec<SoftMarginSVM<K>>,
+}
+
+impl<K: Kernel + Clone> OneVsRestSVM<K> {
+    /// Constructs an untrained one-vs-rest model from an untrained binary model.
+    ///
+    /// The binary model's kernel and training options are used for every class.
+    pub fn new(base: SoftMarginSVM<K>) -> OneVsRestSVM<K> {
+        OneVsRestSVM {
+            base: base,
+            classes: None,
+            models: Vec::new(),
+        }
+    }
+
+    /// The classes seen during training, in the order of the decision values.
+    pub fn classes(&self) -> Option<&[usize]> {
+        self.classes.as_ref().map(|c| &c[..])
+    }
+
+    /// The binary model trained for each class.
+    pub fn models(&self) -> &[SoftMarginSVM<K>] {
+        &self.models
+    }
+
+    /// Computes the decision value of each class at each input.
+    ///
+    /// The output has a row for each input and a column for each class.
+    pub fn decision_function(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        if self.classes.is_none() {
+            return Err(Error::new_untrained());
+        }
+
+        let mut decisions = Vec::with_capacity(self.models.len() * inputs.rows());
+        for model in &self.models {
+            decisions.extend(model.decision_function(inputs)?.into_vec());
+        }
+
+        Ok(Matrix::new(self.models.len(), inputs.rows(), decisions).transpose())
+    }
+}
+
+impl<K: Kernel + Clone> SupModel<Matrix<f64>, Vector<usize>> for OneVsRestSVM<K> {
+    /// Predicts the class with the largest decision value.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<usize>> {
+        let decisions = self.decision_function(inputs)?;
+        let classes = self.classes.as_ref().expect("Trained models have classes.");
+
+        Ok(Vector::new(decisions.row_iter()
+            .map(|row| {
+                let (idx, _) = row.raw_slice()
+                    .iter()
+                    .enumerate()
+                    .fold((0, f64::NEG_INFINITY),
+                          |(bi, bv), (i, &v)| if v > bv { (i, v) } else { (bi, bv) });
+                classes[idx]
+            })
+            .collect::<Vec<_>>()))
+    }
+
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<usize>) -> LearningResult<()> {
+        let classes = distinct_classes(inputs, targets)?;
+
+        let mut models = Vec::with_capacity(classes.len());
+        for &class in &classes {
+            let binary = targets.iter()
+                .map(|&t| if t == class { 1f64 } else { -1f64 })
+                .collect::<Vec<_>>();
+
+            let mut model = self.base.clone();
+            model.train(inputs, &Vector::new(binary))?;
+            models.push(model);
+        }
+
+        self.classes = Some(classes);
+        self.models = models;
+        Ok(())
+    }
+}
+
+/// One-vs-one multi-class Support Vector Machine
+///
+/// Trains a copy of the given binary model for each pair of classes,
+/// using only the inputs of those two classes. Ties in the vote are
+/// broken in favour of the smaller class.
+#[derive(Debug)]
+pub struct OneVsOneSVM<K: Kernel + Clone> {
+    base: SoftMarginSVM<K>,
+    classes: Option<Vec<usize>>,
+    // The models for each pair of indices into the classes.
+    models: Vec<((usize, usize), SoftMarginSVM<K>)>,
+}
+
+impl<K: Kernel + Clone> OneVsOneSVM<K> {
+    /// Constructs an untrained one-vs-one model from an untrained binary model.
+    ///
+    /// The binary model's kernel and training options are used for every pair.
+    pub fn new(base: SoftMarginSVM<K>) -> OneVsOneSVM<K> {
+        OneVsOneSVM {
+            base: base,
+            classes: None,
+            models: Vec::new(),
+        }
+    }
+
+    /// The classes seen during training.
+    pub fn classes(&self) -> Option<&[usize]> {
+        self.classes.as_ref().map(|c| &c[..])
+    }
+
+    /// The pairs of classes, in the order of the decision values.
+    ///
+    /// A positive decision value is a vote for the first class of the pair.
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        match self.classes {
+            Some(ref classes) => {
+                self.models.iter().map(|&((a, b), _)| (classes[a], classes[b])).collect()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Computes the decision value of each pair of classes at each input.
+    ///
+    /// The output has a row for each input and a column for each pair.
+    pub fn decision_function(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        if self.classes.is_none() {
+            return Err(Error::new_untrained());
+        }
+
+        let mut decisions = Vec::with_capacity(self.models.len() * inputs.rows());
+        for &(_, ref model) in &self.models {
+            decisions.extend(model.decision_function(inputs)?.into_vec());
+        }
+
+        Ok(Matrix::new(self.models.len(), inputs.rows(), decisions).transpose())
+    }
+}
+
+impl<K: Kernel + Clone> SupModel<Matrix<f64>, Vector<usize>> for OneVsOneSVM<K> {
+    /// Predicts the class with the most votes.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<usize>> {
+        let decisions = self.decision_function(inputs)?;
+        let classes = self.classes.as_ref().expect("Trained models have classes.");
+
+        Ok(Vector::new(decisions.row_iter()
+            .map(|row| {
+                let mut votes = vec![0usize; classes.len()];
+                for (&((a, b), _), &d) in self.models.iter().zip(row.raw_slice()) {
+                    votes[if d >= 0f64 { a } else { b }] += 1;
+                }
+
+                let (idx, _) = votes.iter().enumerate().fold((0, 0), |(bi, bv), (i, &v)| {
+                    if v > bv { (i, v) } else { (bi, bv) }
+                });
+                classes[idx]
+            })
+            .collect::<Vec<_>>()))
+    }
+
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<usize>) -> LearningResult<()> {
+        let classes = distinct_classes(inputs, targets)?;
+
+        let mut models = Vec::with_capacity(classes.len() * (classes.len() - 1) / 2);
+        for a in 0..classes.len() {
+            for b in a + 1..classes.len() {
+                let (idxs, binary): (Vec<usize>, Vec<f64>) = targets.iter()
+                    .enumerate()
+                    .filter(|&(_, &t)| t == classes[a] || t == classes[b])
+                    .map(|(i, &t)| (i, if t == classes[a] { 1f64 } else { -1f64 }))
+                    .unzip();
+
+                let mut model = self.base.clone();
+                model.train(&inputs.select_rows(&idxs), &Vector::new(binary))?;
+                models.push(((a, b), model));
+            }
+        }
+
+        self.classes = Some(classes);
+        self.models = models;
+        Ok(())
+    }
+}
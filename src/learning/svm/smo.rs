@@ -0,0 +1,407 @@
+//! Sequential Minimal Optimization
+//!
+//! A solver for the dual problems of support vector machines using the
+//! second order working set selection of Fan, Chen and Lin (2005), as
+//! in LIBSVM. It solves
+//!
+//! min<sub>a</sub> ½a<sup>T</sup>Qa + p<sup>T</sup>a subject to
+//! y<sup>T</sup>a = Δ and 0 ≤ a<sub>i</sub> ≤ C<sub>i</sub>
+//!
+//! where each y<sub>i</sub> is `1` or `-1`.
+
+use std::collections::{HashMap, VecDeque};
+use std::f64;
+use std::rc::Rc;
+
+use linalg::{Matrix, BaseMatrix};
+use learning::toolkit::kernel::Kernel;
+
+/// Used in place of non-positive curvatures.
+const TAU: f64 = 1e-12;
+
+/// A least recently used cache of kernel matrix rows.
+#[derive(Debug)]
+pub struct KernelCache<'a, K: 'a + Kernel> {
+    ker: &'a K,
+    rows: Vec<&'a [f64]>,
+    diag: Vec<f64>,
+    capacity: usize,
+    cache: HashMap<usize, Rc<Vec<f64>>>,
+    order: VecDeque<usize>,
+}
+
+impl<'a, K: Kernel> KernelCache<'a, K> {
+    /// Constructs a cache over the rows of `inputs` holding at most `capacity` kernel rows.
+    pub fn new(ker: &'a K, inputs: &'a Matrix<f64>, capacity: usize) -> KernelCache<'a, K> {
+        let rows = inputs.row_iter().map(|r| r.raw_slice()).collect::<Vec<_>>();
+        let diag = rows.iter().map(|r| ker.kernel(r, r)).collect();
+
+        KernelCache {
+            ker: ker,
+            rows: rows,
+            diag: diag,
+            capacity: capacity,
+            cache: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// The number of rows in the kernel matrix.
+    pub fn size(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The diagonal entry `K[i, i]`.
+    pub fn diag(&self, i: usize) -> f64 {
+        self.diag[i]
+    }
+
+    /// The `i`th row of the kernel matrix.
+    pub fn row(&mut self, i: usize) -> Rc<Vec<f64>> {
+        if let Some(row) = self.cache.get(&i) {
+            if let Some(pos) = self.order.iter().position(|&j| j == i) {
+                self.order.remove(pos);
+            }
+            self.order.push_back(i);
+            return row.clone();
+        }
+
+        let x = self.rows[i];
+        let row = Rc::new(self.rows.iter().map(|r| self.ker.kernel(x, r)).collect::<Vec<_>>());
+
+        if self.capacity > 0 {
+            if self.cache.len() >= self.capacity {
+                if let Some(old) = self.order.pop_front() {
+                    self.cache.remove(&old);
+                }
+            }
+            self.cache.insert(i, row.clone());
+            self.order.push_back(i);
+        }
+        row
+    }
+}
+
+/// The quadratic term of a dual problem.
+pub trait QMatrix {
+    /// The number of variables.
+    fn size(&self) -> usize;
+
+    /// The `i`th row of Q.
+    fn row(&mut self, i: usize) -> Vec<f64>;
+
+    /// The diagonal entry `Q[i, i]`.
+    fn diag(&self, i: usize) -> f64;
+}
+
+/// The dual of soft margin classification, `Q[i, j] = y_i y_j K[i, j]`.
+#[derive(Debug)]
+pub struct ClassQ<'a, K: 'a + Kernel> {
+    cache: KernelCache<'a, K>,
+    y: &'a [f64],
+}
+
+impl<'a, K: Kernel> ClassQ<'a, K> {
+    /// Constructs the quadratic term from a kernel cache and the labels.
+    pub fn new(cache: KernelCache<'a, K>, y: &'a [f64]) -> ClassQ<'a, K> {
+        ClassQ { cache: cache, y: y }
+    }
+}
+
+impl<'a, K: Kernel> QMatrix for ClassQ<'a, K> {
+    fn size(&self) -> usize {
+        self.cache.size()
+    }
+
+    fn row(&mut self, i: usize) -> Vec<f64> {
+        let yi = self.y[i];
+        self.cache.row(i).iter().zip(self.y).map(|(k, yj)| yi * yj * k).collect()
+    }
+
+    fn diag(&self, i: usize) -> f64 {
+        self.cache.diag(i)
+    }
+}
+
+/// The dual of epsilon support vector regression.
+///
+/// There are two variables for each training point, the first `n`
+/// with sign `1` and the last `n` with sign `-1`, so that
+/// `Q[i, j] = s_i s_j K[i mod n, j mod n]`.
+#[derive(Debug)]
+pub struct RegressionQ<'a, K: 'a + Kernel> {
+    cache: KernelCache<'a, K>,
+}
+
+impl<'a, K: Kernel> RegressionQ<'a, K> {
+    /// Constructs the quadratic term from a kernel cache.
+    pub fn new(cache: KernelCache<'a, K>) -> RegressionQ<'a, K> {
+        RegressionQ { cache: cache }
+    }
+}
+
+impl<'a, K: Kernel> QMatrix for RegressionQ<'a, K> {
+    fn size(&self) -> usize {
+        2 * self.cache.size()
+    }
+
+    fn row(&mut self, i: usize) -> Vec<f64> {
+        let n = self.cache.size();
+        let ker_row = self.cache.row(i % n);
+        let sign = if i < n { 1f64 } else { -1f64 };
+
+        let mut row = Vec::with_capacity(2 * n);
+        row.extend(ker_row.iter().map(|k| sign * k));
+        row.extend(ker_row.iter().map(|k| -sign * k));
+        row
+    }
+
+    fn diag(&self, i: usize) -> f64 {
+        self.cache.diag(i % self.cache.size())
+    }
+}
+
+/// The solution of a dual problem.
+#[derive(Debug)]
+pub struct Solution {
+    /// The optimal variables.
+    pub alpha: Vec<f64>,
+    /// The offset such that the decision function is `f(x) - rho`.
+    pub rho: f64,
+    /// The number of iterations taken.
+    pub iterations: usize,
+}
+
+/// Solves the dual problem from a feasible starting point `alpha`.
+pub fn solve<Q: QMatrix>(q: &mut Q,
+                         p: &[f64],
+                         y: &[f64],
+                         c: &[f64],
+                         mut alpha: Vec<f64>,
+                         tol: f64,
+                         max_iters: usize)
+                         -> Solution {
+    let n = q.size();
+
+    let mut grad = p.to_vec();
+    for (i, &a) in alpha.iter().enumerate() {
+        if a != 0f64 {
+            for (g, q_it) in grad.iter_mut().zip(q.row(i)) {
+                *g += a * q_it;
+            }
+        }
+    }
+
+    let is_upper = |a: f64, i: usize| a >= c[i];
+    let is_lower = |a: f64| a <= 0f64;
+
+    let mut iterations = 0;
+    while iterations < max_iters {
+        // Select i, the maximal violator.
+        let mut g_max = f64::NEG_INFINITY;
+        let mut i_idx = None;
+        for t in 0..n {
+            let movable = if y[t] > 0f64 {
+                !is_upper(alpha[t], t)
+            } else {
+                !is_lower(alpha[t])
+            };
+            if movable && -y[t] * grad[t] >= g_max {
+                g_max = -y[t] * grad[t];
+                i_idx = Some(t);
+            }
+        }
+
+        let i = match i_idx {
+            Some(i) => i,
+            None => break,
+        };
+        let q_i = q.row(i);
+
+        // Select j by the second order decrease of the objective.
+        let mut g_max2 = f64::NEG_INFINITY;
+        let mut obj_min = f64::INFINITY;
+        let mut j_idx = None;
+        for t in 0..n {
+            let movable = if y[t] > 0f64 {
+                !is_lower(alpha[t])
+            } else {
+                !is_upper(alpha[t], t)
+            };
+            if !movable {
+                continue;
+            }
+
+            let yg = y[t] * grad[t];
+            g_max2 = g_max2.max(yg);
+
+            let grad_diff = g_max + yg;
+            if grad_diff > 0f64 {
+                let mut quad = q.diag(i) + q.diag(t) - 2f64 * y[i] * y[t] * q_i[t];
+                if quad <= 0f64 {
+                    quad = TAU;
+                }
+                let obj = -grad_diff * grad_diff / quad;
+                if obj <= obj_min {
+                    obj_min = obj;
+                    j_idx = Some(t);
+                }
+            }
+        }
+
+        let j = match j_idx {
+            Some(j) if g_max + g_max2 >= tol => j,
+            _ => break,
+        };
+        let q_j = q.row(j);
+
+        iterations += 1;
+
+        let (old_i, old_j) = (alpha[i], alpha[j]);
+        let (c_i, c_j) = (c[i], c[j]);
+
+        if y[i] != y[j] {
+            let mut quad = q.diag(i) + q.diag(j) + 2f64 * q_i[j];
+            if quad <= 0f64 {
+                quad = TAU;
+            }
+            let delta = (-grad[i] - grad[j]) / quad;
+            let diff = alpha[i] - alpha[j];
+            alpha[i] += delta;
+            alpha[j] += delta;
+
+            if diff > 0f64 {
+                if alpha[j] < 0f64 {
+                    alpha[j] = 0f64;
+                    alpha[i] = diff;
+                }
+            } else if alpha[i] < 0f64 {
+                alpha[i] = 0f64;
+                alpha[j] = -diff;
+            }
+            if diff > c_i - c_j {
+                if alpha[i] > c_i {
+                    alpha[i] = c_i;
+                    alpha[j] = c_i - diff;
+                }
+            } else if alpha[j] > c_j {
+                alpha[j] = c_j;
+                alpha[i] = c_j + diff;
+            }
+        } else {
+            let mut quad = q.diag(i) + q.diag(j) - 2f64 * q_i[j];
+            if quad <= 0f64 {
+                quad = TAU;
+            }
+            let delta = (grad[i] - grad[j]) / quad;
+            let sum = alpha[i] + alpha[j];
+            alpha[i] -= delta;
+            alpha[j] += delta;
+
+            if sum > c_i {
+                if alpha[i] > c_i {
+                    alpha[i] = c_i;
+                    alpha[j] = sum - c_i;
+                }
+            } else if alpha[j] < 0f64 {
+                alpha[j] = 0f64;
+                alpha[i] = sum;
+            }
+            if sum > c_j {
+                if alpha[j] > c_j {
+                    alpha[j] = c_j;
+                    alpha[i] = sum - c_j;
+                }
+            } else if alpha[i] < 0f64 {
+                alpha[i] = 0f64;
+                alpha[j] = sum;
+            }
+        }
+
+        let (d_i, d_j) = (alpha[i] - old_i, alpha[j] - old_j);
+        for t in 0..n {
+            grad[t] += q_i[t] * d_i + q_j[t] * d_j;
+        }
+    }
+
+    let rho = compute_rho(&alpha, &grad, y, c);
+
+    Solution {
+        alpha: alpha,
+        rho: rho,
+        iterations: iterations,
+    }
+}
+
+/// Computes the offset from the free variables, or the midpoint
+/// of the feasible range if there are none.
+fn compute_rho(alpha: &[f64], grad: &[f64], y: &[f64], c: &[f64]) -> f64 {
+    let mut upper = f64::INFINITY;
+    let mut lower = f64::NEG_INFINITY;
+    let mut sum_free = 0f64;
+    let mut num_free = 0;
+
+    for t in 0..alpha.len() {
+        let yg = y[t] * grad[t];
+        if alpha[t] >= c[t] {
+            if y[t] < 0f64 {
+                upper = upper.min(yg);
+            } else {
+                lower = lower.max(yg);
+            }
+        } else if alpha[t] <= 0f64 {
+            if y[t] > 0f64 {
+                upper = upper.min(yg);
+            } else {
+                lower = lower.max(yg);
+            }
+        } else {
+            num_free += 1;
+            sum_free += yg;
+        }
+    }
+
+    if num_free > 0 {
+        sum_free / num_free as f64
+    } else {
+        (upper + lower) / 2f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KernelCache, ClassQ, solve};
+    use linalg::Matrix;
+    use learning::toolkit::kernel::Linear;
+
+    #[test]
+    fn test_kernel_cache_evicts_least_recent() {
+        let inputs = Matrix::new(3, 1, vec![1., 2., 3.]);
+        let ker = Linear::default();
+        let mut cache = KernelCache::new(&ker, &inputs, 2);
+
+        assert_eq!(*cache.row(0), vec![1., 2., 3.]);
+        assert_eq!(*cache.row(1), vec![2., 4., 6.]);
+        cache.row(0);
+        assert_eq!(*cache.row(2), vec![3., 6., 9.]);
+
+        assert!(cache.cache.contains_key(&0));
+        assert!(!cache.cache.contains_key(&1));
+        assert_eq!(cache.diag(2), 9.);
+    }
+
+    #[test]
+    fn test_solve_separable() {
+        // Two points at -1 and 1 give a margin of width 2 around 0.
+        let inputs = Matrix::new(2, 1, vec![-1., 1.]);
+        let y = vec![-1., 1.];
+        let ker = Linear::default();
+        let mut q = ClassQ::new(KernelCache::new(&ker, &inputs, 10), &y);
+
+        let sol = solve(&mut q, &[-1., -1.], &y, &[10., 10.], vec![0., 0.], 1e-6, 100);
+
+        assert!((sol.alpha[0] - 0.5).abs() < 1e-8);
+        assert!((sol.alpha[1] - 0.5).abs() < 1e-8);
+        assert!(sol.rho.abs() < 1e-8);
+    }
+}
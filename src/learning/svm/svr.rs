@@ -0,0 +1,182 @@
+//! Support Vector Regression
+//!
+//! Contains an implementation of epsilon support vector regression,
+//! trained by solving its dual problem with Sequential Minimal
+//! Optimization.
+//!
+//! Errors smaller than `epsilon` are ignored and larger errors are
+//! penalised linearly with weight `c`. Only the support vectors, the
+//! training points lying on or outside the epsilon tube, are kept.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::svm::svr::SVR;
+//! use rusty_machine::learning::toolkit::kernel::SquaredExp;
+//! use rusty_machine::learning::SupModel;
+//! use rusty_machine::linalg::{Matrix, Vector};
+//!
+//! let inputs = Matrix::new(6, 1, vec![0., 1., 2., 3., 4., 5.]);
+//! let targets = Vector::new(vec![0., 0.8, 0.9, 0.1, -0.8, -1.]);
+//!
+//! let mut svr = SVR::new(SquaredExp::new(1., 1.), 10., 0.1);
+//! svr.train(&inputs, &targets).unwrap();
+//!
+//! let outputs = svr.predict(&inputs).unwrap();
+//! for (y, t) in outputs.iter().zip(targets.iter()) {
+//!     assert!((y - t).abs() < 0.15);
+//! }
+//! ```
+
+use linalg::{Matrix, BaseMatrix, Vector};
+use learning::{LearningResult, SupModel};
+use learning::error::{Error, ErrorKind};
+use learning::toolkit::kernel::{Kernel, SquaredExp, kernel_matrix};
+
+use super::smo::{self, KernelCache, RegressionQ};
+
+/// Epsilon Support Vector Regression
+#[derive(Clone, Debug)]
+pub struct SVR<K: Kernel> {
+    ker: K,
+    /// The penalty for errors larger than `epsilon`.
+    pub c: f64,
+    /// The width of the tube within which errors are ignored.
+    pub epsilon: f64,
+    /// The tolerance on the optimality conditions at which training stops.
+    pub tol: f64,
+    /// The maximum number of SMO iterations.
+    pub max_iters: usize,
+    /// The number of kernel matrix rows to cache during training.
+    pub cache_size: usize,
+    support_vectors: Option<Matrix<f64>>,
+    dual_coefs: Option<Vector<f64>>,
+    bias: Option<f64>,
+    iterations: Option<usize>,
+}
+
+/// The default Support Vector Regression model.
+///
+/// The defaults are:
+///
+/// - `ker` = `SquaredExp::default()`
+/// - `c` = `1`
+/// - `epsilon` = `0.1`
+/// - `tol` = `1e-3`
+/// - `max_iters` = `100000`
+/// - `cache_size` = `1000`
+impl Default for SVR<SquaredExp> {
+    fn default() -> SVR<SquaredExp> {
+        SVR::new(SquaredExp::default(), 1f64, 0.1)
+    }
+}
+
+impl<K: Kernel> SVR<K> {
+    /// Constructs an untrained SVR with the specified kernel,
+    /// error penalty `c` and tube width `epsilon`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::svm::svr::SVR;
+    /// use rusty_machine::learning::toolkit::kernel::SquaredExp;
+    ///
+    /// let _ = SVR::new(SquaredExp::default(), 10., 0.05);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `c` is not positive.
+    /// - `epsilon` is negative.
+    pub fn new(ker: K, c: f64, epsilon: f64) -> SVR<K> {
+        assert!(c > 0f64, "The error penalty must be positive.");
+        assert!(epsilon >= 0f64, "The tube width must be non-negative.");
+
+        SVR {
+            ker: ker,
+            c: c,
+            epsilon: epsilon,
+            tol: 1e-3,
+            max_iters: 100000,
+            cache_size: 1000,
+            support_vectors: None,
+            dual_coefs: None,
+            bias: None,
+            iterations: None,
+        }
+    }
+
+    /// The support vectors of the trained model, one per row.
+    pub fn support_vectors(&self) -> Option<&Matrix<f64>> {
+        self.support_vectors.as_ref()
+    }
+
+    /// The dual coefficients `α_i - α*_i` of each support vector.
+    pub fn dual_coefs(&self) -> Option<&Vector<f64>> {
+        self.dual_coefs.as_ref()
+    }
+
+    /// The bias term of the regression function.
+    pub fn bias(&self) -> Option<f64> {
+        self.bias
+    }
+
+    /// The number of SMO iterations taken by the last training.
+    ///
+    /// This is equal to `max_iters` if training did not converge.
+    pub fn iterations(&self) -> Option<usize> {
+        self.iterations
+    }
+}
+
+impl<K: Kernel> SupModel<Matrix<f64>, Vector<f64>> for SVR<K> {
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if let (&Some(ref sv), &Some(ref coefs), Some(bias)) =
+               (&self.support_vectors, &self.dual_coefs, self.bias) {
+            let ker_mat = kernel_matrix(&self.ker, inputs, sv)?;
+            Ok((ker_mat * coefs).apply(&|d| d + bias))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        let n = inputs.rows();
+        if targets.size() != n {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets do not match."));
+        }
+        if n == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "There are no training inputs."));
+        }
+
+        // The first n variables are α and the last n are α*.
+        let mut p = Vec::with_capacity(2 * n);
+        p.extend(targets.iter().map(|y| self.epsilon - y));
+        p.extend(targets.iter().map(|y| self.epsilon + y));
+
+        let mut y = vec![1f64; n];
+        y.resize(2 * n, -1f64);
+
+        let solution = {
+            let mut q = RegressionQ::new(KernelCache::new(&self.ker, inputs, self.cache_size));
+            smo::solve(&mut q,
+                       &p,
+                       &y,
+                       &vec![self.c; 2 * n],
+                       vec![0f64; 2 * n],
+                       self.tol,
+                       self.max_iters)
+        };
+
+        let coefs = (0..n).map(|i| solution.alpha[i] - solution.alpha[i + n]).collect::<Vec<_>>();
+        let support = (0..n).filter(|&i| coefs[i] != 0f64).collect::<Vec<_>>();
+
+        self.support_vectors = Some(inputs.select_rows(&support));
+        self.dual_coefs = Some(Vector::new(support.iter().map(|&i| coefs[i]).collect::<Vec<_>>()));
+        self.bias = Some(-solution.rho);
+        self.iterations = Some(solution.iterations);
+
+        Ok(())
+    }
+}
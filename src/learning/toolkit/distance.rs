@@ -0,0 +1,307 @@
+//! Module for distance metrics
+//!
+//! Currently used within k-nearest neighbors search, DBSCAN and K-Means.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::toolkit::distance::{Metric, Euclidean, Manhattan, Chebyshev};
+//!
+//! let x = [0., 0.];
+//! let y = [3., 4.];
+//!
+//! assert_eq!(Euclidean.distance(&x, &y), 5.);
+//! assert_eq!(Manhattan.distance(&x, &y), 7.);
+//! assert_eq!(Chebyshev.distance(&x, &y), 4.);
+//! ```
+
+use std::fmt::Debug;
+
+use linalg::{Matrix, BaseMatrix, Vector};
+
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
+
+/// The Metric trait
+///
+/// Requires a function mapping two points to a non-negative scalar.
+///
+/// Search structures which prune using the triangle inequality
+/// are only exact for true metrics.
+pub trait Metric: Debug + Send + Sync {
+    /// The distance function.
+    ///
+    /// Takes two equal length slices and returns a scalar.
+    fn distance(&self, x: &[f64], y: &[f64]) -> f64;
+
+    /// Whether the distance satisfies the triangle inequality.
+    ///
+    /// Defaults to `true`, distances which do not should override it.
+    fn is_true_metric(&self) -> bool {
+        true
+    }
+}
+
+/// Marker trait for metrics of the Minkowski family.
+///
+/// These metrics are monotone in each coordinate difference, so the
+/// distance from a point to a hyper-rectangle is the distance to the
+/// closest point of that rectangle. This is required by `KDTree`.
+pub trait MinkowskiMetric: Metric {}
+
+/// Euclidean distance
+///
+/// The square root of the sum of squared differences.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Euclidean;
+
+impl Metric for Euclidean {
+    fn distance(&self, x: &[f64], y: &[f64]) -> f64 {
+        x.iter()
+            .zip(y.iter())
+            .map(|(&a, &b)| (a - b) * (a - b))
+            .fold(0., |s, v| s + v)
+            .sqrt()
+    }
+}
+
+impl MinkowskiMetric for Euclidean {}
+
+/// Manhattan distance
+///
+/// The sum of absolute differences.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Manhattan;
+
+impl Metric for Manhattan {
+    fn distance(&self, x: &[f64], y: &[f64]) -> f64 {
+        x.iter()
+            .zip(y.iter())
+            .map(|(&a, &b)| (a - b).abs())
+            .fold(0., |s, v| s + v)
+    }
+}
+
+impl MinkowskiMetric for Manhattan {}
+
+/// Chebyshev distance
+///
+/// The largest absolute difference.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Chebyshev;
+
+impl Metric for Chebyshev {
+    fn distance(&self, x: &[f64], y: &[f64]) -> f64 {
+        x.iter()
+            .zip(y.iter())
+            .map(|(&a, &b)| (a - b).abs())
+            .fold(0., f64::max)
+    }
+}
+
+impl MinkowskiMetric for Chebyshev {}
+
+/// Minkowski distance
+///
+/// The p-th root of the sum of absolute differences raised to the power p.
+#[derive(Clone, Copy, Debug)]
+pub struct Minkowski {
+    p: f64,
+}
+
+impl Minkowski {
+    /// Constructs a new Minkowski metric with the given power.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::distance::{Metric, Minkowski};
+    ///
+    /// let m = Minkowski::new(3.);
+    /// assert!((m.distance(&[0., 0.], &[1., 1.]) - 2f64.powf(1. / 3.)).abs() < 1e-12);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `p` is less than 1.
+    pub fn new(p: f64) -> Minkowski {
+        assert!(p >= 1., "The power of the Minkowski metric must be at least 1.");
+        Minkowski { p: p }
+    }
+
+    /// The power of the metric.
+    pub fn p(&self) -> f64 {
+        self.p
+    }
+}
+
+impl Metric for Minkowski {
+    fn distance(&self, x: &[f64], y: &[f64]) -> f64 {
+        x.iter()
+            .zip(y.iter())
+            .map(|(&a, &b)| (a - b).abs().powf(self.p))
+            .fold(0., |s, v| s + v)
+            .powf(1. / self.p)
+    }
+}
+
+impl MinkowskiMetric for Minkowski {}
+
+/// Cosine distance
+///
+/// One minus the cosine of the angle between the points. The distance
+/// to a zero point is 1.
+///
+/// This is not a true metric as it does not satisfy the triangle
+/// inequality, so `BallTree` searches using it may be approximate.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Cosine;
+
+impl Metric for Cosine {
+    fn distance(&self, x: &[f64], y: &[f64]) -> f64 {
+        let mut dot = 0.;
+        let mut x_norm = 0.;
+        let mut y_norm = 0.;
+        for (&a, &b) in x.iter().zip(y.iter()) {
+            dot += a * b;
+            x_norm += a * a;
+            y_norm += b * b;
+        }
+
+        if x_norm == 0. || y_norm == 0. {
+            1.
+        } else {
+            1. - dot / (x_norm.sqrt() * y_norm.sqrt())
+        }
+    }
+
+    fn is_true_metric(&self) -> bool {
+        false
+    }
+}
+
+/// Mahalanobis distance
+///
+/// The Euclidean distance after whitening by a covariance matrix.
+#[derive(Clone, Debug)]
+pub struct Mahalanobis {
+    inv_cov: Matrix<f64>,
+}
+
+impl Mahalanobis {
+    /// Constructs a new Mahalanobis metric from a covariance matrix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::distance::{Metric, Mahalanobis};
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// let cov = Matrix::new(2, 2, vec![4., 0., 0., 1.]);
+    /// let m = Mahalanobis::new(&cov).unwrap();
+    ///
+    /// assert!((m.distance(&[0., 0.], &[2., 1.]) - 2f64.sqrt()).abs() < 1e-12);
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The covariance matrix is not square.
+    /// - The covariance matrix is singular.
+    pub fn new(cov: &Matrix<f64>) -> LearningResult<Mahalanobis> {
+        if cov.rows() != cov.cols() {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "The covariance matrix must be square."));
+        }
+
+        let inv_cov = cov.clone().inverse()?;
+        Ok(Mahalanobis { inv_cov: inv_cov })
+    }
+
+    /// The inverse of the covariance matrix.
+    pub fn inv_cov(&self) -> &Matrix<f64> {
+        &self.inv_cov
+    }
+}
+
+impl Metric for Mahalanobis {
+    fn distance(&self, x: &[f64], y: &[f64]) -> f64 {
+        assert!(x.len() == self.inv_cov.rows() && y.len() == self.inv_cov.rows(),
+                "The points must match the dimension of the covariance matrix.");
+
+        let diff = Vector::new(x.iter().zip(y.iter()).map(|(&a, &b)| a - b).collect::<Vec<_>>());
+        let d = diff.dot(&(&self.inv_cov * &diff));
+        // Guard against small negative values from rounding.
+        d.max(0.).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Metric, Euclidean, Manhattan, Chebyshev, Minkowski, Cosine, Mahalanobis};
+    use linalg::Matrix;
+
+    #[test]
+    fn test_minkowski_family() {
+        let x = [1., 2., 3.];
+        let y = [4., 0., 3.];
+
+        assert_eq!(Euclidean.distance(&x, &y), 13f64.sqrt());
+        assert_eq!(Manhattan.distance(&x, &y), 5.);
+        assert_eq!(Chebyshev.distance(&x, &y), 3.);
+
+        assert!((Minkowski::new(1.).distance(&x, &y) - 5.).abs() < 1e-12);
+        assert!((Minkowski::new(2.).distance(&x, &y) - 13f64.sqrt()).abs() < 1e-12);
+        assert!((Minkowski::new(3.).distance(&x, &y) - 35f64.powf(1. / 3.)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_zero_distance() {
+        let x = [1.5, -2., 0.];
+
+        assert_eq!(Euclidean.distance(&x, &x), 0.);
+        assert_eq!(Manhattan.distance(&x, &x), 0.);
+        assert_eq!(Chebyshev.distance(&x, &x), 0.);
+        assert_eq!(Minkowski::new(4.).distance(&x, &x), 0.);
+        assert!(Cosine.distance(&x, &x).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_minkowski_invalid_power() {
+        let _ = Minkowski::new(0.5);
+    }
+
+    #[test]
+    fn test_cosine() {
+        assert!((Cosine.distance(&[1., 0.], &[0., 2.]) - 1.).abs() < 1e-12);
+        assert!((Cosine.distance(&[1., 1.], &[3., 3.])).abs() < 1e-12);
+        assert!((Cosine.distance(&[1., 0.], &[-1., 0.]) - 2.).abs() < 1e-12);
+        assert_eq!(Cosine.distance(&[0., 0.], &[1., 2.]), 1.);
+        assert!(!Cosine.is_true_metric());
+    }
+
+    #[test]
+    fn test_mahalanobis_identity() {
+        let m = Mahalanobis::new(&Matrix::identity(3)).unwrap();
+        let x = [1., 2., 3.];
+        let y = [4., 0., 3.];
+
+        assert!((m.distance(&x, &y) - Euclidean.distance(&x, &y)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_mahalanobis_correlated() {
+        // The inverse of [[2, 1], [1, 2]] is [[2, -1], [-1, 2]] / 3.
+        let cov = Matrix::new(2, 2, vec![2., 1., 1., 2.]);
+        let m = Mahalanobis::new(&cov).unwrap();
+
+        assert!((m.distance(&[0., 0.], &[1., 1.]) - (2f64 / 3.).sqrt()).abs() < 1e-12);
+        assert!((m.distance(&[0., 0.], &[1., -1.]) - 2f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_mahalanobis_invalid_cov() {
+        assert!(Mahalanobis::new(&Matrix::new(2, 3, vec![1.; 6])).is_err());
+        assert!(Mahalanobis::new(&Matrix::new(2, 2, vec![1., 1., 1., 1.])).is_err());
+    }
+}
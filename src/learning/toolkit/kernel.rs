@@ -2,20 +2,102 @@
 //!
 //! Currently used within Gaussian Processes and SVMs.
 
+use std::f64::consts::PI;
 use std::ops::{Add, Mul};
 
-use linalg::Vector;
+use linalg::{Matrix, BaseMatrix, Vector};
 use linalg::norm::{Euclidean, VectorNorm, VectorMetric};
 use rulinalg::utils;
 
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
+
 /// The Kernel trait
 ///
 /// Requires a function mapping two vectors to a scalar.
+///
+/// Kernels may also expose their (positive) hyperparameters in log space,
+/// along with the gradient of the kernel function with respect to them.
+/// This is used to fit the hyperparameters of Gaussian processes. The
+/// default implementations expose no hyperparameters.
 pub trait Kernel {
     /// The kernel function.
     ///
     /// Takes two equal length slices and returns a scalar.
     fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64;
+
+    /// The log of the kernel's hyperparameters.
+    fn hyperparams(&self) -> Vec<f64> {
+        Vec::new()
+    }
+
+    /// Sets the kernel's hyperparameters from their logs.
+    ///
+    /// The slice has the same layout as returned by `hyperparams`.
+    fn set_hyperparams(&mut self, _log_params: &[f64]) {}
+
+    /// The gradient of the kernel function with respect to the log hyperparameters.
+    fn hyperparam_grad(&self, _x1: &[f64], _x2: &[f64]) -> Vec<f64> {
+        Vec::new()
+    }
+}
+
+/// Computes the kernel matrix between the rows of two matrices.
+///
+/// The entry at `[i, j]` is the kernel function applied to the `i`th
+/// row of `m1` and the `j`th row of `m2`.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::learning::toolkit::kernel::{self, SquaredExp};
+/// use rusty_machine::linalg::{Matrix, BaseMatrix};
+///
+/// let inputs = Matrix::new(3, 2, vec![0., 1., 1., 2., 2., 3.]);
+/// let ker_mat = kernel::kernel_matrix(&SquaredExp::default(), &inputs, &inputs).unwrap();
+///
+/// assert_eq!(ker_mat.rows(), 3);
+/// assert_eq!(ker_mat[[1, 1]], 1.);
+/// ```
+///
+/// # Failures
+///
+/// - The matrices have different column counts.
+pub fn kernel_matrix<K: Kernel>(ker: &K,
+                                m1: &Matrix<f64>,
+                                m2: &Matrix<f64>)
+                                -> LearningResult<Matrix<f64>> {
+    if m1.cols() != m2.cols() {
+        Err(Error::new(ErrorKind::InvalidState,
+                       "Inputs to kernel matrices have different column counts."))
+    } else {
+        let dim1 = m1.rows();
+        let dim2 = m2.rows();
+
+        let mut ker_data = Vec::with_capacity(dim1 * dim2);
+        ker_data.extend(m1.row_iter().flat_map(|row1| {
+            m2.row_iter()
+                .map(move |row2| ker.kernel(row1.raw_slice(), row2.raw_slice()))
+        }));
+
+        Ok(Matrix::new(dim1, dim2, ker_data))
+    }
+}
+
+/// The squared euclidean distance between two slices.
+fn sq_dist(x1: &[f64], x2: &[f64]) -> f64 {
+    assert_eq!(x1.len(), x2.len());
+    x1.iter().zip(x2).map(|(a, b)| (a - b) * (a - b)).sum()
+}
+
+/// The squared differences of two slices, each scaled by a length scale.
+fn scaled_sq_diffs(x1: &[f64], x2: &[f64], ls: &[f64]) -> Vec<f64> {
+    assert_eq!(x1.len(), x2.len());
+    assert!(x1.len() == ls.len(),
+            "The inputs have {} dimensions but there are {} length scales.",
+            x1.len(),
+            ls.len());
+    x1.iter().zip(x2).zip(ls).map(|((a, b), l)| ((a - b) / l) * ((a - b) / l)).collect()
 }
 
 /// The sum of two kernels
@@ -40,7 +122,7 @@ pub trait Kernel {
 /// println!("{0}", poly_plus_hypert_ker.kernel(&[1f64,2f64,3f64],
 ///                                             &[3f64,1f64,2f64]));
 /// ```
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct KernelSum<T, U>
     where T: Kernel,
           U: Kernel
@@ -57,6 +139,25 @@ impl<T, U> Kernel for KernelSum<T, U>
     fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
         self.k1.kernel(x1, x2) + self.k2.kernel(x1, x2)
     }
+
+    /// The hyperparameters of the first kernel followed by the second.
+    fn hyperparams(&self) -> Vec<f64> {
+        let mut params = self.k1.hyperparams();
+        params.extend(self.k2.hyperparams());
+        params
+    }
+
+    fn set_hyperparams(&mut self, log_params: &[f64]) {
+        let split = self.k1.hyperparams().len();
+        self.k1.set_hyperparams(&log_params[..split]);
+        self.k2.set_hyperparams(&log_params[split..]);
+    }
+
+    fn hyperparam_grad(&self, x1: &[f64], x2: &[f64]) -> Vec<f64> {
+        let mut grad = self.k1.hyperparam_grad(x1, x2);
+        grad.extend(self.k2.hyperparam_grad(x1, x2));
+        grad
+    }
 }
 
 /// The pointwise product of two kernels
@@ -81,7 +182,7 @@ impl<T, U> Kernel for KernelSum<T, U>
 /// println!("{0}", poly_plus_hypert_ker.kernel(&[1f64,2f64,3f64],
 ///                                             &[3f64,1f64,2f64]));
 /// ```
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct KernelProd<T, U>
     where T: Kernel,
           U: Kernel
@@ -98,10 +199,30 @@ impl<T, U> Kernel for KernelProd<T, U>
     fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
         self.k1.kernel(x1, x2) * self.k2.kernel(x1, x2)
     }
+
+    /// The hyperparameters of the first kernel followed by the second.
+    fn hyperparams(&self) -> Vec<f64> {
+        let mut params = self.k1.hyperparams();
+        params.extend(self.k2.hyperparams());
+        params
+    }
+
+    fn set_hyperparams(&mut self, log_params: &[f64]) {
+        let split = self.k1.hyperparams().len();
+        self.k1.set_hyperparams(&log_params[..split]);
+        self.k2.set_hyperparams(&log_params[split..]);
+    }
+
+    fn hyperparam_grad(&self, x1: &[f64], x2: &[f64]) -> Vec<f64> {
+        let (v1, v2) = (self.k1.kernel(x1, x2), self.k2.kernel(x1, x2));
+        let mut grad = self.k1.hyperparam_grad(x1, x2).into_iter().map(|g| g * v2).collect::<Vec<_>>();
+        grad.extend(self.k2.hyperparam_grad(x1, x2).into_iter().map(|g| g * v1));
+        grad
+    }
 }
 
 /// A wrapper tuple struct used for kernel arithmetic
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct KernelArith<K: Kernel>(pub K);
 
 impl<T: Kernel, U: Kernel> Add<KernelArith<T>> for KernelArith<U> {
@@ -291,6 +412,23 @@ impl Kernel for SquaredExp {
         let x = -diff.dot(&diff) / (2f64 * self.ls * self.ls);
         (self.ampl * x.exp())
     }
+
+    /// The log of the length scale and amplitude.
+    fn hyperparams(&self) -> Vec<f64> {
+        vec![self.ls.ln(), self.ampl.ln()]
+    }
+
+    fn set_hyperparams(&mut self, log_params: &[f64]) {
+        assert_eq!(log_params.len(), 2);
+        self.ls = log_params[0].exp();
+        self.ampl = log_params[1].exp();
+    }
+
+    fn hyperparam_grad(&self, x1: &[f64], x2: &[f64]) -> Vec<f64> {
+        let r2 = sq_dist(x1, x2);
+        let k = self.ampl * (-r2 / (2f64 * self.ls * self.ls)).exp();
+        vec![k * r2 / (self.ls * self.ls), k]
+    }
 }
 
 /// The Exponential Kernel
@@ -353,6 +491,23 @@ impl Kernel for Exponential {
         let x = -Euclidean.norm(&diff) / (2f64 * self.ls * self.ls);
         (self.ampl * x.exp())
     }
+
+    /// The log of the length scale and amplitude.
+    fn hyperparams(&self) -> Vec<f64> {
+        vec![self.ls.ln(), self.ampl.ln()]
+    }
+
+    fn set_hyperparams(&mut self, log_params: &[f64]) {
+        assert_eq!(log_params.len(), 2);
+        self.ls = log_params[0].exp();
+        self.ampl = log_params[1].exp();
+    }
+
+    fn hyperparam_grad(&self, x1: &[f64], x2: &[f64]) -> Vec<f64> {
+        let r = sq_dist(x1, x2).sqrt();
+        let k = self.ampl * (-r / (2f64 * self.ls * self.ls)).exp();
+        vec![k * r / (self.ls * self.ls), k]
+    }
 }
 
 /// The Hyperbolic Tangent Kernel.
@@ -510,4 +665,631 @@ impl Kernel for RationalQuadratic {
 
         (1f64 + diff.dot(&diff) / (2f64 * self.alpha * self.ls * self.ls)).powf(-self.alpha)
     }
+
+    /// The log of alpha and the length scale.
+    fn hyperparams(&self) -> Vec<f64> {
+        vec![self.alpha.ln(), self.ls.ln()]
+    }
+
+    fn set_hyperparams(&mut self, log_params: &[f64]) {
+        assert_eq!(log_params.len(), 2);
+        self.alpha = log_params[0].exp();
+        self.ls = log_params[1].exp();
+    }
+
+    fn hyperparam_grad(&self, x1: &[f64], x2: &[f64]) -> Vec<f64> {
+        let r2 = sq_dist(x1, x2);
+        let l2 = self.ls * self.ls;
+        let u = 1f64 + r2 / (2f64 * self.alpha * l2);
+        let k = u.powf(-self.alpha);
+
+        vec![k * (r2 / (2f64 * l2 * u) - self.alpha * u.ln()),
+             k * r2 / (l2 * u)]
+    }
+}
+
+/// Squared exponential kernel with automatic relevance determination
+///
+/// k(x,y) = A _exp_(-Σ<sub>i</sub> (x<sub>i</sub>-y<sub>i</sub>)<sup>2</sup> / 2l<sub>i</sub><sup>2</sup>)
+///
+/// Where A is the amplitude and l<sub>i</sub> the length scale of the i-th input dimension.
+#[derive(Clone, Debug)]
+pub struct ARDSquaredExp {
+    /// The length scale of each input dimension.
+    pub ls: Vec<f64>,
+    /// The amplitude of the kernel.
+    pub ampl: f64,
+}
+
+impl ARDSquaredExp {
+    /// Construct a new ARD squared exponential kernel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::kernel;
+    /// use rusty_machine::learning::toolkit::kernel::Kernel;
+    ///
+    /// // Construct a kernel over 3 dimensions with amplitude 1.
+    /// let ker = kernel::ARDSquaredExp::new(vec![1f64, 10f64, 0.5], 1f64);
+    ///
+    /// println!("{0}", ker.kernel(&[1.,2.,3.], &[3.,4.,5.]));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - No length scales are given.
+    pub fn new(ls: Vec<f64>, ampl: f64) -> ARDSquaredExp {
+        assert!(!ls.is_empty(), "At least one length scale is required.");
+        ARDSquaredExp {
+            ls: ls,
+            ampl: ampl,
+        }
+    }
+}
+
+impl Kernel for ARDSquaredExp {
+    fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        let r2 = scaled_sq_diffs(x1, x2, &self.ls).iter().sum::<f64>();
+        self.ampl * (-0.5 * r2).exp()
+    }
+
+    /// The log of each length scale followed by the log of the amplitude.
+    fn hyperparams(&self) -> Vec<f64> {
+        let mut params = self.ls.iter().map(|l| l.ln()).collect::<Vec<_>>();
+        params.push(self.ampl.ln());
+        params
+    }
+
+    fn set_hyperparams(&mut self, log_params: &[f64]) {
+        assert_eq!(log_params.len(), self.ls.len() + 1);
+        for (l, p) in self.ls.iter_mut().zip(log_params) {
+            *l = p.exp();
+        }
+        self.ampl = log_params[self.ls.len()].exp();
+    }
+
+    fn hyperparam_grad(&self, x1: &[f64], x2: &[f64]) -> Vec<f64> {
+        let diffs = scaled_sq_diffs(x1, x2, &self.ls);
+        let k = self.ampl * (-0.5 * diffs.iter().sum::<f64>()).exp();
+
+        let mut grad = diffs.into_iter().map(|d| k * d).collect::<Vec<_>>();
+        grad.push(k);
+        grad
+    }
+}
+
+/// Exponential kernel with automatic relevance determination
+///
+/// k(x,y) = A _exp_(-r / 2)
+///
+/// Where r = _sqrt_(Σ<sub>i</sub> (x<sub>i</sub>-y<sub>i</sub>)<sup>2</sup> / l<sub>i</sub><sup>4</sup>),
+/// A is the amplitude and l<sub>i</sub> the length scale of the i-th input dimension.
+///
+/// This follows the convention of `Exponential`, which it matches when all
+/// length scales are equal.
+#[derive(Clone, Debug)]
+pub struct ARDExponential {
+    /// The length scale of each input dimension.
+    pub ls: Vec<f64>,
+    /// The amplitude of the kernel.
+    pub ampl: f64,
+}
+
+impl ARDExponential {
+    /// Construct a new ARD exponential kernel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::kernel;
+    /// use rusty_machine::learning::toolkit::kernel::Kernel;
+    ///
+    /// // Construct a kernel over 3 dimensions with amplitude 1.
+    /// let ker = kernel::ARDExponential::new(vec![1f64, 10f64, 0.5], 1f64);
+    ///
+    /// println!("{0}", ker.kernel(&[1.,2.,3.], &[3.,4.,5.]));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - No length scales are given.
+    pub fn new(ls: Vec<f64>, ampl: f64) -> ARDExponential {
+        assert!(!ls.is_empty(), "At least one length scale is required.");
+        ARDExponential {
+            ls: ls,
+            ampl: ampl,
+        }
+    }
+}
+
+impl ARDExponential {
+    /// The squared differences of two slices, each divided by the fourth
+    /// power of its length scale.
+    fn quartic_scaled_diffs(&self, x1: &[f64], x2: &[f64]) -> Vec<f64> {
+        scaled_sq_diffs(x1, x2, &self.ls)
+            .into_iter()
+            .zip(&self.ls)
+            .map(|(d, l)| d / (l * l))
+            .collect()
+    }
+}
+
+impl Kernel for ARDExponential {
+    fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        let r = self.quartic_scaled_diffs(x1, x2).iter().sum::<f64>().sqrt();
+        self.ampl * (-0.5 * r).exp()
+    }
+
+    /// The log of each length scale followed by the log of the amplitude.
+    fn hyperparams(&self) -> Vec<f64> {
+        let mut params = self.ls.iter().map(|l| l.ln()).collect::<Vec<_>>();
+        params.push(self.ampl.ln());
+        params
+    }
+
+    fn set_hyperparams(&mut self, log_params: &[f64]) {
+        assert_eq!(log_params.len(), self.ls.len() + 1);
+        for (l, p) in self.ls.iter_mut().zip(log_params) {
+            *l = p.exp();
+        }
+        self.ampl = log_params[self.ls.len()].exp();
+    }
+
+    fn hyperparam_grad(&self, x1: &[f64], x2: &[f64]) -> Vec<f64> {
+        let diffs = self.quartic_scaled_diffs(x1, x2);
+        let r = diffs.iter().sum::<f64>().sqrt();
+        let k = self.ampl * (-0.5 * r).exp();
+
+        // The kernel is not differentiable in the length scales at r = 0,
+        // but the one-sided derivatives there are zero.
+        let mut grad = diffs.into_iter()
+            .map(|d| if r > 0f64 { k * d / r } else { 0f64 })
+            .collect::<Vec<_>>();
+        grad.push(k);
+        grad
+    }
+}
+
+/// The Matérn kernel with ν = 3/2
+///
+/// k(x,y) = A (1 + s) _exp_(-s)
+///
+/// Where s = √3||x-y|| / l, A is the amplitude and l the length scale.
+#[derive(Clone, Copy, Debug)]
+pub struct Matern32 {
+    /// The length scale of the kernel.
+    pub ls: f64,
+    /// The amplitude of the kernel.
+    pub ampl: f64,
+}
+
+impl Matern32 {
+    /// Construct a new Matérn 3/2 kernel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::kernel;
+    /// use rusty_machine::learning::toolkit::kernel::Kernel;
+    ///
+    /// // Construct a kernel with lengthscale 2 and amplitude 1.
+    /// let ker = kernel::Matern32::new(2f64, 1f64);
+    ///
+    /// println!("{0}", ker.kernel(&[1.,2.,3.], &[3.,4.,5.]));
+    /// ```
+    pub fn new(ls: f64, ampl: f64) -> Matern32 {
+        Matern32 {
+            ls: ls,
+            ampl: ampl,
+        }
+    }
+
+    /// The scaled distance s = √3||x-y|| / l.
+    fn scaled_dist(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        3f64.sqrt() * sq_dist(x1, x2).sqrt() / self.ls
+    }
+}
+
+/// Constructs the default Matérn 3/2 kernel.
+///
+/// The defaults are:
+///
+/// - ls = 1
+/// - ampl = 1
+impl Default for Matern32 {
+    fn default() -> Matern32 {
+        Matern32 {
+            ls: 1f64,
+            ampl: 1f64,
+        }
+    }
+}
+
+impl Kernel for Matern32 {
+    fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        let s = self.scaled_dist(x1, x2);
+        self.ampl * (1f64 + s) * (-s).exp()
+    }
+
+    /// The log of the length scale and amplitude.
+    fn hyperparams(&self) -> Vec<f64> {
+        vec![self.ls.ln(), self.ampl.ln()]
+    }
+
+    fn set_hyperparams(&mut self, log_params: &[f64]) {
+        assert_eq!(log_params.len(), 2);
+        self.ls = log_params[0].exp();
+        self.ampl = log_params[1].exp();
+    }
+
+    fn hyperparam_grad(&self, x1: &[f64], x2: &[f64]) -> Vec<f64> {
+        let s = self.scaled_dist(x1, x2);
+        let e = self.ampl * (-s).exp();
+        vec![e * s * s, e * (1f64 + s)]
+    }
+}
+
+/// The Matérn kernel with ν = 5/2
+///
+/// k(x,y) = A (1 + s + s<sup>2</sup>/3) _exp_(-s)
+///
+/// Where s = √5||x-y|| / l, A is the amplitude and l the length scale.
+#[derive(Clone, Copy, Debug)]
+pub struct Matern52 {
+    /// The length scale of the kernel.
+    pub ls: f64,
+    /// The amplitude of the kernel.
+    pub ampl: f64,
+}
+
+impl Matern52 {
+    /// Construct a new Matérn 5/2 kernel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::kernel;
+    /// use rusty_machine::learning::toolkit::kernel::Kernel;
+    ///
+    /// // Construct a kernel with lengthscale 2 and amplitude 1.
+    /// let ker = kernel::Matern52::new(2f64, 1f64);
+    ///
+    /// println!("{0}", ker.kernel(&[1.,2.,3.], &[3.,4.,5.]));
+    /// ```
+    pub fn new(ls: f64, ampl: f64) -> Matern52 {
+        Matern52 {
+            ls: ls,
+            ampl: ampl,
+        }
+    }
+
+    /// The scaled distance s = √5||x-y|| / l.
+    fn scaled_dist(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        5f64.sqrt() * sq_dist(x1, x2).sqrt() / self.ls
+    }
+}
+
+/// Constructs the default Matérn 5/2 kernel.
+///
+/// The defaults are:
+///
+/// - ls = 1
+/// - ampl = 1
+impl Default for Matern52 {
+    fn default() -> Matern52 {
+        Matern52 {
+            ls: 1f64,
+            ampl: 1f64,
+        }
+    }
+}
+
+impl Kernel for Matern52 {
+    fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        let s = self.scaled_dist(x1, x2);
+        self.ampl * (1f64 + s + s * s / 3f64) * (-s).exp()
+    }
+
+    /// The log of the length scale and amplitude.
+    fn hyperparams(&self) -> Vec<f64> {
+        vec![self.ls.ln(), self.ampl.ln()]
+    }
+
+    fn set_hyperparams(&mut self, log_params: &[f64]) {
+        assert_eq!(log_params.len(), 2);
+        self.ls = log_params[0].exp();
+        self.ampl = log_params[1].exp();
+    }
+
+    fn hyperparam_grad(&self, x1: &[f64], x2: &[f64]) -> Vec<f64> {
+        let s = self.scaled_dist(x1, x2);
+        let e = self.ampl * (-s).exp();
+        vec![e * s * s * (1f64 + s) / 3f64, e * (1f64 + s + s * s / 3f64)]
+    }
+}
+
+/// The Periodic Kernel
+///
+/// k(x,y) = A _exp_(-2 _sin_<sup>2</sup>(π||x-y|| / p) / l<sup>2</sup>)
+///
+/// Where A is the amplitude, p the period and l the length scale.
+#[derive(Clone, Copy, Debug)]
+pub struct Periodic {
+    /// The length scale of the kernel.
+    pub ls: f64,
+    /// The period of the kernel.
+    pub period: f64,
+    /// The amplitude of the kernel.
+    pub ampl: f64,
+}
+
+impl Periodic {
+    /// Construct a new periodic kernel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::kernel;
+    /// use rusty_machine::learning::toolkit::kernel::Kernel;
+    ///
+    /// // Construct a kernel with lengthscale 1, period 3 and amplitude 1.
+    /// let ker = kernel::Periodic::new(1f64, 3f64, 1f64);
+    ///
+    /// println!("{0}", ker.kernel(&[1.,2.,3.], &[3.,4.,5.]));
+    /// ```
+    pub fn new(ls: f64, period: f64, ampl: f64) -> Periodic {
+        Periodic {
+            ls: ls,
+            period: period,
+            ampl: ampl,
+        }
+    }
+}
+
+/// Constructs the default Periodic kernel.
+///
+/// The defaults are:
+///
+/// - ls = 1
+/// - period = 1
+/// - ampl = 1
+impl Default for Periodic {
+    fn default() -> Periodic {
+        Periodic {
+            ls: 1f64,
+            period: 1f64,
+            ampl: 1f64,
+        }
+    }
+}
+
+impl Kernel for Periodic {
+    fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        let sin = (PI * sq_dist(x1, x2).sqrt() / self.period).sin();
+        self.ampl * (-2f64 * sin * sin / (self.ls * self.ls)).exp()
+    }
+
+    /// The log of the length scale, period and amplitude.
+    fn hyperparams(&self) -> Vec<f64> {
+        vec![self.ls.ln(), self.period.ln(), self.ampl.ln()]
+    }
+
+    fn set_hyperparams(&mut self, log_params: &[f64]) {
+        assert_eq!(log_params.len(), 3);
+        self.ls = log_params[0].exp();
+        self.period = log_params[1].exp();
+        self.ampl = log_params[2].exp();
+    }
+
+    fn hyperparam_grad(&self, x1: &[f64], x2: &[f64]) -> Vec<f64> {
+        let u = PI * sq_dist(x1, x2).sqrt() / self.period;
+        let l2 = self.ls * self.ls;
+        let k = self.ampl * (-2f64 * u.sin() * u.sin() / l2).exp();
+
+        vec![k * 4f64 * u.sin() * u.sin() / l2,
+             k * 2f64 * u * (2f64 * u).sin() / l2,
+             k]
+    }
+}
+
+/// The White Noise Kernel
+///
+/// k(x,y) = σ<sup>2</sup> if x = y and 0 otherwise.
+///
+/// Adding this to another kernel models independent noise on each observation.
+#[derive(Clone, Copy, Debug)]
+pub struct WhiteNoise {
+    /// The variance of the noise.
+    pub var: f64,
+}
+
+impl WhiteNoise {
+    /// Construct a new white noise kernel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::kernel;
+    /// use rusty_machine::learning::toolkit::kernel::Kernel;
+    ///
+    /// let ker = kernel::WhiteNoise::new(0.1);
+    ///
+    /// assert_eq!(ker.kernel(&[1.,2.,3.], &[1.,2.,3.]), 0.1);
+    /// assert_eq!(ker.kernel(&[1.,2.,3.], &[3.,4.,5.]), 0.);
+    /// ```
+    pub fn new(var: f64) -> WhiteNoise {
+        WhiteNoise { var: var }
+    }
+}
+
+/// Constructs the default White Noise kernel.
+///
+/// The defaults are:
+///
+/// - var = 1
+impl Default for WhiteNoise {
+    fn default() -> WhiteNoise {
+        WhiteNoise { var: 1f64 }
+    }
+}
+
+impl Kernel for WhiteNoise {
+    fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        assert_eq!(x1.len(), x2.len());
+        if x1 == x2 { self.var } else { 0f64 }
+    }
+
+    /// The log of the variance.
+    fn hyperparams(&self) -> Vec<f64> {
+        vec![self.var.ln()]
+    }
+
+    fn set_hyperparams(&mut self, log_params: &[f64]) {
+        assert_eq!(log_params.len(), 1);
+        self.var = log_params[0].exp();
+    }
+
+    fn hyperparam_grad(&self, x1: &[f64], x2: &[f64]) -> Vec<f64> {
+        vec![self.kernel(x1, x2)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Kernel, KernelArith, SquaredExp, Exponential, RationalQuadratic, ARDSquaredExp,
+                ARDExponential, Matern32, Matern52, Periodic, WhiteNoise};
+
+    /// Checks the hyperparameter gradient against central finite differences.
+    fn check_hyperparam_grad<K: Kernel>(mut ker: K) {
+        let (x1, x2) = ([0.3, -1.2, 0.5], [1.1, 0.4, -0.2]);
+        let grad = ker.hyperparam_grad(&x1, &x2);
+        let params = ker.hyperparams();
+        assert_eq!(grad.len(), params.len());
+
+        let eps = 1e-6;
+        for i in 0..params.len() {
+            let mut p = params.clone();
+            p[i] += eps;
+            ker.set_hyperparams(&p);
+            let plus = ker.kernel(&x1, &x2);
+            p[i] -= 2f64 * eps;
+            ker.set_hyperparams(&p);
+            let minus = ker.kernel(&x1, &x2);
+            ker.set_hyperparams(&params);
+
+            let numerical = (plus - minus) / (2f64 * eps);
+            assert!((grad[i] - numerical).abs() < 1e-6,
+                    "param {}: analytic {} numerical {}", i, grad[i], numerical);
+        }
+    }
+
+    #[test]
+    fn test_hyperparam_round_trip() {
+        let mut ker = SquaredExp::new(2.0, 3.0);
+        let params = ker.hyperparams();
+        ker.set_hyperparams(&params);
+
+        assert!((ker.ls - 2.0).abs() < 1e-12);
+        assert!((ker.ampl - 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_squared_exp_hyperparam_grad() {
+        check_hyperparam_grad(SquaredExp::new(1.5, 0.7));
+    }
+
+    #[test]
+    fn test_exponential_hyperparam_grad() {
+        check_hyperparam_grad(Exponential::new(1.5, 0.7));
+    }
+
+    #[test]
+    fn test_rational_quadratic_hyperparam_grad() {
+        check_hyperparam_grad(RationalQuadratic::new(0.8, 1.3));
+    }
+
+    #[test]
+    fn test_kernel_arith_hyperparam_grad() {
+        check_hyperparam_grad(KernelArith(SquaredExp::new(1.5, 0.7)) +
+                              KernelArith(RationalQuadratic::new(0.8, 1.3)));
+        check_hyperparam_grad(KernelArith(SquaredExp::new(1.5, 0.7)) *
+                              KernelArith(Exponential::new(2.0, 1.2)));
+    }
+
+    #[test]
+    fn test_ard_squared_exp_matches_isotropic() {
+        let ard = ARDSquaredExp::new(vec![1.5; 3], 0.7);
+        let iso = SquaredExp::new(1.5, 0.7);
+        let (x1, x2) = ([0.3, -1.2, 0.5], [1.1, 0.4, -0.2]);
+
+        assert!((ard.kernel(&x1, &x2) - iso.kernel(&x1, &x2)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_ard_exponential_matches_isotropic() {
+        let ard = ARDExponential::new(vec![1.5; 3], 0.7);
+        let iso = Exponential::new(1.5, 0.7);
+        let (x1, x2) = ([0.3, -1.2, 0.5], [1.1, 0.4, -0.2]);
+
+        assert!((ard.kernel(&x1, &x2) - iso.kernel(&x1, &x2)).abs() < 1e-12);
+
+        let ard_grad = ard.hyperparam_grad(&x1, &x2);
+        let iso_grad = iso.hyperparam_grad(&x1, &x2);
+        let ls_grad = ard_grad[..3].iter().sum::<f64>();
+        assert!((ls_grad - iso_grad[0]).abs() < 1e-12);
+        assert!((ard_grad[3] - iso_grad[1]).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_ard_hyperparam_grad() {
+        check_hyperparam_grad(ARDSquaredExp::new(vec![0.5, 2.0, 1.3], 0.7));
+        check_hyperparam_grad(ARDExponential::new(vec![0.5, 2.0, 1.3], 0.7));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ard_wrong_dimension() {
+        let ker = ARDSquaredExp::new(vec![1.0, 2.0], 1.0);
+        ker.kernel(&[1.0, 2.0, 3.0], &[1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_matern_kernels() {
+        let m32 = Matern32::new(1.5, 2.0);
+        let m52 = Matern52::new(1.5, 2.0);
+        let x = [0.3, -1.2, 0.5];
+
+        assert!((m32.kernel(&x, &x) - 2.0).abs() < 1e-12);
+        assert!((m52.kernel(&x, &x) - 2.0).abs() < 1e-12);
+
+        check_hyperparam_grad(m32);
+        check_hyperparam_grad(m52);
+    }
+
+    #[test]
+    fn test_periodic_kernel() {
+        let ker = Periodic::new(1.0, 2.0, 1.5);
+
+        // Points a whole period apart are perfectly correlated.
+        assert!((ker.kernel(&[0.5], &[2.5]) - 1.5).abs() < 1e-12);
+        check_hyperparam_grad(ker);
+    }
+
+    #[test]
+    fn test_white_noise_kernel() {
+        let ker = WhiteNoise::new(0.3);
+
+        assert_eq!(ker.kernel(&[1.0, 2.0], &[1.0, 2.0]), 0.3);
+        assert_eq!(ker.kernel(&[1.0, 2.0], &[1.0, 2.1]), 0.0);
+        check_hyperparam_grad(ker);
+    }
+
+    #[test]
+    fn test_new_kernels_combine() {
+        let prod = KernelArith(Matern52::default()) * KernelArith(Periodic::default());
+        let ker = KernelArith(prod) + KernelArith(WhiteNoise::new(0.1));
+        assert_eq!(ker.hyperparams().len(), 6);
+        check_hyperparam_grad(ker);
+    }
 }
@@ -0,0 +1,138 @@
+//! Utilities for parallel computation.
+//!
+//! The parallel computations are only available with the `parallel`
+//! feature. `MaybeSync` is always available, so that the thread safety
+//! they need can be required only when the feature is enabled.
+//!
+//! Work is split into independent items which are processed across
+//! scoped std threads. The results are always returned in item order,
+//! so the outcome of a computation does not depend on the number of
+//! threads used.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "parallel")] {
+//! use rusty_machine::learning::toolkit::parallel;
+//!
+//! let squares = parallel::par_map(&[1, 2, 3, 4], |x| x * x);
+//! assert_eq!(squares, vec![1, 4, 9, 16]);
+//! # }
+//! ```
+
+#[cfg(feature = "parallel")]
+use std::cmp;
+#[cfg(feature = "parallel")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "parallel")]
+use std::thread;
+#[cfg(all(test, feature = "parallel"))]
+use std::sync::Mutex;
+
+/// Thread safety required of values shared by parallel computations.
+///
+/// With the `parallel` feature this requires `Send + Sync`. Without
+/// the feature it is implemented for every type.
+#[cfg(feature = "parallel")]
+pub trait MaybeSync: Send + Sync {}
+
+#[cfg(feature = "parallel")]
+impl<T: Send + Sync + ?Sized> MaybeSync for T {}
+
+/// Thread safety required of values shared by parallel computations.
+///
+/// With the `parallel` feature this requires `Send + Sync`. Without
+/// the feature it is implemented for every type.
+#[cfg(not(feature = "parallel"))]
+pub trait MaybeSync {}
+
+#[cfg(not(feature = "parallel"))]
+impl<T: ?Sized> MaybeSync for T {}
+
+/// The requested number of threads, `0` meaning the available parallelism.
+#[cfg(feature = "parallel")]
+static NUM_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets the number of threads used by parallel computations.
+///
+/// A value of `0` (the default) uses the available parallelism of the machine.
+#[cfg(feature = "parallel")]
+pub fn set_num_threads(n: usize) {
+    NUM_THREADS.store(n, Ordering::SeqCst);
+}
+
+/// The number of threads used by parallel computations.
+#[cfg(feature = "parallel")]
+pub fn num_threads() -> usize {
+    match NUM_THREADS.load(Ordering::SeqCst) {
+        0 => thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        n => n,
+    }
+}
+
+/// Held by tests while they change the number of threads.
+#[cfg(all(test, feature = "parallel"))]
+static THREADS_LOCK: Mutex<()> = Mutex::new(());
+
+/// Runs `f` with the number of threads set to `n`, then restores the default.
+///
+/// The thread count is global, so tests changing it are serialised
+/// to keep them from interfering with each other.
+#[cfg(all(test, feature = "parallel"))]
+pub fn with_num_threads<R, F: FnOnce() -> R>(n: usize, f: F) -> R {
+    let _guard = THREADS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    set_num_threads(n);
+    let result = f();
+    set_num_threads(0);
+    result
+}
+
+/// Applies `f` to each item across threads.
+///
+/// The results are returned in the same order as the items.
+///
+/// # Panics
+///
+/// - `f` panics for any of the items.
+#[cfg(feature = "parallel")]
+pub fn par_map<T, U, F>(items: &[T], f: F) -> Vec<U>
+    where T: Sync,
+          U: Send,
+          F: Fn(&T) -> U + Sync
+{
+    let threads = cmp::min(num_threads(), items.len());
+    if threads <= 1 {
+        return items.iter().map(&f).collect();
+    }
+
+    let chunk_size = (items.len() + threads - 1) / threads;
+    let f = &f;
+    thread::scope(|s| {
+        let handles = items.chunks(chunk_size)
+            .map(|chunk| s.spawn(move || chunk.iter().map(f).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+
+        handles.into_iter()
+            .flat_map(|h| h.join().expect("A worker thread panicked."))
+            .collect()
+    })
+}
+
+#[cfg(all(test, feature = "parallel"))]
+mod tests {
+    use super::par_map;
+
+    #[test]
+    fn test_par_map_keeps_order() {
+        let items = (0..100).collect::<Vec<usize>>();
+        let doubled = par_map(&items, |x| 2 * x);
+
+        assert_eq!(doubled, (0..100).map(|x| 2 * x).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_par_map_empty() {
+        let items: Vec<usize> = Vec::new();
+        assert!(par_map(&items, |x| *x).is_empty());
+    }
+}
@@ -97,8 +97,34 @@ pub fn fisher_yates<T: Copy>(arr: &[T]) -> Vec<T> {
 /// rand_utils::in_place_fisher_yates(&mut a);
 /// ```
 pub fn in_place_fisher_yates<T>(arr: &mut [T]) {
+    in_place_fisher_yates_with_rng(arr, &mut thread_rng());
+}
+
+/// The in place Fisher-Yates shuffle using the given random number generator.
+///
+/// This allows the permutation to be reproduced with a seeded generator.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rand;
+/// # extern crate rusty_machine;
+/// use rand::{SeedableRng, StdRng};
+/// use rusty_machine::learning::toolkit::rand_utils;
+///
+/// # fn main() {
+/// let seed: &[_] = &[1, 2, 3, 4];
+/// let mut a = (0..5).collect::<Vec<_>>();
+/// let mut b = a.clone();
+///
+/// rand_utils::in_place_fisher_yates_with_rng(&mut a, &mut StdRng::from_seed(seed));
+/// rand_utils::in_place_fisher_yates_with_rng(&mut b, &mut StdRng::from_seed(seed));
+///
+/// assert_eq!(a, b);
+/// # }
+/// ```
+pub fn in_place_fisher_yates_with_rng<T, R: Rng>(arr: &mut [T], rng: &mut R) {
     let n = arr.len();
-    let mut rng = thread_rng();
 
     for i in 0..n {
         // Swap i with a random point after it
@@ -21,13 +21,20 @@
 //! - K-Means Clustering
 //! - Neural Networks
 //! - Gaussian Process Regression
+//! - Sparse Gaussian Process Regression
+//! - Bayesian Optimization
 //! - Support Vector Machines
+//! - Probability Calibration
 //! - Gaussian Mixture Models
 //! - Naive Bayes Classifiers
-//! - DBSCAN
-//! - k-Nearest Neighbor Classifiers
+//! - DBSCAN, OPTICS and HDBSCAN
+//! - Agglomerative Clustering
+//! - k-Nearest Neighbor Classification and Regression
 //! - Principal Component Analysis
 //!
+//! Enabling the `parallel` feature computes neural network batch gradients
+//! and cross-validation folds across threads.
+//!
 //! ### linalg
 //!
 //! The linalg module reexports some structs and traits from the
@@ -133,6 +140,9 @@ pub mod data {
 /// Module for machine learning.
 pub mod learning {
     pub mod dbscan;
+    pub mod optics;
+    pub mod hdbscan;
+    pub mod agglomerative;
     pub mod glm;
     pub mod gmm;
     pub mod lin_reg;
@@ -140,7 +150,10 @@ pub mod learning {
     pub mod k_means;
     pub mod nnet;
     pub mod gp;
+    pub mod sparse_gp;
+    pub mod bayes_opt;
     pub mod svm;
+    pub mod calibration;
     pub mod naive_bayes;
     pub mod knn;
     pub mod pca;
@@ -170,6 +183,7 @@ pub mod learning {
 
     /// Module for optimization in machine learning setting.
     pub mod optim {
+        use super::LearningResult;
 
         /// Trait for models which can be gradient-optimized.
         pub trait Optimizable {
@@ -191,25 +205,31 @@ pub mod learning {
             /// Return the optimized parameter using gradient optimization.
             ///
             /// Takes in a set of starting parameters and related model data.
+            ///
+            /// Returns an error if the optimization fails, for example
+            /// when the cost diverges.
             fn optimize(&self,
                         model: &M,
                         start: &[f64],
                         inputs: &M::Inputs,
                         targets: &M::Targets)
-                        -> Vec<f64>;
+                        -> LearningResult<Vec<f64>>;
         }
 
         pub mod grad_desc;
         pub mod fmincg;
+        pub mod grad_check;
     }
 
     /// Module for learning tools.
     pub mod toolkit {
         pub mod activ_fn;
         pub mod cost_fn;
+        pub mod distance;
         pub mod kernel;
         pub mod rand_utils;
         pub mod regularization;
+        pub mod parallel;
     }
 }
 
@@ -243,4 +263,6 @@ pub mod datasets;
 pub mod plot {
     /// TODO doc
     pub mod plot_comparison;
+    /// Dendrogram plots of hierarchical clusterings.
+    pub mod dendrogram;
 }
\ No newline at end of file
@@ -0,0 +1,140 @@
+use plotters::prelude::*;
+
+use linalg::{BaseMatrix, Matrix};
+
+/// Plot the dendrogram of a linkage matrix and save it to `filename`.
+///
+/// The linkage matrix is in the format returned by
+/// `AgglomerativeClustering::linkage_matrix`: one row per merge holding
+/// the two merged nodes, the merge distance and the cluster size.
+/// Leaves are labelled with their point index.
+pub fn plot_dendrogram(
+    linkage: &Matrix<f64>,
+    filename: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if linkage.rows() == 0 || linkage.cols() != 4 {
+        return Err("The linkage matrix must have at least one row and four columns.".into());
+    }
+
+    let (order, segments) = dendrogram_segments(linkage);
+    let n = order.len();
+    let y_max = segments
+        .iter()
+        .flat_map(|s| s.iter())
+        .fold(0f64, |a, &(_, y)| a.max(y));
+    let y_max = if y_max > 0.0 { 1.05 * y_max } else { 1.0 };
+
+    let root = BitMapBackend::new(filename, (800, 600)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Dendrogram", ("sans-serif", 30).into_font())
+        .margin(5)
+        .x_label_area_size(30)
+        .y_label_area_size(40)
+        .build_cartesian_2d(-0.5f64..(n as f64 - 0.5), 0.0f64..y_max)?;
+
+    // Only label the leaf positions, using the point indices.
+    let leaf_label = |x: &f64| {
+        if (x - x.round()).abs() < 1e-6 && x.round() >= 0.0 {
+            order
+                .get(x.round() as usize)
+                .map(|leaf| leaf.to_string())
+                .unwrap_or_default()
+        } else {
+            String::new()
+        }
+    };
+
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .x_labels(n)
+        .x_label_formatter(&leaf_label)
+        .x_desc("point")
+        .y_desc("distance")
+        .draw()?;
+
+    chart.draw_series(
+        segments
+            .into_iter()
+            .map(|points| PathElement::new(points, &BLUE)),
+    )?;
+
+    root.present()?;
+
+    Ok(())
+}
+
+/// Compute the leaf order and the U-shaped segment joining each merge.
+///
+/// Leaf `i` of the order is drawn at `x = i` and each merge is drawn
+/// from its children up to its distance and across.
+fn dendrogram_segments(linkage: &Matrix<f64>) -> (Vec<usize>, Vec<Vec<(f64, f64)>>) {
+    let n = linkage.rows() + 1;
+    let children = |node: usize| {
+        let row = linkage.row(node - n);
+        (row.raw_slice()[0] as usize, row.raw_slice()[1] as usize)
+    };
+
+    // Depth first traversal from the root gives an order without crossings.
+    let mut order = Vec::with_capacity(n);
+    let mut stack = vec![2 * n - 2];
+    while let Some(node) = stack.pop() {
+        if node < n {
+            order.push(node);
+        } else {
+            let (left, right) = children(node);
+            stack.push(right);
+            stack.push(left);
+        }
+    }
+
+    let mut x = vec![0f64; 2 * n - 1];
+    let mut y = vec![0f64; 2 * n - 1];
+    for (i, &leaf) in order.iter().enumerate() {
+        x[leaf] = i as f64;
+    }
+
+    let mut segments = Vec::with_capacity(n - 1);
+    for (i, row) in linkage.row_iter().enumerate() {
+        let (left, right, height) = (
+            row.raw_slice()[0] as usize,
+            row.raw_slice()[1] as usize,
+            row.raw_slice()[2],
+        );
+
+        segments.push(vec![
+            (x[left], y[left]),
+            (x[left], height),
+            (x[right], height),
+            (x[right], y[right]),
+        ]);
+        x[n + i] = (x[left] + x[right]) / 2.0;
+        y[n + i] = height;
+    }
+
+    (order, segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dendrogram_segments;
+    use linalg::Matrix;
+
+    #[test]
+    fn test_dendrogram_segments() {
+        // Points 0 and 2 merge first, then point 1 joins them.
+        let linkage = Matrix::new(2, 4, vec![0., 2., 1., 2., 1., 3., 2.5, 3.]);
+        let (order, segments) = dendrogram_segments(&linkage);
+
+        assert_eq!(order, vec![1, 0, 2]);
+        assert_eq!(
+            segments,
+            vec![
+                vec![(1.0, 0.0), (1.0, 1.0), (2.0, 1.0), (2.0, 0.0)],
+                vec![(0.0, 0.0), (0.0, 2.5), (1.5, 2.5), (1.5, 1.0)],
+            ]
+        );
+    }
+}
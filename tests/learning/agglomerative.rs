@@ -0,0 +1,125 @@
+use rm::linalg::{Matrix, BaseMatrix};
+
+use rm::learning::agglomerative::{AgglomerativeClustering, Linkage, Cut};
+use rm::learning::toolkit::distance::Manhattan;
+use rm::learning::UnSupModel;
+
+// Three well separated groups of three points.
+fn three_groups() -> Matrix<f64> {
+    Matrix::new(9, 2, vec![0.0, 0.0,
+                           0.2, 0.1,
+                           0.1, 0.3,
+                           5.0, 5.0,
+                           5.2, 4.9,
+                           4.9, 5.1,
+                           0.0, 8.0,
+                           0.3, 8.1,
+                           -0.1, 7.8])
+}
+
+#[test]
+fn test_linkages_agree() {
+    let inputs = three_groups();
+
+    for &linkage in &[Linkage::Single, Linkage::Complete, Linkage::Average, Linkage::Ward] {
+        let mut model = AgglomerativeClustering::new(linkage, Cut::Clusters(3));
+        model.train(&inputs).unwrap();
+
+        let clustering = model.clusters().unwrap();
+        assert_eq!(clustering.data(), &vec![0, 0, 0, 1, 1, 1, 2, 2, 2]);
+    }
+}
+
+#[test]
+fn test_linkage_matrix() {
+    let inputs = three_groups();
+
+    let mut model = AgglomerativeClustering::new(Linkage::Average, Cut::Clusters(1));
+    model.train(&inputs).unwrap();
+
+    let linkage = model.linkage_matrix().unwrap();
+    assert_eq!(linkage.rows(), 8);
+    assert_eq!(linkage.cols(), 4);
+
+    let rows = linkage.row_iter().map(|r| r.raw_slice().to_vec()).collect::<Vec<_>>();
+    assert!(rows.windows(2).all(|w| w[0][2] <= w[1][2]));
+    assert_eq!(rows[7][3], 9.);
+    assert!(rows.iter().enumerate().all(|(i, r)| r[0] < r[1] && r[1] < 9. + i as f64));
+}
+
+#[test]
+fn test_distance_cut() {
+    let inputs = three_groups();
+
+    let mut model = AgglomerativeClustering::new(Linkage::Complete, Cut::Distance(1.));
+    model.train(&inputs).unwrap();
+    assert_eq!(model.clusters().unwrap().data(), &vec![0, 0, 0, 1, 1, 1, 2, 2, 2]);
+
+    let clustering = model.flat_clusters(Cut::Distance(100.)).unwrap();
+    assert_eq!(clustering.data(), &vec![0; 9]);
+
+    let clustering = model.flat_clusters(Cut::Distance(0.)).unwrap();
+    assert_eq!(clustering.into_vec(), (0..9).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_metric() {
+    let inputs = three_groups();
+
+    let mut model = AgglomerativeClustering::with_metric(Linkage::Single,
+                                                         Cut::Clusters(3),
+                                                         Manhattan);
+    model.train(&inputs).unwrap();
+
+    assert_eq!(model.clusters().unwrap().data(), &vec![0, 0, 0, 1, 1, 1, 2, 2, 2]);
+}
+
+#[test]
+#[should_panic]
+fn test_ward_metric() {
+    let _ = AgglomerativeClustering::with_metric(Linkage::Ward, Cut::Clusters(2), Manhattan);
+}
+
+#[test]
+fn test_predict() {
+    let inputs = three_groups();
+
+    let mut model = AgglomerativeClustering::default();
+    assert!(model.predict(&inputs).is_err());
+
+    model.train(&inputs).unwrap();
+    let new_points = Matrix::new(2, 2, vec![0.5, 7.5, 4.5, 4.5]);
+    let clustering = model.predict(&new_points).unwrap();
+    let trained = model.clusters().unwrap();
+
+    assert_eq!(clustering[0], trained[6]);
+    assert_eq!(clustering[1], trained[3]);
+}
+
+#[test]
+fn test_invalid_cuts() {
+    let inputs = three_groups();
+
+    let mut model = AgglomerativeClustering::new(Linkage::Single, Cut::Clusters(10));
+    assert!(model.train(&inputs).is_err());
+    assert!(model.flat_clusters(Cut::Clusters(2)).is_err());
+
+    let mut model = AgglomerativeClustering::new(Linkage::Single, Cut::Clusters(2));
+    model.train(&inputs).unwrap();
+    assert!(model.flat_clusters(Cut::Clusters(10)).is_err());
+}
+
+#[test]
+fn test_empty_inputs() {
+    let mut model = AgglomerativeClustering::default();
+    assert!(model.train(&Matrix::new(0, 2, Vec::<f64>::new())).is_err());
+}
+
+#[test]
+fn test_single_point() {
+    let mut model = AgglomerativeClustering::new(Linkage::Ward, Cut::Clusters(1));
+    model.train(&Matrix::new(1, 2, vec![1., 2.])).unwrap();
+
+    assert_eq!(model.linkage_matrix().unwrap().rows(), 0);
+    assert_eq!(model.clusters().unwrap().data(), &vec![0]);
+}
@@ -0,0 +1,89 @@
+use rm::learning::bayes_opt::{BayesianOptimizer, Acquisition};
+use rm::learning::toolkit::kernel::SquaredExp;
+use rm::benchmark_functions::analytic::Multimodal1D;
+
+use rand::{StdRng, SeedableRng};
+
+fn grid_maximum(lo: f64, hi: f64) -> f64 {
+    (0..10001).map(|i| (lo + (hi - lo) * i as f64 / 10000.).multimodal1_d())
+        .fold(::std::f64::NEG_INFINITY, f64::max)
+}
+
+fn check_acquisition(acquisition: Acquisition) {
+    let mut optimizer = BayesianOptimizer::new(vec![(0., 1.2)],
+                                               SquaredExp::new(0.1, 1.),
+                                               acquisition);
+
+    let seed: &[_] = &[1, 2, 3];
+    let mut rng = StdRng::from_seed(seed);
+    let (x, y) = optimizer.maximize(|x| x[0].multimodal1_d(), 3, 20, &mut rng).unwrap();
+
+    assert_eq!(optimizer.num_observations(), 23);
+    assert!(x[0] >= 0. && x[0] <= 1.2);
+    assert!((y - x[0].multimodal1_d()).abs() < 1e-12);
+    assert!(y > grid_maximum(0., 1.2) - 0.1, "found {} at {}", y, x[0]);
+}
+
+#[test]
+fn test_bayes_opt_expected_improvement() {
+    check_acquisition(Acquisition::ExpectedImprovement { xi: 0.01 });
+}
+
+#[test]
+fn test_bayes_opt_upper_confidence_bound() {
+    check_acquisition(Acquisition::UpperConfidenceBound { kappa: 2.5 });
+}
+
+#[test]
+fn test_bayes_opt_probability_of_improvement() {
+    check_acquisition(Acquisition::ProbabilityOfImprovement { xi: 0.05 });
+}
+
+#[test]
+fn test_bayes_opt_suggest_within_bounds() {
+    let mut optimizer = BayesianOptimizer::new(vec![(-1., 1.), (2., 3.)],
+                                               SquaredExp::new(0.5, 1.),
+                                               Acquisition::default());
+    let seed: &[_] = &[4, 5, 6];
+    let mut rng = StdRng::from_seed(seed);
+
+    assert!(optimizer.best().is_none());
+
+    for _ in 0..5 {
+        let x = optimizer.suggest(&mut rng).unwrap();
+        assert!(x[0] >= -1. && x[0] <= 1.);
+        assert!(x[1] >= 2. && x[1] <= 3.);
+        let y = -(x[0] * x[0]) - (x[1] - 2.5) * (x[1] - 2.5);
+        optimizer.observe(&x, y);
+    }
+
+    assert_eq!(optimizer.best().unwrap().0.len(), 2);
+}
+
+#[test]
+fn test_acquisition_values() {
+    let ei = Acquisition::ExpectedImprovement { xi: 0. };
+    let pi = Acquisition::ProbabilityOfImprovement { xi: 0. };
+
+    assert_eq!(ei.evaluate(2., 0., 1.), 1.);
+    assert_eq!(pi.evaluate(0., 0., 1.), 0.);
+    assert!((pi.evaluate(1., 1., 1.) - 0.5).abs() < 1e-10);
+    assert!(ei.evaluate(1., 1., 1.) > 0.);
+}
+
+#[test]
+fn test_bayes_opt_reports_hyperopt_error() {
+    let mut optimizer = BayesianOptimizer::new(vec![(0., 1.)],
+                                               SquaredExp::new(0.1, 1.),
+                                               Acquisition::default());
+    let seed: &[_] = &[7, 8, 9];
+    let mut rng = StdRng::from_seed(seed);
+
+    assert!(optimizer.hyperopt_error().is_none());
+
+    // The marginal likelihood of a NaN observation cannot be maximised.
+    optimizer.observe(&[0.2], 1.);
+    optimizer.observe(&[0.7], ::std::f64::NAN);
+    let _ = optimizer.suggest(&mut rng);
+    assert!(optimizer.hyperopt_error().is_some());
+}
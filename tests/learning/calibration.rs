@@ -0,0 +1,170 @@
+use rm::linalg::Matrix;
+use rm::linalg::Vector;
+use rm::learning::{LearningResult, SupModel};
+use rm::learning::calibration::{CalibratedClassifier, CalibrationMethod, PlattScaling};
+use rm::learning::nnet::{NeuralNet, BCECriterion};
+use rm::learning::optim::grad_desc::StochasticGD;
+use rm::learning::svm::SoftMarginSVM;
+use rm::learning::toolkit::kernel::Linear;
+use rm::learning::toolkit::activ_fn::Sigmoid;
+use rm::learning::toolkit::regularization::Regularization;
+use rm::analysis::score::{neg_brier_score, reliability_curve};
+
+/// A fixed model whose scores are an overconfident multiple of the input.
+#[derive(Debug)]
+struct Overconfident;
+
+impl SupModel<Matrix<f64>, Vector<f64>> for Overconfident {
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        Ok(Vector::new(inputs.data().iter().map(|x| 1. / (1. + (-10. * x).exp())).collect::<Vec<_>>()))
+    }
+
+    fn train(&mut self, _: &Matrix<f64>, _: &Vector<f64>) -> LearningResult<()> {
+        Ok(())
+    }
+}
+
+/// Inputs on a grid where the positive fraction rises slowly with the input.
+fn noisy_data() -> (Matrix<f64>, Vector<f64>) {
+    let mut inputs = Vec::new();
+    let mut targets = Vec::new();
+    for i in 0..10 {
+        let x = i as f64 / 4.5 - 1.;
+        // Each input has i + 5 positives out of 20.
+        for j in 0..20 {
+            inputs.push(x);
+            targets.push(if j < i + 5 { 1. } else { 0. });
+        }
+    }
+    (Matrix::new(200, 1, inputs), Vector::new(targets))
+}
+
+#[test]
+fn test_calibration_improves_brier_score() {
+    let (inputs, targets) = noisy_data();
+
+    let raw = Overconfident.predict(&inputs).unwrap();
+    let raw_score = neg_brier_score(raw.iter(), targets.iter());
+
+    for &method in &[CalibrationMethod::Platt, CalibrationMethod::Isotonic] {
+        let mut model = CalibratedClassifier::new(Overconfident, method);
+        model.train(&inputs, &targets).unwrap();
+
+        let probs = model.predict(&inputs).unwrap();
+        assert!(probs.iter().all(|&p| p >= 0. && p <= 1.));
+        assert!(neg_brier_score(probs.iter(), targets.iter()) > raw_score);
+
+    }
+}
+
+#[test]
+fn test_isotonic_calibration_recovers_frequencies() {
+    let (inputs, targets) = noisy_data();
+
+    let mut model = CalibratedClassifier::new(Overconfident, CalibrationMethod::Isotonic);
+    model.train(&inputs, &targets).unwrap();
+
+    // The positive fraction is already increasing so no pooling occurs.
+    let grid = Matrix::new(10, 1, (0..10).map(|i| i as f64 / 4.5 - 1.).collect::<Vec<_>>());
+    let probs = model.predict(&grid).unwrap();
+    for (i, p) in probs.iter().enumerate() {
+        assert!((p - (i + 5) as f64 / 20.).abs() < 1e-12);
+    }
+    assert!(model.isotonic_regression().is_some());
+
+    // Each bin holds whole groups of equal inputs, which are fitted exactly.
+    let probs = model.predict(&inputs).unwrap();
+    let (mean_probs, frac_pos) = reliability_curve(probs.iter(), targets.iter(), 5);
+    for (p, f) in mean_probs.iter().zip(frac_pos.iter()) {
+        assert!((p - f).abs() < 1e-12);
+    }
+    assert!(model.platt_scaling().is_none());
+}
+
+#[test]
+fn test_calibration_untrained() {
+    let model = CalibratedClassifier::new(Overconfident, CalibrationMethod::Platt);
+    assert!(model.predict(&Matrix::new(1, 1, vec![0.])).is_err());
+}
+
+#[test]
+fn test_calibration_invalid_targets() {
+    let inputs = Matrix::new(2, 1, vec![0., 1.]);
+    let targets = Vector::new(vec![-1., 1.]);
+
+    let mut model = CalibratedClassifier::new(Overconfident, CalibrationMethod::Platt);
+    assert!(model.train(&inputs, &targets).is_err());
+}
+
+#[test]
+fn test_calibrated_neural_net() {
+    let inputs = Matrix::new(6, 1, vec![0., 1., 2., 3., 4., 5.]);
+    let targets = Matrix::new(6, 1, vec![0., 0., 0., 1., 1., 1.]);
+
+    let criterion = BCECriterion::new(Regularization::L2(0.));
+    let mut net = NeuralNet::mlp(&[1, 3, 1], criterion, StochasticGD::new(0.1, 0.1, 20), Sigmoid);
+    net.train(&inputs, &targets).unwrap();
+
+    let mut model = CalibratedClassifier::new(net, CalibrationMethod::Platt);
+    model.train(&inputs, &Vector::new(targets.into_vec())).unwrap();
+
+    let probs = model.predict(&inputs).unwrap();
+    assert_eq!(probs.size(), 6);
+    assert!(probs.iter().all(|&p| p > 0. && p < 1.));
+}
+
+#[test]
+fn test_calibration_multiple_score_columns() {
+    let inputs = Matrix::new(4, 1, vec![0., 1., 2., 3.]);
+    let targets = Matrix::new(4, 2, vec![1., 0., 1., 0., 0., 1., 0., 1.]);
+
+    let criterion = BCECriterion::new(Regularization::L2(0.));
+    let mut net = NeuralNet::mlp(&[1, 2], criterion, StochasticGD::new(0.1, 0.1, 20), Sigmoid);
+    net.train(&inputs, &targets).unwrap();
+
+    let mut model = CalibratedClassifier::new(net, CalibrationMethod::Isotonic);
+    assert!(model.train(&inputs, &Vector::new(vec![1., 1., 0., 0.])).is_err());
+}
+
+#[test]
+fn test_platt_scaling_svm_scores() {
+    // Decision values of a margin classifier, where the sign is mostly right.
+    let scores = [-2.1, -1.3, -1., -0.4, 0.2, -0.1, 0.5, 1.1, 1.4, 2.];
+    let targets = [0., 0., 0., 1., 0., 0., 1., 1., 1., 1.];
+
+    let platt = PlattScaling::fit(&scores, &targets).unwrap();
+    assert!(platt.slope() < 0.);
+    assert!(platt.transform(-2.) < 0.2);
+    assert!(platt.transform(2.) > 0.8);
+    assert!((platt.transform(0.) - 0.5).abs() < 0.2);
+}
+
+#[test]
+fn test_calibrated_soft_margin_svm() {
+    let (inputs, targets) = noisy_data();
+    let labels = Vector::new(targets.iter().map(|&t| 2. * t - 1.).collect::<Vec<_>>());
+
+    let mut svm = SoftMarginSVM::new(Linear::new(1.), 1.);
+    svm.train(&inputs, &labels).unwrap();
+
+    // Calibrating the predicted labels leaves at most two probabilities.
+    let mut from_labels = CalibratedClassifier::new(svm.clone(), CalibrationMethod::Platt);
+    from_labels.train(&inputs, &targets).unwrap();
+
+    let mut model = CalibratedClassifier::with_scores(svm,
+                                                      CalibrationMethod::Platt,
+                                                      SoftMarginSVM::decision_function);
+    model.train(&inputs, &targets).unwrap();
+    assert!(model.platt_scaling().unwrap().slope() < 0.);
+
+    let grid = Matrix::new(10, 1, (0..10).map(|i| i as f64 / 4.5 - 1.).collect::<Vec<_>>());
+    let probs = model.predict(&grid).unwrap();
+    assert!(probs.iter().all(|&p| p > 0. && p < 1.));
+    assert!(probs.data().windows(2).all(|w| w[0] <= w[1]));
+    assert!(probs[9] > probs[0]);
+
+    let probs = model.predict(&inputs).unwrap();
+    let label_probs = from_labels.predict(&inputs).unwrap();
+    assert!(neg_brier_score(probs.iter(), targets.iter()) >
+            neg_brier_score(label_probs.iter(), targets.iter()));
+}
@@ -2,6 +2,8 @@ use rm::linalg::Matrix;
 
 use rm::learning::dbscan::DBSCAN;
 use rm::learning::UnSupModel;
+use rm::learning::toolkit::distance::Manhattan;
+use rm::analysis::score::adjusted_rand_score;
 
 #[test]
 fn test_basic_clusters() {
@@ -41,3 +43,68 @@ fn test_basic_prediction() {
     assert!(classes[0] == Some(0));
     assert!(classes[1] == None);
 }
+
+#[test]
+fn test_metric_clusters() {
+    // Neighbouring points are 0.85 apart under the Euclidean
+    // metric and 1.2 apart under the Manhattan metric.
+    let inputs = Matrix::new(4, 2, vec![0.0, 0.0,
+                                        0.6, 0.6,
+                                        5.0, 5.0,
+                                        5.6, 5.6]);
+
+    let mut model = DBSCAN::new(1.0, 2);
+    model.train(&inputs).unwrap();
+    assert_eq!(model.clusters().unwrap().data(), &vec![Some(0), Some(0), Some(1), Some(1)]);
+
+    let mut model = DBSCAN::with_metric(1.0, 2, Manhattan);
+    model.set_predictive(true);
+    model.train(&inputs).unwrap();
+    assert!(model.clusters().unwrap().data().iter().all(|x| x.is_none()));
+
+    let classes = model.predict(&Matrix::new(1, 2, vec![0.4, 0.4])).unwrap();
+    assert!(classes[0] == None);
+}
+
+#[test]
+fn test_duplicate_points() {
+    // More identical points than fit in a single tree leaf.
+    let mut data = vec![1.0; 80];
+    data.extend(vec![5.0, 5.0, 5.1, 5.1]);
+    let inputs = Matrix::new(42, 2, data);
+
+    let mut model = DBSCAN::new(0.5, 3);
+    model.train(&inputs).unwrap();
+
+    let clustering = model.clusters().unwrap();
+    assert!(clustering.data().iter().take(40).all(|x| *x == Some(0)));
+    assert!(clustering.data().iter().skip(40).all(|x| x.is_none()));
+}
+
+#[test]
+fn test_empty_inputs() {
+    let mut model = DBSCAN::new(0.5, 3);
+    assert!(model.train(&Matrix::new(0, 2, Vec::<f64>::new())).is_err());
+}
+
+#[test]
+fn test_recovers_clusters_and_noise() {
+    let inputs = Matrix::new(7, 2, vec![1.0, 2.0,
+                                        1.1, 2.2,
+                                        0.9, 1.9,
+                                        1.0, 2.1,
+                                        -2.0, 3.0,
+                                        -2.2, 3.1,
+                                        10.0, 10.0]);
+
+    let mut model = DBSCAN::new(0.5, 2);
+    model.train(&inputs).unwrap();
+
+    // The number of clusters is found from the density, and the
+    // isolated point is left as noise rather than forced into a cluster.
+    let clustering = model.clusters().unwrap();
+    assert_eq!(clustering[6], None);
+
+    let truth = [0, 0, 0, 0, 1, 1, 2];
+    assert_eq!(adjusted_rand_score(clustering.iter(), truth.iter()), 1.);
+}
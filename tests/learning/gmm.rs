@@ -0,0 +1,187 @@
+use rm::linalg::{Matrix, BaseMatrix, Axes};
+use rm::learning::UnSupModel;
+use rm::learning::gmm::{self, GaussianMixtureModel, CovOption, MeanInit, SelectionCriterion};
+use rm::analysis::score::{hard_labels, normalized_mutual_info_score};
+
+use rand::{StdRng, SeedableRng};
+
+/// Two well separated square grids of points.
+fn two_clusters() -> Matrix<f64> {
+    let mut data = Vec::new();
+    for &(cx, cy) in &[(0., 0.), (10., 10.)] {
+        for i in 0..4 {
+            for j in 0..4 {
+                data.push(cx + i as f64 * 0.3);
+                data.push(cy + j as f64 * 0.3);
+            }
+        }
+    }
+    Matrix::new(32, 2, data)
+}
+
+#[test]
+fn test_score_samples_matches_log_likelihood() {
+    let inputs = two_clusters();
+
+    let mut model = GaussianMixtureModel::new(2);
+    model.cov_option = CovOption::Diagonal;
+    model.train(&inputs).unwrap();
+
+    let log_lik = model.log_likelihood().unwrap();
+    let score = model.score(&inputs).unwrap();
+    assert!((log_lik - score).abs() < 1e-9 * log_lik.abs().max(1.));
+
+    let log_dens = model.score_samples(&inputs).unwrap();
+    assert_eq!(log_dens.size(), 32);
+    assert!((log_dens.sum() - score).abs() < 1e-9 * score.abs().max(1.));
+}
+
+#[test]
+fn test_single_gaussian_log_likelihood() {
+    let inputs = Matrix::new(4, 1, vec![-1., 0., 0., 1.]);
+
+    let mut model = GaussianMixtureModel::new(1);
+    model.set_max_iters(1);
+    model.train(&inputs).unwrap();
+
+    // One EM step gives the maximum likelihood mean 0 and variance 0.5.
+    let var = 0.5f64;
+    let expected = inputs.data()
+        .iter()
+        .map(|x| -0.5 * (2. * ::std::f64::consts::PI * var).ln() - x * x / (2. * var))
+        .sum::<f64>();
+    assert!((model.log_likelihood().unwrap() - expected).abs() < 1e-9);
+    assert!((model.bic(&inputs).unwrap() - (-2. * expected + 2. * 4f64.ln())).abs() < 1e-9);
+    assert!((model.aic(&inputs).unwrap() - (-2. * expected + 4.)).abs() < 1e-9);
+}
+
+#[test]
+fn test_tied_and_spherical_covariances() {
+    let inputs = two_clusters();
+
+    let mut tied = GaussianMixtureModel::new(2);
+    tied.cov_option = CovOption::Tied;
+    tied.train(&inputs).unwrap();
+
+    let covs = tied.covariances().unwrap();
+    assert_eq!(covs[0].data(), covs[1].data());
+
+    let mut spherical = GaussianMixtureModel::new(2);
+    spherical.cov_option = CovOption::Spherical;
+    spherical.train(&inputs).unwrap();
+
+    for cov in spherical.covariances().unwrap() {
+        assert_eq!(cov[[0, 1]], 0.);
+        assert_eq!(cov[[1, 0]], 0.);
+        assert!((cov[[0, 0]] - cov[[1, 1]]).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_select_comp_count() {
+    let inputs = two_clusters();
+
+    for &criterion in &[SelectionCriterion::BIC, SelectionCriterion::AIC] {
+        let model = gmm::select_comp_count(&inputs, 1..3, CovOption::Diagonal, criterion)
+            .unwrap();
+        assert_eq!(model.mixture_weights().size(), 2);
+    }
+}
+
+#[test]
+fn test_select_comp_count_empty() {
+    let inputs = two_clusters();
+    assert!(gmm::select_comp_count(&inputs, vec![], CovOption::Full, SelectionCriterion::BIC)
+        .is_err());
+}
+
+#[test]
+fn test_sample() {
+    let inputs = two_clusters();
+
+    let mut model = GaussianMixtureModel::new(1);
+    model.train(&inputs).unwrap();
+
+    let seed: &[_] = &[1, 2, 3];
+    let mut rng = StdRng::from_seed(seed);
+    let samples = model.sample(2000, &mut rng).unwrap();
+
+    assert_eq!(samples.rows(), 2000);
+    assert_eq!(samples.cols(), 2);
+
+    let sample_mean = samples.mean(Axes::Row);
+    let model_mean = model.means().unwrap();
+    assert!((sample_mean[0] - model_mean[[0, 0]]).abs() < 0.5);
+    assert!((sample_mean[1] - model_mean[[0, 1]]).abs() < 0.5);
+}
+
+#[test]
+fn test_sample_untrained() {
+    let model = GaussianMixtureModel::new(2);
+
+    let seed: &[_] = &[1, 2, 3];
+    let mut rng = StdRng::from_seed(seed);
+    assert!(model.sample(5, &mut rng).is_err());
+}
+
+#[test]
+fn test_kplusplus_mean_init() {
+    let inputs = two_clusters();
+
+    let mut model = GaussianMixtureModel::new(2);
+    model.cov_option = CovOption::Diagonal;
+    model.mean_init = MeanInit::KPlusPlus;
+    model.train(&inputs).unwrap();
+
+    // The well separated clusters are each found by one Gaussian.
+    let means = model.means().unwrap();
+    let (lo, hi) = if means[[0, 0]] < means[[1, 0]] { (0, 1) } else { (1, 0) };
+    assert!((means[[lo, 0]] - 0.45).abs() < 1e-6);
+    assert!((means[[hi, 0]] - 10.45).abs() < 1e-6);
+}
+
+#[test]
+fn test_restarts_and_iterations() {
+    let inputs = two_clusters();
+
+    let mut model = GaussianMixtureModel::new(2);
+    model.cov_option = CovOption::Diagonal;
+    assert_eq!(model.iterations(), None);
+
+    model.set_n_init(5);
+    model.set_tol(1e-8);
+    model.train(&inputs).unwrap();
+
+    let iterations = model.iterations().unwrap();
+    assert!(iterations >= 1 && iterations <= 100);
+    assert!((model.log_likelihood().unwrap() - model.score(&inputs).unwrap()).abs() < 1e-9);
+}
+
+#[test]
+fn test_too_many_components() {
+    let inputs = Matrix::new(3, 1, vec![0., 1., 2.]);
+
+    let mut model = GaussianMixtureModel::new(4);
+    assert!(model.train(&inputs).is_err());
+}
+
+#[test]
+fn test_recovers_clusters() {
+    let inputs = two_clusters();
+
+    let mut model = GaussianMixtureModel::new(2);
+    model.cov_option = CovOption::Diagonal;
+    model.mean_init = MeanInit::KPlusPlus;
+    model.train(&inputs).unwrap();
+
+    let probs = model.predict(&inputs).unwrap();
+    let labels = hard_labels(&probs);
+    let truth = (0..32).map(|i| i / 16).collect::<Vec<usize>>();
+
+    let score = normalized_mutual_info_score(labels.iter(), truth.iter());
+    assert!((score - 1.).abs() < 1e-12);
+
+    // Each point is confidently assigned and the components share the points evenly.
+    assert!(probs.row_iter().all(|r| r.raw_slice().iter().cloned().fold(0., f64::max) > 0.99));
+    assert!(model.mixture_weights().iter().all(|w| (w - 0.5).abs() < 1e-6));
+}
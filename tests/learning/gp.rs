@@ -1,7 +1,10 @@
-use rm::linalg::Matrix;
+use rm::linalg::{Matrix, BaseMatrix, Axes};
 use rm::linalg::Vector;
 use rm::learning::SupModel;
-use rm::learning::gp::GaussianProcess;
+use rm::learning::gp::{GaussianProcess, MultiOutputGaussianProcess, ConstMean};
+use rm::learning::toolkit::kernel::SquaredExp;
+
+use rand::{StdRng, SeedableRng};
 
 #[test]
 fn test_default_gp() {
@@ -17,3 +20,131 @@ fn test_default_gp() {
 
 	let _outputs = gp.predict(&test_inputs).unwrap();
 }
+
+#[test]
+fn test_log_marginal_likelihood_grad() {
+    let inputs = Matrix::new(6,1,vec![0.,0.7,1.5,2.2,3.1,4.]);
+    let targets = Vector::new(vec![0.1,0.8,1.,0.6,-0.2,-0.9]);
+
+    let make_gp = |params: &[f64]| {
+        GaussianProcess::new(SquaredExp::new(params[0].exp(), params[1].exp()),
+                             ConstMean::default(),
+                             params[2].exp())
+    };
+
+    let params = vec![0.3f64.ln(), 1.2f64.ln(), 0.1f64.ln()];
+    let (lml, grad) = make_gp(&params).log_marginal_likelihood_grad(&inputs, &targets).unwrap();
+
+    assert!((lml - make_gp(&params).log_marginal_likelihood(&inputs, &targets).unwrap()).abs() < 1e-12);
+    assert_eq!(grad.len(), 3);
+
+    let eps = 1e-6;
+    for i in 0..params.len() {
+        let mut plus = params.clone();
+        plus[i] += eps;
+        let mut minus = params.clone();
+        minus[i] -= eps;
+
+        let lml_plus = make_gp(&plus).log_marginal_likelihood(&inputs, &targets).unwrap();
+        let lml_minus = make_gp(&minus).log_marginal_likelihood(&inputs, &targets).unwrap();
+        let numeric = (lml_plus - lml_minus) / (2f64 * eps);
+
+        assert!((numeric - grad[i]).abs() < 1e-5, "param {}: {} vs {}", i, numeric, grad[i]);
+    }
+}
+
+#[test]
+fn test_hyperopt_improves_marginal_likelihood() {
+    let mut gp = GaussianProcess::new(SquaredExp::new(0.1, 5.), ConstMean::default(), 1.);
+
+    let inputs = Matrix::new(10,1,vec![0.,1.,2.,3.,4.,5.,6.,7.,8.,9.]);
+    let targets = Vector::new(vec![0.,1.,2.,3.,4.,4.,3.,2.,1.,0.]);
+
+    let before = gp.log_marginal_likelihood(&inputs, &targets).unwrap();
+    let seed: &[_] = &[3, 1, 4];
+    let mut rng = StdRng::from_seed(seed);
+    let after = gp.train_with_hyperopt_with_rng(&inputs, &targets, 2, &mut rng).unwrap();
+
+    assert!(after > before);
+    assert!((after - gp.log_marginal_likelihood(&inputs, &targets).unwrap()).abs() < 1e-8);
+
+    let _outputs = gp.predict(&inputs).unwrap();
+}
+
+#[test]
+fn test_predict_with_variance_matches_posterior() {
+    let mut gp = GaussianProcess::default();
+    gp.noise = 0.1;
+
+    let inputs = Matrix::new(6,1,vec![0.,0.7,1.5,2.2,3.1,4.]);
+    let targets = Vector::new(vec![0.1,0.8,1.,0.6,-0.2,-0.9]);
+    gp.train(&inputs, &targets).unwrap();
+
+    let test_inputs = Matrix::new(4,1,vec![-1.,0.5,2.,6.]);
+    let (mean, cov) = gp.get_posterior(&test_inputs).unwrap();
+    let (fast_mean, var) = gp.predict_with_variance(&test_inputs).unwrap();
+
+    for i in 0..4 {
+        assert!((mean[i] - fast_mean[i]).abs() < 1e-10);
+        assert!((cov[[i, i]] - var[i]).abs() < 1e-10);
+    }
+}
+
+#[test]
+fn test_sample_posterior_is_seedable() {
+    let mut gp = GaussianProcess::default();
+    gp.noise = 0.1;
+
+    let inputs = Matrix::new(4,1,vec![0.,1.,2.,3.]);
+    let targets = Vector::new(vec![0.,1.,0.,-1.]);
+    gp.train(&inputs, &targets).unwrap();
+
+    let test_inputs = Matrix::new(3,1,vec![0.5,1.5,2.5]);
+    let seed: &[_] = &[1, 2, 3];
+    let samples = gp.sample_posterior(&test_inputs, 2000, &mut StdRng::from_seed(seed)).unwrap();
+    let repeat = gp.sample_posterior(&test_inputs, 2000, &mut StdRng::from_seed(seed)).unwrap();
+
+    assert_eq!(samples.rows(), 2000);
+    assert_eq!(samples.data(), repeat.data());
+
+    let (mean, var) = gp.predict_with_variance(&test_inputs).unwrap();
+    let sample_mean = samples.mean(Axes::Row);
+    for i in 0..3 {
+        assert!((sample_mean[i] - mean[i]).abs() < 5. * (var[i] / 2000.).sqrt() + 1e-3);
+    }
+}
+
+#[test]
+fn test_multi_output_gp_matches_single_outputs() {
+    let inputs = Matrix::new(5,1,vec![0.,1.,2.,3.,4.]);
+    let y1 = vec![0.,1.,2.,1.,0.];
+    let y2 = vec![1.,0.,-1.,0.,1.];
+    let targets = Matrix::new(5,2,y1.iter().zip(&y2).flat_map(|(a, b)| vec![*a, *b]).collect::<Vec<_>>());
+
+    let mut multi = MultiOutputGaussianProcess::new(SquaredExp::default(), ConstMean::default(), 0.1);
+    multi.train(&inputs, &targets).unwrap();
+
+    let test_inputs = Matrix::new(3,1,vec![0.5,2.5,7.]);
+    let (outputs, var) = multi.predict_with_variance(&test_inputs).unwrap();
+    assert_eq!(outputs.data(), multi.predict(&test_inputs).unwrap().data());
+
+    for (j, y) in vec![y1, y2].into_iter().enumerate() {
+        let mut single = GaussianProcess::new(SquaredExp::default(), ConstMean::default(), 0.1);
+        single.train(&inputs, &Vector::new(y)).unwrap();
+        let (mean, single_var) = single.predict_with_variance(&test_inputs).unwrap();
+
+        for i in 0..3 {
+            assert!((outputs[[i, j]] - mean[i]).abs() < 1e-10);
+            assert!((var[i] - single_var[i]).abs() < 1e-10);
+        }
+    }
+}
+
+#[test]
+fn test_multi_output_gp_mismatched_targets() {
+    let mut multi = MultiOutputGaussianProcess::default();
+    let inputs = Matrix::new(5,1,vec![0.,1.,2.,3.,4.]);
+    let targets = Matrix::new(4,2,vec![0.;8]);
+
+    assert!(multi.train(&inputs, &targets).is_err());
+}
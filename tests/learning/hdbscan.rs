@@ -0,0 +1,105 @@
+use rm::linalg::Matrix;
+
+use rm::learning::hdbscan::HDBSCAN;
+use rm::learning::dbscan::DBSCAN;
+use rm::learning::UnSupModel;
+
+// A dense cluster with spacing 0.05, a sparse cluster
+// with spacing 0.5 and an outlier.
+fn varying_density() -> Matrix<f64> {
+    let mut data = (0..20).map(|i| i as f64 * 0.05).collect::<Vec<_>>();
+    data.extend((0..20).map(|i| 10. + i as f64 * 0.5));
+    data.push(50.);
+    Matrix::new(41, 1, data)
+}
+
+#[test]
+fn test_varying_density() {
+    let inputs = varying_density();
+
+    let mut model = HDBSCAN::new(5);
+    model.train(&inputs).unwrap();
+
+    let clustering = model.clusters().unwrap();
+    assert!(clustering.data().iter().take(20).all(|x| *x == clustering[0]));
+    assert!(clustering.data().iter().skip(20).take(20).all(|x| *x == clustering[20]));
+    assert!(clustering[0].is_some() && clustering[20].is_some());
+    assert!(clustering[0] != clustering[20]);
+    assert_eq!(clustering[40], None);
+}
+
+#[test]
+fn test_dbscan_single_eps_fails() {
+    // No single epsilon separates the dense cluster
+    // while keeping the sparse one.
+    let inputs = varying_density();
+
+    let mut model = DBSCAN::new(0.2, 5);
+    model.train(&inputs).unwrap();
+
+    let clustering = model.clusters().unwrap();
+    assert!(clustering.data().iter().skip(20).take(20).all(|x| x.is_none()));
+}
+
+#[test]
+fn test_outlier_scores() {
+    let inputs = varying_density();
+
+    let mut model = HDBSCAN::new(5);
+    model.train(&inputs).unwrap();
+
+    let scores = model.outlier_scores().unwrap();
+    assert!(scores.data().iter().all(|&s| s >= 0. && s <= 1.));
+    assert!(scores[40] > 0.9);
+    assert!(scores.data().iter().take(40).all(|&s| s < scores[40]));
+}
+
+#[test]
+fn test_condensed_tree() {
+    let inputs = varying_density();
+
+    let mut model = HDBSCAN::new(5);
+    model.train(&inputs).unwrap();
+
+    let tree = model.condensed_tree().unwrap();
+    // Every point leaves the tree exactly once.
+    let mut points = tree.iter().filter(|e| e.size == 1).map(|e| e.child).collect::<Vec<_>>();
+    points.sort();
+    assert_eq!(points, (0..41).collect::<Vec<_>>());
+    // The root cluster is labelled by the number of points.
+    assert!(tree.iter().all(|e| e.parent >= 41));
+    assert!(tree.iter().any(|e| e.parent == 41));
+}
+
+#[test]
+fn test_prediction() {
+    let inputs = varying_density();
+
+    let mut model = HDBSCAN::new(5);
+    model.train(&inputs).unwrap();
+
+    let classes = model.predict(&Matrix::new(3, 1, vec![0.52, 15.2, 30.])).unwrap();
+    assert_eq!(classes[0], model.clusters().unwrap()[0]);
+    assert_eq!(classes[1], model.clusters().unwrap()[20]);
+    assert_eq!(classes[2], None);
+}
+
+#[test]
+fn test_too_few_points() {
+    let mut model = HDBSCAN::new(5);
+    model.train(&Matrix::new(3, 1, vec![0., 1., 2.])).unwrap();
+
+    assert!(model.clusters().unwrap().data().iter().all(|x| x.is_none()));
+}
+
+#[test]
+fn test_untrained() {
+    let model = HDBSCAN::default();
+    assert!(model.predict(&Matrix::new(1, 1, vec![0.])).is_err());
+}
+
+#[test]
+#[should_panic]
+fn test_min_cluster_size_too_small() {
+    let _ = HDBSCAN::new(1);
+}
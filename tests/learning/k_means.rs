@@ -1,7 +1,9 @@
-use rm::linalg::Matrix;
-use rm::learning::UnSupModel;
+use rm::linalg::{Matrix, BaseMatrix};
+use rm::learning::{LearningResult, UnSupModel};
 use rm::learning::k_means::KMeansClassifier;
-use rm::learning::k_means::{Forgy, RandomPartition, KPlusPlus};
+use rm::learning::k_means::{Forgy, RandomPartition, KPlusPlus, Initializer};
+use rm::learning::toolkit::distance::Manhattan;
+use rm::analysis::score::adjusted_rand_score;
 
 #[test]
 fn test_model_default() {
@@ -99,3 +101,117 @@ fn test_two_centroids() {
     assert!(classes.data().iter().take(3).all(|x| *x == class_a));
     assert!(classes.data().iter().skip(3).all(|x| *x == class_b));
 }
+
+#[test]
+fn test_inertia_and_iterations() {
+    let mut model = KMeansClassifier::new(2);
+    let inputs = Matrix::new(4, 1, vec![0.0, 1.0, 10.0, 11.0]);
+
+    assert_eq!(model.inertia(), None);
+    assert_eq!(model.iterations(), None);
+
+    model.set_n_init(10);
+    model.train(&inputs).unwrap();
+
+    assert!((model.inertia().unwrap() - 1.0).abs() < 1e-12);
+    let iterations = model.iterations().unwrap();
+    assert!(iterations >= 1 && iterations <= model.iters());
+}
+
+#[test]
+fn test_restarts_keep_lowest_inertia() {
+    let inputs = Matrix::new(9, 1, vec![0.0, 0.1, 0.2, 5.0, 5.1, 5.2, 10.0, 10.1, 10.2]);
+
+    let mut single = KMeansClassifier::new_specified(3, 100, Forgy);
+    single.train(&inputs).unwrap();
+
+    let mut restarted = KMeansClassifier::new_specified(3, 100, Forgy);
+    restarted.set_n_init(50);
+    restarted.train(&inputs).unwrap();
+
+    assert!(restarted.inertia().unwrap() <= single.inertia().unwrap() + 1e-12);
+    assert!((restarted.inertia().unwrap() - 0.06).abs() < 1e-9);
+}
+
+/// Places the second centroid far away from every point.
+#[derive(Debug)]
+struct FarCentroid;
+
+impl Initializer for FarCentroid {
+    fn init_centroids(&self, _: usize, _: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        Ok(Matrix::new(2, 1, vec![0.0, 100.0]))
+    }
+}
+
+#[test]
+fn test_empty_cluster_keeps_centroid() {
+    let inputs = Matrix::new(3, 1, vec![0.0, 1.0, 2.0]);
+
+    let mut model = KMeansClassifier::new_specified(2, 10, FarCentroid);
+    model.train(&inputs).unwrap();
+
+    let centroids = model.centroids().as_ref().unwrap();
+    assert_eq!(centroids.data(), &vec![1.0, 100.0]);
+}
+
+#[test]
+fn test_large_tolerance_stops_early() {
+    let mut model = KMeansClassifier::new_specified(2, 100, RandomPartition);
+    let inputs = Matrix::new(6, 1, vec![0.0, 0.5, 1.0, 8.0, 8.5, 9.0]);
+
+    model.set_tol(1e10);
+    model.train(&inputs).unwrap();
+
+    // The first iteration has no previous inertia to compare against.
+    assert_eq!(model.iterations(), Some(2));
+}
+
+#[test]
+fn test_manhattan_metric() {
+    let inputs = Matrix::new(4, 2, vec![0.0, 0.0, 1.0, 1.0, 10.0, 10.0, 11.0, 11.0]);
+
+    let mut model = KMeansClassifier::new(2);
+    model.set_metric(Manhattan);
+    model.set_n_init(10);
+    model.train(&inputs).unwrap();
+
+    let classes = model.predict(&inputs).unwrap();
+    assert_eq!(classes[0], classes[1]);
+    assert_eq!(classes[2], classes[3]);
+    assert!(classes[0] != classes[2]);
+
+    // Each point is a Manhattan distance of 1 from its centroid.
+    assert!((model.inertia().unwrap() - 4.0).abs() < 1e-12);
+}
+
+#[test]
+#[should_panic]
+fn test_zero_n_init() {
+    let mut model = KMeansClassifier::new(2);
+    model.set_n_init(0);
+}
+
+#[test]
+fn test_recovers_clusters() {
+    let mut model = KMeansClassifier::new(2);
+    let inputs = Matrix::new(6, 2, vec![59.59375, 270.6875,
+                                        51.59375, 307.6875,
+                                        86.59375, 286.6875,
+                                        319.59375, 145.6875,
+                                        314.59375, 174.6875,
+                                        350.59375, 161.6875]);
+
+    model.train(&inputs).unwrap();
+    let classes = model.predict(&inputs).unwrap();
+
+    let truth = [0, 0, 0, 1, 1, 1];
+    assert_eq!(adjusted_rand_score(classes.iter(), truth.iter()), 1.);
+
+    // Each centroid is the mean of its cluster.
+    let centroids = model.centroids().as_ref().unwrap();
+    for &(i, mean) in &[(0, [65.92708333, 288.35416667]), (3, [328.26041667, 160.6875])] {
+        let centroid = centroids.row(classes[i]);
+        assert!((centroid[0] - mean[0]).abs() < 1e-6);
+        assert!((centroid[1] - mean[1]).abs() < 1e-6);
+    }
+}
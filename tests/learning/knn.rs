@@ -1,6 +1,11 @@
-use rm::linalg::{Matrix, Vector};
-use rm::learning::SupModel;
-use rm::learning::knn::KNNClassifier;
+use rm::linalg::{Matrix, BaseMatrix, Vector};
+use rm::learning::{LearningResult, SupModel};
+use rm::learning::knn::{KNNClassifier, KNNRegressor, Weighting, KDTree, BallTree, BruteForce};
+use rm::learning::knn::KNearestSearch;
+use rm::learning::toolkit::distance::Manhattan;
+use rm::analysis::cross_validation::{k_fold_validate, cross_validate, KFold, StratifiedKFold,
+                                     TimeSeriesSplit};
+use rm::analysis::score::{r2_score, adjusted_r2_score, neg_mean_absolute_error};
 
 #[test]
 fn test_knn() {
@@ -41,6 +46,226 @@ fn test_knn_long() {
     assert_eq!(res, exp);
 }
 
+#[test]
+fn test_knn_distance_weighting() {
+    let data = matrix![0.; 1.; 1.2; 10.];
+    let target = Vector::new(vec![0, 1, 1, 0]);
+
+    // Uniform votes are 2:1 for class 1, but the nearer point wins by distance.
+    let mut knn = KNNClassifier::new(3);
+    knn.train(&data, &target).unwrap();
+    assert_eq!(knn.predict(&matrix![0.1]).unwrap(), Vector::new(vec![1]));
+
+    knn.set_weighting(Weighting::Distance);
+    assert_eq!(knn.weighting(), Weighting::Distance);
+    assert_eq!(knn.predict(&matrix![0.1]).unwrap(), Vector::new(vec![0]));
+}
+
+#[test]
+fn test_knn_predict_proba() {
+    let data = matrix![0.; 1.; 2.; 3.; 10.];
+    let target = Vector::new(vec![3, 1, 3, 7, 7]);
+
+    let mut knn = KNNClassifier::new(4);
+    assert!(knn.predict_proba(&data).is_err());
+    knn.train(&data, &target).unwrap();
+
+    assert_eq!(knn.classes().unwrap(), &[1, 3, 7]);
+
+    let proba = knn.predict_proba(&matrix![1.; 9.]).unwrap();
+    assert_eq!(proba.rows(), 2);
+    assert_eq!(proba.cols(), 3);
+    assert_eq!(proba.data(), &vec![0.25, 0.5, 0.25,
+                                   0.25, 0.25, 0.5]);
+
+    knn.set_weighting(Weighting::Distance);
+    let proba = knn.predict_proba(&matrix![1.; 0.5]).unwrap();
+    // An exact match takes all of the weight.
+    assert_eq!(proba.select_rows(&[0]).into_vec(), vec![1., 0., 0.]);
+    for row in proba.row_iter() {
+        assert!((row.sum() - 1.).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_knn_metric() {
+    // The closest point to the origin is [2, 2] under the Euclidean metric
+    // but [0, 3] under the Manhattan metric.
+    let data = matrix![0., 3.;
+                       2., 2.;
+                       10., 10.];
+    let target = Vector::new(vec![0, 1, 1]);
+    let origin = matrix![0., 0.];
+
+    let mut knn = KNNClassifier::new(1);
+    knn.train(&data, &target).unwrap();
+    assert_eq!(knn.predict(&origin).unwrap(), Vector::new(vec![1]));
+
+    let mut knn = KNNClassifier::new_specified(1, KDTree::with_metric(2, Manhattan));
+    knn.train(&data, &target).unwrap();
+    assert_eq!(knn.predict(&origin).unwrap(), Vector::new(vec![0]));
+
+    let mut knn = KNNClassifier::new_specified(1, BallTree::with_metric(2, Manhattan));
+    knn.train(&data, &target).unwrap();
+    assert_eq!(knn.predict(&origin).unwrap(), Vector::new(vec![0]));
+
+    let mut knn = KNNClassifier::new_specified(1, BruteForce::with_metric(Manhattan));
+    knn.train(&data, &target).unwrap();
+    assert_eq!(knn.predict(&origin).unwrap(), Vector::new(vec![0]));
+}
+
+#[test]
+fn test_search_radius_searchers_agree() {
+    let data = Matrix::new(50, 2, (0..50).flat_map(|i| vec![((i * 37) % 23) as f64 / 4.,
+                                                            ((i * 11) % 17) as f64 / 3.])
+                                         .collect::<Vec<_>>());
+    let point = [2.6, 3.1];
+
+    let mut brute = BruteForce::new();
+    brute.build(data.clone());
+    let expected = brute.search_radius(&point, 1.5).unwrap();
+    assert!(!expected.0.is_empty());
+    assert!(expected.1.iter().all(|&d| d < 1.5));
+    assert!(expected.1.windows(2).all(|w| w[0] <= w[1]));
+
+    let mut kd = KDTree::new(4);
+    kd.build(data.clone());
+    assert_eq!(kd.search_radius(&point, 1.5).unwrap(), expected);
+
+    let mut ball = BallTree::new(4);
+    ball.build(data.clone());
+    assert_eq!(ball.search_radius(&point, 1.5).unwrap(), expected);
+
+    let mut knn_only = KNearestOnly::default();
+    knn_only.build(data);
+    assert_eq!(knn_only.search_radius(&point, 1.5).unwrap(), expected);
+    assert_eq!(knn_only.search_radius(&point, 100.).unwrap().0.len(), 50);
+}
+
+/// A searcher relying on the default `search_radius`.
+#[derive(Default)]
+struct KNearestOnly(BruteForce);
+
+impl KNearestSearch for KNearestOnly {
+    fn build(&mut self, data: Matrix<f64>) {
+        self.0.build(data);
+    }
+
+    fn search(&self, point: &[f64], k: usize) -> LearningResult<(Vec<usize>, Vec<f64>)> {
+        self.0.search(point, k)
+    }
+}
+
+#[test]
+fn test_search_many() {
+    let data = matrix![1., 2.;
+                       8., 0.;
+                       6., 10.;
+                       3., 6.;
+                       0., 3.];
+    let points = matrix![3., 4.9;
+                         7., 1.];
+
+    let mut tree = KDTree::new(2);
+    tree.build(data);
+
+    let results = tree.search_many(&points, 2).unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0], tree.search(&[3., 4.9], 2).unwrap());
+    assert_eq!(results[1], tree.search(&[7., 1.], 2).unwrap());
+    assert_eq!(results[1].0[0], 1);
+}
+
+#[test]
+fn test_knn_regressor() {
+    let data = matrix![0.; 1.; 2.; 3.; 4.];
+    let target = Vector::new(vec![0., 1., 4., 9., 16.]);
+
+    let mut knn = KNNRegressor::new(2);
+    knn.train(&data, &target).unwrap();
+
+    let res: Vector<f64> = knn.predict(&matrix![0.2; 3.9]).unwrap();
+    assert_eq!(res, Vector::new(vec![0.5, 12.5]));
+
+    knn.set_weighting(Weighting::Distance);
+    let res: Vector<f64> = knn.predict(&matrix![0.25; 2.]).unwrap();
+    assert!((res[0] - 0.25).abs() < 1e-12);
+    assert_eq!(res[1], 4.);
+}
+
+#[test]
+fn test_knn_regressor_multi_output() {
+    let data = matrix![0.; 1.; 2.; 3.];
+    let target = matrix![0., 10.;
+                         1., 20.;
+                         2., 30.;
+                         3., 40.];
+
+    let mut knn = KNNRegressor::new(2);
+    knn.train(&data, &target).unwrap();
+
+    let res: Matrix<f64> = knn.predict(&matrix![0.4; 2.6]).unwrap();
+    assert_eq!(res, matrix![0.5, 15.; 2.5, 35.]);
+
+    // Multiple outputs can't be predicted as a vector.
+    let res: Result<Vector<f64>, _> = knn.predict(&matrix![1.]);
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_knn_regressor_cross_validation() {
+    let data = Matrix::new(20, 1, (0..20).map(|x| x as f64).collect::<Vec<_>>());
+    let target = Matrix::new(20, 2, (0..20).flat_map(|x| vec![x as f64, 2. * x as f64])
+        .collect::<Vec<_>>());
+
+    let mut knn = KNNRegressor::new(2);
+    let kfold = KFold::new(4).unwrap();
+    let r2 = k_fold_validate(&mut knn, &data, &target, &kfold, r2_score).unwrap();
+    assert_eq!(r2.len(), 4);
+    assert!(r2.iter().all(|&s| s <= 1.));
+
+    let adjusted = k_fold_validate(&mut knn, &data, &target, &kfold,
+                                   |o: &Matrix<f64>, t: &Matrix<f64>| adjusted_r2_score(o, t, 1))
+        .unwrap();
+    assert!(adjusted.iter().all(|&s| s <= 1.));
+
+    let mae = k_fold_validate(&mut knn, &data, &target, &kfold, neg_mean_absolute_error).unwrap();
+    assert!(mae.iter().all(|&s| s <= 0.));
+
+    // Forecasting past the training data repeats the last training targets.
+    let mae = k_fold_validate(&mut knn, &data, &target, &TimeSeriesSplit::new(3).unwrap(),
+                              neg_mean_absolute_error)
+        .unwrap();
+    assert_eq!(mae, vec![-5.25, -5.25, -5.25]);
+}
+
+#[test]
+fn test_knn_classifier_cross_validation() {
+    let data = Matrix::new(12, 1, vec![0., 0.1, 0.2, 0.3, 0.4, 0.5,
+                                       5., 5.1, 5.2, 5.3, 5.4, 5.5]);
+    let target = Vector::new(vec![0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1]);
+    let accuracy = |o: &Vector<usize>, t: &Vector<usize>| {
+        o.data().iter().zip(t.data()).filter(|&(a, b)| a == b).count() as f64 / t.size() as f64
+    };
+
+    let mut knn = KNNClassifier::new(2);
+    let splitter = StratifiedKFold::new(3, target.data()).unwrap();
+    let cv = cross_validate(&mut knn, &data, &target, &splitter, accuracy).unwrap();
+    assert_eq!(cv.scores(), &[1., 1., 1.]);
+    assert_eq!(cv.out_of_fold_predictions().unwrap(), target);
+
+    let kfold = KFold::new(4).unwrap();
+    let scores = k_fold_validate(&mut knn, &data, &target, &kfold, accuracy).unwrap();
+    assert_eq!(scores, vec![1.; 4]);
+}
+
+#[test]
+fn test_knn_regressor_untrained() {
+    let knn = KNNRegressor::new(2);
+    let res: Result<Vector<f64>, _> = knn.predict(&matrix![1.]);
+    assert!(res.is_err());
+}
+
 #[cfg(feature = "datasets")]
 pub mod tests_datasets {
 
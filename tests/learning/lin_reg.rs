@@ -2,7 +2,10 @@ use rm::linalg::Matrix;
 use rm::linalg::Vector;
 use rm::learning::SupModel;
 use rm::learning::lin_reg::LinRegressor;
+use rm::analysis::cross_validation::{cross_validate, cross_validate_with_rng, KFold, LeaveOneOut,
+                                     ShuffleSplit, CrossValidation};
 use libnum::abs;
+use rand::{SeedableRng, StdRng};
 
 #[test]
 fn test_optimized_regression() {
@@ -10,7 +13,7 @@ fn test_optimized_regression() {
     let inputs = Matrix::new(3, 1, vec![2.0, 3.0, 4.0]);
     let targets = Vector::new(vec![5.0, 6.0, 7.0]);
 
-    lin_mod.train_with_optimization(&inputs, &targets);
+    lin_mod.train_with_optimization(&inputs, &targets).unwrap();
 
     let _ = lin_mod.parameters().unwrap();
 }
@@ -83,3 +86,76 @@ fn test_train_no_data() {
 
     assert!(res.is_err());
 }
+
+#[test]
+fn test_cross_validation() {
+    let inputs = Matrix::new(10, 1, (0..10).map(|x| x as f64).collect::<Vec<_>>());
+    let targets = Vector::new((0..10).map(|x| 2. * x as f64 + 1.).collect::<Vec<_>>());
+    let neg_max_error = |o: &Vector<f64>, t: &Vector<f64>| {
+        -o.data().iter().zip(t.data()).fold(0f64, |m, (a, b)| m.max(abs(a - b)))
+    };
+
+    let mut lin_mod = LinRegressor::default();
+    let kfold = KFold::new(5).unwrap();
+    let cv = cross_validate(&mut lin_mod, &inputs, &targets, &kfold, neg_max_error).unwrap();
+    assert_eq!(cv.scores().len(), 5);
+    assert!(cv.scores().iter().all(|&s| s > -1e-6));
+
+    let predictions = cv.out_of_fold_predictions().unwrap();
+    assert_eq!(predictions.size(), 10);
+    assert!(predictions.data().iter().zip(targets.data()).all(|(p, t)| abs(p - t) < 1e-6));
+
+    let cv = cross_validate(&mut lin_mod, &inputs, &targets, &LeaveOneOut, neg_max_error)
+        .unwrap();
+    assert_eq!(cv.scores().len(), 10);
+    assert!(cv.out_of_fold_predictions().is_ok());
+
+    // Test sets drawn independently don't cover each sample exactly once.
+    let splitter = ShuffleSplit::new(3, 0.5).unwrap();
+    let cv = cross_validate(&mut lin_mod, &inputs, &targets, &splitter, neg_max_error).unwrap();
+    assert_eq!(cv.split_predictions().len(), 3);
+    assert!(cv.out_of_fold_predictions().is_err());
+}
+
+#[test]
+fn test_cross_validation_mismatched_rows() {
+    let inputs = Matrix::new(10, 1, (0..10).map(|x| x as f64).collect::<Vec<_>>());
+    let score = |_: &Vector<f64>, _: &Vector<f64>| 0f64;
+    let mut lin_mod = LinRegressor::default();
+
+    let targets = Vector::new((0..9).map(|x| x as f64).collect::<Vec<_>>());
+    let kfold = KFold::new(3).unwrap();
+    assert!(cross_validate(&mut lin_mod, &inputs, &targets, &kfold, score).is_err());
+
+    // There are more folds than samples.
+    let targets = Vector::new((0..10).map(|x| x as f64).collect::<Vec<_>>());
+    let kfold = KFold::new(11).unwrap();
+    assert!(cross_validate(&mut lin_mod, &inputs, &targets, &kfold, score).is_err());
+}
+
+#[test]
+fn test_cross_validation_with_rng() {
+    let inputs = Matrix::new(10, 1, (0..10).map(|x| x as f64).collect::<Vec<_>>());
+    let targets = Vector::new(vec![1., 4., 4., 8., 9., 10., 14., 15., 16., 20.]);
+    let neg_max_error = |o: &Vector<f64>, t: &Vector<f64>| {
+        -o.data().iter().zip(t.data()).fold(0f64, |m, (a, b)| m.max(abs(a - b)))
+    };
+    let seed: &[_] = &[3, 1, 4];
+
+    let mut lin_mod = LinRegressor::default();
+    let splitter = ShuffleSplit::new(3, 0.3).unwrap();
+    let mut validate = || {
+        cross_validate_with_rng(&mut lin_mod, &inputs, &targets, &splitter,
+                                &neg_max_error, &mut StdRng::from_seed(seed))
+            .unwrap()
+    };
+    let first = validate();
+    let second = validate();
+
+    // The same seed gives the same test sets and therefore the same results.
+    assert_eq!(first.scores(), second.scores());
+    let test_sets = |cv: &CrossValidation<Vector<f64>>| {
+        cv.split_predictions().iter().map(|p| p.0.clone()).collect::<Vec<_>>()
+    };
+    assert_eq!(test_sets(&first), test_sets(&second));
+}
@@ -0,0 +1,118 @@
+use rm::linalg::Matrix;
+
+use rm::learning::optics::OPTICS;
+use rm::learning::dbscan::DBSCAN;
+use rm::learning::UnSupModel;
+
+// Two groups of ten points with spacing 0.1 and an outlier.
+fn two_groups() -> Matrix<f64> {
+    let mut data = (0..10).map(|i| i as f64 / 10.).collect::<Vec<_>>();
+    data.extend((0..10).map(|i| 5. + i as f64 / 10.));
+    data.push(20.);
+    Matrix::new(21, 1, data)
+}
+
+#[test]
+fn test_basic_clusters() {
+    let inputs = two_groups();
+
+    let mut model = OPTICS::new(0.5, 3);
+    model.train(&inputs).unwrap();
+
+    let clustering = model.clusters().unwrap();
+    assert!(clustering.data().iter().take(10).all(|x| *x == clustering[0]));
+    assert!(clustering.data().iter().skip(10).take(10).all(|x| *x == clustering[10]));
+    assert!(clustering[0].is_some() && clustering[10].is_some());
+    assert!(clustering[0] != clustering[10]);
+    assert_eq!(clustering[20], None);
+}
+
+#[test]
+fn test_matches_dbscan() {
+    let inputs = two_groups();
+
+    let mut optics = OPTICS::new(0.5, 3);
+    optics.train(&inputs).unwrap();
+
+    let mut dbscan = DBSCAN::new(0.5, 3);
+    dbscan.train(&inputs).unwrap();
+
+    assert_eq!(optics.clusters().unwrap(), dbscan.clusters().unwrap());
+}
+
+#[test]
+fn test_ordering_and_reachability() {
+    let inputs = two_groups();
+
+    let mut model = OPTICS::new(0.5, 3);
+    model.train(&inputs).unwrap();
+
+    let mut ordering = model.ordering().unwrap().to_vec();
+    ordering.sort();
+    assert_eq!(ordering, (0..21).collect::<Vec<_>>());
+
+    // Only the first point has an undefined reachability.
+    let reach = model.reachability().unwrap();
+    assert_eq!(reach.data().iter().filter(|r| r.is_infinite()).count(), 1);
+    assert!(reach[20] > 14.);
+}
+
+#[test]
+fn test_extract_dbscan() {
+    let inputs = two_groups();
+
+    let mut model = OPTICS::new(0.5, 3);
+    model.train(&inputs).unwrap();
+
+    let clustering = model.extract_dbscan(6.).unwrap();
+    assert!(clustering.data().iter().take(20).all(|x| *x == Some(0)));
+    assert_eq!(clustering[20], None);
+
+    let clustering = model.extract_dbscan(0.05).unwrap();
+    assert!(clustering.data().iter().all(|x| x.is_none()));
+}
+
+#[test]
+fn test_extract_beyond_max_eps() {
+    let inputs = two_groups();
+
+    let mut model = OPTICS::new(0.5, 3);
+    model.set_max_eps(1.);
+    model.train(&inputs).unwrap();
+
+    assert!(model.extract_dbscan(0.8).is_ok());
+    assert!(model.extract_dbscan(2.).is_err());
+}
+
+#[test]
+fn test_outlier_scores() {
+    let inputs = two_groups();
+
+    let mut model = OPTICS::new(0.5, 3);
+    model.train(&inputs).unwrap();
+
+    let scores = model.outlier_scores().unwrap();
+    assert!(scores.data().iter().take(20).all(|&s| s < 1.5));
+    assert!(scores[20] > 10.);
+}
+
+#[test]
+fn test_prediction() {
+    let inputs = two_groups();
+
+    let mut model = OPTICS::new(0.5, 3);
+    model.train(&inputs).unwrap();
+
+    let classes = model.predict(&Matrix::new(3, 1, vec![0.45, 5.2, 10.])).unwrap();
+    assert_eq!(classes[0], model.clusters().unwrap()[0]);
+    assert_eq!(classes[1], model.clusters().unwrap()[10]);
+    assert_eq!(classes[2], None);
+}
+
+#[test]
+fn test_untrained() {
+    let model = OPTICS::default();
+
+    assert!(model.predict(&Matrix::new(1, 1, vec![0.])).is_err());
+    assert!(model.extract_dbscan(0.5).is_err());
+}
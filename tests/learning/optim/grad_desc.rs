@@ -1,7 +1,8 @@
 use rm::learning::optim::Optimizable;
 use rm::learning::optim::fmincg::ConjugateGD;
-use rm::learning::optim::grad_desc::{GradientDesc, StochasticGD, AdaGrad, RMSProp};
+use rm::learning::optim::grad_desc::{GradientDesc, StochasticGD, AdaGrad, RMSProp, GradClip};
 use rm::learning::optim::OptimAlgorithm;
+use rm::learning::error::ErrorKind;
 
 use rm::linalg::Matrix;
 
@@ -33,7 +34,7 @@ fn convex_fmincg_training() {
     let params = cgd.optimize(&x_sq,
                               &test_data[..],
                               &Matrix::zeros(1, 1),
-                              &Matrix::zeros(1, 1));
+                              &Matrix::zeros(1, 1)).unwrap();
 
     assert!(params[0] - 20f64 < 1e-10);
     assert!(x_sq.compute_grad(&params, &Matrix::zeros(1, 1), &Matrix::zeros(1, 1)).0 < 1e-10);
@@ -48,7 +49,7 @@ fn convex_gd_training() {
     let params = gd.optimize(&x_sq,
                               &test_data[..],
                               &Matrix::zeros(1, 1),
-                              &Matrix::zeros(1, 1));
+                              &Matrix::zeros(1, 1)).unwrap();
 
     assert!(params[0] - 20f64 < 1e-10);
     assert!(x_sq.compute_grad(&params, &Matrix::zeros(1, 1), &Matrix::zeros(1, 1)).0 < 1e-10);
@@ -63,7 +64,7 @@ fn convex_stochastic_gd_training() {
     let params = gd.optimize(&x_sq,
                               &test_data[..],
                               &Matrix::zeros(100, 1),
-                              &Matrix::zeros(100, 1));
+                              &Matrix::zeros(100, 1)).unwrap();
 
     assert!(params[0] - 20f64 < 1e-10);
     assert!(x_sq.compute_grad(&params, &Matrix::zeros(1, 1), &Matrix::zeros(1, 1)).0 < 1e-10);
@@ -78,7 +79,7 @@ fn convex_adagrad_training() {
     let params = gd.optimize(&x_sq,
                               &test_data[..],
                               &Matrix::zeros(100, 1),
-                              &Matrix::zeros(100, 1));
+                              &Matrix::zeros(100, 1)).unwrap();
 
     assert!(params[0] - 20f64 < 1e-10);
     assert!(x_sq.compute_grad(&params, &Matrix::zeros(1, 1), &Matrix::zeros(1, 1)).0 < 1e-10);
@@ -93,8 +94,144 @@ fn convex_rmsprop_training() {
   let params = rms.optimize(&x_sq,
                               &test_data[..],
                               &Matrix::zeros(100, 1),
-                              &Matrix::zeros(100, 1));
+                              &Matrix::zeros(100, 1)).unwrap();
 
   assert!(params[0] - 20f64 < 1e-10);
   assert!(x_sq.compute_grad(&params, &Matrix::zeros(1, 1), &Matrix::zeros(1, 1)).0 < 1e-10);
-}
\ No newline at end of file
+}
+
+#[test]
+fn diverging_gd_training() {
+    let x_sq = XSqModel { c: 20f64 };
+
+    // The step size is far too large so each step overshoots further.
+    let gd = GradientDesc::new(10f64, 1000);
+    let test_data = vec![25f64];
+    let res = gd.optimize(&x_sq,
+                          &test_data[..],
+                          &Matrix::zeros(1, 1),
+                          &Matrix::zeros(1, 1));
+
+    match res.unwrap_err().kind() {
+        &ErrorKind::Divergence => {}
+        _ => panic!("Expected a divergence error."),
+    }
+}
+
+#[test]
+fn diverging_stochastic_gd_training() {
+    let x_sq = XSqModel { c: 20f64 };
+
+    let gd = StochasticGD::new(10f64, 10f64, 100);
+    let test_data = vec![25f64];
+    let res = gd.optimize(&x_sq,
+                          &test_data[..],
+                          &Matrix::zeros(10, 1),
+                          &Matrix::zeros(10, 1));
+
+    assert!(res.is_err());
+}
+
+#[test]
+fn clipped_gd_training() {
+    let x_sq = XSqModel { c: 20f64 };
+
+    // Clipping keeps the oversized steps bounded.
+    let gd = GradientDesc::new(10f64, 1000).with_clipping(GradClip::Value(0.01));
+    let test_data = vec![25f64];
+    let params = gd.optimize(&x_sq,
+                             &test_data[..],
+                             &Matrix::zeros(1, 1),
+                             &Matrix::zeros(1, 1)).unwrap();
+
+    assert!((params[0] - 20f64).abs() < 1f64);
+}
+
+#[test]
+fn clipped_stochastic_gd_training() {
+    let x_sq = XSqModel { c: 20f64 };
+
+    let gd = StochasticGD::new(0.9f64, 0.1f64, 100).with_clipping(GradClip::Norm(1f64));
+    let test_data = vec![100f64];
+    let params = gd.optimize(&x_sq,
+                             &test_data[..],
+                             &Matrix::zeros(100, 1),
+                             &Matrix::zeros(100, 1)).unwrap();
+
+    assert!((params[0] - 20f64).abs() < 1e-3);
+}
+
+/// A model which uses the cost function
+/// y = (x - 1)^2
+///
+/// The cost and gradient are undefined for x <= 0.9.
+struct BoundedModel;
+
+impl Optimizable for BoundedModel {
+    type Inputs = Matrix<f64>;
+    type Targets = Matrix<f64>;
+
+    fn compute_grad(&self, params: &[f64], _: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
+        if params[0] > 0.9 {
+            ((params[0] - 1f64) * (params[0] - 1f64), vec![2f64 * (params[0] - 1f64)])
+        } else {
+            (f64::NAN, vec![f64::NAN])
+        }
+    }
+}
+
+#[test]
+fn undefined_step_fmincg_training() {
+    // The first step from 1.2 lands on 0.855, so it must be shortened.
+    let cgd = ConjugateGD::default();
+    let test_data = vec![1.2f64];
+    let params = cgd.optimize(&BoundedModel,
+                              &test_data[..],
+                              &Matrix::zeros(1, 1),
+                              &Matrix::zeros(1, 1)).unwrap();
+
+    assert!((params[0] - 1f64).abs() < 1e-3);
+}
+
+#[test]
+fn diverging_fmincg_training() {
+    let cgd = ConjugateGD::default();
+    let test_data = vec![0.5f64];
+    let res = cgd.optimize(&BoundedModel,
+                           &test_data[..],
+                           &Matrix::zeros(1, 1),
+                           &Matrix::zeros(1, 1));
+
+    match res.unwrap_err().kind() {
+        &ErrorKind::Divergence => {}
+        _ => panic!("Expected a divergence error."),
+    }
+}
+
+/// A model with a finite cost but an undefined gradient.
+struct NanGradModel;
+
+impl Optimizable for NanGradModel {
+    type Inputs = Matrix<f64>;
+    type Targets = Matrix<f64>;
+
+    fn compute_grad(&self, params: &[f64], _: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
+        (params[0] * params[0], vec![f64::NAN])
+    }
+}
+
+#[test]
+fn nan_gradient_clipped_gd_training() {
+    // Clipping by value must not turn the NaN gradient into a finite one.
+    let gd = GradientDesc::default().with_clipping(GradClip::Value(1f64));
+    let test_data = vec![1f64];
+    let res = gd.optimize(&NanGradModel,
+                          &test_data[..],
+                          &Matrix::zeros(1, 1),
+                          &Matrix::zeros(1, 1));
+
+    match res.unwrap_err().kind() {
+        &ErrorKind::Divergence => {}
+        _ => panic!("Expected a divergence error."),
+    }
+}
@@ -0,0 +1,85 @@
+use rm::linalg::{Matrix, BaseMatrix};
+use rm::linalg::Vector;
+use rm::learning::SupModel;
+use rm::learning::gp::{GaussianProcess, ConstMean};
+use rm::learning::sparse_gp::{SparseGaussianProcess, InducingPoints};
+use rm::learning::toolkit::kernel::SquaredExp;
+
+fn sine_data(n: usize) -> (Matrix<f64>, Vector<f64>) {
+    let xs = (0..n).map(|i| i as f64 * 10. / n as f64).collect::<Vec<_>>();
+    let ys = xs.iter().map(|x| x.sin()).collect::<Vec<_>>();
+    (Matrix::new(n, 1, xs), Vector::new(ys))
+}
+
+#[test]
+fn test_sparse_gp_matches_full_gp_with_all_inputs() {
+    let (inputs, targets) = sine_data(20);
+
+    let mut full = GaussianProcess::new(SquaredExp::default(), ConstMean::default(), 0.1);
+    let mut sparse = SparseGaussianProcess::new(SquaredExp::default(),
+                                                ConstMean::default(),
+                                                0.1,
+                                                InducingPoints::Fixed(inputs.clone()));
+
+    full.train(&inputs, &targets).unwrap();
+    sparse.train(&inputs, &targets).unwrap();
+
+    let test_inputs = Matrix::new(4, 1, vec![0.5, 2.3, 5.1, 8.7]);
+    let (full_mean, full_cov) = full.get_posterior(&test_inputs).unwrap();
+    let (sparse_mean, sparse_cov) = sparse.get_posterior(&test_inputs).unwrap();
+
+    for (a, b) in full_mean.iter().zip(sparse_mean.iter()) {
+        assert!((a - b).abs() < 1e-4);
+    }
+    for (a, b) in full_cov.iter().zip(sparse_cov.iter()) {
+        assert!((a - b).abs() < 1e-4);
+    }
+
+    let outputs = sparse.predict(&test_inputs).unwrap();
+    for (a, b) in outputs.iter().zip(sparse_mean.iter()) {
+        assert!((a - b).abs() < 1e-10);
+    }
+}
+
+#[test]
+fn test_sparse_gp_kmeans_inducing() {
+    let (inputs, targets) = sine_data(200);
+
+    let mut sparse = SparseGaussianProcess::new(SquaredExp::default(),
+                                                ConstMean::default(),
+                                                0.01,
+                                                InducingPoints::KMeans(15));
+    sparse.train(&inputs, &targets).unwrap();
+
+    assert_eq!(sparse.inducing_inputs().unwrap().rows(), 15);
+
+    let test_inputs = Matrix::new(3, 1, vec![1.1, 4.2, 7.3]);
+    let outputs = sparse.predict(&test_inputs).unwrap();
+    for (x, y) in test_inputs.iter().zip(outputs.iter()) {
+        assert!((x.sin() - y).abs() < 0.1);
+    }
+}
+
+#[test]
+fn test_sparse_gp_subset_inducing() {
+    let (inputs, targets) = sine_data(50);
+
+    let mut sparse = SparseGaussianProcess::new(SquaredExp::default(),
+                                                ConstMean::default(),
+                                                0.01,
+                                                InducingPoints::Subset(10));
+    sparse.train(&inputs, &targets).unwrap();
+
+    assert_eq!(sparse.inducing_inputs().unwrap().rows(), 10);
+    let (_, cov) = sparse.get_posterior(&inputs).unwrap();
+    assert_eq!(cov.rows(), 50);
+}
+
+#[test]
+fn test_sparse_gp_untrained() {
+    let sparse = SparseGaussianProcess::new(SquaredExp::default(),
+                                            ConstMean::default(),
+                                            0.01,
+                                            InducingPoints::Subset(10));
+    assert!(sparse.predict(&Matrix::new(1, 1, vec![0.])).is_err());
+}
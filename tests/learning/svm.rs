@@ -0,0 +1,176 @@
+use rm::linalg::{Matrix, BaseMatrix};
+use rm::linalg::Vector;
+use rm::learning::SupModel;
+use rm::learning::svm::SoftMarginSVM;
+use rm::learning::svm::multiclass::{OneVsRestSVM, OneVsOneSVM};
+use rm::learning::svm::svr::SVR;
+use rm::learning::toolkit::kernel::{Linear, SquaredExp};
+
+#[test]
+fn test_soft_margin_svm_linear_margin() {
+    let inputs = Matrix::new(6,1,vec![-5.,-3.,-1.,1.,3.,5.]);
+    let targets = Vector::new(vec![-1.,-1.,-1.,1.,1.,1.]);
+
+    let mut svm = SoftMarginSVM::new(Linear::default(), 100.);
+    svm.tol = 1e-6;
+    svm.train(&inputs, &targets).unwrap();
+
+    // Only the two points closest to the boundary are support vectors.
+    let sv = svm.support_vectors().unwrap();
+    assert_eq!(sv.rows(), 2);
+    assert!(sv.iter().all(|x| x.abs() == 1.));
+
+    let decision = svm.decision_function(&Matrix::new(3,1,vec![-1.,0.,1.])).unwrap();
+    assert!((decision[0] + 1.).abs() < 1e-4);
+    assert!(decision[1].abs() < 1e-4);
+    assert!((decision[2] - 1.).abs() < 1e-4);
+    assert!(svm.bias().unwrap().abs() < 1e-4);
+}
+
+#[test]
+fn test_soft_margin_svm_dual_coefs_bounded() {
+    // The classes overlap so some points must violate the margin.
+    let inputs = Matrix::new(8,1,vec![-3.,-2.,-1.,0.5,-0.5,1.,2.,3.]);
+    let targets = Vector::new(vec![-1.,-1.,-1.,-1.,1.,1.,1.,1.]);
+
+    let c = 0.5;
+    let mut svm = SoftMarginSVM::new(Linear::default(), c);
+    svm.train(&inputs, &targets).unwrap();
+
+    let coefs = svm.dual_coefs().unwrap();
+    assert!(coefs.iter().all(|a| a.abs() <= c + 1e-12));
+    assert!(coefs.sum().abs() < 1e-8);
+}
+
+#[test]
+fn test_soft_margin_svm_rbf_circle() {
+    // Points inside the unit circle against points outside it.
+    let mut data = Vec::new();
+    let mut targets = Vec::new();
+    for i in 0..40 {
+        let angle = i as f64 * 0.3;
+        let r = if i % 2 == 0 { 0.5 } else { 2. };
+        data.push(r * angle.cos());
+        data.push(r * angle.sin());
+        targets.push(if i % 2 == 0 { 1. } else { -1. });
+    }
+    let inputs = Matrix::new(40, 2, data);
+    let targets = Vector::new(targets);
+
+    let mut svm = SoftMarginSVM::new(SquaredExp::new(1., 1.), 10.);
+    svm.train(&inputs, &targets).unwrap();
+
+    assert!(svm.support_vectors().unwrap().rows() < 40);
+    assert_eq!(svm.predict(&inputs).unwrap().into_vec(), targets.into_vec());
+
+    let test_inputs = Matrix::new(2, 2, vec![0.1, -0.2, 2.5, 0.]);
+    assert_eq!(svm.predict(&test_inputs).unwrap().into_vec(), vec![1., -1.]);
+}
+
+#[test]
+fn test_soft_margin_svm_invalid_targets() {
+    let inputs = Matrix::new(3,1,vec![1.,2.,3.]);
+    let mut svm = SoftMarginSVM::new(Linear::default(), 1.);
+
+    assert!(svm.train(&inputs, &Vector::new(vec![0.,1.,1.])).is_err());
+    assert!(svm.train(&inputs, &Vector::new(vec![1.,1.,1.])).is_err());
+    assert!(svm.train(&inputs, &Vector::new(vec![-1.,1.])).is_err());
+    assert!(svm.predict(&inputs).is_err());
+}
+
+fn three_blobs() -> (Matrix<f64>, Vector<usize>) {
+    let centres = [(0., 0.), (4., 0.), (2., 4.)];
+    let offsets = [(0.3, 0.1), (-0.2, 0.3), (0.1, -0.3), (-0.3, -0.2)];
+
+    let mut data = Vec::new();
+    let mut targets = Vec::new();
+    for (class, &(cx, cy)) in centres.iter().enumerate() {
+        for &(dx, dy) in &offsets {
+            data.push(cx + dx);
+            data.push(cy + dy);
+            targets.push(class);
+        }
+    }
+    (Matrix::new(12, 2, data), Vector::new(targets))
+}
+
+#[test]
+fn test_one_vs_rest_svm() {
+    let (inputs, targets) = three_blobs();
+
+    let mut model = OneVsRestSVM::new(SoftMarginSVM::new(SquaredExp::new(1., 1.), 10.));
+    model.train(&inputs, &targets).unwrap();
+
+    assert_eq!(model.classes().unwrap(), &[0, 1, 2]);
+    assert_eq!(model.decision_function(&inputs).unwrap().cols(), 3);
+    assert_eq!(model.predict(&inputs).unwrap().into_vec(), targets.into_vec());
+
+    let test_inputs = Matrix::new(3, 2, vec![0.1, 0.1, 3.9, -0.1, 2., 3.8]);
+    assert_eq!(model.predict(&test_inputs).unwrap().into_vec(), vec![0, 1, 2]);
+}
+
+#[test]
+fn test_one_vs_one_svm() {
+    let (inputs, targets) = three_blobs();
+    // Use non-contiguous class labels.
+    let targets = Vector::new(targets.iter().map(|t| t * 2 + 1).collect::<Vec<_>>());
+
+    let mut model = OneVsOneSVM::new(SoftMarginSVM::new(SquaredExp::new(1., 1.), 10.));
+    model.train(&inputs, &targets).unwrap();
+
+    assert_eq!(model.pairs(), vec![(1, 3), (1, 5), (3, 5)]);
+    assert_eq!(model.predict(&inputs).unwrap().into_vec(), targets.into_vec());
+
+    let test_inputs = Matrix::new(3, 2, vec![0.1, 0.1, 3.9, -0.1, 2., 3.8]);
+    assert_eq!(model.predict(&test_inputs).unwrap().into_vec(), vec![1, 3, 5]);
+}
+
+#[test]
+fn test_multiclass_svm_single_class() {
+    let inputs = Matrix::new(3,1,vec![1.,2.,3.]);
+    let targets = Vector::new(vec![2, 2, 2]);
+
+    let mut model = OneVsRestSVM::new(SoftMarginSVM::new(Linear::default(), 1.));
+    assert!(model.train(&inputs, &targets).is_err());
+    assert!(model.predict(&inputs).is_err());
+}
+
+#[test]
+fn test_svr_sine() {
+    let xs = (0..50).map(|i| i as f64 * 0.125).collect::<Vec<_>>();
+    let ys = xs.iter().map(|x| x.sin()).collect::<Vec<_>>();
+    let inputs = Matrix::new(50, 1, xs);
+    let targets = Vector::new(ys);
+
+    let epsilon = 0.05;
+    let mut svr = SVR::new(SquaredExp::new(1., 1.), 100., epsilon);
+    svr.tol = 1e-6;
+    svr.train(&inputs, &targets).unwrap();
+
+    // Points strictly inside the tube are not support vectors.
+    assert!(svr.support_vectors().unwrap().rows() < 50);
+
+    let outputs = svr.predict(&inputs).unwrap();
+    for (y, t) in outputs.iter().zip(targets.iter()) {
+        assert!((y - t).abs() <= epsilon + 1e-3);
+    }
+
+    let test_inputs = Matrix::new(2, 1, vec![1.3, 4.1]);
+    let test_outputs = svr.predict(&test_inputs).unwrap();
+    assert!((test_outputs[0] - 1.3f64.sin()).abs() < 0.1);
+    assert!((test_outputs[1] - 4.1f64.sin()).abs() < 0.1);
+}
+
+#[test]
+fn test_svr_dual_coefs_bounded() {
+    let inputs = Matrix::new(5,1,vec![0.,1.,2.,3.,4.]);
+    let targets = Vector::new(vec![0.,5.,0.,5.,0.]);
+
+    let c = 0.5;
+    let mut svr = SVR::new(Linear::default(), c, 0.1);
+    svr.train(&inputs, &targets).unwrap();
+
+    let coefs = svr.dual_coefs().unwrap();
+    assert!(coefs.iter().all(|a| a.abs() <= c + 1e-12));
+    assert!(coefs.sum().abs() < 1e-8);
+}
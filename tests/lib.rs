@@ -2,12 +2,21 @@
 extern crate rulinalg;
 extern crate rusty_machine as rm;
 extern crate num as libnum;
+extern crate rand;
 
 pub mod learning {
     mod dbscan;
+    mod optics;
+    mod hdbscan;
+    mod agglomerative;
     mod lin_reg;
     mod k_means;
+    mod gmm;
     mod gp;
+    mod sparse_gp;
+    mod bayes_opt;
+    mod svm;
+    mod calibration;
     mod knn;
     mod pca;
 